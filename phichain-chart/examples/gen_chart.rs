@@ -36,6 +36,7 @@ fn main() {
             .collect();
         let events: Vec<LineEvent> = (0..per_line_events)
             .map(|i| LineEvent {
+                id: None,
                 kind: LineEventKind::X,
                 start_beat: phichain_chart::beat!(i, 1, 3),
                 end_beat: phichain_chart::beat!(i + 1, 1, 3),
@@ -0,0 +1,443 @@
+//! Note density and a rough difficulty estimation of a chart
+//!
+//! Times are in seconds from the first beat, fake notes are not played and are left out
+
+use crate::bpm_list::BpmList;
+use crate::constants::{CANVAS_HEIGHT, CANVAS_WIDTH};
+use crate::event::{LineEvent, LineEventKind};
+use crate::note::{Note, NoteKind};
+use crate::serialization::SerializedLine;
+use serde::Serialize;
+
+/// Lengths in seconds of the sliding windows the peak density is computed over
+pub const PEAK_WINDOWS: [f32; 3] = [1.0, 5.0, 10.0];
+
+/// Seconds the notes may run past the end of the music before they are left out
+pub const DURATION_MARGIN: f32 = 10.0;
+
+/// The longest duration in seconds analyzed when the length of the music is unknown
+pub const MAX_DURATION: f32 = 3600.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct NoteKindDistribution {
+    pub tap: usize,
+    pub drag: usize,
+    pub hold: usize,
+    pub flick: usize,
+}
+
+impl NoteKindDistribution {
+    pub fn total(&self) -> usize {
+        self.tap + self.drag + self.hold + self.flick
+    }
+}
+
+/// The densest window of a length
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PeakDensity {
+    /// Length of the window in seconds
+    pub window: f32,
+    /// Notes per second within the window
+    pub nps: f32,
+    /// Start of the window
+    pub start: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ChartAnalysis {
+    /// Length of the music if known, extended to the end of the last note
+    ///
+    /// The extension is capped at [`DURATION_MARGIN`] past the music, or at [`MAX_DURATION`] if
+    /// the length is unknown, so a stray note far past the chart does not stretch the analysis.
+    /// Events are left out as they often run far past the chart, e.g. official charts end their
+    /// last events at around 1e9 time units. Anything after the duration is ignored
+    pub duration: f32,
+    /// Amount of notes in each second
+    pub density: Vec<u32>,
+    /// The densest window of each of the [`PEAK_WINDOWS`]
+    pub peak_nps: Vec<PeakDensity>,
+    pub note_kinds: NoteKindDistribution,
+    /// Fraction of the duration with any hold being held, from `0.0` to `1.0`
+    pub hold_coverage: f32,
+    /// Distance the lines move in each second, in screens
+    ///
+    /// X and Y movements are relative to the canvas size and a half turn counts as a screen. Only
+    /// transitions are taken into account, evenly spread over their duration regardless of easing
+    pub movement: Vec<f32>,
+}
+
+impl ChartAnalysis {
+    /// Analyze the notes and events, `length` is the length of the music in seconds if known
+    pub fn new<'a>(
+        bpm_list: &BpmList,
+        notes: impl IntoIterator<Item = &'a Note>,
+        events: impl IntoIterator<Item = &'a LineEvent>,
+        length: Option<f32>,
+    ) -> Self {
+        let mut analysis = Self {
+            duration: length.unwrap_or_default(),
+            ..Default::default()
+        };
+
+        let mut times = vec![];
+        let mut holds = vec![];
+        for note in notes.into_iter().filter(|note| !note.is_fake) {
+            let time = bpm_list.time_at(note.beat);
+            let end_time = bpm_list.time_at(note.end_beat());
+            times.push(time);
+            analysis.duration = analysis.duration.max(end_time);
+
+            match note.kind {
+                NoteKind::Tap => analysis.note_kinds.tap += 1,
+                NoteKind::Drag => analysis.note_kinds.drag += 1,
+                NoteKind::Hold { .. } => {
+                    analysis.note_kinds.hold += 1;
+                    holds.push((time, end_time));
+                }
+                NoteKind::Flick => analysis.note_kinds.flick += 1,
+            }
+        }
+
+        // the buckets are allocated for every second up to the duration
+        analysis.duration = analysis
+            .duration
+            .min(length.map_or(MAX_DURATION, |length| length + DURATION_MARGIN));
+        times.retain(|time| *time <= analysis.duration);
+        holds.retain(|(time, _)| *time <= analysis.duration);
+        for (_, end_time) in &mut holds {
+            *end_time = end_time.min(analysis.duration);
+        }
+
+        let mut movements = vec![];
+        for event in events {
+            let start = bpm_list.time_at(event.start_beat);
+            let end = bpm_list.time_at(event.end_beat);
+            if start > analysis.duration {
+                continue;
+            }
+
+            let scale = match event.kind {
+                LineEventKind::X => CANVAS_WIDTH,
+                LineEventKind::Y => CANVAS_HEIGHT,
+                LineEventKind::Rotation => 180.0,
+                _ => continue,
+            };
+            let mut distance = (event.value.end() - event.value.start()).abs() / scale;
+            // only the part of the movement within the duration is kept
+            let clamped = end.min(analysis.duration);
+            if end > start {
+                distance *= (clamped - start) / (end - start);
+            }
+            movements.push((start, clamped, distance));
+        }
+
+        let seconds = analysis.duration.max(0.0) as usize + 1;
+
+        analysis.density = vec![0; seconds];
+        for time in &times {
+            let second = (time.max(0.0) as usize).min(analysis.density.len() - 1);
+            analysis.density[second] += 1;
+        }
+
+        times.sort_by(f32::total_cmp);
+        analysis.peak_nps = PEAK_WINDOWS
+            .iter()
+            .map(|window| peak_density(&times, *window))
+            .collect();
+
+        if analysis.duration > 0.0 {
+            analysis.hold_coverage = covered(holds) / analysis.duration;
+        }
+
+        analysis.movement = vec![0.0; seconds];
+        for (start, end, distance) in movements {
+            spread(&mut analysis.movement, start, end, distance);
+        }
+
+        analysis
+    }
+
+    /// Analyze the lines and all their descendants, see [`ChartAnalysis::new`]
+    pub fn collect(bpm_list: &BpmList, lines: &[SerializedLine], length: Option<f32>) -> Self {
+        fn flatten<'a>(lines: &'a [SerializedLine], flat: &mut Vec<&'a SerializedLine>) {
+            for line in lines {
+                flat.push(line);
+                flatten(&line.children, flat);
+            }
+        }
+
+        let mut flat = vec![];
+        flatten(lines, &mut flat);
+
+        Self::new(
+            bpm_list,
+            flat.iter().flat_map(|line| &line.notes),
+            flat.iter().flat_map(|line| &line.events),
+            length,
+        )
+    }
+
+    /// Notes per second between the first and the last note, or `0.0` if there are no notes
+    pub fn average_nps(&self) -> f32 {
+        let notes = self.note_kinds.total();
+        let first = self.density.iter().position(|x| *x > 0);
+        let last = self.density.iter().rposition(|x| *x > 0);
+        match (first, last) {
+            (Some(first), Some(last)) => notes as f32 / (last - first + 1) as f32,
+            _ => 0.0,
+        }
+    }
+
+    /// The densest window of a length in [`PEAK_WINDOWS`]
+    pub fn peak(&self, window: f32) -> Option<&PeakDensity> {
+        self.peak_nps.iter().find(|peak| peak.window == window)
+    }
+
+    /// Average distance the lines move per second, in screens
+    pub fn movement_intensity(&self) -> f32 {
+        match self.duration {
+            0.0 => 0.0,
+            duration => self.movement.iter().sum::<f32>() / duration,
+        }
+    }
+
+    /// A crude estimation of the Phigros level of the chart, from `1.0` to `16.0`
+    ///
+    /// This is a linear fit of the average and the 5-second peak density plus the movement
+    /// intensity, meant to be a first guess rather than a rating
+    pub fn estimated_level(&self) -> f32 {
+        if self.note_kinds.total() == 0 {
+            return 1.0;
+        }
+
+        let peak = self.peak(5.0).map(|peak| peak.nps).unwrap_or_default();
+        (0.9 * self.average_nps() + 0.4 * peak + 2.0 * self.movement_intensity()).clamp(1.0, 16.0)
+    }
+}
+
+/// The densest window of `window` seconds among sorted note times
+fn peak_density(times: &[f32], window: f32) -> PeakDensity {
+    let mut peak = PeakDensity {
+        window,
+        nps: 0.0,
+        start: 0.0,
+    };
+
+    let mut end = 0;
+    for (start, time) in times.iter().enumerate() {
+        while end < times.len() && times[end] < time + window {
+            end += 1;
+        }
+        let nps = (end - start) as f32 / window;
+        if nps > peak.nps {
+            peak.nps = nps;
+            peak.start = *time;
+        }
+    }
+
+    peak
+}
+
+/// Total length of the union of the ranges
+fn covered(mut ranges: Vec<(f32, f32)>) -> f32 {
+    ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut total = 0.0;
+    let mut current: Option<(f32, f32)> = None;
+    for (start, end) in ranges {
+        match current {
+            Some((current_start, current_end)) if start <= current_end => {
+                current = Some((current_start, current_end.max(end)));
+            }
+            _ => {
+                if let Some((current_start, current_end)) = current {
+                    total += current_end - current_start;
+                }
+                current = Some((start, end));
+            }
+        }
+    }
+    if let Some((current_start, current_end)) = current {
+        total += current_end - current_start;
+    }
+
+    total
+}
+
+/// Spread `amount` evenly over the seconds between `start` and `end`
+fn spread(buckets: &mut [f32], start: f32, end: f32, amount: f32) {
+    let last = buckets.len() - 1;
+    let start = start.max(0.0);
+    if end <= start {
+        buckets[(start as usize).min(last)] += amount;
+        return;
+    }
+
+    let rate = amount / (end - start);
+    for (second, bucket) in buckets
+        .iter_mut()
+        .enumerate()
+        .take((end.ceil() as usize).min(last + 1))
+        .skip(start as usize)
+    {
+        let overlap = end.min(second as f32 + 1.0) - start.max(second as f32);
+        *bucket += rate * overlap.max(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beat;
+    use crate::event::LineEventValue;
+    use crate::line::Line;
+
+    fn note(kind: NoteKind, beat: i32, denominator: i32) -> Note {
+        Note::new(kind, true, beat!(beat, denominator), 0.0, 1.0)
+    }
+
+    #[test]
+    fn test_density() {
+        // 60 bpm, a beat is a second
+        let bpm_list = BpmList::single(60.0);
+        let mut notes = (0..8)
+            .map(|i| note(NoteKind::Tap, i, 4))
+            .collect::<Vec<_>>();
+        notes.push(note(NoteKind::Drag, 5, 1));
+        notes.push(Note {
+            is_fake: true,
+            ..note(NoteKind::Flick, 5, 1)
+        });
+
+        let analysis = ChartAnalysis::new(&bpm_list, &notes, &[], None);
+
+        assert_eq!(analysis.density, vec![4, 4, 0, 0, 0, 1]);
+        assert_eq!(analysis.peak(1.0).unwrap().nps, 4.0);
+        assert_eq!(analysis.peak(5.0).unwrap().nps, 8.0 / 5.0);
+        assert_eq!(analysis.note_kinds.tap, 8);
+        assert_eq!(analysis.note_kinds.flick, 0);
+        assert_eq!(analysis.average_nps(), 9.0 / 6.0);
+    }
+
+    #[test]
+    fn test_hold_coverage() {
+        let bpm_list = BpmList::single(60.0);
+        let hold = |beat: i32, hold_beat: i32| {
+            Note::new(
+                NoteKind::Hold {
+                    hold_beat: beat!(hold_beat),
+                },
+                true,
+                beat!(beat),
+                0.0,
+                1.0,
+            )
+        };
+        let notes = [hold(0, 2), hold(1, 2), hold(6, 2)];
+
+        let analysis = ChartAnalysis::new(&bpm_list, &notes, &[], None);
+
+        assert_eq!(analysis.duration, 8.0);
+        assert_eq!(analysis.hold_coverage, 5.0 / 8.0);
+    }
+
+    #[test]
+    fn test_movement() {
+        let bpm_list = BpmList::single(60.0);
+        let events = [
+            LineEvent {
+                id: None,
+                kind: LineEventKind::X,
+                start_beat: beat!(0),
+                end_beat: beat!(2),
+                value: LineEventValue::transition(0.0, CANVAS_WIDTH, Default::default()),
+            },
+            LineEvent {
+                id: None,
+                kind: LineEventKind::Rotation,
+                start_beat: beat!(1, 1, 2),
+                end_beat: beat!(2, 1, 2),
+                value: LineEventValue::transition(0.0, 90.0, Default::default()),
+            },
+            LineEvent {
+                id: None,
+                kind: LineEventKind::Opacity,
+                start_beat: beat!(0),
+                end_beat: beat!(4),
+                value: LineEventValue::transition(0.0, 255.0, Default::default()),
+            },
+        ];
+
+        let analysis = ChartAnalysis::new(&bpm_list, &[], &events, Some(4.0));
+
+        assert_eq!(analysis.movement, vec![0.5, 0.75, 0.25, 0.0, 0.0]);
+        assert_eq!(analysis.movement_intensity(), 1.5 / 4.0);
+    }
+
+    #[test]
+    fn test_events_past_the_last_note() {
+        let bpm_list = BpmList::single(60.0);
+        let notes = [note(NoteKind::Tap, 2, 1)];
+        // official charts end their last events far past the chart
+        let events = [LineEvent {
+            id: None,
+            kind: LineEventKind::X,
+            start_beat: beat!(0),
+            end_beat: beat!(1_000_000),
+            value: LineEventValue::transition(0.0, CANVAS_WIDTH * 1_000_000.0, Default::default()),
+        }];
+
+        let analysis = ChartAnalysis::new(&bpm_list, &notes, &events, None);
+        assert_eq!(analysis.duration, 2.0);
+        assert_eq!(analysis.density.len(), 3);
+        assert_eq!(analysis.movement, vec![1.0, 1.0, 0.0]);
+        assert_eq!(analysis.movement_intensity(), 1.0);
+
+        let analysis = ChartAnalysis::new(&bpm_list, &notes, &events, Some(4.0));
+        assert_eq!(analysis.duration, 4.0);
+        assert_eq!(analysis.movement.len(), 5);
+        assert_eq!(analysis.movement.iter().sum::<f32>(), 4.0);
+    }
+
+    #[test]
+    fn test_notes_past_the_music() {
+        let bpm_list = BpmList::single(60.0);
+        let notes = [
+            note(NoteKind::Tap, 2, 1),
+            note(NoteKind::Tap, 1_000_000_000, 1),
+        ];
+
+        let analysis = ChartAnalysis::new(&bpm_list, &notes, &[], Some(4.0));
+        assert_eq!(analysis.duration, 4.0 + DURATION_MARGIN);
+        assert_eq!(analysis.density.len(), 15);
+        assert_eq!(analysis.density.iter().sum::<u32>(), 1);
+        assert_eq!(analysis.note_kinds.tap, 2);
+
+        let analysis = ChartAnalysis::new(&bpm_list, &notes, &[], None);
+        assert_eq!(analysis.duration, MAX_DURATION);
+        assert_eq!(analysis.density.len(), MAX_DURATION as usize + 1);
+    }
+
+    #[test]
+    fn test_collect_children() {
+        let bpm_list = BpmList::single(60.0);
+        let child = SerializedLine::new(
+            Line::default(),
+            vec![note(NoteKind::Tap, 1, 1)],
+            vec![],
+            vec![],
+            vec![],
+        );
+        let parent = SerializedLine::new(
+            Line::default(),
+            vec![note(NoteKind::Flick, 2, 1)],
+            vec![],
+            vec![child],
+            vec![],
+        );
+
+        let analysis = ChartAnalysis::collect(&bpm_list, &[parent], None);
+        assert_eq!(analysis.note_kinds.total(), 2);
+        assert!(analysis.estimated_level() >= 1.0);
+    }
+}
@@ -1,10 +1,11 @@
 use crate::beat;
 use crate::easing::Easing;
+use crate::id::Id;
 use crate::note::{Note, NoteKind};
 use num::iter;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CurveNoteTrackOptions {
     #[serde(flatten)]
     pub kind: NoteKind,
@@ -22,10 +23,12 @@ impl Default for CurveNoteTrackOptions {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CurveNoteTrack {
-    pub from: usize,
-    pub to: usize,
+    /// [`Id`] of the note the track starts from, a note on the same line
+    pub from: Id,
+    /// [`Id`] of the note the track ends at, a note on the same line
+    pub to: Id,
 
     #[serde(flatten)]
     pub options: CurveNoteTrackOptions,
@@ -81,8 +84,8 @@ mod tests {
     #[test]
     fn test_serialize_curve_note_track_with_hold_kind() {
         let track = CurveNoteTrack {
-            from: 1,
-            to: 2,
+            from: Id(1),
+            to: Id(2),
             options: CurveNoteTrackOptions {
                 kind: NoteKind::Hold {
                     hold_beat: beat!(0, 1, 2),
@@ -132,8 +135,8 @@ mod tests {
 
         let track: CurveNoteTrack = serde_json::from_value(value).unwrap();
 
-        assert_eq!(track.from, 0);
-        assert_eq!(track.to, 1);
+        assert_eq!(track.from, Id(0));
+        assert_eq!(track.to, Id(1));
         assert!(matches!(track.options.kind, NoteKind::Drag));
         assert_eq!(track.options.density, 16);
         assert_eq!(track.options.curve, Easing::Linear);
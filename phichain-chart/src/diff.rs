@@ -0,0 +1,406 @@
+//! Semantic differences between two [`PhichainChart`]s
+//!
+//! Unlike a textual diff of the serialized chart, items are matched by what they mean:
+//!
+//! - Lines are matched by name among their siblings, remaining lines are matched by position
+//! - Notes are matched by beat, x and kind
+//! - Events are matched by kind and beat range
+
+use crate::bpm_list::BpmPoint;
+use crate::event::LineEvent;
+use crate::line::Line;
+use crate::note::Note;
+use crate::serialization::{PhichainChart, SerializedLine};
+use serde::Serialize;
+
+/// A value changed from `old` to `new`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+/// A difference of an item on a line
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum ItemDiff<T> {
+    Added { value: T },
+    Removed { value: T },
+    Modified { old: T, new: T },
+}
+
+/// A difference of a line
+///
+/// The path of a line is made of the names of its ancestors and itself, joined by `/`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum LineDiff {
+    Added {
+        path: String,
+        notes: usize,
+        events: usize,
+    },
+    Removed {
+        path: String,
+        notes: usize,
+        events: usize,
+    },
+    Modified {
+        path: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        line: Option<Box<Change<Line>>>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        notes: Vec<ItemDiff<Note>>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        events: Vec<ItemDiff<LineEvent>>,
+    },
+}
+
+impl LineDiff {
+    pub fn path(&self) -> &str {
+        match self {
+            LineDiff::Added { path, .. }
+            | LineDiff::Removed { path, .. }
+            | LineDiff::Modified { path, .. } => path,
+        }
+    }
+}
+
+/// All differences between two charts, see the [module documentation](self) for how items are matched
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ChartDiff {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<Change<f32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bpm_list: Option<Change<Vec<BpmPoint>>>,
+    /// Lines with differences, in tree order. The children of an added or removed line are not listed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<LineDiff>,
+}
+
+impl ChartDiff {
+    /// Compare an `old` chart with a `new` one
+    pub fn new(old: &PhichainChart, new: &PhichainChart) -> Self {
+        let mut diff = Self::default();
+
+        if old.offset.0 != new.offset.0 {
+            diff.offset = Some(Change {
+                old: old.offset.0,
+                new: new.offset.0,
+            });
+        }
+
+        if old.bpm_list.0 != new.bpm_list.0 {
+            diff.bpm_list = Some(Change {
+                old: old.bpm_list.0.clone(),
+                new: new.bpm_list.0.clone(),
+            });
+        }
+
+        diff_lines(&mut diff.lines, "", &old.lines, &new.lines);
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offset.is_none() && self.bpm_list.is_none() && self.lines.is_empty()
+    }
+}
+
+fn join_path(parent: &str, line: &SerializedLine) -> String {
+    if parent.is_empty() {
+        line.line.name.clone()
+    } else {
+        format!("{}/{}", parent, line.line.name)
+    }
+}
+
+/// Pair up `old` and `new` items considered the same by `matches`, in order
+///
+/// Returns the pairs, then the indices of unmatched old and new items
+fn pair<T>(
+    old: &[T],
+    new: &[T],
+    matches: impl Fn(&T, &T) -> bool,
+) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+    let mut pairs = vec![];
+    let mut removed = vec![];
+    let mut matched = vec![false; new.len()];
+
+    for (i, old) in old.iter().enumerate() {
+        match (0..new.len()).find(|&j| !matched[j] && matches(old, &new[j])) {
+            Some(j) => {
+                matched[j] = true;
+                pairs.push((i, j));
+            }
+            None => removed.push(i),
+        }
+    }
+
+    let added = (0..new.len()).filter(|&j| !matched[j]).collect();
+
+    (pairs, removed, added)
+}
+
+fn diff_items<T: Clone + PartialEq>(
+    old: &[T],
+    new: &[T],
+    matches: impl Fn(&T, &T) -> bool,
+) -> Vec<ItemDiff<T>> {
+    let (pairs, removed, added) = pair(old, new, matches);
+
+    let mut diffs = removed
+        .into_iter()
+        .map(|i| ItemDiff::Removed {
+            value: old[i].clone(),
+        })
+        .collect::<Vec<_>>();
+    diffs.extend(
+        pairs
+            .into_iter()
+            .filter(|(i, j)| old[*i] != new[*j])
+            .map(|(i, j)| ItemDiff::Modified {
+                old: old[i].clone(),
+                new: new[j].clone(),
+            }),
+    );
+    diffs.extend(added.into_iter().map(|j| ItemDiff::Added {
+        value: new[j].clone(),
+    }));
+
+    diffs
+}
+
+fn same_note(old: &Note, new: &Note) -> bool {
+    old.beat == new.beat
+        && old.x == new.x
+        && std::mem::discriminant(&old.kind) == std::mem::discriminant(&new.kind)
+}
+
+fn same_event(old: &LineEvent, new: &LineEvent) -> bool {
+    old.kind == new.kind && old.start_beat == new.start_beat && old.end_beat == new.end_beat
+}
+
+fn diff_line(diffs: &mut Vec<LineDiff>, parent: &str, old: &SerializedLine, new: &SerializedLine) {
+    let path = join_path(parent, new);
+
+    let sort_notes = |line: &SerializedLine| {
+        let mut notes = line.notes.clone();
+        notes.sort_by_key(|note| note.beat);
+        notes
+    };
+    let notes = diff_items(&sort_notes(old), &sort_notes(new), same_note);
+
+    let sort_events = |line: &SerializedLine| {
+        let mut events = line.events.clone();
+        events.sort_by_key(|event| (u8::from(event.kind), event.start_beat));
+        events
+    };
+    let events = diff_items(&sort_events(old), &sort_events(new), same_event);
+
+    let line = (old.line != new.line).then(|| {
+        Box::new(Change {
+            old: old.line.clone(),
+            new: new.line.clone(),
+        })
+    });
+
+    if line.is_some() || !notes.is_empty() || !events.is_empty() {
+        diffs.push(LineDiff::Modified {
+            path: path.clone(),
+            line,
+            notes,
+            events,
+        });
+    }
+
+    diff_lines(diffs, &path, &old.children, &new.children);
+}
+
+fn diff_lines(
+    diffs: &mut Vec<LineDiff>,
+    parent: &str,
+    old: &[SerializedLine],
+    new: &[SerializedLine],
+) {
+    // lines are first matched by name, renamed lines are then matched by position
+    let (mut pairs, removed, added) = pair(old, new, |old, new| old.line.name == new.line.name);
+    let renamed = removed.len().min(added.len());
+    pairs.extend(removed.iter().copied().zip(added.iter().copied()));
+    pairs.sort();
+
+    for (i, j) in pairs {
+        diff_line(diffs, parent, &old[i], &new[j]);
+    }
+
+    for &i in &removed[renamed..] {
+        diffs.push(LineDiff::Removed {
+            path: join_path(parent, &old[i]),
+            notes: old[i].notes.len(),
+            events: old[i].events.len(),
+        });
+    }
+
+    for &j in &added[renamed..] {
+        diffs.push(LineDiff::Added {
+            path: join_path(parent, &new[j]),
+            notes: new[j].notes.len(),
+            events: new[j].events.len(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beat;
+    use crate::bpm_list::BpmList;
+    use crate::event::{LineEventKind, LineEventValue};
+    use crate::note::NoteKind;
+    use crate::offset::Offset;
+
+    fn line(name: &str, notes: Vec<Note>, children: Vec<SerializedLine>) -> SerializedLine {
+        SerializedLine {
+            line: Line {
+                name: name.to_owned(),
+                ..Default::default()
+            },
+            notes,
+            children,
+            ..Default::default()
+        }
+    }
+
+    fn chart(lines: Vec<SerializedLine>) -> PhichainChart {
+        PhichainChart::new(0.0, BpmList::default(), lines)
+    }
+
+    #[test]
+    fn test_identical() {
+        let a = chart(vec![line("A", vec![], vec![line("B", vec![], vec![])])]);
+        let b = chart(vec![line("A", vec![], vec![line("B", vec![], vec![])])]);
+        assert!(ChartDiff::new(&a, &b).is_empty());
+    }
+
+    #[test]
+    fn test_offset_and_bpm_list() {
+        let a = chart(vec![]);
+        let b = PhichainChart {
+            offset: Offset(100.0),
+            bpm_list: BpmList::single(180.0),
+            ..chart(vec![])
+        };
+
+        let diff = ChartDiff::new(&a, &b);
+        assert_eq!(
+            diff.offset,
+            Some(Change {
+                old: 0.0,
+                new: 100.0
+            })
+        );
+        assert!(diff.bpm_list.is_some());
+        assert!(diff.lines.is_empty());
+    }
+
+    #[test]
+    fn test_notes() {
+        let tap = Note::new(NoteKind::Tap, true, beat!(1), 0.0, 1.0);
+        let drag = Note::new(NoteKind::Drag, true, beat!(2), 100.0, 1.0);
+        let hold = Note::new(
+            NoteKind::Hold {
+                hold_beat: beat!(1),
+            },
+            true,
+            beat!(3),
+            0.0,
+            1.0,
+        );
+        let longer_hold = Note {
+            kind: NoteKind::Hold {
+                hold_beat: beat!(2),
+            },
+            ..hold
+        };
+        let flick = Note::new(NoteKind::Flick, false, beat!(4), 0.0, 1.0);
+
+        let a = chart(vec![line("A", vec![tap, drag, hold], vec![])]);
+        let b = chart(vec![line("A", vec![flick, longer_hold, tap], vec![])]);
+
+        assert_eq!(
+            ChartDiff::new(&a, &b).lines,
+            vec![LineDiff::Modified {
+                path: "A".to_owned(),
+                line: None,
+                notes: vec![
+                    ItemDiff::Removed { value: drag },
+                    ItemDiff::Modified {
+                        old: hold,
+                        new: longer_hold
+                    },
+                    ItemDiff::Added { value: flick },
+                ],
+                events: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_events() {
+        let event = |start: i32, value: f32| LineEvent {
+            id: None,
+            kind: LineEventKind::X,
+            start_beat: beat!(start),
+            end_beat: beat!(start + 1),
+            value: LineEventValue::constant(value),
+        };
+
+        let a = chart(vec![SerializedLine {
+            events: vec![event(0, 0.0), event(1, 0.0)],
+            ..line("A", vec![], vec![])
+        }]);
+        let b = chart(vec![SerializedLine {
+            events: vec![event(1, 100.0), event(0, 0.0)],
+            ..line("A", vec![], vec![])
+        }]);
+
+        assert_eq!(
+            ChartDiff::new(&a, &b).lines,
+            vec![LineDiff::Modified {
+                path: "A".to_owned(),
+                line: None,
+                notes: vec![],
+                events: vec![ItemDiff::Modified {
+                    old: event(1, 0.0),
+                    new: event(1, 100.0),
+                }],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_lines() {
+        let note = Note::new(NoteKind::Tap, true, beat!(1), 0.0, 1.0);
+        let a = chart(vec![
+            line("A", vec![], vec![line("Child", vec![], vec![])]),
+            line("B", vec![], vec![]),
+            line("C", vec![], vec![]),
+        ]);
+        let b = chart(vec![
+            line("C", vec![], vec![]),
+            line("A", vec![], vec![line("Child", vec![note], vec![])]),
+            line("D", vec![], vec![]),
+            line("E", vec![], vec![]),
+        ]);
+
+        let diff = ChartDiff::new(&a, &b);
+        let paths = diff.lines.iter().map(LineDiff::path).collect::<Vec<_>>();
+        // `B` is renamed into `D`, `E` is added
+        assert_eq!(paths, vec!["A/Child", "D", "E"]);
+        assert!(matches!(
+            &diff.lines[1],
+            LineDiff::Modified { line: Some(change), .. } if change.old.name == "B"
+        ));
+        assert!(matches!(diff.lines[2], LineDiff::Added { .. }));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::beat::Beat;
 use crate::easing::{Easing, Tween};
+use crate::id::Id;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
@@ -21,9 +22,32 @@ pub enum LineEventKind {
     Rotation,
     Opacity,
     Speed,
+    ScaleX,
+    ScaleY,
+    ColorR,
+    ColorG,
+    ColorB,
+    Incline,
+    Paint,
 }
 
 impl LineEventKind {
+    /// All kinds in their declaration order
+    pub const ALL: [LineEventKind; 12] = [
+        LineEventKind::X,
+        LineEventKind::Y,
+        LineEventKind::Rotation,
+        LineEventKind::Opacity,
+        LineEventKind::Speed,
+        LineEventKind::ScaleX,
+        LineEventKind::ScaleY,
+        LineEventKind::ColorR,
+        LineEventKind::ColorG,
+        LineEventKind::ColorB,
+        LineEventKind::Incline,
+        LineEventKind::Paint,
+    ];
+
     pub fn is_x(&self) -> bool {
         matches!(self, LineEventKind::X)
     }
@@ -43,6 +67,44 @@ impl LineEventKind {
     pub fn is_speed(&self) -> bool {
         matches!(self, LineEventKind::Speed)
     }
+
+    pub fn is_scale_x(&self) -> bool {
+        matches!(self, LineEventKind::ScaleX)
+    }
+
+    pub fn is_scale_y(&self) -> bool {
+        matches!(self, LineEventKind::ScaleY)
+    }
+
+    pub fn is_color(&self) -> bool {
+        matches!(
+            self,
+            LineEventKind::ColorR | LineEventKind::ColorG | LineEventKind::ColorB
+        )
+    }
+
+    pub fn is_incline(&self) -> bool {
+        matches!(self, LineEventKind::Incline)
+    }
+
+    pub fn is_paint(&self) -> bool {
+        matches!(self, LineEventKind::Paint)
+    }
+
+    /// Whether this kind is one of the extended layers (scale, color, incline and paint)
+    /// introduced by RPE, as opposed to the five basic kinds every format supports
+    pub fn is_extended(&self) -> bool {
+        u8::from(*self) > u8::from(LineEventKind::Speed)
+    }
+
+    /// The value a line holds for this kind before any event of this kind affects it
+    pub fn default_value(&self) -> f32 {
+        match self {
+            LineEventKind::ScaleX | LineEventKind::ScaleY => 1.0,
+            LineEventKind::ColorR | LineEventKind::ColorG | LineEventKind::ColorB => 255.0,
+            _ => 0.0,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -153,6 +215,11 @@ impl LineEventValue {
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "bevy", derive(bevy::prelude::Component))]
 pub struct LineEvent {
+    /// Persistent identifier of the event, see [`PhichainChart::assign_ids`]
+    ///
+    /// [`PhichainChart::assign_ids`]: crate::serialization::PhichainChart::assign_ids
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Id>,
     pub kind: LineEventKind,
     pub start_beat: Beat,
     pub end_beat: Beat,
@@ -303,10 +370,58 @@ impl LineEvent {
     }
 }
 
+/// An event changing the text displayed in place of a line
+///
+/// Text can not be interpolated like numeric values, so it lives beside [`LineEvent`] instead of being one of its kinds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextEvent {
+    pub start_beat: Beat,
+    pub end_beat: Beat,
+    pub start: String,
+    pub end: String,
+    pub easing: Easing,
+}
+
+impl TextEvent {
+    /// Evaluate the text shown at the given beat, returns [`None`] if the event has not started yet
+    ///
+    /// If one of `start` and `end` is a prefix of the other, characters are typed in (or erased) progressively.
+    /// Otherwise, `start` is shown for the whole event and `end` takes over once the event is over
+    pub fn evaluate(&self, beat: f32) -> Option<String> {
+        let start_beat = self.start_beat.value();
+        let end_beat = self.end_beat.value();
+
+        if beat < start_beat {
+            return None;
+        }
+        if beat >= end_beat {
+            return Some(self.end.clone());
+        }
+
+        let (short, long) = if self.start.chars().count() <= self.end.chars().count() {
+            (&self.start, &self.end)
+        } else {
+            (&self.end, &self.start)
+        };
+
+        if !long.starts_with(short.as_str()) {
+            return Some(self.start.clone());
+        }
+
+        let percent = (beat - start_beat) / (end_beat - start_beat);
+        let start_len = self.start.chars().count() as f32;
+        let end_len = self.end.chars().count() as f32;
+        let len = start_len.ease_to(end_len, percent, self.easing).round() as usize;
+
+        Some(long.chars().take(len).collect())
+    }
+}
+
 #[macro_export]
 macro_rules! event {
     ($kind:expr, $from:expr => $to:expr, $start_value:expr => $end_value:expr, $easing:expr $(,)?) => {
         $crate::event::LineEvent {
+            id: None,
             kind: $kind,
             start_beat: $from,
             end_beat: $to,
@@ -322,6 +437,7 @@ macro_rules! event {
     };
     ($kind:expr, $from:expr => $to:expr, $value:expr $(,)?) => {
         $crate::event::LineEvent {
+            id: None,
             kind: $kind,
             start_beat: $from,
             end_beat: $to,
@@ -329,3 +445,41 @@ macro_rules! event {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beat;
+
+    fn text_event(start: &str, end: &str) -> TextEvent {
+        TextEvent {
+            start_beat: beat!(1),
+            end_beat: beat!(3),
+            start: start.to_owned(),
+            end: end.to_owned(),
+            easing: Easing::Linear,
+        }
+    }
+
+    #[test]
+    fn test_text_event_typing() {
+        let event = text_event("", "phichain");
+        assert_eq!(event.evaluate(0.0), None);
+        assert_eq!(event.evaluate(1.0).as_deref(), Some(""));
+        assert_eq!(event.evaluate(2.0).as_deref(), Some("phic"));
+        assert_eq!(event.evaluate(3.0).as_deref(), Some("phichain"));
+        assert_eq!(event.evaluate(10.0).as_deref(), Some("phichain"));
+
+        let erasing = text_event("phichain", "phi");
+        assert_eq!(erasing.evaluate(1.0).as_deref(), Some("phichain"));
+        assert_eq!(erasing.evaluate(3.0).as_deref(), Some("phi"));
+    }
+
+    #[test]
+    fn test_text_event_switching() {
+        let event = text_event("foo", "bar");
+        assert_eq!(event.evaluate(1.0).as_deref(), Some("foo"));
+        assert_eq!(event.evaluate(2.9).as_deref(), Some("foo"));
+        assert_eq!(event.evaluate(3.0).as_deref(), Some("bar"));
+    }
+}
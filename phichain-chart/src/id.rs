@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// A persistent identifier of a line, a note or an event, unique within a chart
+///
+/// Unlike positions in the chart, IDs stay the same when items are reordered, so they can be used to
+/// refer to items across edits and files
+///
+/// Once loaded into a world, the [`Id`] is a component of the entity and the `id` field of the item
+/// is left empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "bevy", derive(bevy::prelude::Component))]
+#[serde(transparent)]
+pub struct Id(pub u64);
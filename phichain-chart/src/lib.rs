@@ -1,13 +1,18 @@
+pub mod analysis;
 pub mod beat;
 pub mod bpm_list;
 pub mod constants;
 pub mod curve_note_track;
+pub mod diff;
 pub mod easing;
 pub mod event;
+pub mod id;
 pub mod line;
+pub mod merge;
 pub mod metrics;
 pub mod migration;
 pub mod note;
 pub mod offset;
 pub mod project;
+pub mod quantize;
 pub mod serialization;
@@ -1,6 +1,7 @@
+use crate::id::Id;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "bevy", derive(bevy::prelude::Component))]
 #[cfg_attr(
     feature = "bevy",
@@ -11,20 +12,67 @@ use serde::{Deserialize, Serialize};
         LineRotation,
         LineOpacity,
         LineSpeed,
+        LineScale,
+        LineColor,
+        LineIncline,
+        LineText,
+        LineTextEvents,
     )
 )]
 pub struct Line {
+    /// Persistent identifier of the line, see [`PhichainChart::assign_ids`]
+    ///
+    /// [`PhichainChart::assign_ids`]: crate::serialization::PhichainChart::assign_ids
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Id>,
     pub name: String,
+
+    /// Path of the image displayed in place of the line, relative to the project directory.
+    /// [`None`] means the default line texture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture: Option<String>,
+    /// The point of the texture placed at the line position, `[0.0, 0.0]` being the bottom-left corner
+    /// and `[1.0, 1.0]` being the top-right corner. [`None`] means the center
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<[f32; 2]>,
+    /// Rendering order of the line, lines with a greater z-order are drawn above. [`None`] means `0`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub z_order: Option<i32>,
+    /// Whether notes that reach the other side of the line are hidden. [`None`] means `true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover: Option<bool>,
 }
 
 impl Default for Line {
     fn default() -> Self {
         Self {
+            id: None,
             name: "Unnamed Line".to_owned(),
+            texture: None,
+            anchor: None,
+            z_order: None,
+            cover: None,
         }
     }
 }
 
+impl Line {
+    /// Get the anchor of this [`Line`], falling back to the center
+    pub fn anchor(&self) -> [f32; 2] {
+        self.anchor.unwrap_or([0.5, 0.5])
+    }
+
+    /// Get the z-order of this [`Line`], falling back to `0`
+    pub fn z_order(&self) -> i32 {
+        self.z_order.unwrap_or(0)
+    }
+
+    /// Whether this [`Line`] hides notes that reach its other side, falling back to `true`
+    pub fn is_cover(&self) -> bool {
+        self.cover.unwrap_or(true)
+    }
+}
+
 // TODO: types below should be moved to phichain-game
 
 #[cfg(feature = "bevy")]
@@ -43,3 +91,71 @@ pub struct LineOpacity(pub f32);
 #[cfg(feature = "bevy")]
 #[derive(bevy::prelude::Component, Debug, Default)]
 pub struct LineSpeed(pub f32);
+
+#[cfg(feature = "bevy")]
+#[derive(bevy::prelude::Component, Debug)]
+pub struct LineScale(pub bevy::prelude::Vec2);
+
+#[cfg(feature = "bevy")]
+impl Default for LineScale {
+    fn default() -> Self {
+        Self(bevy::prelude::Vec2::ONE)
+    }
+}
+
+/// The tint of a line in RGB, ranging from 0 to 255, [`None`] if the line is never affected by a color event
+#[cfg(feature = "bevy")]
+#[derive(bevy::prelude::Component, Debug, Default)]
+pub struct LineColor(pub Option<bevy::prelude::Vec3>);
+
+/// The incline angle of a line in radians
+#[cfg(feature = "bevy")]
+#[derive(bevy::prelude::Component, Debug, Default)]
+pub struct LineIncline(pub f32);
+
+/// The text currently displayed in place of a line, [`None`] if the line is rendered with its texture
+#[cfg(feature = "bevy")]
+#[derive(bevy::prelude::Component, Debug, Default)]
+pub struct LineText(pub Option<String>);
+
+/// The [`TextEvent`](crate::event::TextEvent)s of a line
+///
+/// Unlike [`LineEvent`](crate::event::LineEvent)s, text events are not editable and are kept on the line entity as a whole
+#[cfg(feature = "bevy")]
+#[derive(bevy::prelude::Component, Debug, Default, Clone)]
+pub struct LineTextEvents(pub Vec<crate::event::TextEvent>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_serialize_default() {
+        let value: Value = serde_json::to_value(Line::default()).unwrap();
+        assert_eq!(value, json!({"name": "Unnamed Line"}));
+
+        let line: Line = serde_json::from_value(value).unwrap();
+        assert_eq!(line.anchor(), [0.5, 0.5]);
+        assert_eq!(line.z_order(), 0);
+        assert!(line.is_cover());
+    }
+
+    #[test]
+    fn test_serialize_attributes() {
+        let line = Line {
+            id: None,
+            name: "image".to_owned(),
+            texture: Some("textures/cat.png".to_owned()),
+            anchor: Some([0.0, 1.0]),
+            z_order: Some(-2),
+            cover: Some(false),
+        };
+        let value: Value = serde_json::to_value(&line).unwrap();
+        assert_eq!(
+            value,
+            json!({"name": "image", "texture": "textures/cat.png", "anchor": [0.0, 1.0], "z_order": -2, "cover": false})
+        );
+        assert_eq!(serde_json::from_value::<Line>(value).unwrap(), line);
+    }
+}
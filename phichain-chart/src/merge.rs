@@ -0,0 +1,944 @@
+//! Three-way merge of [`PhichainChart`]s, for charts edited concurrently under version control
+//!
+//! Items are matched by their [`Id`] if the base has an item with it, and by their content the same
+//! way as [`crate::diff`] does otherwise. A change made on one side only is taken,
+//! the same change made on both sides is taken once, and different changes of the same item are a
+//! [`Conflict`], which is resolved to our side.
+//!
+//! Events of the same kind added on both sides are also a conflict when they overlap, as merging
+//! them would produce an invalid event sequence.
+//!
+//! An item present on both sides keeps the [`Id`] of our side. An item added by them is given a new
+//! [`Id`] if ours already uses it for another item, as IDs are assigned independently on each side.
+
+use crate::beat::Beat;
+use crate::bpm_list::BpmList;
+use crate::curve_note_track::{CurveNoteTrack, CurveNoteTrackOptions};
+use crate::event::{LineEvent, LineEventKind};
+use crate::id::Id;
+use crate::line::Line;
+use crate::note::{Note, NoteKind};
+use crate::offset::Offset;
+use crate::serialization::{PhichainChart, SerializedLine};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+use std::mem::Discriminant;
+
+/// What two sides changed differently
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConflictKind {
+    Offset,
+    BpmList,
+    /// The attributes of a line, such as its texture
+    Line,
+    /// A line removed on one side and modified on the other
+    LineRemoved,
+    Note {
+        beat: Beat,
+        x: f32,
+    },
+    Event {
+        event_kind: LineEventKind,
+        start_beat: Beat,
+        end_beat: Beat,
+    },
+    /// Events of the same kind added on both sides, overlapping each other
+    OverlappingEvents {
+        event_kind: LineEventKind,
+        ours: (Beat, Beat),
+        theirs: (Beat, Beat),
+    },
+    TextEvents,
+    CurveNoteTrack {
+        from: Beat,
+        to: Beat,
+    },
+}
+
+/// A conflict of a merge, the path of the line is empty for conflicts of the chart itself
+///
+/// The path of a line is made of the names of its ancestors and itself, joined by `/`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Conflict {
+    pub path: String,
+    #[serde(flatten)]
+    pub kind: ConflictKind,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ConflictKind::Offset => write!(f, "offset"),
+            ConflictKind::BpmList => write!(f, "bpm list"),
+            ConflictKind::Line => write!(f, "{}: line attributes", self.path),
+            ConflictKind::LineRemoved => {
+                write!(
+                    f,
+                    "{}: removed on one side, modified on the other",
+                    self.path
+                )
+            }
+            ConflictKind::Note { beat, x } => write!(f, "{}: note at {beat:?} (x: {x})", self.path),
+            ConflictKind::Event {
+                event_kind,
+                start_beat,
+                end_beat,
+            } => write!(
+                f,
+                "{}: {event_kind:?} event {start_beat:?} ~ {end_beat:?}",
+                self.path
+            ),
+            ConflictKind::OverlappingEvents {
+                event_kind,
+                ours,
+                theirs,
+            } => write!(
+                f,
+                "{}: {event_kind:?} events {:?} ~ {:?} and {:?} ~ {:?} overlap",
+                self.path, ours.0, ours.1, theirs.0, theirs.1
+            ),
+            ConflictKind::TextEvents => write!(f, "{}: text events", self.path),
+            ConflictKind::CurveNoteTrack { from, to } => {
+                write!(f, "{}: curve note track {from:?} ~ {to:?}", self.path)
+            }
+        }
+    }
+}
+
+/// The result of [`merge`]
+pub struct MergeOutcome {
+    /// The merged chart, conflicts are resolved to our side
+    pub chart: PhichainChart,
+    pub conflicts: Vec<Conflict>,
+}
+
+impl MergeOutcome {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Take the side that changed, returns [`None`] if both sides changed differently
+fn merge_value<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &T) -> Option<T> {
+    if ours == theirs || theirs == base {
+        Some(ours.clone())
+    } else if ours == base {
+        Some(theirs.clone())
+    } else {
+        None
+    }
+}
+
+/// Items carrying an [`Id`], which is ignored when comparing the two sides
+trait Identified: Clone {
+    fn id(&self) -> Option<Id>;
+
+    fn with_id(&self, id: Option<Id>) -> Self;
+}
+
+macro_rules! impl_identified {
+    ($($ty:ty),*) => {
+        $(
+            impl Identified for $ty {
+                fn id(&self) -> Option<Id> {
+                    self.id
+                }
+
+                fn with_id(&self, id: Option<Id>) -> Self {
+                    Self { id, ..self.clone() }
+                }
+            }
+        )*
+    };
+}
+
+impl_identified!(Line, Note, LineEvent);
+
+/// Key items, numbering the items sharing a key so that each key is unique
+fn keyed<T, K: Hash + Eq + Clone>(items: &[T], key: impl Fn(&T) -> K) -> Vec<(K, usize)> {
+    let mut occurrences = HashMap::<K, usize>::new();
+    items
+        .iter()
+        .map(|item| {
+            let key = key(item);
+            let occurrence = occurrences.entry(key.clone()).or_default();
+            *occurrence += 1;
+            (key, *occurrence)
+        })
+        .collect()
+}
+
+/// What an item is matched by, see [`id_or_key`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ItemKey<K> {
+    Id(Id),
+    Content(K),
+}
+
+/// Match items by their [`Id`] if the base has an item with it, by `key` otherwise
+///
+/// IDs are assigned independently on each side, so only the ones from the base refer to the same
+/// item on both sides
+fn id_or_key<T, K>(
+    base: &[T],
+    id: impl Fn(&T) -> Option<Id>,
+    key: impl Fn(&T) -> K,
+) -> impl Fn(&T) -> ItemKey<K> {
+    let base_ids = base.iter().filter_map(&id).collect::<HashSet<_>>();
+    move |item| match id(item) {
+        Some(id) if base_ids.contains(&id) => ItemKey::Id(id),
+        _ => ItemKey::Content(key(item)),
+    }
+}
+
+/// How an item ended up in the merged list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Origin {
+    Base,
+    Ours,
+    Theirs,
+}
+
+/// Merge lists of items matched by `key`, see the [module documentation](self)
+///
+/// `merge` merges an item present on both sides, returning [`None`] on a conflict
+/// and `changed` tells whether an item is modified compared to the base
+fn merge_keyed<T: Clone, K: Hash + Eq + Clone>(
+    base: &[T],
+    ours: &[T],
+    theirs: &[T],
+    key: impl Fn(&T) -> K,
+    mut merge: impl FnMut(Option<&T>, &T, &T) -> Option<T>,
+    changed: impl Fn(&T, &T) -> bool,
+    mut conflict: impl FnMut(&T),
+) -> Vec<(T, Origin)> {
+    let base_keys = keyed(base, &key);
+    let ours_keys = keyed(ours, &key);
+    let theirs_keys = keyed(theirs, &key);
+
+    let base_items: HashMap<_, _> = base_keys.into_iter().zip(base).collect();
+    let theirs_items: HashMap<_, _> = theirs_keys.iter().cloned().zip(theirs).collect();
+    let ours_set: HashSet<_> = ours_keys.iter().cloned().collect();
+
+    let mut merged = vec![];
+
+    for (key, ours) in ours_keys.iter().zip(ours) {
+        let base = base_items.get(key).copied();
+        match (base, theirs_items.get(key).copied()) {
+            (base, Some(theirs)) => match merge(base, ours, theirs) {
+                Some(item) => {
+                    let origin = if base.is_some() {
+                        Origin::Base
+                    } else {
+                        Origin::Ours
+                    };
+                    merged.push((item, origin));
+                }
+                None => {
+                    conflict(ours);
+                    merged.push((ours.clone(), Origin::Ours));
+                }
+            },
+            // removed by them
+            (Some(base), None) => {
+                if changed(base, ours) {
+                    conflict(ours);
+                    merged.push((ours.clone(), Origin::Ours));
+                }
+            }
+            // added by us
+            (None, None) => merged.push((ours.clone(), Origin::Ours)),
+        }
+    }
+
+    for (key, theirs) in theirs_keys.iter().zip(theirs) {
+        if ours_set.contains(key) {
+            continue;
+        }
+        match base_items.get(key) {
+            // removed by us
+            Some(base) => {
+                if changed(base, theirs) {
+                    conflict(theirs);
+                }
+            }
+            // added by them
+            None => merged.push((theirs.clone(), Origin::Theirs)),
+        }
+    }
+
+    merged
+}
+
+/// Merge lists of plain items, which conflict when both sides changed them differently
+fn merge_items<T: Identified + PartialEq, K: Hash + Eq + Clone>(
+    base: &[T],
+    ours: &[T],
+    theirs: &[T],
+    key: impl Fn(&T) -> K,
+    conflict: impl FnMut(&T),
+) -> Vec<(T, Origin)> {
+    merge_keyed(
+        base,
+        ours,
+        theirs,
+        key,
+        |base, ours, theirs| {
+            let theirs = theirs.with_id(ours.id());
+            match base {
+                Some(base) => merge_value(&base.with_id(ours.id()), ours, &theirs),
+                None => (*ours == theirs).then(|| ours.clone()),
+            }
+        },
+        |base, item| base.with_id(item.id()) != *item,
+        conflict,
+    )
+}
+
+type NoteKey = (Beat, u32, Discriminant<NoteKind>);
+
+fn note_key(note: &Note) -> NoteKey {
+    (
+        note.beat,
+        note.x.to_bits(),
+        std::mem::discriminant(&note.kind),
+    )
+}
+
+fn event_key(event: &LineEvent) -> (LineEventKind, Beat, Beat) {
+    (event.kind, event.start_beat, event.end_beat)
+}
+
+/// A [`CurveNoteTrack`] referring to its notes by the key they are matched by
+#[derive(Debug, Clone, PartialEq)]
+struct KeyedTrack {
+    from: (ItemKey<NoteKey>, usize),
+    to: (ItemKey<NoteKey>, usize),
+    from_beat: Beat,
+    to_beat: Beat,
+    options: CurveNoteTrackOptions,
+}
+
+/// Tracks are identified by their notes
+impl Identified for KeyedTrack {
+    fn id(&self) -> Option<Id> {
+        None
+    }
+
+    fn with_id(&self, _: Option<Id>) -> Self {
+        self.clone()
+    }
+}
+
+fn keyed_tracks(line: &SerializedLine, key: impl Fn(&Note) -> ItemKey<NoteKey>) -> Vec<KeyedTrack> {
+    let keys = keyed(&line.notes, key);
+    let index = |id: Id| line.notes.iter().position(|note| note.id == Some(id));
+    line.curve_note_tracks
+        .iter()
+        .filter_map(|track| {
+            let (from, to) = (index(track.from)?, index(track.to)?);
+            Some(KeyedTrack {
+                from: keys[from],
+                to: keys[to],
+                from_beat: line.notes[from].beat,
+                to_beat: line.notes[to].beat,
+                options: track.options.clone(),
+            })
+        })
+        .collect()
+}
+
+fn collect_ids(lines: &[SerializedLine], ids: &mut HashSet<Id>) {
+    for line in lines {
+        ids.extend(line.line.id);
+        ids.extend(line.notes.iter().filter_map(|note| note.id));
+        ids.extend(line.events.iter().filter_map(|event| event.id));
+        collect_ids(&line.children, ids);
+    }
+}
+
+struct Merger {
+    conflicts: Vec<Conflict>,
+    /// IDs used by our side
+    ours_ids: HashSet<Id>,
+    next_id: u64,
+}
+
+impl Merger {
+    fn new(ours: &PhichainChart, theirs: &PhichainChart) -> Self {
+        let mut ours_ids = HashSet::new();
+        collect_ids(&ours.lines, &mut ours_ids);
+        let mut theirs_ids = HashSet::new();
+        collect_ids(&theirs.lines, &mut theirs_ids);
+
+        let next_id = ours_ids
+            .iter()
+            .chain(&theirs_ids)
+            .map(|id| id.0 + 1)
+            .max()
+            .unwrap_or_default();
+
+        Self {
+            conflicts: vec![],
+            ours_ids,
+            next_id,
+        }
+    }
+
+    fn conflict(&mut self, path: &str, kind: ConflictKind) {
+        self.conflicts.push(Conflict {
+            path: path.to_owned(),
+            kind,
+        });
+    }
+
+    /// Give a new [`Id`] to an item added by them if our side already uses its [`Id`]
+    fn reassign<T: Identified>(&mut self, item: T, origin: Origin) -> T {
+        match item.id() {
+            Some(id) if origin == Origin::Theirs && self.ours_ids.contains(&id) => {
+                self.next_id += 1;
+                item.with_id(Some(Id(self.next_id - 1)))
+            }
+            _ => item,
+        }
+    }
+
+    /// [Reassign](Self::reassign) the IDs of a line added by them along with its descendants
+    fn reassign_line(&mut self, line: SerializedLine) -> SerializedLine {
+        let mut remapped = HashMap::new();
+        let notes = line
+            .notes
+            .into_iter()
+            .map(|note| {
+                let reassigned = self.reassign(note, Origin::Theirs);
+                if let (Some(old), Some(new)) = (note.id, reassigned.id) {
+                    remapped.insert(old, new);
+                }
+                reassigned
+            })
+            .collect();
+        let remap = |id: Id| remapped.get(&id).copied().unwrap_or(id);
+
+        SerializedLine {
+            line: self.reassign(line.line, Origin::Theirs),
+            notes,
+            events: line
+                .events
+                .into_iter()
+                .map(|event| self.reassign(event, Origin::Theirs))
+                .collect(),
+            curve_note_tracks: line
+                .curve_note_tracks
+                .into_iter()
+                .map(|track| CurveNoteTrack {
+                    from: remap(track.from),
+                    to: remap(track.to),
+                    options: track.options,
+                })
+                .collect(),
+            children: line
+                .children
+                .into_iter()
+                .map(|child| self.reassign_line(child))
+                .collect(),
+            text_events: line.text_events,
+        }
+    }
+
+    fn merge_notes(
+        &mut self,
+        path: &str,
+        base: &SerializedLine,
+        ours: &SerializedLine,
+        theirs: &SerializedLine,
+    ) -> (Vec<Note>, Vec<CurveNoteTrack>) {
+        let mut conflicts = vec![];
+        let key = id_or_key(&base.notes, |note| note.id, note_key);
+        let notes = merge_items(&base.notes, &ours.notes, &theirs.notes, &key, |note| {
+            conflicts.push(ConflictKind::Note {
+                beat: note.beat,
+                x: note.x,
+            })
+        })
+        .into_iter()
+        .map(|(note, origin)| self.reassign(note, origin))
+        .collect::<Vec<_>>();
+
+        // curve note tracks refer to notes by id, which are matched by key to the merged notes
+        let tracks = merge_items(
+            &keyed_tracks(base, &key),
+            &keyed_tracks(ours, &key),
+            &keyed_tracks(theirs, &key),
+            |track| (track.from, track.to),
+            |track| {
+                conflicts.push(ConflictKind::CurveNoteTrack {
+                    from: track.from_beat,
+                    to: track.to_beat,
+                })
+            },
+        );
+        let ids: HashMap<_, _> = keyed(&notes, &key)
+            .into_iter()
+            .zip(&notes)
+            .filter_map(|(key, note)| Some((key, note.id?)))
+            .collect();
+        let tracks = tracks
+            .into_iter()
+            .filter_map(|(track, _)| {
+                Some(CurveNoteTrack {
+                    from: *ids.get(&track.from)?,
+                    to: *ids.get(&track.to)?,
+                    options: track.options,
+                })
+            })
+            .collect();
+
+        for kind in conflicts {
+            self.conflict(path, kind);
+        }
+
+        (notes, tracks)
+    }
+
+    fn merge_events(
+        &mut self,
+        path: &str,
+        base: &[LineEvent],
+        ours: &[LineEvent],
+        theirs: &[LineEvent],
+    ) -> Vec<LineEvent> {
+        let mut conflicts = vec![];
+        let key = id_or_key(base, |event| event.id, event_key);
+        let events = merge_items(base, ours, theirs, key, |event| {
+            conflicts.push(ConflictKind::Event {
+                event_kind: event.kind,
+                start_beat: event.start_beat,
+                end_beat: event.end_beat,
+            })
+        });
+
+        let added = |origin: Origin| {
+            events
+                .iter()
+                .filter(move |(_, x)| *x == origin)
+                .map(|(event, _)| event)
+        };
+        for ours in added(Origin::Ours) {
+            for theirs in added(Origin::Theirs) {
+                if ours.kind == theirs.kind
+                    && ours.start_beat < theirs.end_beat
+                    && theirs.start_beat < ours.end_beat
+                {
+                    conflicts.push(ConflictKind::OverlappingEvents {
+                        event_kind: ours.kind,
+                        ours: (ours.start_beat, ours.end_beat),
+                        theirs: (theirs.start_beat, theirs.end_beat),
+                    });
+                }
+            }
+        }
+
+        for kind in conflicts {
+            self.conflict(path, kind);
+        }
+
+        events
+            .into_iter()
+            .map(|(event, origin)| self.reassign(event, origin))
+            .collect()
+    }
+
+    fn merge_line(
+        &mut self,
+        parent: &str,
+        base: &SerializedLine,
+        ours: &SerializedLine,
+        theirs: &SerializedLine,
+    ) -> SerializedLine {
+        let path = join_path(parent, &ours.line);
+
+        let id = ours.line.id;
+        let line = merge_value(&base.line.with_id(id), &ours.line, &theirs.line.with_id(id))
+            .unwrap_or_else(|| {
+                self.conflict(&path, ConflictKind::Line);
+                ours.line.clone()
+            });
+        let (notes, curve_note_tracks) = self.merge_notes(&path, base, ours, theirs);
+        let events = self.merge_events(&path, &base.events, &ours.events, &theirs.events);
+        let text_events = merge_value(&base.text_events, &ours.text_events, &theirs.text_events)
+            .unwrap_or_else(|| {
+                self.conflict(&path, ConflictKind::TextEvents);
+                ours.text_events.clone()
+            });
+        let children = self.merge_lines(&path, &base.children, &ours.children, &theirs.children);
+
+        SerializedLine {
+            line,
+            notes,
+            events,
+            children,
+            curve_note_tracks,
+            text_events,
+        }
+    }
+
+    fn merge_lines(
+        &mut self,
+        parent: &str,
+        base: &[SerializedLine],
+        ours: &[SerializedLine],
+        theirs: &[SerializedLine],
+    ) -> Vec<SerializedLine> {
+        let mut removed = vec![];
+        let lines = merge_keyed(
+            base,
+            ours,
+            theirs,
+            id_or_key(base, |line| line.line.id, |line| line.line.name.clone()),
+            |base, ours, theirs| {
+                // a line added on both sides is merged as if it was empty before
+                let empty = SerializedLine::new(
+                    Line {
+                        name: ours.line.name.clone(),
+                        ..Default::default()
+                    },
+                    vec![],
+                    vec![],
+                    vec![],
+                    vec![],
+                );
+                Some(self.merge_line(parent, base.unwrap_or(&empty), ours, theirs))
+            },
+            |base, line| base != line,
+            |line| removed.push(join_path(parent, &line.line)),
+        );
+
+        for path in removed {
+            self.conflict(&path, ConflictKind::LineRemoved);
+        }
+
+        lines
+            .into_iter()
+            .map(|(line, origin)| match origin {
+                Origin::Theirs => self.reassign_line(line),
+                _ => line,
+            })
+            .collect()
+    }
+}
+
+fn join_path(parent: &str, line: &Line) -> String {
+    if parent.is_empty() {
+        line.name.clone()
+    } else {
+        format!("{}/{}", parent, line.name)
+    }
+}
+
+/// Merge the changes made to `base` in `ours` and `theirs`, see the [module documentation](self)
+pub fn merge(base: &PhichainChart, ours: &PhichainChart, theirs: &PhichainChart) -> MergeOutcome {
+    let mut merger = Merger::new(ours, theirs);
+
+    let offset =
+        merge_value(&base.offset.0, &ours.offset.0, &theirs.offset.0).unwrap_or_else(|| {
+            merger.conflict("", ConflictKind::Offset);
+            ours.offset.0
+        });
+    let bpm_list = merge_value(&base.bpm_list.0, &ours.bpm_list.0, &theirs.bpm_list.0)
+        .map(BpmList::new)
+        .unwrap_or_else(|| {
+            merger.conflict("", ConflictKind::BpmList);
+            ours.bpm_list.clone()
+        });
+    let lines = merger.merge_lines("", &base.lines, &ours.lines, &theirs.lines);
+
+    MergeOutcome {
+        chart: PhichainChart {
+            format: ours.format,
+            offset: Offset(offset),
+            bpm_list,
+            lines,
+        },
+        conflicts: merger.conflicts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beat;
+    use crate::event::LineEventValue;
+
+    fn note(beat: i32) -> Note {
+        Note::new(NoteKind::Tap, true, beat!(beat), 0.0, 1.0)
+    }
+
+    fn event(start: i32, end: i32, value: f32) -> LineEvent {
+        LineEvent {
+            id: None,
+            kind: LineEventKind::X,
+            start_beat: beat!(start),
+            end_beat: beat!(end),
+            value: LineEventValue::constant(value),
+        }
+    }
+
+    fn line(name: &str, notes: Vec<Note>, events: Vec<LineEvent>) -> SerializedLine {
+        SerializedLine::new(
+            Line {
+                name: name.to_owned(),
+                ..Default::default()
+            },
+            notes,
+            events,
+            vec![],
+            vec![],
+        )
+    }
+
+    fn chart(lines: Vec<SerializedLine>) -> PhichainChart {
+        PhichainChart::new(0.0, BpmList::default(), lines)
+    }
+
+    #[test]
+    fn test_note_additions_on_different_lines() {
+        let base = chart(vec![
+            line("A", vec![note(0)], vec![]),
+            line("B", vec![], vec![]),
+        ]);
+        let ours = chart(vec![
+            line("A", vec![note(0), note(1)], vec![]),
+            line("B", vec![], vec![]),
+        ]);
+        let theirs = chart(vec![
+            line("A", vec![note(0)], vec![]),
+            line("B", vec![note(2)], vec![]),
+        ]);
+
+        let outcome = merge(&base, &ours, &theirs);
+        assert!(outcome.is_clean());
+        assert_eq!(outcome.chart.lines[0].notes, vec![note(0), note(1)]);
+        assert_eq!(outcome.chart.lines[1].notes, vec![note(2)]);
+    }
+
+    #[test]
+    fn test_line_changes() {
+        let base = chart(vec![
+            line("A", vec![], vec![]),
+            line("B", vec![note(0)], vec![]),
+            line("C", vec![], vec![]),
+        ]);
+        // we remove B and add D, they modify A and remove C
+        let ours = chart(vec![
+            line("A", vec![], vec![]),
+            line("C", vec![], vec![]),
+            line("D", vec![], vec![]),
+        ]);
+        let theirs = chart(vec![
+            line("A", vec![note(1)], vec![]),
+            line("B", vec![note(0)], vec![]),
+        ]);
+
+        let outcome = merge(&base, &ours, &theirs);
+        assert!(outcome.is_clean());
+        let names = outcome
+            .chart
+            .lines
+            .iter()
+            .map(|line| line.line.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["A", "D"]);
+        assert_eq!(outcome.chart.lines[0].notes, vec![note(1)]);
+    }
+
+    #[test]
+    fn test_line_removed_and_modified() {
+        let base = chart(vec![line("A", vec![], vec![])]);
+        let ours = chart(vec![]);
+        let theirs = chart(vec![line("A", vec![note(0)], vec![])]);
+
+        let outcome = merge(&base, &ours, &theirs);
+        assert_eq!(
+            outcome.conflicts,
+            vec![Conflict {
+                path: "A".to_owned(),
+                kind: ConflictKind::LineRemoved,
+            }]
+        );
+        assert!(outcome.chart.lines.is_empty());
+    }
+
+    #[test]
+    fn test_event_conflicts() {
+        let base = chart(vec![line("A", vec![], vec![event(0, 1, 0.0)])]);
+        let ours = chart(vec![line(
+            "A",
+            vec![],
+            vec![event(0, 1, 100.0), event(2, 4, 0.0)],
+        )]);
+        let theirs = chart(vec![line(
+            "A",
+            vec![],
+            vec![event(0, 1, 200.0), event(3, 5, 0.0)],
+        )]);
+
+        let outcome = merge(&base, &ours, &theirs);
+        assert_eq!(
+            outcome.conflicts,
+            vec![
+                Conflict {
+                    path: "A".to_owned(),
+                    kind: ConflictKind::Event {
+                        event_kind: LineEventKind::X,
+                        start_beat: beat!(0),
+                        end_beat: beat!(1),
+                    },
+                },
+                Conflict {
+                    path: "A".to_owned(),
+                    kind: ConflictKind::OverlappingEvents {
+                        event_kind: LineEventKind::X,
+                        ours: (beat!(2), beat!(4)),
+                        theirs: (beat!(3), beat!(5)),
+                    },
+                },
+            ]
+        );
+        // conflicts are resolved to our side
+        assert_eq!(outcome.chart.lines[0].events[0], event(0, 1, 100.0));
+    }
+
+    #[test]
+    fn test_same_change_on_both_sides() {
+        let base = chart(vec![line("A", vec![], vec![event(0, 1, 0.0)])]);
+        let changed = || chart(vec![line("A", vec![note(0)], vec![event(0, 1, 100.0)])]);
+
+        let outcome = merge(&base, &changed(), &changed());
+        assert!(outcome.is_clean());
+        assert!(outcome.chart.lines == changed().lines);
+    }
+
+    #[test]
+    fn test_curve_note_tracks_follow_notes() {
+        let note = |beat, id| Note {
+            id: Some(Id(id)),
+            ..note(beat)
+        };
+        let track = |from, to| CurveNoteTrack {
+            from: Id(from),
+            to: Id(to),
+            options: Default::default(),
+        };
+
+        let base = chart(vec![line(
+            "A",
+            vec![note(1, 1), note(2, 2), note(4, 4)],
+            vec![],
+        )]);
+        let ours = chart(vec![SerializedLine {
+            curve_note_tracks: vec![track(2, 4)],
+            ..line(
+                "A",
+                vec![note(1, 1), note(2, 2), note(4, 4), note(5, 5)],
+                vec![],
+            )
+        }]);
+        // they remove the first note and add a note taking the same id as ours
+        let theirs = chart(vec![SerializedLine {
+            curve_note_tracks: vec![track(4, 5)],
+            ..line("A", vec![note(2, 2), note(4, 4), note(6, 5)], vec![])
+        }]);
+
+        let outcome = merge(&base, &ours, &theirs);
+        assert!(outcome.is_clean());
+        let line = &outcome.chart.lines[0];
+        assert_eq!(
+            line.notes,
+            vec![note(2, 2), note(4, 4), note(5, 5), note(6, 6)]
+        );
+        assert_eq!(line.curve_note_tracks, vec![track(2, 4), track(4, 6)]);
+    }
+
+    #[test]
+    fn test_ids_of_line_added_by_them() {
+        let with_id = |mut line: SerializedLine, id| {
+            line.line.id = Some(Id(id));
+            line
+        };
+
+        let base = chart(vec![with_id(line("A", vec![], vec![]), 0)]);
+        let ours = chart(vec![
+            with_id(line("A", vec![], vec![]), 0),
+            with_id(line("B", vec![], vec![]), 1),
+        ]);
+        let theirs = chart(vec![
+            with_id(line("A", vec![], vec![]), 0),
+            with_id(line("C", vec![], vec![]), 1),
+        ]);
+
+        let outcome = merge(&base, &ours, &theirs);
+        assert!(outcome.is_clean());
+        let ids = outcome
+            .chart
+            .lines
+            .iter()
+            .map(|line| line.line.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![Some(Id(0)), Some(Id(1)), Some(Id(2))]);
+    }
+
+    #[test]
+    fn test_items_matched_by_id() {
+        let with_id = |mut line: SerializedLine, id| {
+            line.line.id = Some(Id(id));
+            line
+        };
+        let note = |beat, id| Note {
+            id: Some(Id(id)),
+            ..note(beat)
+        };
+
+        // they renamed the line we added a note to, and we both moved the same note differently
+        let base = chart(vec![with_id(line("A", vec![note(0, 1)], vec![]), 0)]);
+        let ours = chart(vec![with_id(line("A", vec![note(1, 1)], vec![]), 0)]);
+        let theirs = chart(vec![with_id(
+            line("B", vec![note(2, 1), note(3, 2)], vec![]),
+            0,
+        )]);
+
+        let outcome = merge(&base, &ours, &theirs);
+        assert_eq!(
+            outcome.conflicts,
+            vec![Conflict {
+                path: "A".to_owned(),
+                kind: ConflictKind::Note {
+                    beat: beat!(1),
+                    x: 0.0,
+                },
+            }]
+        );
+        assert_eq!(outcome.chart.lines.len(), 1);
+        assert_eq!(outcome.chart.lines[0].line.name, "B");
+        assert_eq!(outcome.chart.lines[0].notes, vec![note(1, 1), note(3, 2)]);
+
+        // IDs not in the base are assigned independently on each side and do not match
+        let ours = chart(vec![with_id(
+            line("A", vec![note(0, 1), note(1, 2)], vec![]),
+            0,
+        )]);
+        let theirs = chart(vec![with_id(
+            line("A", vec![note(0, 1), note(2, 2)], vec![]),
+            0,
+        )]);
+
+        let outcome = merge(&base, &ours, &theirs);
+        assert!(outcome.is_clean());
+        assert_eq!(
+            outcome.chart.lines[0].notes,
+            vec![note(0, 1), note(1, 2), note(2, 3)]
+        );
+    }
+}
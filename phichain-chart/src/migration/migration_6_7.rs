@@ -0,0 +1,74 @@
+use crate::migration::Migration;
+use serde_json::{json, Value};
+
+/// Migration from format `6` to `7`
+///
+/// # Changes
+///
+/// - Note: added optional visual attributes `alpha`, `size`, `y_offset` and `visible_time`
+/// - Note: added optional `is_fake`
+/// - SerializedLine: added optional `text_events`
+/// - Line: added optional `texture`, `anchor`, `z_order` and `cover`
+///
+/// # Modifications
+///
+/// - None except the format bump, absent fields fall back to their defaults.
+///   The bump keeps older versions from silently dropping these fields when loading newer charts
+pub struct Migration6To7;
+
+impl Migration for Migration6To7 {
+    fn migrate(old: &Value) -> anyhow::Result<Value> {
+        let mut chart = old.clone();
+
+        chart["format"] = json!(7);
+
+        Ok(chart)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migration::test_utils::assert_can_deserialize_after_migrating_to_latest;
+
+    fn old_chart() -> Value {
+        json!({
+            "format": 6,
+            "offset": 0.0,
+            "bpm_list": [
+                { "beat": [0, 0, 1], "bpm": 120.0, "time": 0.0 }
+            ],
+            "lines": [
+                {
+                    "name": "Unnamed Line",
+                    "notes": [
+                        {
+                            "kind": "tap",
+                            "above": true,
+                            "beat": [0, 1, 1],
+                            "x": 0.0,
+                            "speed": 3.0
+                        }
+                    ],
+                    "events": [],
+                    "children": [],
+                    "curve_note_tracks": []
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_migration_6_to_7() {
+        let mut new = old_chart();
+        new["format"] = json!(7);
+
+        assert_eq!(Migration6To7::migrate(&old_chart()).unwrap(), new);
+    }
+
+    #[test]
+    fn test_migration_6_to_7_output_can_reach_latest_and_deserialize() {
+        let new = Migration6To7::migrate(&old_chart()).unwrap();
+        assert_can_deserialize_after_migrating_to_latest(&new);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::migration::{for_each_line_recursive, Migration};
+use serde_json::{json, Value};
+
+/// Migration from format `7` to `8`
+///
+/// # Changes
+///
+/// - Line, Note, LineEvent: added `id`, unique within the chart
+/// - CurveNoteTrack: `from` and `to` refer to notes by `id` instead of by index in `notes`
+///
+/// # Modifications
+///
+/// - Every line, note and event is given an `id`, counting from `0` in depth-first order.
+///   For each line, the line itself comes first, followed by its notes, its events and its child lines
+/// - Curve note tracks: `from` and `to` are replaced by the `id` of the notes at those indices.
+///   Tracks referring to notes out of range are dropped, they were ignored when loading before
+pub struct Migration7To8;
+
+fn assign_ids(items: Option<&mut Vec<Value>>, next: &mut u64) -> Vec<u64> {
+    let mut ids = vec![];
+    for item in items.into_iter().flatten() {
+        item["id"] = json!(*next);
+        ids.push(*next);
+        *next += 1;
+    }
+    ids
+}
+
+fn migrate_line(line: &mut Value, next: &mut u64) {
+    line["id"] = json!(*next);
+    *next += 1;
+
+    let notes = assign_ids(
+        line.get_mut("notes").and_then(|v| v.as_array_mut()),
+        next,
+    );
+    assign_ids(
+        line.get_mut("events").and_then(|v| v.as_array_mut()),
+        next,
+    );
+
+    if let Some(tracks) = line
+        .get_mut("curve_note_tracks")
+        .and_then(|v| v.as_array_mut())
+    {
+        let note_id = |track: &Value, key: &str| {
+            track
+                .get(key)
+                .and_then(Value::as_u64)
+                .and_then(|index| notes.get(index as usize))
+                .copied()
+        };
+
+        tracks.retain_mut(|track| match (note_id(track, "from"), note_id(track, "to")) {
+            (Some(from), Some(to)) => {
+                track["from"] = json!(from);
+                track["to"] = json!(to);
+                true
+            }
+            _ => {
+                tracing::warn!("Dropping invalid curve note track: {}", track);
+                false
+            }
+        });
+    }
+}
+
+impl Migration for Migration7To8 {
+    fn migrate(old: &Value) -> anyhow::Result<Value> {
+        let mut chart = old.clone();
+
+        let mut next = 0;
+        for_each_line_recursive(&mut chart, &mut |line| {
+            migrate_line(line, &mut next);
+            Ok(())
+        })?;
+
+        chart["format"] = json!(8);
+
+        Ok(chart)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migration::test_utils::assert_can_deserialize_after_migrating_to_latest;
+
+    fn note(beat: u32) -> Value {
+        json!({
+            "kind": "tap",
+            "above": true,
+            "beat": [beat, 0, 1],
+            "x": 0.0,
+            "speed": 1.0
+        })
+    }
+
+    fn event() -> Value {
+        json!({
+            "kind": "x",
+            "start_beat": [0, 0, 1],
+            "end_beat": [1, 0, 1],
+            "value": { "type": "constant", "value": 0.0 }
+        })
+    }
+
+    fn track(from: u64, to: u64) -> Value {
+        json!({
+            "from": from,
+            "to": to,
+            "kind": "drag",
+            "density": 16,
+            "curve": { "type": "linear" }
+        })
+    }
+
+    fn old_chart() -> Value {
+        json!({
+            "format": 7,
+            "offset": 0.0,
+            "bpm_list": [
+                { "beat": [0, 0, 1], "bpm": 120.0, "time": 0.0 }
+            ],
+            "lines": [
+                {
+                    "name": "Parent",
+                    "notes": [note(0), note(1), note(2)],
+                    "events": [event()],
+                    "children": [
+                        {
+                            "name": "Child",
+                            "notes": [note(0)],
+                            "events": [],
+                            "children": [],
+                            "curve_note_tracks": []
+                        }
+                    ],
+                    "curve_note_tracks": [track(2, 0), track(0, 3)]
+                },
+                {
+                    "name": "Other",
+                    "notes": [],
+                    "events": [event()],
+                    "children": [],
+                    "curve_note_tracks": []
+                }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_migration_7_to_8() {
+        let new = Migration7To8::migrate(&old_chart()).unwrap();
+
+        assert_eq!(new["format"], json!(8));
+
+        let parent = &new["lines"][0];
+        assert_eq!(parent["id"], json!(0));
+        let notes = parent["notes"].as_array().unwrap();
+        assert_eq!(
+            notes.iter().map(|x| x["id"].clone()).collect::<Vec<_>>(),
+            vec![json!(1), json!(2), json!(3)]
+        );
+        assert_eq!(parent["events"][0]["id"], json!(4));
+
+        let child = &parent["children"][0];
+        assert_eq!(child["id"], json!(5));
+        assert_eq!(child["notes"][0]["id"], json!(6));
+
+        let other = &new["lines"][1];
+        assert_eq!(other["id"], json!(7));
+        assert_eq!(other["events"][0]["id"], json!(8));
+
+        // the track out of range is dropped
+        assert_eq!(parent["curve_note_tracks"], json!([track(3, 1)]));
+    }
+
+    #[test]
+    fn test_migration_7_to_8_output_can_reach_latest_and_deserialize() {
+        let new = Migration7To8::migrate(&old_chart()).unwrap();
+        assert_can_deserialize_after_migrating_to_latest(&new);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::beat::Beat;
+use crate::id::Id;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::fmt;
@@ -57,21 +58,48 @@ impl NoteKind {
     require(bevy::prelude::Sprite, bevy::prelude::Pickable)
 )]
 pub struct Note {
+    /// Persistent identifier of the note, see [`PhichainChart::assign_ids`]
+    ///
+    /// [`PhichainChart::assign_ids`]: crate::serialization::PhichainChart::assign_ids
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Id>,
     #[serde(flatten)]
     pub kind: NoteKind,
     pub above: bool,
     pub beat: Beat,
     pub x: f32,
     pub speed: f32,
+
+    /// Opacity of the note ranging from 0 to 255, [`None`] means fully opaque
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpha: Option<u8>,
+    /// Width multiplier of the note, [`None`] means `1.0`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<f32>,
+    /// Extra distance between the note and the line in canvas units, [`None`] means `0.0`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub y_offset: Option<f32>,
+    /// Seconds before being hit at which the note shows up, [`None`] means the note is always visible
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visible_time: Option<f32>,
+    /// Whether this note is a fake note, which is rendered but never judged
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_fake: bool,
 }
 
 impl fmt::Debug for Note {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct(format!("{:?}", self.kind).as_str())
+            .field("id", &self.id)
             .field("above", &self.above)
             .field("beat", &self.beat)
             .field("x", &self.x)
             .field("speed", &self.speed)
+            .field("alpha", &self.alpha)
+            .field("size", &self.size)
+            .field("y_offset", &self.y_offset)
+            .field("visible_time", &self.visible_time)
+            .field("is_fake", &self.is_fake)
             .finish()
     }
 }
@@ -85,14 +113,41 @@ impl PartialOrd for Note {
 impl Note {
     pub fn new(kind: NoteKind, above: bool, beat: Beat, x: f32, speed: f32) -> Self {
         Self {
+            id: None,
             kind,
             above,
             beat,
             x,
             speed,
+            alpha: None,
+            size: None,
+            y_offset: None,
+            visible_time: None,
+            is_fake: false,
         }
     }
 
+    /// Get the opacity of this [`Note`] ranging from 0 to 255, falling back to fully opaque
+    pub fn alpha(&self) -> u8 {
+        self.alpha.unwrap_or(u8::MAX)
+    }
+
+    /// Get the width multiplier of this [`Note`], falling back to `1.0`
+    pub fn size(&self) -> f32 {
+        self.size.unwrap_or(1.0)
+    }
+
+    /// Get the y offset of this [`Note`] in canvas units, falling back to `0.0`
+    pub fn y_offset(&self) -> f32 {
+        self.y_offset.unwrap_or(0.0)
+    }
+
+    /// Whether this [`Note`] should be visible, given the time remaining until it is hit
+    pub fn is_visible_at(&self, time_until_hit: f32) -> bool {
+        self.visible_time
+            .is_none_or(|visible_time| time_until_hit <= visible_time)
+    }
+
     /// Get the hold beat of this [`Note`] if possible
     ///
     /// Returns [`Some`] wrapping the inner `hold_beat` when self if a [`Hold`](NoteKind::Hold)
@@ -192,6 +247,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_visual_attributes() {
+        let mut note = Note::new(NoteKind::Tap, true, beat!(1), 0.0, 1.0);
+        assert_eq!(note.alpha(), 255);
+        assert_eq!(note.size(), 1.0);
+        assert_eq!(note.y_offset(), 0.0);
+        assert!(note.is_visible_at(1000.0));
+
+        note.alpha = Some(128);
+        note.size = Some(1.5);
+        note.visible_time = Some(2.0);
+        assert!(note.is_visible_at(1.0));
+        assert!(!note.is_visible_at(3.0));
+
+        let value: Value = serde_json::to_value(note).unwrap();
+        assert_eq!(
+            value,
+            json!({"kind": "tap", "above": true, "beat": [1, 0, 1], "x": 0.0, "speed": 1.0, "alpha": 128, "size": 1.5, "visible_time": 2.0})
+        );
+        assert_eq!(serde_json::from_value::<Note>(value).unwrap(), note);
+    }
+
+    #[test]
+    fn test_fake_note() {
+        let mut note = Note::new(NoteKind::Flick, false, beat!(2), 10.0, 1.0);
+        assert!(!serde_json::to_value(note)
+            .unwrap()
+            .as_object()
+            .unwrap()
+            .contains_key("is_fake"));
+
+        note.is_fake = true;
+        let value: Value = serde_json::to_value(note).unwrap();
+        assert_eq!(
+            value,
+            json!({"kind": "flick", "above": false, "beat": [2, 0, 1], "x": 10.0, "speed": 1.0, "is_fake": true})
+        );
+        assert_eq!(serde_json::from_value::<Note>(value).unwrap(), note);
+    }
+
     #[test]
     fn test_roundtrip() {
         let notes = vec![
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::fs::File;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -51,6 +51,18 @@ impl ProjectPath {
         self.0.join(path)
     }
 
+    /// Resolve a path relative to the project directory, such as a line texture
+    ///
+    /// Returns [`None`] if the path is absolute or escapes the project directory
+    pub fn resolve(&self, path: impl AsRef<Path>) -> Option<PathBuf> {
+        let path = path.as_ref();
+        let inside = path.components().next().is_some()
+            && path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        inside.then(|| self.0.join(path))
+    }
+
     fn find_file(&self, name: &str, allowed_extensions: &[impl ToString]) -> Option<PathBuf> {
         std::fs::read_dir(&self.0)
             .ok()?
@@ -104,3 +116,24 @@ impl ProjectPath {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let project = ProjectPath(PathBuf::from("project"));
+        assert_eq!(
+            project.resolve("textures/line.png"),
+            Some(PathBuf::from("project/textures/line.png"))
+        );
+        assert_eq!(
+            project.resolve("./line.png"),
+            Some(PathBuf::from("project/./line.png"))
+        );
+        assert_eq!(project.resolve("../line.png"), None);
+        assert_eq!(project.resolve("/etc/line.png"), None);
+        assert_eq!(project.resolve(""), None);
+    }
+}
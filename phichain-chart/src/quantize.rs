@@ -0,0 +1,236 @@
+//! Snap the beats of notes and events to a grid
+//!
+//! Charts converted from formats storing times as floats, like the official format, have their
+//! beats reconstructed from those floats and end up slightly off the grid they were charted on
+
+use crate::beat::Beat;
+use crate::event::LineEvent;
+use crate::note::Note;
+use crate::serialization::SerializedLine;
+use num::Rational32;
+use serde::Serialize;
+
+/// Snaps beats to the nearest `1/density` beat
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantize {
+    pub density: u32,
+    /// Beats moved further than this, in beats, are reported as [`Deviation`]s
+    pub tolerance: f32,
+}
+
+/// What a [`Deviation`] happened on
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuantizedItem {
+    Note(Note),
+    Event(LineEvent),
+}
+
+/// An item with a beat moved further than the tolerance
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Deviation {
+    /// Names of the ancestors of the line and itself, joined by `/`
+    pub path: String,
+    /// The item before it was quantized
+    pub item: QuantizedItem,
+    /// How far the beat moved the most, in beats
+    pub distance: f32,
+}
+
+impl Quantize {
+    pub fn new(density: u32, tolerance: f32) -> Self {
+        Self { density, tolerance }
+    }
+
+    fn step(&self) -> Beat {
+        Beat::new(0, Rational32::new(1, self.density as i32))
+    }
+
+    /// Snap a beat to the nearest grid line
+    pub fn beat(&self, beat: Beat) -> Beat {
+        let density = Rational32::from_integer(self.density as i32);
+        Beat::from((Rational32::from(beat) * density).round() / density).reduced()
+    }
+
+    /// Snap a range, the end stays at least one grid line after the start
+    fn range(&self, start: Beat, end: Beat) -> (Beat, Beat, f32) {
+        let snapped_start = self.beat(start);
+        let snapped_end = self.beat(end).max(snapped_start + self.step());
+
+        let distance = (snapped_start - start)
+            .abs()
+            .value()
+            .max((snapped_end - end).abs().value());
+
+        (snapped_start, snapped_end, distance)
+    }
+
+    /// Quantize a note, returns the quantized note and how far its beats moved the most
+    ///
+    /// The end of a hold is snapped as well
+    pub fn note(&self, note: &Note) -> (Note, f32) {
+        let mut quantized = *note;
+
+        let distance = if note.kind.is_hold() {
+            let (beat, end_beat, distance) = self.range(note.beat, note.end_beat());
+            quantized.beat = beat;
+            quantized.set_end_beat(end_beat);
+            distance
+        } else {
+            quantized.beat = self.beat(note.beat);
+            (quantized.beat - note.beat).abs().value()
+        };
+
+        (quantized, distance)
+    }
+
+    /// Quantize an event, returns the quantized event and how far its beats moved the most
+    pub fn event(&self, event: &LineEvent) -> (LineEvent, f32) {
+        let (start_beat, end_beat, distance) = self.range(event.start_beat, event.end_beat);
+
+        (
+            LineEvent {
+                start_beat,
+                end_beat,
+                ..*event
+            },
+            distance,
+        )
+    }
+
+    pub fn exceeds(&self, distance: f32) -> bool {
+        distance > self.tolerance
+    }
+
+    /// Quantize the notes and events of the lines and their descendants in place
+    ///
+    /// Returns the items moved further than the tolerance, in tree order
+    pub fn lines(&self, lines: &mut [SerializedLine]) -> Vec<Deviation> {
+        let mut deviations = vec![];
+        self.lines_inner(lines, "", &mut deviations);
+        deviations
+    }
+
+    fn lines_inner(
+        &self,
+        lines: &mut [SerializedLine],
+        parent: &str,
+        deviations: &mut Vec<Deviation>,
+    ) {
+        for line in lines {
+            let path = if parent.is_empty() {
+                line.line.name.clone()
+            } else {
+                format!("{}/{}", parent, line.line.name)
+            };
+
+            for note in &mut line.notes {
+                let (quantized, distance) = self.note(note);
+                if self.exceeds(distance) {
+                    deviations.push(Deviation {
+                        path: path.clone(),
+                        item: QuantizedItem::Note(*note),
+                        distance,
+                    });
+                }
+                *note = quantized;
+            }
+
+            for event in &mut line.events {
+                let (quantized, distance) = self.event(event);
+                if self.exceeds(distance) {
+                    deviations.push(Deviation {
+                        path: path.clone(),
+                        item: QuantizedItem::Event(*event),
+                        distance,
+                    });
+                }
+                *event = quantized;
+            }
+
+            self.lines_inner(&mut line.children, &path, deviations);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beat;
+    use crate::event::{LineEventKind, LineEventValue};
+    use crate::line::Line;
+    use crate::note::NoteKind;
+
+    #[test]
+    fn test_beat() {
+        let quantize = Quantize::new(4, 0.01);
+        assert_eq!(quantize.beat(beat!(1, 63, 256)), beat!(1, 1, 4));
+        assert_eq!(quantize.beat(beat!(2, 1, 3)), beat!(2, 1, 4));
+        assert_eq!(quantize.beat(beat!(0, 127, 128)), beat!(1));
+    }
+
+    #[test]
+    fn test_note() {
+        let quantize = Quantize::new(4, 0.01);
+
+        let tap = Note::new(NoteKind::Tap, true, beat!(1, 65, 256), 0.0, 1.0);
+        let (quantized, distance) = quantize.note(&tap);
+        assert_eq!(quantized.beat, beat!(1, 1, 4));
+        assert_eq!(distance, 1.0 / 256.0);
+        assert!(!quantize.exceeds(distance));
+
+        // a hold collapsing to its start keeps one grid line
+        let hold = Note::new(
+            NoteKind::Hold {
+                hold_beat: beat!(0, 1, 64),
+            },
+            true,
+            beat!(3),
+            0.0,
+            1.0,
+        );
+        let (quantized, _) = quantize.note(&hold);
+        assert_eq!(quantized.beat, beat!(3));
+        assert_eq!(quantized.hold_beat(), Some(&beat!(0, 1, 4)));
+    }
+
+    #[test]
+    fn test_lines() {
+        let quantize = Quantize::new(4, 0.01);
+        let event = LineEvent {
+            id: None,
+            kind: LineEventKind::X,
+            start_beat: beat!(0, 1, 3),
+            end_beat: beat!(2, 255, 256),
+            value: LineEventValue::constant(0.0),
+        };
+        let child = SerializedLine::new(
+            Line {
+                name: "child".to_owned(),
+                ..Default::default()
+            },
+            vec![],
+            vec![event],
+            vec![],
+            vec![],
+        );
+        let mut lines = vec![SerializedLine::new(
+            Line {
+                name: "parent".to_owned(),
+                ..Default::default()
+            },
+            vec![Note::new(NoteKind::Tap, true, beat!(1), 0.0, 1.0)],
+            vec![],
+            vec![child],
+            vec![],
+        )];
+
+        let deviations = quantize.lines(&mut lines);
+
+        assert_eq!(deviations.len(), 1);
+        assert_eq!(deviations[0].path, "parent/child");
+        assert_eq!(deviations[0].item, QuantizedItem::Event(event));
+        assert_eq!(lines[0].children[0].events[0].start_beat, beat!(0, 1, 4));
+        assert_eq!(lines[0].children[0].events[0].end_beat, beat!(3));
+    }
+}
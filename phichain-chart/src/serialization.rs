@@ -3,11 +3,13 @@ use serde::{Deserialize, Serialize};
 
 use crate::bpm_list::BpmList;
 use crate::curve_note_track::CurveNoteTrack;
-use crate::event::{LineEvent, LineEventKind, LineEventValue};
+use crate::event::{LineEvent, LineEventKind, LineEventValue, TextEvent};
+use crate::id::Id;
 use crate::line::Line;
 use crate::migration::{migrate, CURRENT_FORMAT};
 use crate::note::Note;
 use crate::offset::Offset;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -40,6 +42,8 @@ impl PhichainChart {
     ///
     /// Charts already at the latest format are deserialized directly,
     /// skipping the costly [`serde_json::Value`] intermediate representation
+    ///
+    /// Lines, notes and events without an [`Id`] are given one, see [`PhichainChart::assign_ids`]
     pub fn from_json_str(json: &str) -> Result<Self, ParseChartError> {
         #[derive(Deserialize)]
         struct FormatProbe {
@@ -49,13 +53,84 @@ impl PhichainChart {
 
         let probe: FormatProbe = serde_json::from_str(json)?;
 
-        if probe.format == CURRENT_FORMAT {
-            Ok(serde_json::from_str(json)?)
+        let mut chart: Self = if probe.format == CURRENT_FORMAT {
+            serde_json::from_str(json)?
         } else {
             let value: serde_json::Value = serde_json::from_str(json)?;
             let migrated = migrate(&value).map_err(ParseChartError::MigrationFailed)?;
-            Ok(serde_json::from_value(migrated)?)
+            serde_json::from_value(migrated)?
+        };
+        chart.assign_ids();
+
+        Ok(chart)
+    }
+
+    /// The smallest ID greater than every [`Id`] in the chart, `0` for a chart without any
+    pub fn next_id(&self) -> u64 {
+        fn next(lines: &[SerializedLine]) -> Option<u64> {
+            lines
+                .iter()
+                .flat_map(|line| {
+                    line.line
+                        .id
+                        .into_iter()
+                        .chain(line.notes.iter().filter_map(|x| x.id))
+                        .chain(line.events.iter().filter_map(|x| x.id))
+                        .map(|id| id.0 + 1)
+                        .chain(next(&line.children))
+                })
+                .max()
+        }
+
+        next(&self.lines).unwrap_or_default()
+    }
+
+    /// Give a new [`Id`] to every line, note and event without one or sharing it with an item before
+    ///
+    /// New IDs are greater than every ID in the chart, IDs already assigned are left untouched.
+    /// Curve note tracks referring to a note given a new ID are updated accordingly
+    pub fn assign_ids(&mut self) {
+        fn visit(lines: &mut [SerializedLine], f: &mut impl FnMut(&mut Option<Id>)) {
+            for line in lines {
+                f(&mut line.line.id);
+
+                let mut remapped = HashMap::new();
+                for note in &mut line.notes {
+                    let old = note.id;
+                    f(&mut note.id);
+                    if let (Some(old), Some(new)) = (old, note.id) {
+                        if old != new {
+                            remapped.entry(old).or_insert(new);
+                        }
+                    }
+                }
+                // tracks keep referring to the notes of the line whose ID is taken by another item
+                let ids = line
+                    .notes
+                    .iter()
+                    .filter_map(|x| x.id)
+                    .collect::<HashSet<_>>();
+                for track in &mut line.curve_note_tracks {
+                    for id in [&mut track.from, &mut track.to] {
+                        if !ids.contains(id) {
+                            *id = remapped.get(id).copied().unwrap_or(*id);
+                        }
+                    }
+                }
+
+                line.events.iter_mut().for_each(|event| f(&mut event.id));
+                visit(&mut line.children, f);
+            }
         }
+
+        let mut next = self.next_id();
+        let mut seen = HashSet::new();
+        visit(&mut self.lines, &mut |id| {
+            if !id.is_some_and(|id| seen.insert(id)) {
+                *id = Some(Id(next));
+                next += 1;
+            }
+        });
     }
 }
 
@@ -81,7 +156,7 @@ impl Default for PhichainChart {
 }
 
 /// A wrapper struct to handle line serialization and deserialization
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SerializedLine {
     #[serde(flatten)]
     pub line: Line,
@@ -89,6 +164,8 @@ pub struct SerializedLine {
     pub events: Vec<LineEvent>,
     pub children: Vec<SerializedLine>,
     pub curve_note_tracks: Vec<CurveNoteTrack>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub text_events: Vec<TextEvent>,
 }
 
 impl SerializedLine {
@@ -105,6 +182,7 @@ impl SerializedLine {
             events,
             children,
             curve_note_tracks,
+            text_events: vec![],
         }
     }
 }
@@ -117,30 +195,35 @@ impl Default for SerializedLine {
             notes: Default::default(),
             events: vec![
                 LineEvent {
+                    id: None,
                     kind: LineEventKind::X,
                     value: LineEventValue::constant(0.0),
                     start_beat: Beat::ZERO,
                     end_beat: Beat::ONE,
                 },
                 LineEvent {
+                    id: None,
                     kind: LineEventKind::Y,
                     value: LineEventValue::constant(0.0),
                     start_beat: Beat::ZERO,
                     end_beat: Beat::ONE,
                 },
                 LineEvent {
+                    id: None,
                     kind: LineEventKind::Rotation,
                     value: LineEventValue::constant(0.0),
                     start_beat: Beat::ZERO,
                     end_beat: Beat::ONE,
                 },
                 LineEvent {
+                    id: None,
                     kind: LineEventKind::Opacity,
                     value: LineEventValue::constant(0.0),
                     start_beat: Beat::ZERO,
                     end_beat: Beat::ONE,
                 },
                 LineEvent {
+                    id: None,
                     kind: LineEventKind::Speed,
                     value: LineEventValue::constant(10.0),
                     start_beat: Beat::ZERO,
@@ -149,6 +232,7 @@ impl Default for SerializedLine {
             ],
             children: vec![],
             curve_note_tracks: vec![],
+            text_events: vec![],
         }
     }
 }
@@ -178,6 +262,66 @@ mod tests {
         assert_eq!(chart.lines.len(), 1);
     }
 
+    #[test]
+    fn test_assign_ids() {
+        let mut chart = PhichainChart::default();
+        chart.lines[0].line.id = Some(Id(3));
+        chart.lines[0].events[1].id = Some(Id(3));
+        chart.lines[0].events[2].id = Some(Id(1));
+        chart.lines[0].notes.push(Note::new(
+            crate::note::NoteKind::Tap,
+            true,
+            Beat::ZERO,
+            0.0,
+            1.0,
+        ));
+        assert_eq!(chart.next_id(), 4);
+        chart.assign_ids();
+        assert_eq!(chart.next_id(), 9);
+
+        let line = &chart.lines[0];
+        assert_eq!(line.line.id, Some(Id(3)));
+        assert_eq!(line.notes[0].id, Some(Id(4)));
+        let events = line.events.iter().map(|x| x.id).collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                Some(Id(5)),
+                Some(Id(6)),
+                Some(Id(1)),
+                Some(Id(7)),
+                Some(Id(8))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_assign_ids_updates_curve_note_tracks() {
+        let mut chart = PhichainChart::default();
+        let note = |id| Note {
+            id: Some(Id(id)),
+            ..Note::new(crate::note::NoteKind::Tap, true, Beat::ZERO, 0.0, 1.0)
+        };
+        let track = |from, to| CurveNoteTrack {
+            from: Id(from),
+            to: Id(to),
+            options: Default::default(),
+        };
+        chart.lines[0].line.id = Some(Id(0));
+        chart.lines[0].events.clear();
+        chart.lines.push(SerializedLine {
+            notes: vec![note(0), note(1)],
+            events: vec![],
+            curve_note_tracks: vec![track(0, 1)],
+            ..Default::default()
+        });
+        chart.assign_ids();
+
+        let line = &chart.lines[1];
+        assert_eq!(line.notes[0].id, Some(Id(3)));
+        assert_eq!(line.curve_note_tracks, vec![track(3, 1)]);
+    }
+
     #[test]
     fn test_from_json_str_invalid() {
         assert!(matches!(
@@ -73,6 +73,7 @@ pub fn fit_easing(events: &[LineEvent], epsilon: f32) -> Result<LineEvent, Vec<L
 
     for easing in EASING_FITTING_POSSIBLE_EASINGS {
         let target_event = LineEvent {
+            id: None,
             kind: first.kind,
             start_beat: first.start_beat,
             end_beat: last.end_beat,
@@ -208,6 +209,7 @@ pub fn cut_with_options(event: LineEvent, minimum: Beat, options: CutOptions) ->
 
             if start == end {
                 return vec![LineEvent {
+                    id: None,
                     kind: event.kind,
                     start_beat: event.start_beat,
                     end_beat: event.end_beat,
@@ -229,6 +231,7 @@ pub fn cut_with_options(event: LineEvent, minimum: Beat, options: CutOptions) ->
                 let end_value = event.evaluate_inclusive(end_beat.value()).value().unwrap();
 
                 events.push(LineEvent {
+                    id: None,
                     kind: event.kind,
                     start_beat,
                     end_beat,
@@ -332,6 +335,7 @@ pub fn fill_gap_until(
     for event in sorted(events) {
         if event.start_beat > last_end {
             filled.push(LineEvent {
+                id: None,
                 kind,
                 start_beat: last_end,
                 end_beat: event.start_beat,
@@ -346,6 +350,7 @@ pub fn fill_gap_until(
 
     if last_end < until {
         filled.push(LineEvent {
+            id: None,
             kind,
             start_beat: last_end,
             end_beat: until,
@@ -378,6 +383,7 @@ mod tests {
     #[test]
     fn cut_preserves_tail_for_non_divisible_duration() {
         let source = LineEvent {
+            id: None,
             kind: LineEventKind::X,
             start_beat: beat!(0),
             end_beat: beat!(1, 3),
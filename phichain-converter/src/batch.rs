@@ -0,0 +1,290 @@
+//! Batch mode: convert every chart under a directory or matching a glob pattern
+
+use crate::error::ConvertError;
+use crate::{
+    convert_chart, parse_chart, print_deviations, read_input, track, Args, ConvertOptions, Format,
+};
+use owo_colors::OwoColorize;
+use phichain_chart::quantize::Deviation;
+use phichain_i18n::locale;
+use rayon::prelude::*;
+use rust_i18n::t;
+use serde::Serialize;
+use std::any::Any;
+use std::collections::{BTreeMap, HashSet};
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Extensions of files picked up when the input is a directory
+const CHART_EXTENSIONS: [&str; 2] = ["json", "pez"];
+
+#[derive(Serialize)]
+struct BatchTelemetry {
+    locale: String,
+    from: Option<Format>,
+    to: Format,
+    total: usize,
+    succeeded: usize,
+    error_kinds: BTreeMap<&'static str, usize>,
+    duration_ms: u64,
+    options: serde_json::Value,
+}
+
+/// The outcome of converting a single file
+struct Entry {
+    /// Path of the input, relative to the input root
+    relative: PathBuf,
+    /// The inferred or provided input format on success
+    result: Result<Format, ConvertError>,
+    /// The items quantization moved further than the tolerance
+    deviations: Vec<Deviation>,
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// The leading components of a glob pattern without any wildcard, e.g. `charts/` for `charts/**/*.json`
+fn glob_root(pattern: &Path) -> PathBuf {
+    pattern
+        .components()
+        .take_while(|component| !is_glob(&component.as_os_str().to_string_lossy()))
+        .collect()
+}
+
+/// Collect the files to convert, along with the root their relative paths are based on
+fn collect_inputs(input: &Path, output: &Path) -> Result<(PathBuf, Vec<PathBuf>), ConvertError> {
+    let (root, mut files) = if input.is_dir() {
+        let files = WalkDir::new(input)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| CHART_EXTENSIONS.iter().any(|x| extension == *x))
+            })
+            .collect::<Vec<_>>();
+        (input.to_path_buf(), files)
+    } else if is_glob(&input.to_string_lossy()) {
+        let files = glob::glob(&input.to_string_lossy())?
+            .filter_map(Result::ok)
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+        (glob_root(input), files)
+    } else {
+        return Err(ConvertError::ExpectedDirectoryOrGlob(input.to_path_buf()));
+    };
+
+    // do not pick up the outputs of a previous run, canonicalized so `./output` and `output` match
+    if let Ok(output) = output.canonicalize() {
+        files.retain(|path| {
+            path.canonicalize()
+                .map_or(true, |path| !path.starts_with(&output))
+        });
+    }
+    files.sort();
+
+    if files.is_empty() {
+        return Err(ConvertError::NoChartsFound(input.to_path_buf()));
+    }
+
+    Ok((root, files))
+}
+
+/// The path of a file relative to the input root, or its file name if it is the root itself
+fn relative_path(path: &Path, root: &Path) -> PathBuf {
+    path.strip_prefix(root)
+        .ok()
+        .filter(|relative| !relative.as_os_str().is_empty())
+        .or_else(|| path.file_name().map(Path::new))
+        .unwrap_or(path)
+        .to_path_buf()
+}
+
+/// The output path of each file, relative to the output directory
+///
+/// Each file is written to its relative path with a `.json` extension. Files whose output would
+/// collide with an earlier one, e.g. `a.pez` after `a.json`, keep their extension (`a.pez.json`),
+/// followed by a counter if that is taken as well
+fn output_paths(relatives: &[PathBuf]) -> Vec<PathBuf> {
+    let mut taken = HashSet::new();
+    relatives
+        .iter()
+        .map(|relative| {
+            let file_name = relative.file_name().unwrap_or_default().to_string_lossy();
+            let mut path = relative.with_extension("json");
+            let mut index = 0;
+            while taken.contains(&path) {
+                path = relative.with_file_name(if index == 0 {
+                    format!("{file_name}.json")
+                } else {
+                    format!("{file_name}({index}).json")
+                });
+                index += 1;
+            }
+            taken.insert(path.clone());
+            path
+        })
+        .collect()
+}
+
+fn convert_file(
+    input: &Path,
+    output: &Path,
+    from: Option<Format>,
+    to: Format,
+    options: &ConvertOptions,
+) -> Result<(Format, Vec<Deviation>), ConvertError> {
+    let content = read_input(input)?;
+    let (chart, from, _) = parse_chart(&content, from)?;
+    let (chart, deviations) = convert_chart(chart, to, options)?;
+    let chart = chart.apply_common_output_options(&options.common_output);
+
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    serde_json::to_writer(std::fs::File::create(output)?, &chart)?;
+
+    Ok((from, deviations))
+}
+
+/// The message of a panic payload, which is a `&str` or a `String` for panics raised by `panic!`
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string())
+}
+
+fn print_summary(entries: &[Entry], to: Format) {
+    let width = entries
+        .iter()
+        .map(|entry| entry.relative.display().to_string().chars().count())
+        .max()
+        .unwrap_or_default();
+
+    for entry in entries {
+        let input = format!("{:<width$}", entry.relative.display().to_string());
+        match &entry.result {
+            Ok(from) => eprintln!(
+                "{}  {}  {} -> {}",
+                t!("cli.batch_summary.ok").green(),
+                input,
+                from.to_string().cyan(),
+                to.to_string().green()
+            ),
+            Err(error) => eprintln!(
+                "{}  {}  {}",
+                t!("cli.batch_summary.failed").red(),
+                input,
+                error.red()
+            ),
+        }
+    }
+
+    let succeeded = entries.iter().filter(|entry| entry.result.is_ok()).count();
+    eprintln!();
+    eprintln!(
+        "{}",
+        t!(
+            "cli.batch_summary.total",
+            total = entries.len(),
+            succeeded = succeeded.green(),
+            failed = (entries.len() - succeeded).red()
+        )
+    );
+}
+
+/// Convert all charts matched by the input into the output directory, mirroring the directory tree
+///
+/// A chart failing to convert does not stop the others, returns whether all charts were converted
+pub fn run(args: Args) -> Result<bool, ConvertError> {
+    let start = std::time::Instant::now();
+
+    let options = ConvertOptions::from(&args);
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from("output"));
+    let (root, files) = collect_inputs(&args.input, &output)?;
+
+    let relatives = files
+        .iter()
+        .map(|path| relative_path(path, &root))
+        .collect::<Vec<_>>();
+    // resolved up front, converting in parallel must not let charts with the same name overwrite each other
+    let outputs = output_paths(&relatives);
+
+    let entries = files
+        .par_iter()
+        .zip(relatives)
+        .zip(outputs)
+        .map(|((path, relative), target)| {
+            // a chart panicking the converter must not abort the whole batch
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                convert_file(path, &output.join(target), args.from, args.to, &options)
+            }))
+            .unwrap_or_else(|payload| Err(ConvertError::Panicked(panic_message(&*payload))));
+            match result {
+                Ok((from, deviations)) => Entry {
+                    relative,
+                    result: Ok(from),
+                    deviations,
+                },
+                Err(error) => Entry {
+                    relative,
+                    result: Err(error),
+                    deviations: vec![],
+                },
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // printed once all charts are converted, so the output of different charts does not interleave
+    if let Some(quantize) = &options.quantize {
+        for entry in &entries {
+            print_deviations(
+                &entry.relative.display().to_string(),
+                quantize,
+                &entry.deviations,
+            );
+        }
+    }
+
+    print_summary(&entries, args.to);
+
+    let mut error_kinds: BTreeMap<&str, usize> = BTreeMap::new();
+    for error in entries
+        .iter()
+        .filter_map(|entry| entry.result.as_ref().err())
+    {
+        *error_kinds.entry(error.variant_name()).or_default() += 1;
+    }
+
+    if !args.no_telemetry {
+        track(
+            "phichain.converter.batch",
+            BatchTelemetry {
+                locale: locale(),
+                from: args.from,
+                to: args.to,
+                total: entries.len(),
+                succeeded: entries.len() - error_kinds.values().sum::<usize>(),
+                error_kinds,
+                duration_ms: start.elapsed().as_millis() as u64,
+                options: serde_json::json!({
+                    "official_input": &args.official_input_options,
+                    "official_output": &args.official_output_options,
+                    "rpe_input": &args.rpe_input_options,
+                    "common_output": &args.common_output_options,
+                    "quantize": &args.quantize_options,
+                }),
+            },
+        );
+    }
+
+    Ok(entries.iter().all(|entry| entry.result.is_ok()))
+}
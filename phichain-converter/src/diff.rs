@@ -0,0 +1,145 @@
+//! The `diff` subcommand, which compares two charts semantically
+
+use crate::error::ConvertError;
+use crate::inspect::{load, InputOptions};
+use crate::Format;
+use owo_colors::OwoColorize;
+use phichain_chart::bpm_list::BpmPoint;
+use phichain_chart::diff::{ChartDiff, ItemDiff, LineDiff};
+use rust_i18n::t;
+use std::fmt::Debug;
+use std::path::PathBuf;
+
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+    #[arg(required = true, help = t!("cli.diff.old").to_string())]
+    old: PathBuf,
+
+    #[arg(required = true, help = t!("cli.diff.new").to_string())]
+    new: PathBuf,
+
+    #[arg(long, help = t!("cli.from").to_string())]
+    from: Option<Format>,
+
+    #[arg(long, help = t!("cli.diff.json").to_string())]
+    json: bool,
+
+    #[command(flatten)]
+    input_options: InputOptions,
+}
+
+fn print_bpm_list(sign: &str, points: &[BpmPoint]) {
+    for point in points {
+        println!(
+            "  {} {}",
+            sign,
+            t!(
+                "cli.info.bpm_point",
+                beat = format!("{:?}", point.beat),
+                bpm = point.bpm
+            )
+        );
+    }
+}
+
+fn print_items<T: Debug>(label: &str, items: &[ItemDiff<T>]) {
+    for item in items {
+        match item {
+            ItemDiff::Added { value } => {
+                println!("    {}", format!("+ {label} {value:?}").green())
+            }
+            ItemDiff::Removed { value } => {
+                println!("    {}", format!("- {label} {value:?}").red())
+            }
+            ItemDiff::Modified { old, new } => {
+                println!("    {}", format!("~ {label}").yellow());
+                println!("        {}", format!("- {old:?}").red());
+                println!("        {}", format!("+ {new:?}").green());
+            }
+        }
+    }
+}
+
+fn print_diff(diff: &ChartDiff) {
+    if let Some(offset) = &diff.offset {
+        println!(
+            "{}",
+            t!("cli.diff.offset", old = offset.old, new = offset.new).yellow()
+        );
+    }
+
+    if let Some(bpm_list) = &diff.bpm_list {
+        println!("{}", t!("cli.diff.bpm_list").yellow());
+        print_bpm_list(&"-".red().to_string(), &bpm_list.old);
+        print_bpm_list(&"+".green().to_string(), &bpm_list.new);
+    }
+
+    for line in &diff.lines {
+        match line {
+            LineDiff::Added {
+                path,
+                notes,
+                events,
+            } => println!(
+                "{}",
+                format!(
+                    "+ {}",
+                    t!("cli.diff.line", path = path, notes = notes, events = events)
+                )
+                .green()
+            ),
+            LineDiff::Removed {
+                path,
+                notes,
+                events,
+            } => println!(
+                "{}",
+                format!(
+                    "- {}",
+                    t!("cli.diff.line", path = path, notes = notes, events = events)
+                )
+                .red()
+            ),
+            LineDiff::Modified {
+                path,
+                line,
+                notes,
+                events,
+            } => {
+                println!("{}", format!("~ {path}").yellow());
+                if let Some(line) = line {
+                    println!(
+                        "    {}",
+                        format!("~ {}", t!("cli.diff.attributes")).yellow()
+                    );
+                    println!("        {}", format!("- {:?}", line.old).red());
+                    println!("        {}", format!("+ {:?}", line.new).green());
+                }
+                print_items(&t!("cli.diff.note"), notes);
+                print_items(&t!("cli.diff.event"), events);
+            }
+        }
+    }
+}
+
+/// Compare two charts, exits with `1` if they differ like `diff` does
+pub fn run(args: DiffArgs) -> Result<(), ConvertError> {
+    let (_, old) = load(&args.old, args.from, &args.input_options)?;
+    let (_, new) = load(&args.new, args.from, &args.input_options)?;
+
+    let diff = ChartDiff::new(&old, &new);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else if diff.is_empty() {
+        println!("{}", t!("cli.diff.identical").green());
+    } else {
+        print_diff(&diff);
+    }
+
+    if !diff.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
@@ -1,3 +1,4 @@
+use phichain_format::detect::DetectError;
 use rust_i18n::t;
 use std::path::PathBuf;
 use thiserror::Error;
@@ -6,12 +7,18 @@ use thiserror::Error;
 pub enum ConvertError {
     NoSuchFile(PathBuf),
     ExpectedFile(PathBuf),
-    UnableToInferFormat,
+    ExpectedDirectoryOrGlob(PathBuf),
+    NoChartsFound(PathBuf),
+    /// Converting a chart panicked, with the panic message
+    Panicked(String),
+    Detect(#[from] phichain_format::detect::DetectError),
     Io(#[from] std::io::Error),
     Json(#[from] serde_json::Error),
+    Pattern(#[from] glob::PatternError),
     OfficialInput(#[from] phichain_format::official::OfficialInputError),
     OfficialOutput(#[from] phichain_format::official::OfficialOutputError),
     RpeInput(#[from] phichain_format::rpe::RpeInputError),
+    Pez(#[from] phichain_format::pez::PezError),
 }
 
 impl std::fmt::Display for ConvertError {
@@ -27,14 +34,56 @@ impl std::fmt::Display for ConvertError {
                     t!("cli.error.expected_file", path = path.display())
                 )
             }
-            ConvertError::UnableToInferFormat => {
-                write!(f, "{}", t!("cli.error.unable_to_infer_format"))
+            ConvertError::ExpectedDirectoryOrGlob(path) => {
+                write!(
+                    f,
+                    "{}",
+                    t!(
+                        "cli.error.expected_directory_or_glob",
+                        path = path.display()
+                    )
+                )
+            }
+            ConvertError::NoChartsFound(path) => {
+                write!(
+                    f,
+                    "{}",
+                    t!("cli.error.no_charts_found", path = path.display())
+                )
+            }
+            ConvertError::Panicked(message) => {
+                write!(f, "{}", t!("cli.error.panicked", message = message))
+            }
+            ConvertError::Detect(DetectError::Unrecognized(candidates)) => {
+                write!(f, "{}", t!("cli.error.unable_to_infer_format"))?;
+                for candidate in candidates {
+                    let keys = candidate
+                        .sniff
+                        .missing
+                        .iter()
+                        .map(|key| format!("`{key}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    write!(
+                        f,
+                        "\n    {}",
+                        t!(
+                            "cli.error.missing_keys",
+                            format = candidate.format,
+                            keys = keys
+                        )
+                    )?;
+                }
+                Ok(())
             }
+            ConvertError::Detect(e) => write!(f, "{e}"),
             ConvertError::Io(e) => write!(f, "{e}"),
             ConvertError::Json(e) => write!(f, "{e}"),
+            ConvertError::Pattern(e) => write!(f, "{e}"),
             ConvertError::OfficialInput(e) => write!(f, "{e}"),
             ConvertError::OfficialOutput(e) => write!(f, "{e}"),
             ConvertError::RpeInput(e) => write!(f, "{e}"),
+            ConvertError::Pez(e) => write!(f, "{e}"),
         }
     }
 }
@@ -44,12 +93,17 @@ impl ConvertError {
         match self {
             ConvertError::NoSuchFile(_) => "NoSuchFile",
             ConvertError::ExpectedFile(_) => "ExpectedFile",
-            ConvertError::UnableToInferFormat => "UnableToInferFormat",
+            ConvertError::ExpectedDirectoryOrGlob(_) => "ExpectedDirectoryOrGlob",
+            ConvertError::NoChartsFound(_) => "NoChartsFound",
+            ConvertError::Panicked(_) => "Panicked",
+            ConvertError::Detect(_) => "Detect",
             ConvertError::Io(_) => "Io",
             ConvertError::Json(_) => "Json",
+            ConvertError::Pattern(_) => "Pattern",
             ConvertError::OfficialInput(_) => "OfficialInput",
             ConvertError::OfficialOutput(_) => "OfficialOutput",
             ConvertError::RpeInput(_) => "RpeInput",
+            ConvertError::Pez(_) => "Pez",
         }
     }
 }
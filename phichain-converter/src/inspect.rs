@@ -0,0 +1,201 @@
+//! The `validate`, `info`, `diff` and `report` subcommands, which load charts without converting them
+
+use crate::diff::{self, DiffArgs};
+use crate::error::ConvertError;
+use crate::options::{CliOfficialInputOptions, CliRpeInputOptions};
+use crate::report::{self, ReportArgs};
+use crate::{parse_chart, read_input, Format};
+use clap::Subcommand;
+use owo_colors::OwoColorize;
+use phichain_chart::beat::Beat;
+use phichain_chart::metrics::ChartMetrics;
+use phichain_chart::note::NoteKind;
+use phichain_chart::serialization::{PhichainChart, SerializedLine};
+use phichain_format::official::check_phichain_to_official;
+use phichain_i18n::i18n_str;
+use rust_i18n::t;
+use std::path::{Path, PathBuf};
+
+/// Subcommands of the converter, which converts the input chart if none is given
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    #[command(about = i18n_str!("cli.validate.about"))]
+    Validate(InspectArgs),
+    #[command(about = i18n_str!("cli.info.about"))]
+    Info(InspectArgs),
+    #[command(about = i18n_str!("cli.diff.about"))]
+    Diff(DiffArgs),
+    #[command(about = i18n_str!("cli.report.about"))]
+    Report(ReportArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct InspectArgs {
+    #[arg(required = true, help = t!("cli.input").to_string())]
+    input: PathBuf,
+
+    #[arg(long, help = t!("cli.from").to_string())]
+    from: Option<Format>,
+
+    #[command(flatten)]
+    input_options: InputOptions,
+}
+
+/// Options for loading the input charts, shared by the subcommands
+#[derive(clap::Args, Debug)]
+pub struct InputOptions {
+    #[command(flatten)]
+    #[command(
+        next_help_heading = i18n_str!("cli.official_input.heading")
+    )]
+    official_input_options: CliOfficialInputOptions,
+
+    #[command(flatten)]
+    #[command(
+        next_help_heading = i18n_str!("cli.rpe_input.heading")
+    )]
+    rpe_input_options: CliRpeInputOptions,
+}
+
+/// Run one of the `validate`, `info`, `diff` and `report` subcommands
+pub fn run(command: Command) -> Result<(), ConvertError> {
+    match command {
+        Command::Validate(args) => validate(args),
+        Command::Info(args) => info(args),
+        Command::Diff(args) => diff::run(args),
+        Command::Report(args) => report::run(args),
+    }
+}
+
+/// Read and parse an input chart, then convert it into a [`PhichainChart`]
+pub fn load(
+    path: &Path,
+    from: Option<Format>,
+    options: &InputOptions,
+) -> Result<(Format, PhichainChart), ConvertError> {
+    let input = read_input(path)?;
+    let (chart, from, inferred) = parse_chart(&input, from)?;
+
+    if inferred {
+        eprintln!(
+            "{}",
+            t!(
+                "cli.status.inferred_format",
+                format = from.to_string().cyan()
+            )
+        );
+    }
+
+    let phichain = chart.into_phichain(
+        &options.official_input_options.clone().into(),
+        &options.rpe_input_options.clone().into(),
+    )?;
+
+    Ok((from, phichain))
+}
+
+fn validate(args: InspectArgs) -> Result<(), ConvertError> {
+    let (_, phichain) = load(&args.input, args.from, &args.input_options)?;
+
+    let errors = check_phichain_to_official(phichain);
+    for error in &errors {
+        println!("{} {}", "-".red(), error);
+    }
+
+    if errors.is_empty() {
+        println!("{}", t!("cli.validate.valid").green());
+    } else {
+        println!("{}", t!("cli.validate.invalid", count = errors.len()).red());
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn for_each_line(lines: &[SerializedLine], f: &mut impl FnMut(&SerializedLine)) {
+    for line in lines {
+        f(line);
+        for_each_line(&line.children, f);
+    }
+}
+
+/// The maximum nesting depth of child lines, `0` if no line has children
+fn max_depth(lines: &[SerializedLine]) -> usize {
+    lines
+        .iter()
+        .filter(|line| !line.children.is_empty())
+        .map(|line| 1 + max_depth(&line.children))
+        .max()
+        .unwrap_or_default()
+}
+
+fn info(args: InspectArgs) -> Result<(), ConvertError> {
+    let (from, phichain) = load(&args.input, args.from, &args.input_options)?;
+
+    let metrics = ChartMetrics::collect(&phichain.lines);
+
+    let (mut tap, mut drag, mut hold, mut flick, mut fake) = (0, 0, 0, 0, 0);
+    let mut end_beat = Beat::ZERO;
+    for_each_line(&phichain.lines, &mut |line| {
+        for note in &line.notes {
+            match note.kind {
+                NoteKind::Tap => tap += 1,
+                NoteKind::Drag => drag += 1,
+                NoteKind::Hold { .. } => hold += 1,
+                NoteKind::Flick => flick += 1,
+            }
+            if note.is_fake {
+                fake += 1;
+            }
+            end_beat = end_beat.max(note.end_beat());
+        }
+    });
+
+    println!(
+        "{}",
+        t!("cli.info.format", format = from.to_string().cyan())
+    );
+    println!(
+        "{}",
+        t!(
+            "cli.info.lines",
+            lines = metrics.lines,
+            depth = max_depth(&phichain.lines)
+        )
+    );
+    println!("{}", t!("cli.info.notes", notes = metrics.notes));
+    println!(
+        "{}",
+        t!(
+            "cli.info.note_kinds",
+            tap = tap,
+            drag = drag,
+            hold = hold,
+            flick = flick,
+            fake = fake
+        )
+    );
+    println!("{}", t!("cli.info.events", events = metrics.events));
+    let duration = if phichain.bpm_list.0.is_empty() {
+        0.0
+    } else {
+        phichain.bpm_list.time_at(end_beat)
+    };
+    println!(
+        "{}",
+        t!("cli.info.duration", duration = format!("{:.2}", duration))
+    );
+    println!("{}", t!("cli.info.bpm_list"));
+    for point in &phichain.bpm_list.0 {
+        println!(
+            "  {}",
+            t!(
+                "cli.info.bpm_point",
+                beat = format!("{:?}", point.beat),
+                bpm = point.bpm
+            )
+        );
+    }
+
+    Ok(())
+}
@@ -1,16 +1,22 @@
+mod batch;
+mod diff;
 mod error;
+mod inspect;
 mod options;
+mod report;
 
 use crate::error::{unwrap_infallible, ConvertError};
 use crate::options::{
-    CliCommonOutputOptions, CliOfficialInputOptions, CliOfficialOutputOptions, CliRpeInputOptions,
+    CliCommonOutputOptions, CliOfficialInputOptions, CliOfficialOutputOptions, CliQuantizeOptions,
+    CliRpeInputOptions,
 };
 use clap::{Parser, ValueEnum};
 use owo_colors::OwoColorize;
 use phichain_chart::metrics::ChartMetrics;
+use phichain_chart::quantize::{Deviation, Quantize, QuantizedItem};
 use phichain_chart::serialization::PhichainChart;
-use phichain_format::official::OfficialChart;
-use phichain_format::rpe::RpeChart;
+use phichain_format::official::{OfficialChart, OfficialInputOptions, OfficialOutputOptions};
+use phichain_format::rpe::{RpeChart, RpeInputOptions};
 use phichain_format::{ChartFormat, CommonOutputOptions};
 use phichain_i18n::{i18n_str, locale};
 use rust_i18n::t;
@@ -30,6 +36,18 @@ enum Chart {
 }
 
 impl Chart {
+    fn into_phichain(
+        self,
+        official_input: &OfficialInputOptions,
+        rpe_input: &RpeInputOptions,
+    ) -> Result<PhichainChart, ConvertError> {
+        Ok(match self {
+            Chart::Official(official) => official.to_phichain(official_input)?,
+            Chart::Phichain(phichain) => unwrap_infallible(phichain.to_phichain(&())),
+            Chart::Rpe(rpe) => rpe.to_phichain(rpe_input)?,
+        })
+    }
+
     fn apply_common_output_options(self, common_options: &CommonOutputOptions) -> Self {
         match self {
             Chart::Official(chart) => {
@@ -53,20 +71,33 @@ enum Format {
     Rpe,
 }
 
-#[derive(Parser, Debug, Clone)]
+#[derive(Parser, Debug)]
 #[command(name = "phichain-converter")]
 #[command(about = i18n_str!("cli.about"))]
 #[command(after_help = i18n_str!("cli.examples"))]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<inspect::Command>,
+
+    /// Arguments to convert the input chart, present unless a subcommand is given
+    #[command(flatten)]
+    args: Option<Args>,
+}
+
+#[derive(clap::Args, Debug, Clone)]
 pub struct Args {
     #[arg(required = true, help = t!("cli.input").to_string())]
     input: PathBuf,
-    #[arg(required = false, default_value = "output.json", help = t!("cli.output").to_string())]
-    output: PathBuf,
+    #[arg(required = false, help = t!("cli.output").to_string())]
+    output: Option<PathBuf>,
 
     #[arg(long, help = t!("cli.from").to_string())]
     from: Option<Format>,
     #[arg(long, help = t!("cli.to").to_string())]
     to: Format,
+    #[arg(long, help = t!("cli.batch").to_string())]
+    batch: bool,
 
     #[command(flatten)]
     #[command(
@@ -92,29 +123,24 @@ pub struct Args {
     )]
     common_output_options: CliCommonOutputOptions,
 
+    #[command(flatten)]
+    #[command(
+        next_help_heading = i18n_str!("cli.quantize.heading")
+    )]
+    quantize_options: CliQuantizeOptions,
+
     #[arg(long, help = t!("cli.no_telemetry").to_string())]
     no_telemetry: bool,
 }
 
-fn infer_format(content: &str) -> Result<Format, ConvertError> {
-    let value: serde_json::Value = serde_json::from_str(content)?;
-
-    if value.get("BPMList").is_some() && value.get("META").is_some() {
-        return Ok(Format::Rpe);
-    }
-
-    if value.get("formatVersion").is_some() && value.get("judgeLineList").is_some() {
-        return Ok(Format::Official);
-    }
-
-    if value.get("format").is_some()
-        && value.get("bpm_list").is_some()
-        && value.get("lines").is_some()
-    {
-        return Ok(Format::Phichain);
+impl From<phichain_format::detect::Format> for Format {
+    fn from(format: phichain_format::detect::Format) -> Self {
+        match format {
+            phichain_format::detect::Format::Official => Format::Official,
+            phichain_format::detect::Format::Phichain => Format::Phichain,
+            phichain_format::detect::Format::Rpe => Format::Rpe,
+        }
     }
-
-    Err(ConvertError::UnableToInferFormat)
 }
 
 fn read_input(path: &std::path::Path) -> Result<String, ConvertError> {
@@ -132,6 +158,11 @@ fn read_input(path: &std::path::Path) -> Result<String, ConvertError> {
         return Err(ConvertError::ExpectedFile(path.to_path_buf()));
     }
 
+    if path.extension().is_some_and(|extension| extension == "pez") {
+        let chart = phichain_format::pez::read_chart(std::fs::File::open(path)?)?;
+        return Ok(String::from_utf8_lossy(&chart).into_owned());
+    }
+
     Ok(std::fs::read_to_string(path)?)
 }
 
@@ -195,14 +226,121 @@ impl Chart {
     }
 }
 
-fn convert(args: Args, meta: &mut ConvertTelemetry) -> Result<(), ConvertError> {
-    let input = read_input(&args.input)?;
+/// Options used to convert charts, resolved from the CLI arguments
+struct ConvertOptions {
+    official_input: OfficialInputOptions,
+    official_output: OfficialOutputOptions,
+    rpe_input: RpeInputOptions,
+    common_output: CommonOutputOptions,
+    quantize: Option<Quantize>,
+}
+
+impl From<&Args> for ConvertOptions {
+    fn from(args: &Args) -> Self {
+        Self {
+            official_input: args.official_input_options.clone().into(),
+            official_output: args.official_output_options.clone().into(),
+            rpe_input: args.rpe_input_options.clone().into(),
+            common_output: args.common_output_options.clone().into(),
+            quantize: args.quantize_options.resolve(),
+        }
+    }
+}
 
-    let (from, inferred) = match args.from {
+/// Parse the content of a chart, inferring its format if `from` is not provided
+///
+/// Returns the format of the chart and whether it was inferred
+fn parse_chart(content: &str, from: Option<Format>) -> Result<(Chart, Format, bool), ConvertError> {
+    let (from, inferred) = match from {
         Some(f) => (f, false),
-        None => (infer_format(&input)?, true),
+        None => (phichain_format::detect::detect(content)?.into(), true),
+    };
+
+    let chart = match from {
+        Format::Official => Chart::Official(serde_json::from_str(content)?),
+        Format::Phichain => Chart::Phichain(serde_json::from_str(content)?),
+        Format::Rpe => Chart::Rpe(serde_json::from_str(content)?),
+    };
+
+    Ok((chart, from, inferred))
+}
+
+/// Convert a chart to the given format
+///
+/// Returns the converted chart along with the items quantization moved further than the tolerance
+fn convert_chart(
+    chart: Chart,
+    to: Format,
+    options: &ConvertOptions,
+) -> Result<(Chart, Vec<Deviation>), ConvertError> {
+    let mut phichain = chart.into_phichain(&options.official_input, &options.rpe_input)?;
+
+    let deviations = options
+        .quantize
+        .as_ref()
+        .map(|quantize| quantize.lines(&mut phichain.lines))
+        .unwrap_or_default();
+
+    let chart = match to {
+        Format::Official => Chart::Official(OfficialChart::from_phichain(
+            phichain,
+            &options.official_output,
+        )?),
+        Format::Phichain => Chart::Phichain(unwrap_infallible(PhichainChart::from_phichain(
+            phichain,
+            &(),
+        ))),
+        Format::Rpe => Chart::Rpe(unwrap_infallible(RpeChart::from_phichain(phichain, &()))),
     };
 
+    Ok((chart, deviations))
+}
+
+/// Print the items quantization moved further than the tolerance while converting the input
+fn print_deviations(input: &str, quantize: &Quantize, deviations: &[Deviation]) {
+    if deviations.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "{}",
+        t!(
+            "cli.quantize.deviations",
+            input = input,
+            amount = deviations.len(),
+            tolerance = quantize.tolerance
+        )
+        .yellow()
+    );
+    for deviation in deviations {
+        let item = match deviation.item {
+            QuantizedItem::Note(note) => t!("cli.quantize.note", beat = format!("{:?}", note.beat)),
+            QuantizedItem::Event(event) => t!(
+                "cli.quantize.event",
+                kind = format!("{:?}", event.kind),
+                start = format!("{:?}", event.start_beat),
+                end = format!("{:?}", event.end_beat)
+            ),
+        };
+        eprintln!(
+            "    {}",
+            t!(
+                "cli.quantize.deviation",
+                path = deviation.path,
+                item = item,
+                distance = format!("{:.4}", deviation.distance)
+            )
+            .yellow()
+        );
+    }
+}
+
+fn convert(args: Args, meta: &mut ConvertTelemetry) -> Result<(), ConvertError> {
+    let options = ConvertOptions::from(&args);
+    let input = read_input(&args.input)?;
+
+    let (input_chart, from, inferred) = parse_chart(&input, args.from)?;
+
     if inferred {
         eprintln!(
             "{}",
@@ -215,45 +353,26 @@ fn convert(args: Args, meta: &mut ConvertTelemetry) -> Result<(), ConvertError>
 
     meta.from = Some(from);
     meta.format_inferred = inferred;
-
-    let input_chart = match from {
-        Format::Official => Chart::Official(serde_json::from_str(&input)?),
-        Format::Phichain => Chart::Phichain(serde_json::from_str(&input)?),
-        Format::Rpe => Chart::Rpe(serde_json::from_str(&input)?),
-    };
-
     meta.input = Some(input_chart.metrics());
 
-    let phichain = match input_chart {
-        Chart::Official(official) => official.to_phichain(&args.official_input_options.into())?,
-        Chart::Phichain(phichain) => unwrap_infallible(phichain.to_phichain(&())),
-        Chart::Rpe(rpe) => rpe.to_phichain(&args.rpe_input_options.into())?,
-    };
-
-    let output_chart = match args.to {
-        Format::Official => Chart::Official(OfficialChart::from_phichain(
-            phichain,
-            &args.official_output_options.into(),
-        )?),
-        Format::Phichain => Chart::Phichain(unwrap_infallible(PhichainChart::from_phichain(
-            phichain,
-            &(),
-        ))),
-        Format::Rpe => Chart::Rpe(unwrap_infallible(RpeChart::from_phichain(phichain, &()))),
-    };
+    let (output_chart, deviations) = convert_chart(input_chart, args.to, &options)?;
+    if let Some(quantize) = &options.quantize {
+        print_deviations(&args.input.display().to_string(), quantize, &deviations);
+    }
 
     meta.output = Some(output_chart.metrics());
 
-    let output = output_chart.apply_common_output_options(&args.common_output_options.into());
+    let output = output_chart.apply_common_output_options(&options.common_output);
+    let output_path = args.output.unwrap_or_else(|| PathBuf::from("output.json"));
 
-    let output_name = if args.output.as_os_str() == "-" {
+    let output_name = if output_path.as_os_str() == "-" {
         serde_json::to_writer(std::io::stdout(), &output)?;
         println!(); // newline after JSON
         t!("cli.status.stdout").to_string()
     } else {
-        let output_file = std::fs::File::create(&args.output)?;
+        let output_file = std::fs::File::create(&output_path)?;
         serde_json::to_writer(output_file, &output)?;
-        args.output.display().to_string()
+        output_path.display().to_string()
     };
 
     eprintln!(
@@ -270,6 +389,20 @@ fn convert(args: Args, meta: &mut ConvertTelemetry) -> Result<(), ConvertError>
     Ok(())
 }
 
+/// Report a telemetry event unless telemetry is disabled by the environment
+fn track(event: &str, payload: impl Serialize) {
+    if phichain_telemetry::env::telemetry_disabled() {
+        return;
+    }
+
+    let reporter = phichain_telemetry::Reporter::new(
+        "phichain-converter",
+        env!("CARGO_PKG_VERSION"),
+        cfg!(debug_assertions),
+    );
+    let _ = reporter.track(event, serde_json::to_value(payload).unwrap());
+}
+
 fn main() {
     if phichain_telemetry::handle_subcommand() {
         return;
@@ -278,9 +411,32 @@ fn main() {
     tracing_subscriber::fmt().init();
     rust_i18n::set_locale(&locale());
 
-    let args = Args::parse();
+    let cli = Cli::parse();
+    if let Some(command) = cli.command {
+        if let Err(err) = inspect::run(command) {
+            eprintln!("{}", err.red());
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let args = cli
+        .args
+        .expect("the conversion arguments are required without a subcommand");
     let no_telemetry = args.no_telemetry;
 
+    if args.batch {
+        match batch::run(args) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(err) => {
+                eprintln!("{}", err.red());
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     let mut meta = ConvertTelemetry {
         locale: locale(),
         from: args.from,
@@ -296,6 +452,7 @@ fn main() {
             "official_output": &args.official_output_options,
             "rpe_input": &args.rpe_input_options,
             "common_output": &args.common_output_options,
+            "quantize": &args.quantize_options,
         }),
     };
 
@@ -307,16 +464,8 @@ fn main() {
         meta.error_kind = Some(e.variant_name());
     }
 
-    if !no_telemetry && !phichain_telemetry::env::telemetry_disabled() {
-        let reporter = phichain_telemetry::Reporter::new(
-            "phichain-converter",
-            env!("CARGO_PKG_VERSION"),
-            cfg!(debug_assertions),
-        );
-        let _ = reporter.track(
-            "phichain.converter.convert",
-            serde_json::to_value(&meta).unwrap(),
-        );
+    if !no_telemetry {
+        track("phichain.converter.convert", &meta);
     }
 
     if let Err(err) = result {
@@ -1,5 +1,6 @@
 use o2o::o2o;
 use phichain_chart::beat::Beat;
+use phichain_chart::quantize::Quantize;
 use phichain_format::official::{OfficialInputOptions, OfficialOutputOptions};
 use phichain_format::rpe::RpeInputOptions;
 use phichain_format::CommonOutputOptions;
@@ -53,3 +54,20 @@ pub struct CliCommonOutputOptions {
     #[arg(long, help = t!("cli.common_output.round").to_string(), default_value_t = 2)]
     round: u32,
 }
+
+/// CLI options to quantize the converted chart, disabled unless a density is provided
+#[derive(Debug, Clone, serde::Serialize, clap::Args)]
+pub struct CliQuantizeOptions {
+    #[arg(long, value_name = "DENSITY", value_parser = clap::value_parser!(u32).range(1..), help = t!("cli.quantize.quantize").to_string())]
+    quantize: Option<u32>,
+
+    #[arg(long, default_value_t = 0.01, help = t!("cli.quantize.quantize_tolerance").to_string())]
+    quantize_tolerance: f32,
+}
+
+impl CliQuantizeOptions {
+    pub fn resolve(&self) -> Option<Quantize> {
+        self.quantize
+            .map(|density| Quantize::new(density, self.quantize_tolerance))
+    }
+}
@@ -0,0 +1,443 @@
+//! The `report` subcommand, which renders the analytics of a chart into a self-contained SVG
+
+use crate::error::ConvertError;
+use crate::inspect::{load, InputOptions};
+use crate::Format;
+use owo_colors::OwoColorize;
+use phichain_chart::analysis::ChartAnalysis;
+use phichain_chart::bpm_list::BpmList;
+use phichain_chart::event::{EventEvaluationResult, LineEventKind};
+use phichain_chart::metrics::ChartMetrics;
+use phichain_chart::project::{ProjectMeta, ProjectPath};
+use phichain_chart::serialization::{PhichainChart, SerializedLine};
+use rust_i18n::t;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+const WIDTH: f32 = 960.0;
+const MARGIN: f32 = 48.0;
+const HEADER_HEIGHT: f32 = 180.0;
+const PANEL_HEIGHT: f32 = 160.0;
+const PANEL_GAP: f32 = 56.0;
+
+const BACKGROUND: &str = "#1e1e24";
+const FOREGROUND: &str = "#e8e8f0";
+const MUTED: &str = "#8a8a99";
+const DENSITY: &str = "#50a0ff";
+const SPIKE: &str = "#ff5050";
+const BPM: &str = "#ffbe3c";
+const LINES: &str = "#a2eeff";
+
+/// Seconds with at least this many times the average density are highlighted as spikes
+const SPIKE_THRESHOLD: f32 = 2.0;
+/// Most ticks on the time axis of a panel
+const MAX_TICKS: f32 = 20.0;
+
+#[derive(clap::Args, Debug)]
+pub struct ReportArgs {
+    #[arg(required = true, help = t!("cli.report.input").to_string())]
+    input: PathBuf,
+
+    #[arg(required = false, help = t!("cli.report.output").to_string())]
+    output: Option<PathBuf>,
+
+    #[arg(long, help = t!("cli.report.meta").to_string())]
+    meta: Option<PathBuf>,
+
+    #[arg(long, help = t!("cli.from").to_string())]
+    from: Option<Format>,
+
+    #[command(flatten)]
+    input_options: InputOptions,
+}
+
+/// Render a report of the chart, or of the chart of a project if the input is a directory
+pub fn run(args: ReportArgs) -> Result<(), ConvertError> {
+    let (chart_path, meta_path) = if args.input.is_dir() {
+        let project = ProjectPath(args.input.clone());
+        (project.chart_path(), Some(project.meta_path()))
+    } else {
+        (args.input.clone(), None)
+    };
+
+    let meta = match args.meta.or(meta_path) {
+        Some(path) if path.is_file() => serde_json::from_reader(std::fs::File::open(path)?)?,
+        Some(path) => return Err(ConvertError::NoSuchFile(path)),
+        None => ProjectMeta::default(),
+    };
+
+    let (_, chart) = load(&chart_path, args.from, &args.input_options)?;
+
+    let output = args.output.unwrap_or_else(|| PathBuf::from("report.svg"));
+    std::fs::write(&output, render(&chart, &meta))?;
+
+    eprintln!(
+        "{}",
+        t!(
+            "cli.report.written",
+            output = output.display().to_string().green()
+        )
+    );
+
+    Ok(())
+}
+
+/// Render the report of a chart as an SVG document
+pub fn render(chart: &PhichainChart, meta: &ProjectMeta) -> String {
+    let analysis = ChartAnalysis::collect(&chart.bpm_list, &chart.lines, None);
+    let metrics = ChartMetrics::collect(&chart.lines);
+    let duration = analysis.duration.max(1.0);
+
+    let height = HEADER_HEIGHT + 3.0 * (PANEL_HEIGHT + PANEL_GAP) + MARGIN;
+    let mut svg = String::new();
+
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{height}" viewBox="0 0 {WIDTH} {height}" font-family="sans-serif">"#
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{BACKGROUND}"/>"#
+    );
+
+    header(&mut svg, meta, &analysis, &metrics);
+
+    let panel = |index: usize| Panel {
+        top: HEADER_HEIGHT + index as f32 * (PANEL_HEIGHT + PANEL_GAP),
+        duration,
+    };
+
+    density(&mut svg, panel(0), &analysis);
+    bpm(&mut svg, panel(1), &chart.bpm_list);
+    visible_lines(&mut svg, panel(2), &chart.bpm_list, &chart.lines);
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn header(svg: &mut String, meta: &ProjectMeta, analysis: &ChartAnalysis, metrics: &ChartMetrics) {
+    let name = if meta.name.is_empty() {
+        t!("cli.report.untitled").to_string()
+    } else {
+        meta.name.clone()
+    };
+
+    text(svg, MARGIN, 52.0, 28.0, FOREGROUND, &name);
+    end_text(svg, WIDTH - MARGIN, 52.0, 28.0, FOREGROUND, &meta.level);
+
+    let credits = t!(
+        "cli.report.credits",
+        composer = meta.composer,
+        charter = meta.charter,
+        illustrator = meta.illustrator
+    );
+    text(svg, MARGIN, 80.0, 14.0, MUTED, &credits);
+
+    let peak = analysis.peak(5.0).map(|peak| peak.nps).unwrap_or_default();
+    let kinds = analysis.note_kinds;
+    let summary = t!(
+        "cli.report.summary",
+        notes = metrics.notes,
+        lines = metrics.lines,
+        events = metrics.events,
+        duration = format!("{:.1}", analysis.duration)
+    );
+    let stats = t!(
+        "cli.report.stats",
+        tap = kinds.tap,
+        drag = kinds.drag,
+        hold = kinds.hold,
+        flick = kinds.flick,
+        average = format!("{:.2}", analysis.average_nps()),
+        peak = format!("{peak:.2}"),
+        level = format!("{:.1}", analysis.estimated_level())
+    );
+    text(svg, MARGIN, 108.0, 14.0, FOREGROUND, &summary);
+    text(svg, MARGIN, 128.0, 14.0, FOREGROUND, &stats);
+}
+
+/// A plot spanning the width of the report, with time on the x axis
+#[derive(Clone, Copy)]
+struct Panel {
+    top: f32,
+    duration: f32,
+}
+
+impl Panel {
+    fn left(&self) -> f32 {
+        MARGIN
+    }
+
+    fn right(&self) -> f32 {
+        WIDTH - MARGIN
+    }
+
+    fn bottom(&self) -> f32 {
+        self.top + PANEL_HEIGHT
+    }
+
+    fn x(&self, time: f32) -> f32 {
+        self.left() + time.clamp(0.0, self.duration) / self.duration * (self.right() - self.left())
+    }
+
+    /// The y of `value` in `min..=max`
+    fn y(&self, value: f32, min: f32, max: f32) -> f32 {
+        let percent = if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.5
+        };
+        self.bottom() - percent.clamp(0.0, 1.0) * PANEL_HEIGHT
+    }
+
+    /// Draw the title, the frame and the time axis of the panel
+    fn frame(&self, svg: &mut String, title: &str, max_label: &str) {
+        text(svg, self.left(), self.top - 10.0, 15.0, FOREGROUND, title);
+        end_text(svg, self.right(), self.top - 10.0, 12.0, MUTED, max_label);
+
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{PANEL_HEIGHT}" fill="none" stroke="{MUTED}" stroke-opacity="0.4"/>"#,
+            self.left(),
+            self.top,
+            self.right() - self.left()
+        );
+
+        let step = tick_step(self.duration);
+        let mut tick = 0.0;
+        while tick <= self.duration {
+            let x = self.x(tick);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="{MUTED}" stroke-opacity="0.4"/>"#,
+                self.bottom(),
+                self.bottom() + 4.0
+            );
+            text(
+                svg,
+                x - 8.0,
+                self.bottom() + 18.0,
+                11.0,
+                MUTED,
+                &format_time(tick),
+            );
+            tick += step;
+        }
+    }
+}
+
+fn density(svg: &mut String, panel: Panel, analysis: &ChartAnalysis) {
+    let max = analysis.density.iter().copied().max().unwrap_or_default();
+    panel.frame(
+        svg,
+        &t!("cli.report.density"),
+        &t!("cli.report.max", value = max),
+    );
+
+    let spike = analysis.average_nps() * SPIKE_THRESHOLD;
+    let width = (panel.x(1.0) - panel.x(0.0) - 1.0).max(0.5);
+    for (second, amount) in analysis.density.iter().enumerate() {
+        if *amount == 0 {
+            continue;
+        }
+        let y = panel.y(*amount as f32, 0.0, max as f32);
+        let color = if *amount as f32 >= spike {
+            SPIKE
+        } else {
+            DENSITY
+        };
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{y}" width="{width}" height="{}" fill="{color}"/>"#,
+            panel.x(second as f32),
+            panel.bottom() - y
+        );
+    }
+}
+
+fn bpm(svg: &mut String, panel: Panel, bpm_list: &BpmList) {
+    if bpm_list.0.is_empty() {
+        panel.frame(svg, &t!("cli.report.bpm"), "");
+        return;
+    }
+
+    let min = bpm_list
+        .0
+        .iter()
+        .map(|point| point.bpm)
+        .fold(f32::MAX, f32::min);
+    let max = bpm_list
+        .0
+        .iter()
+        .map(|point| point.bpm)
+        .fold(f32::MIN, f32::max);
+    panel.frame(
+        svg,
+        &t!("cli.report.bpm"),
+        &t!("cli.report.range", min = min, max = max),
+    );
+
+    let mut points = vec![];
+    for (i, point) in bpm_list.0.iter().enumerate() {
+        let start = bpm_list.time_at(point.beat);
+        let end = bpm_list
+            .0
+            .get(i + 1)
+            .map(|next| bpm_list.time_at(next.beat))
+            .unwrap_or(panel.duration);
+        let y = panel.y(point.bpm, min, max);
+        points.push((panel.x(start), y));
+        points.push((panel.x(end), y));
+    }
+    polyline(svg, &points, BPM);
+}
+
+fn visible_lines(svg: &mut String, panel: Panel, bpm_list: &BpmList, lines: &[SerializedLine]) {
+    fn flatten<'a>(lines: &'a [SerializedLine], flat: &mut Vec<&'a SerializedLine>) {
+        for line in lines {
+            flat.push(line);
+            flatten(&line.children, flat);
+        }
+    }
+
+    let mut flat = vec![];
+    flatten(lines, &mut flat);
+
+    let opacity_events = flat
+        .iter()
+        .map(|line| {
+            line.events
+                .iter()
+                .filter(|event| event.kind == LineEventKind::Opacity)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // the duration is bounded by the notes, see `ChartAnalysis::duration`
+    let counts = (0..=panel.duration as usize)
+        .map(|second| {
+            let beat = bpm_list.beat_at_f32(second as f32);
+            opacity_events
+                .iter()
+                .filter(|events| {
+                    let opacity = events
+                        .iter()
+                        .map(|event| event.evaluate_inclusive(beat))
+                        .fold(
+                            EventEvaluationResult::Unaffected,
+                            EventEvaluationResult::max,
+                        );
+                    opacity
+                        .value()
+                        .unwrap_or(LineEventKind::Opacity.default_value())
+                        > 0.0
+                })
+                .count()
+        })
+        .collect::<Vec<_>>();
+
+    let max = counts.iter().copied().max().unwrap_or_default();
+    panel.frame(
+        svg,
+        &t!("cli.report.visible_lines", total = flat.len()),
+        &t!("cli.report.max", value = max),
+    );
+
+    let mut points = vec![];
+    for (second, count) in counts.iter().enumerate() {
+        let y = panel.y(*count as f32, 0.0, max as f32);
+        points.push((panel.x(second as f32), y));
+        points.push((panel.x(second as f32 + 1.0), y));
+    }
+    polyline(svg, &points, LINES);
+}
+
+fn polyline(svg: &mut String, points: &[(f32, f32)], color: &str) {
+    let points = points
+        .iter()
+        .map(|(x, y)| format!("{x:.1},{y:.1}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let _ = writeln!(
+        svg,
+        r#"<polyline points="{points}" fill="none" stroke="{color}" stroke-width="2"/>"#
+    );
+}
+
+fn text(svg: &mut String, x: f32, y: f32, size: f32, color: &str, content: &str) {
+    let _ = writeln!(
+        svg,
+        r#"<text x="{x}" y="{y}" font-size="{size}" fill="{color}">{}</text>"#,
+        escape(content)
+    );
+}
+
+/// Text ending at `x`
+fn end_text(svg: &mut String, x: f32, y: f32, size: f32, color: &str, content: &str) {
+    let _ = writeln!(
+        svg,
+        r#"<text x="{x}" y="{y}" font-size="{size}" fill="{color}" text-anchor="end">{}</text>"#,
+        escape(content)
+    );
+}
+
+/// Seconds between two ticks of the time axis, so that there are at most [`MAX_TICKS`] ticks
+fn tick_step(duration: f32) -> f32 {
+    let step = duration / MAX_TICKS;
+    [5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0]
+        .into_iter()
+        .find(|candidate| *candidate >= step)
+        // longer charts are ticked at a multiple of 5 minutes
+        .unwrap_or_else(|| (step / 300.0).ceil() * 300.0)
+}
+
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn escape(content: &str) -> String {
+    content
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::beat::Beat;
+    use phichain_chart::line::Line;
+    use phichain_chart::note::{Note, NoteKind};
+
+    #[test]
+    fn test_render_escapes_meta() {
+        let notes = vec![Note::new(NoteKind::Tap, true, Beat::ONE, 0.0, 1.0)];
+        let line = SerializedLine::new(Line::default(), notes, vec![], vec![], vec![]);
+        let chart = PhichainChart::new(0.0, BpmList::default(), vec![line]);
+        let meta = ProjectMeta {
+            name: "Tom & <Jerry>".to_string(),
+            ..Default::default()
+        };
+
+        let svg = render(&chart, &meta);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains("Tom &amp; &lt;Jerry&gt;"));
+        assert!(!svg.contains("<Jerry>"));
+    }
+
+    #[test]
+    fn test_tick_step() {
+        for duration in [0.0, 1.0, 59.0, 100.0, 600.0, 3600.0, 1e6] {
+            let step = tick_step(duration);
+            assert!(step >= 5.0);
+            assert!(duration / step <= MAX_TICKS);
+        }
+
+        assert_eq!(tick_step(100.0), 5.0);
+        assert_eq!(tick_step(3600.0), 300.0);
+        assert_eq!(tick_step(7200.0), 600.0);
+    }
+}
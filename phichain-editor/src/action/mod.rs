@@ -15,6 +15,8 @@ pub struct RegisteredAction {
     system: BoxedSystem<(), Result>,
     pub enable_hotkey: bool,
     pub is_heavy: bool,
+    /// Shown instead of the translation of `action.<id>`, for actions defined at runtime such as scripts
+    pub title: Option<String>,
 }
 
 impl RegisteredAction {
@@ -33,6 +35,14 @@ impl RegisteredAction {
 pub struct ActionRegistry(pub IndexMap<ActionIdentifier, RegisteredAction>);
 
 impl ActionRegistry {
+    /// The title of an action, as shown in the action panel and the hotkey settings
+    pub fn title(&self, id: &ActionIdentifier) -> String {
+        self.0
+            .get(id)
+            .and_then(|action| action.title.clone())
+            .unwrap_or_else(|| t!(format!("action.{id}").as_str()).to_string())
+    }
+
     pub fn run_action(&mut self, world: &mut World, id: impl Into<ActionIdentifier>) {
         let id = id.into();
         if let Some(action) = self.0.get_mut(&id) {
@@ -66,6 +76,7 @@ fn add_action_impl<M1>(
     system: impl IntoSystem<(), Result, M1>,
     hotkey: Option<Hotkey>,
     heavy: bool,
+    title: Option<String>,
 ) {
     let id = id.into();
 
@@ -77,6 +88,7 @@ fn add_action_impl<M1>(
         }),
         enable_hotkey: hotkey.is_some(),
         is_heavy: heavy,
+        title,
     };
 
     app.world_mut()
@@ -103,6 +115,15 @@ pub trait ActionRegistrationExt {
         system: impl IntoSystem<(), Result, M1>,
         hotkey: Option<Hotkey>,
     ) -> &mut Self;
+
+    /// Add an action with a title that is not translated
+    fn add_titled_action<M1>(
+        &mut self,
+        id: impl Into<ActionIdentifier>,
+        title: impl Into<String>,
+        system: impl IntoSystem<(), Result, M1>,
+        hotkey: Option<Hotkey>,
+    ) -> &mut Self;
 }
 
 impl ActionRegistrationExt for App {
@@ -112,7 +133,7 @@ impl ActionRegistrationExt for App {
         system: impl IntoSystem<(), Result, M1>,
         hotkey: Option<Hotkey>,
     ) -> &mut Self {
-        add_action_impl(self, id, system, hotkey, false);
+        add_action_impl(self, id, system, hotkey, false, None);
         self
     }
 
@@ -122,7 +143,18 @@ impl ActionRegistrationExt for App {
         system: impl IntoSystem<(), Result, M1>,
         hotkey: Option<Hotkey>,
     ) -> &mut Self {
-        add_action_impl(self, id, system, hotkey, true);
+        add_action_impl(self, id, system, hotkey, true, None);
+        self
+    }
+
+    fn add_titled_action<M1>(
+        &mut self,
+        id: impl Into<ActionIdentifier>,
+        title: impl Into<String>,
+        system: impl IntoSystem<(), Result, M1>,
+        hotkey: Option<Hotkey>,
+    ) -> &mut Self {
+        add_action_impl(self, id, system, hotkey, false, Some(title.into()));
         self
     }
 }
@@ -0,0 +1,134 @@
+use crate::editing::pending::Pending;
+use crate::project::project_loaded;
+use crate::timeline::TimelineContext;
+use crate::utils::convert::BevyEguiConvert;
+use bevy::ecs::system::SystemState;
+use bevy::prelude::*;
+use egui::{Color32, Painter, Pos2, Rect, Stroke};
+use phichain_chart::analysis::ChartAnalysis;
+use phichain_chart::bpm_list::BpmList;
+use phichain_chart::event::LineEvent;
+use phichain_chart::note::Note;
+use phichain_game::audio::AudioDuration;
+
+/// Seconds with at least this many times the average density are highlighted as spikes
+const SPIKE_THRESHOLD: f32 = 2.0;
+/// Fraction of the timeline width taken by the densest second
+const GRAPH_WIDTH: f32 = 0.3;
+
+const DENSITY_COLOR: Color32 = Color32::from_rgb(80, 160, 255);
+const SPIKE_COLOR: Color32 = Color32::from_rgb(255, 80, 80);
+const MOVEMENT_COLOR: Color32 = Color32::from_rgb(255, 190, 60);
+
+pub struct AnalysisPlugin;
+
+impl Plugin for AnalysisPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Analysis::default())
+            .add_systems(Update, update_analysis_system.run_if(project_loaded()));
+    }
+}
+
+/// The [`ChartAnalysis`] of the loaded chart, kept up to date with the notes and events
+#[derive(Resource, Debug, Default)]
+pub struct Analysis(pub ChartAnalysis);
+
+fn update_analysis_system(
+    mut analysis: ResMut<Analysis>,
+    note_query: Query<&Note, Without<Pending>>,
+    event_query: Query<&LineEvent, Without<Pending>>,
+    changed_query: Query<(), Or<(Changed<Note>, Changed<LineEvent>)>>,
+    mut removed_notes: RemovedComponents<Note>,
+    mut removed_events: RemovedComponents<LineEvent>,
+    mut removed_pending: RemovedComponents<Pending>,
+    bpm_list: Res<BpmList>,
+    audio_duration: Option<Res<AudioDuration>>,
+) {
+    // every reader has to be drained, or the removals are reported again next frame
+    let removed = removed_notes.read().count()
+        + removed_events.read().count()
+        + removed_pending.read().count()
+        > 0;
+
+    let audio_changed = audio_duration
+        .as_ref()
+        .is_some_and(|duration| duration.is_changed());
+    if !removed && changed_query.is_empty() && !bpm_list.is_changed() && !audio_changed {
+        return;
+    }
+
+    analysis.0 = ChartAnalysis::new(
+        &bpm_list,
+        &note_query,
+        &event_query,
+        audio_duration.map(|duration| duration.0.as_secs_f32()),
+    );
+}
+
+/// Draw the note density and the line movement of each second behind the timelines
+pub fn draw(painter: &Painter, world: &mut World) {
+    let mut state = SystemState::<(TimelineContext, Res<Analysis>)>::new(world);
+    let (ctx, analysis) = state.get_mut(world);
+
+    if !ctx.settings.show_density_graph {
+        return;
+    }
+
+    let analysis = &analysis.0;
+    let Some(max_density) = analysis.density.iter().max().filter(|x| **x > 0) else {
+        return;
+    };
+
+    let rect = ctx.viewport.0.into_egui();
+    let width = rect.width() * GRAPH_WIDTH;
+    let spike = analysis.average_nps() * SPIKE_THRESHOLD;
+
+    for (second, amount) in analysis.density.iter().enumerate() {
+        if *amount == 0 {
+            continue;
+        }
+
+        let bottom = ctx.time_to_y(second as f32);
+        let top = ctx.time_to_y(second as f32 + 1.0);
+        if bottom < rect.top() || top > rect.bottom() {
+            continue;
+        }
+
+        let color = if *amount as f32 >= spike {
+            SPIKE_COLOR
+        } else {
+            DENSITY_COLOR
+        };
+        painter.rect_filled(
+            Rect::from_min_max(
+                Pos2::new(rect.left(), top),
+                Pos2::new(
+                    rect.left() + width * *amount as f32 / *max_density as f32,
+                    bottom,
+                ),
+            ),
+            0.0,
+            color.gamma_multiply(0.25),
+        );
+    }
+
+    let max_movement = analysis.movement.iter().copied().fold(0.0, f32::max);
+    if max_movement > 0.0 {
+        let points = analysis
+            .movement
+            .iter()
+            .enumerate()
+            .map(|(second, movement)| {
+                Pos2::new(
+                    rect.left() + width * movement / max_movement,
+                    ctx.time_to_y(second as f32 + 0.5),
+                )
+            })
+            // only the seconds around the viewport are drawn
+            .filter(|point| {
+                (rect.top() - rect.height()..=rect.bottom() + rect.height()).contains(&point.y)
+            })
+            .collect::<Vec<_>>();
+        painter.line(points, Stroke::new(1.5, MOVEMENT_COLOR.gamma_multiply(0.6)));
+    }
+}
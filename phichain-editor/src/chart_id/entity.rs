@@ -0,0 +1,126 @@
+//! Serialize an [`Entity`] as the [`Id`] of the chart item it holds, use with
+//! `#[serde(with = "crate::chart_id::entity")]` inside [`IdMapping::scope`](super::IdMapping::scope)
+
+use super::{with_mapping, Id};
+use bevy::prelude::Entity;
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// An [`Entity`] (de)serialized as an [`Id`]
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Ref(pub Entity);
+
+impl Serialize for Ref {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        with_mapping(|mapping| mapping.id(self.0))
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Ref {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = Id::deserialize(deserializer)?;
+        with_mapping(|mapping| mapping.entity(id))
+            .map_err(D::Error::custom)?
+            .map(Ref)
+            .ok_or_else(|| D::Error::custom(format!("no entity with id {}", id.0)))
+    }
+}
+
+pub fn serialize<S: Serializer>(entity: &Entity, serializer: S) -> Result<S::Ok, S::Error> {
+    Ref(*entity).serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Entity, D::Error> {
+    Ref::deserialize(deserializer).map(|x| x.0)
+}
+
+/// For `Option<Entity>`
+pub mod option {
+    use super::Ref;
+    use bevy::prelude::Entity;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        entity: &Option<Entity>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        entity.map(Ref).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Entity>, D::Error> {
+        Option::<Ref>::deserialize(deserializer).map(|x| x.map(|x| x.0))
+    }
+}
+
+/// For `Option<(T, Entity)>`, like a removed item paired with its line
+pub mod paired {
+    use super::Ref;
+    use bevy::prelude::Entity;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<T: Serialize, S: Serializer>(
+        value: &Option<(T, Entity)>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value
+            .as_ref()
+            .map(|(value, entity)| (value, Ref(*entity)))
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<(T, Entity)>, D::Error> {
+        Option::<(T, Ref)>::deserialize(deserializer)
+            .map(|x| x.map(|(value, entity)| (value, entity.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::IdMapping;
+    use super::*;
+    use bevy::prelude::App;
+    use phichain_game::index::ChartIndexPlugin;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Item {
+        #[serde(with = "super")]
+        entity: Entity,
+        #[serde(with = "super::option")]
+        parent: Option<Entity>,
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut app = App::new();
+        app.add_plugins(ChartIndexPlugin);
+        let world = app.world_mut();
+        world.init_resource::<super::super::IdGen>();
+        let entity = world.spawn(Id(3)).id();
+        let removed = world.spawn_empty().id();
+        world.resource_mut::<super::super::IdGen>().reserve(4);
+
+        let item = Item {
+            entity,
+            parent: Some(removed),
+        };
+        let (json, mapping) = IdMapping::new(world).scope(|| serde_json::to_value(&item).unwrap());
+        // the despawned entity gets a new id
+        assert_eq!(json, serde_json::json!({ "entity": 3, "parent": 4 }));
+        assert_eq!(mapping.next(), 5);
+
+        world.spawn(Id(4));
+        let (restored, _) =
+            IdMapping::new(world).scope(|| serde_json::from_value::<Item>(json.clone()).unwrap());
+        assert_eq!(restored.entity, entity);
+        assert_ne!(restored.parent, Some(removed));
+
+        assert!(serde_json::to_value(&item).is_err());
+    }
+}
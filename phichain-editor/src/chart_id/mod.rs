@@ -0,0 +1,182 @@
+//! Stable IDs of lines, notes, events and curve note tracks
+//!
+//! Entities are allocated anew every time a project is loaded, so anything persisted across
+//! sessions refers to chart items by their [`Id`] instead. Lines, notes and events keep their
+//! [`Id`] in the chart, curve note tracks are given one when loaded, see [`phichain_game::loader`]
+
+pub mod entity;
+pub mod track;
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+use phichain_chart::event::LineEvent;
+use phichain_chart::line::Line;
+use phichain_chart::note::Note;
+use phichain_game::curve_note_track::{CurveNote, CurveNoteTrack};
+use phichain_game::index::ChartIndex;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+
+pub struct ChartIdPlugin;
+
+impl Plugin for ChartIdPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<IdGen>()
+            .add_observer(assign_id_observer::<Line>)
+            .add_observer(assign_id_observer::<Note>)
+            .add_observer(assign_id_observer::<LineEvent>)
+            .add_observer(assign_id_observer::<CurveNoteTrack>)
+            .add_observer(reserve_id_observer);
+    }
+}
+
+/// A stable ID of a line, note, event or curve note track
+///
+/// Entities removed with `keep_entity` keep their [`Id`], so they are the same item when restored
+pub use phichain_chart::id::Id;
+
+#[derive(Resource, Debug, Default)]
+pub struct IdGen {
+    next: u64,
+}
+
+impl IdGen {
+    pub fn generate(&mut self) -> Id {
+        let id = Id(self.next);
+        self.next += 1;
+        id
+    }
+
+    /// The ID the next call to [`IdGen::generate`] returns
+    pub fn peek(&self) -> u64 {
+        self.next
+    }
+
+    /// Make sure IDs below `next` are never handed out again
+    pub fn reserve(&mut self, next: u64) {
+        self.next = self.next.max(next);
+    }
+}
+
+/// Give chart items an [`Id`] when they are spawned, notes generated by curve note tracks are skipped
+fn assign_id_observer<C: Component>(
+    event: On<Add, C>,
+    mut commands: Commands,
+    mut id_gen: ResMut<IdGen>,
+    query: Query<(), (Without<Id>, Without<CurveNote>)>,
+) {
+    if query.contains(event.entity) {
+        commands.entity(event.entity).insert(id_gen.generate());
+    }
+}
+
+/// Make sure [`IdGen`] never hands out the [`Id`]s of loaded items
+fn reserve_id_observer(event: On<Insert, Id>, query: Query<&Id>, mut id_gen: ResMut<IdGen>) {
+    if let Ok(id) = query.get(event.entity) {
+        id_gen.reserve(id.0 + 1);
+    }
+}
+
+thread_local! {
+    static MAPPING: RefCell<Option<IdMapping>> = const { RefCell::new(None) };
+}
+
+/// Maps entities to [`Id`]s and back while (de)serializing values referring to entities through
+/// [`entity`] and [`track`]
+#[derive(Debug, Default)]
+pub struct IdMapping {
+    ids: EntityHashMap<Id>,
+    entities: HashMap<Id, Entity>,
+    next: u64,
+    /// Every ID written so far
+    pub written: BTreeSet<Id>,
+}
+
+impl IdMapping {
+    /// A mapping of every entity in the [`ChartIndex`]
+    pub fn new(world: &World) -> Self {
+        let index = world.resource::<ChartIndex>();
+        Self {
+            ids: index.iter().map(|(id, entity)| (entity, id)).collect(),
+            entities: index.iter().collect(),
+            next: world.resource::<IdGen>().peek(),
+            ..default()
+        }
+    }
+
+    /// Run `f` with this mapping in use, returning its result and the mapping afterward
+    pub fn scope<R>(self, f: impl FnOnce() -> R) -> (R, Self) {
+        MAPPING.set(Some(self));
+        let result = f();
+        let mapping = MAPPING.take().expect("IdMapping taken inside its scope");
+        (result, mapping)
+    }
+
+    /// IDs handed out to entities without one have to be reserved in [`IdGen`] afterward
+    pub fn next(&self) -> u64 {
+        self.next
+    }
+
+    fn id(&mut self, entity: Entity) -> Id {
+        // an entity without an ID was despawned for good, it still gets one so the references
+        // to it stay apart from the others
+        let id = *self.ids.entry(entity).or_insert_with(|| {
+            let id = Id(self.next);
+            self.next += 1;
+            id
+        });
+        self.written.insert(id);
+        id
+    }
+
+    fn entity(&self, id: Id) -> Option<Entity> {
+        self.entities.get(&id).copied()
+    }
+}
+
+fn with_mapping<R>(f: impl FnOnce(&mut IdMapping) -> R) -> Result<R, &'static str> {
+    MAPPING.with_borrow_mut(|mapping| {
+        mapping
+            .as_mut()
+            .map(f)
+            .ok_or("entities can only be (de)serialized inside IdMapping::scope")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::beat::Beat;
+    use phichain_chart::note::NoteKind;
+
+    #[test]
+    fn test_assign_id() {
+        let mut app = App::new();
+        app.add_plugins(ChartIdPlugin);
+
+        let world = app.world_mut();
+        let line = world.spawn(Line::default()).id();
+        let note = Note::new(NoteKind::Tap, true, Beat::ZERO, 0.0, 1.0);
+        let entity = world.spawn((note, ChildOf(line))).id();
+        world.flush();
+
+        assert_eq!(world.get::<Id>(line), Some(&Id(0)));
+        assert_eq!(world.get::<Id>(entity), Some(&Id(1)));
+
+        // restoring a removed note keeps its id
+        world.entity_mut(entity).retain::<Id>();
+        world.entity_mut(entity).insert(note);
+        world.flush();
+        assert_eq!(world.get::<Id>(entity), Some(&Id(1)));
+
+        let curve_note = world.spawn((note, CurveNote(entity))).id();
+        world.flush();
+        assert_eq!(world.get::<Id>(curve_note), None);
+
+        // ids of loaded items are never handed out again
+        world.spawn((note, Id(10), ChildOf(line)));
+        let new_line = world.spawn(Line::default()).id();
+        world.flush();
+        assert_eq!(world.get::<Id>(new_line), Some(&Id(11)));
+    }
+}
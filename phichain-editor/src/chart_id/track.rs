@@ -0,0 +1,68 @@
+//! Serialize a [`CurveNoteTrack`] with its notes as [`Id`](super::Id)s, use with
+//! `#[serde(with = "crate::chart_id::track")]` inside [`IdMapping::scope`](super::IdMapping::scope)
+
+use super::entity::Ref;
+use phichain_chart::curve_note_track::CurveNoteTrackOptions;
+use phichain_game::curve_note_track::CurveNoteTrack;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize)]
+struct TrackRef {
+    from: Option<Ref>,
+    to: Option<Ref>,
+    options: CurveNoteTrackOptions,
+}
+
+impl From<&CurveNoteTrack> for TrackRef {
+    fn from(track: &CurveNoteTrack) -> Self {
+        Self {
+            from: track.from.map(Ref),
+            to: track.to.map(Ref),
+            options: track.options.clone(),
+        }
+    }
+}
+
+impl From<TrackRef> for CurveNoteTrack {
+    fn from(track: TrackRef) -> Self {
+        Self {
+            from: track.from.map(|x| x.0),
+            to: track.to.map(|x| x.0),
+            options: track.options,
+        }
+    }
+}
+
+pub fn serialize<S: Serializer>(track: &CurveNoteTrack, serializer: S) -> Result<S::Ok, S::Error> {
+    TrackRef::from(track).serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<CurveNoteTrack, D::Error> {
+    TrackRef::deserialize(deserializer).map(Into::into)
+}
+
+/// For `Option<(CurveNoteTrack, Entity)>`, a removed track paired with its line
+pub mod paired {
+    use super::super::entity::Ref;
+    use super::TrackRef;
+    use bevy::prelude::Entity;
+    use phichain_game::curve_note_track::CurveNoteTrack;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<(CurveNoteTrack, Entity)>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value
+            .as_ref()
+            .map(|(track, entity)| (TrackRef::from(track), Ref(*entity)))
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<(CurveNoteTrack, Entity)>, D::Error> {
+        Option::<(TrackRef, Ref)>::deserialize(deserializer)
+            .map(|x| x.map(|(track, entity)| (track.into(), entity.0)))
+    }
+}
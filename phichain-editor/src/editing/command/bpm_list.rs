@@ -1,8 +1,10 @@
+use crate::editing::command::summary::{Action, Item, Summarize, Summary};
 use bevy::prelude::World;
 use phichain_chart::bpm_list::{BpmList, BpmPoint};
+use serde::{Deserialize, Serialize};
 use undo::Edit;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct CreateBpmPoint(BpmPoint);
 
 impl CreateBpmPoint {
@@ -29,7 +31,13 @@ impl Edit for CreateBpmPoint {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+impl Summarize for CreateBpmPoint {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Create, Item::BpmPoint)]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct RemoveBpmPoint {
     index: usize,
     point: Option<BpmPoint>,
@@ -59,7 +67,13 @@ impl Edit for RemoveBpmPoint {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+impl Summarize for RemoveBpmPoint {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Remove, Item::BpmPoint)]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct EditBpmPoint {
     index: usize,
     from: BpmPoint,
@@ -79,10 +93,18 @@ impl Edit for EditBpmPoint {
     fn edit(&mut self, target: &mut Self::Target) -> Self::Output {
         let mut bpm_list = target.resource_mut::<BpmList>();
         bpm_list.0[self.index] = self.to;
+        bpm_list.compute();
     }
 
     fn undo(&mut self, target: &mut Self::Target) -> Self::Output {
         let mut bpm_list = target.resource_mut::<BpmList>();
         bpm_list.0[self.index] = self.from;
+        bpm_list.compute();
+    }
+}
+
+impl Summarize for EditBpmPoint {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Edit, Item::BpmPoint)]
     }
 }
@@ -1,14 +1,19 @@
+use crate::editing::command::summary::{Action, Item, Summarize, Summary};
 use crate::events::curve_note_track::{DespawnCurveNoteTrackEvent, SpawnCurveNoteTrackEvent};
 use crate::events::EditorEvent;
 use bevy::prelude::{debug, ChildOf, Entity, World};
 use phichain_game::curve_note_track::CurveNoteTrack;
+use serde::{Deserialize, Serialize};
 use undo::Edit;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateCurveNoteTrack {
+    #[serde(with = "crate::chart_id::entity")]
     pub line_entity: Entity,
+    #[serde(with = "crate::chart_id::track")]
     pub track: CurveNoteTrack,
 
+    #[serde(with = "crate::chart_id::entity::option")]
     pub track_entity: Option<Entity>,
 }
 
@@ -56,9 +61,17 @@ impl Edit for CreateCurveNoteTrack {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Summarize for CreateCurveNoteTrack {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Create, Item::CurveNoteTrack).on_line(Some(self.line_entity))]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoveCurveNoteTrack {
+    #[serde(with = "crate::chart_id::entity")]
     pub entity: Entity,
+    #[serde(with = "crate::chart_id::track::paired")]
     pub track: Option<(CurveNoteTrack, Entity)>,
 }
 
@@ -100,3 +113,10 @@ impl Edit for RemoveCurveNoteTrack {
         }
     }
 }
+
+impl Summarize for RemoveCurveNoteTrack {
+    fn summarize(&self, world: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Remove, Item::CurveNoteTrack)
+            .on_line(world.get::<ChildOf>(self.entity).map(ChildOf::parent))]
+    }
+}
@@ -1,14 +1,18 @@
+use crate::editing::command::summary::{Action, Item, Summarize, Summary};
 use crate::events::event::{DespawnLineEventEvent, SpawnLineEventEvent};
 use crate::events::EditorEvent;
 use bevy::prelude::*;
 use phichain_chart::event::LineEvent;
 use phichain_game::event::EventOf;
+use serde::{Deserialize, Serialize};
 use undo::Edit;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct CreateEvent {
+    #[serde(with = "crate::chart_id::entity")]
     pub line_entity: Entity,
     pub event: LineEvent,
+    #[serde(with = "crate::chart_id::entity::option")]
     pub event_entity: Option<Entity>,
 }
 
@@ -47,9 +51,17 @@ impl Edit for CreateEvent {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+impl Summarize for CreateEvent {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Create, Item::Event).on_line(Some(self.line_entity))]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct RemoveEvent {
+    #[serde(with = "crate::chart_id::entity")]
     pub entity: Entity,
+    #[serde(with = "crate::chart_id::entity::paired")]
     pub event: Option<(LineEvent, Entity)>,
 }
 
@@ -92,8 +104,16 @@ impl Edit for RemoveEvent {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+impl Summarize for RemoveEvent {
+    fn summarize(&self, world: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Remove, Item::Event)
+            .on_line(world.get::<EventOf>(self.entity).map(|x| x.0))]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct EditEvent {
+    #[serde(with = "crate::chart_id::entity")]
     entity: Entity,
     from: LineEvent,
     to: LineEvent,
@@ -122,5 +142,12 @@ impl Edit for EditEvent {
     }
 }
 
+impl Summarize for EditEvent {
+    fn summarize(&self, world: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Edit, Item::Event)
+            .on_line(world.get::<EventOf>(self.entity).map(|x| x.0))]
+    }
+}
+
 #[cfg(test)]
 mod tests {}
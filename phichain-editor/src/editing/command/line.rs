@@ -1,3 +1,4 @@
+use crate::editing::command::summary::{Action, Item, Summarize, Summary};
 use crate::events::line::{DespawnLineEvent, SpawnLineEvent};
 use crate::events::EditorEvent;
 use crate::timing::ChartTime;
@@ -9,10 +10,11 @@ use phichain_chart::event::{EventEvaluationResult, LineEvent, LineEventKind, Lin
 use phichain_chart::serialization::SerializedLine;
 use phichain_game::event::Events;
 use phichain_game::serialization::{SerializeLine, SerializeLineParam};
+use serde::{Deserialize, Serialize};
 use undo::Edit;
 
-#[derive(Debug, Copy, Clone)]
-pub struct CreateLine(Option<Entity>);
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct CreateLine(#[serde(with = "crate::chart_id::entity::option")] Option<Entity>);
 
 impl CreateLine {
     pub fn new() -> Self {
@@ -47,15 +49,28 @@ impl Edit for CreateLine {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Summarize for CreateLine {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Create, Item::Line)]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoveLine {
+    #[serde(with = "crate::chart_id::entity")]
     entity: Entity,
-    line: Option<(SerializedLine, Option<Entity>)>,
+    line: Option<SerializedLine>,
+    #[serde(with = "crate::chart_id::entity::option")]
+    parent: Option<Entity>,
 }
 
 impl RemoveLine {
     pub fn new(entity: Entity) -> Self {
-        Self { entity, line: None }
+        Self {
+            entity,
+            line: None,
+            parent: None,
+        }
     }
 }
 
@@ -79,7 +94,8 @@ impl Edit for RemoveLine {
             })
             .expect("Failed to serialize line");
 
-        self.line = Some((serialized_line, parent));
+        self.line = Some(serialized_line);
+        self.parent = parent;
         DespawnLineEvent::builder()
             .target(self.entity)
             .keep_entity(true)
@@ -91,8 +107,8 @@ impl Edit for RemoveLine {
         if let Some(ref line) = self.line {
             // restore line entity and its children
             SpawnLineEvent::builder()
-                .line(line.0.clone())
-                .maybe_parent(line.1)
+                .line(line.clone())
+                .maybe_parent(self.parent)
                 .target(self.entity)
                 .build()
                 .run(target);
@@ -100,12 +116,21 @@ impl Edit for RemoveLine {
     }
 }
 
+impl Summarize for RemoveLine {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Remove, Item::Line)]
+    }
+}
+
 /// Move a line as child of another line
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoveLineAsChild {
+    #[serde(with = "crate::chart_id::entity")]
     entity: Entity,
+    #[serde(with = "crate::chart_id::entity::option")]
     prev_parent: Option<Entity>,
     /// Some = move as child of this line, None = move to root
+    #[serde(with = "crate::chart_id::entity::option")]
     target: Option<Entity>,
 }
 
@@ -146,9 +171,17 @@ impl Edit for MoveLineAsChild {
     }
 }
 
-#[derive(Debug, Clone)]
+impl Summarize for MoveLineAsChild {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Move, Item::Line)]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateLineFromSelected {
+    #[serde(with = "crate::chart_id::entity::option")]
     created_entity: Option<Entity>,
+    #[serde(with = "crate::chart_id::entity")]
     selected_line: Entity,
 }
 
@@ -193,6 +226,7 @@ impl Edit for CreateLineFromSelected {
                                 }
                                 LineEventKind::Opacity => opacity_value = opacity_value.max(value),
                                 LineEventKind::Speed => speed_value = speed_value.max(value),
+                                _ => {}
                             }
                         }
                     }
@@ -213,30 +247,35 @@ impl Edit for CreateLineFromSelected {
             notes: vec![],
             events: vec![
                 LineEvent {
+                    id: None,
                     kind: LineEventKind::X,
                     value: LineEventValue::constant(current_state.0),
                     start_beat: Beat::ZERO,
                     end_beat: Beat::ONE,
                 },
                 LineEvent {
+                    id: None,
                     kind: LineEventKind::Y,
                     value: LineEventValue::constant(current_state.1),
                     start_beat: Beat::ZERO,
                     end_beat: Beat::ONE,
                 },
                 LineEvent {
+                    id: None,
                     kind: LineEventKind::Rotation,
                     value: LineEventValue::constant(current_state.2),
                     start_beat: Beat::ZERO,
                     end_beat: Beat::ONE,
                 },
                 LineEvent {
+                    id: None,
                     kind: LineEventKind::Opacity,
                     value: LineEventValue::constant(current_state.3),
                     start_beat: Beat::ZERO,
                     end_beat: Beat::ONE,
                 },
                 LineEvent {
+                    id: None,
                     kind: LineEventKind::Speed,
                     value: LineEventValue::constant(current_state.4),
                     start_beat: Beat::ZERO,
@@ -245,6 +284,7 @@ impl Edit for CreateLineFromSelected {
             ],
             children: vec![],
             curve_note_tracks: vec![],
+            text_events: vec![],
         };
 
         let entity = SpawnLineEvent::builder().line(new_line).build().run(target);
@@ -262,3 +302,9 @@ impl Edit for CreateLineFromSelected {
         }
     }
 }
+
+impl Summarize for CreateLineFromSelected {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Create, Item::Line)]
+    }
+}
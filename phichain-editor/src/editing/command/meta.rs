@@ -1,9 +1,11 @@
+use crate::editing::command::summary::{Action, Item, Summarize, Summary};
 use crate::project::{Project, ProjectMeta};
 use bevy::prelude::World;
 use phichain_chart::offset::Offset;
+use serde::{Deserialize, Serialize};
 use undo::Edit;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EditMeta {
     from: ProjectMeta,
     to: ProjectMeta,
@@ -30,7 +32,13 @@ impl Edit for EditMeta {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+impl Summarize for EditMeta {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Edit, Item::Meta)]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct EditOffset {
     from: f32,
     to: f32,
@@ -56,3 +64,9 @@ impl Edit for EditOffset {
         offset.0 = self.from;
     }
 }
+
+impl Summarize for EditOffset {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Edit, Item::Offset)]
+    }
+}
@@ -4,6 +4,7 @@ pub mod event;
 pub mod line;
 pub mod meta;
 pub mod note;
+pub mod summary;
 
 use crate::editing::command::bpm_list::{CreateBpmPoint, EditBpmPoint, RemoveBpmPoint};
 use crate::editing::command::curve_note_track::{CreateCurveNoteTrack, RemoveCurveNoteTrack};
@@ -13,10 +14,12 @@ use crate::editing::command::line::{
 };
 use crate::editing::command::meta::{EditMeta, EditOffset};
 use crate::editing::command::note::{CreateNote, EditNote, RemoveNote};
+use crate::editing::command::summary::{Summarize, Summary};
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use undo::Edit;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EditorCommand {
     CreateNote(CreateNote),
     RemoveNote(RemoveNote),
@@ -44,7 +47,7 @@ pub enum EditorCommand {
     CommandSequence(CommandSequence),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandSequence(pub Vec<EditorCommand>);
 
 impl Edit for CommandSequence {
@@ -64,6 +67,12 @@ impl Edit for CommandSequence {
     }
 }
 
+impl Summarize for CommandSequence {
+    fn summarize(&self, world: &World) -> Vec<Summary> {
+        self.0.iter().flat_map(|x| x.summarize(world)).collect()
+    }
+}
+
 // TODO: use enum_dispatch
 macro_rules! impl_edit_for_command {
     ($($variant:ident),*) => {
@@ -87,6 +96,16 @@ macro_rules! impl_edit_for_command {
                 }
             }
         }
+
+        impl Summarize for EditorCommand {
+            fn summarize(&self, world: &World) -> Vec<Summary> {
+                match self {
+                    $(
+                        EditorCommand::$variant(cmd) => cmd.summarize(world),
+                    )*
+                }
+            }
+        }
     };
 }
 
@@ -1,14 +1,18 @@
+use crate::editing::command::summary::{Action, Item, Summarize, Summary};
 use crate::events::note::{DespawnNoteEvent, SpawnNoteEvent};
 use crate::events::EditorEvent;
 use bevy::prelude::*;
 use phichain_chart::note::Note;
+use serde::{Deserialize, Serialize};
 use undo::Edit;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct CreateNote {
+    #[serde(with = "crate::chart_id::entity")]
     pub line_entity: Entity,
     pub note: Note,
 
+    #[serde(with = "crate::chart_id::entity::option")]
     pub note_entity: Option<Entity>,
 }
 
@@ -47,9 +51,17 @@ impl Edit for CreateNote {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+impl Summarize for CreateNote {
+    fn summarize(&self, _: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Create, Item::Note).on_line(Some(self.line_entity))]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct RemoveNote {
+    #[serde(with = "crate::chart_id::entity")]
     pub entity: Entity,
+    #[serde(with = "crate::chart_id::entity::paired")]
     pub note: Option<(Note, Entity)>,
 }
 
@@ -89,8 +101,16 @@ impl Edit for RemoveNote {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+impl Summarize for RemoveNote {
+    fn summarize(&self, world: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Remove, Item::Note)
+            .on_line(world.get::<ChildOf>(self.entity).map(ChildOf::parent))]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct EditNote {
+    #[serde(with = "crate::chart_id::entity")]
     entity: Entity,
     from: Note,
     to: Note,
@@ -119,6 +139,13 @@ impl Edit for EditNote {
     }
 }
 
+impl Summarize for EditNote {
+    fn summarize(&self, world: &World) -> Vec<Summary> {
+        vec![Summary::new(Action::Edit, Item::Note)
+            .on_line(world.get::<ChildOf>(self.entity).map(ChildOf::parent))]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
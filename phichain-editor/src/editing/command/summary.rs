@@ -0,0 +1,117 @@
+//! Readable descriptions of [`EditorCommand`]s, e.g. "Edit 12 notes on line 'Main'"
+
+use crate::editing::command::EditorCommand;
+use bevy::prelude::*;
+use phichain_chart::line::Line;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Create,
+    Remove,
+    Edit,
+    Move,
+}
+
+impl Action {
+    fn key(self) -> &'static str {
+        match self {
+            Action::Create => "history.action.create",
+            Action::Remove => "history.action.remove",
+            Action::Edit => "history.action.edit",
+            Action::Move => "history.action.move",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Item {
+    Note,
+    Event,
+    Line,
+    CurveNoteTrack,
+    BpmPoint,
+    Meta,
+    Offset,
+}
+
+impl Item {
+    fn key(self) -> &'static str {
+        match self {
+            Item::Note => "history.item.note",
+            Item::Event => "history.item.event",
+            Item::Line => "history.item.line",
+            Item::CurveNoteTrack => "history.item.curve_note_track",
+            Item::BpmPoint => "history.item.bpm_point",
+            Item::Meta => "history.item.meta",
+            Item::Offset => "history.item.offset",
+        }
+    }
+}
+
+/// What a single command does, on which line if the item belongs to one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub action: Action,
+    pub item: Item,
+    pub line: Option<Entity>,
+}
+
+impl Summary {
+    pub fn new(action: Action, item: Item) -> Self {
+        Self {
+            action,
+            item,
+            line: None,
+        }
+    }
+
+    pub fn on_line(mut self, line: Option<Entity>) -> Self {
+        self.line = line;
+        self
+    }
+}
+
+pub trait Summarize {
+    /// Summarize the command, a summary for each command in case of a sequence
+    ///
+    /// This is called before the command is applied, so the items it edits or removes are still in the world
+    fn summarize(&self, world: &World) -> Vec<Summary>;
+}
+
+impl EditorCommand {
+    /// A readable description of the command, this should be called before the command is applied
+    pub fn describe(&self, world: &World) -> String {
+        describe(&self.summarize(world), world)
+    }
+}
+
+fn describe(summaries: &[Summary], world: &World) -> String {
+    let Some(first) = summaries.first() else {
+        return t!("history.empty").into();
+    };
+    let count = summaries.len();
+
+    let description = if summaries
+        .iter()
+        .all(|x| x.action == first.action && x.item == first.item)
+    {
+        let items = if count == 1 {
+            t!(format!("{}.one", first.item.key()).as_str())
+        } else {
+            t!(format!("{}.many", first.item.key()).as_str(), count = count)
+        };
+        t!(first.action.key(), items = items)
+    } else {
+        t!("history.changes", count = count)
+    };
+
+    let line = first
+        .line
+        .filter(|line| summaries.iter().all(|x| x.line == Some(*line)))
+        .and_then(|line| world.get::<Line>(line));
+
+    match line {
+        Some(line) => t!("history.on_line", action = description, line = line.name).into(),
+        None => description.into(),
+    }
+}
@@ -10,6 +10,7 @@ use crate::hotkey::{Hotkey, HotkeyContext, HotkeyExt};
 use crate::identifier::{Identifier, IntoIdentifier};
 use crate::schedule::EditorSet;
 use crate::selection::SelectedLine;
+use crate::timeline::event::EVENT_TRACK_COUNT;
 use crate::timeline::{TimelineContext, TimelineItem};
 use crate::utils::convert::BevyEguiConvert;
 use phichain_chart::event::{LineEvent, LineEventKind, LineEventValue};
@@ -87,8 +88,9 @@ fn create_event_system(
                 let beat = bpm_list.beat_at(time).value();
                 let beat = ctx.settings.attach(beat);
 
-                let track =
-                    ((cursor_position.x - viewport.min.x) / (viewport.width() / 5.0)).ceil() as u8;
+                let track = ((cursor_position.x - viewport.min.x)
+                    / (viewport.width() / EVENT_TRACK_COUNT as f32))
+                    .ceil() as u8;
 
                 (track, beat)
             };
@@ -159,6 +161,7 @@ fn create_event_system(
                     };
                     commands.spawn((
                         LineEvent {
+                            id: None,
                             kind,
                             value,
                             start_beat: beat,
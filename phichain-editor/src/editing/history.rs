@@ -1,23 +1,564 @@
+use crate::chart_id::{Id, IdGen, IdMapping};
 use crate::editing::command::EditorCommand;
+use anyhow::{bail, Context};
 use bevy::prelude::*;
-use undo::History;
+use phichain_chart::event::LineEvent;
+use phichain_chart::line::Line;
+use phichain_chart::note::Note;
+use phichain_chart::project::ProjectPath;
+use phichain_game::curve_note_track::CurveNoteTrack;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use undo::{At, Edit, History};
 
 #[derive(Resource, Default)]
 pub struct EditorHistory(pub History<EditorCommand>);
 
 impl EditorHistory {
+    /// A history keeping at most `limit` edits, the oldest ones are dropped first
+    pub fn with_limit(limit: usize) -> Self {
+        Self(History::builder().limit(limit.max(1)).build())
+    }
+
     pub fn edit(&mut self, world: &mut World, edit: EditorCommand) {
         info!("Executing command {:?}", edit);
+        let description = edit.describe(world);
+        let index = self.0.head().index;
         self.0.edit(world, edit);
+
+        let head = self.0.head();
+        let mut tree = world.resource_mut::<HistoryTree>();
+        // the head stays in place when the oldest edit is dropped for exceeding the limit
+        if head.index == index {
+            tree.drop_oldest();
+        }
+        tree.push(description, head);
     }
 
     pub fn undo(&mut self, world: &mut World) {
         info!("Undo");
+        let head = self.0.head();
         self.0.undo(world);
+        if self.0.head() != head {
+            world.resource_mut::<HistoryTree>().undo();
+        }
     }
 
     pub fn redo(&mut self, world: &mut World) {
         info!("Redo");
+        let head = self.0.head();
         self.0.redo(world);
+        if self.0.head() != head {
+            world.resource_mut::<HistoryTree>().redo();
+        }
+    }
+
+    /// Jump to a state of the [`HistoryTree`], switching to its branch if needed
+    pub fn go_to(&mut self, world: &mut World, node: usize) {
+        let Some(at) = world.resource::<HistoryTree>().get(node).map(|x| x.at) else {
+            return;
+        };
+        info!("Go to {:?}", at);
+        self.0.go_to(world, at);
+        world.resource_mut::<HistoryTree>().select(node);
+    }
+}
+
+/// A state of the [`HistoryTree`]
+#[derive(Debug, Clone)]
+pub struct HistoryNode {
+    /// Description of the edit leading to this state, empty for the initial state
+    pub description: String,
+    /// Where the state is in the [`EditorHistory`]
+    pub at: At,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    /// The child redoing leads to, which is the one visited last
+    pub active: Option<usize>,
+}
+
+impl HistoryNode {
+    fn new(description: String, at: At, parent: Option<usize>) -> Self {
+        Self {
+            description,
+            at,
+            parent,
+            children: vec![],
+            active: None,
+        }
+    }
+}
+
+/// The states of the [`EditorHistory`] as a tree, an edit made after undoing starts a new branch
+///
+/// The [`History`] keeps the branches but does not expose them, so they are tracked here as the
+/// history is edited
+#[derive(Resource, Debug, Clone)]
+pub struct HistoryTree {
+    nodes: BTreeMap<usize, HistoryNode>,
+    root: usize,
+    current: usize,
+    next: usize,
+}
+
+impl FromWorld for HistoryTree {
+    fn from_world(world: &mut World) -> Self {
+        Self::new(world.resource::<EditorHistory>().0.head())
+    }
+}
+
+impl HistoryTree {
+    /// A tree holding only the initial state, which is at `at` in the history
+    pub fn new(at: At) -> Self {
+        Self {
+            nodes: BTreeMap::from([(0, HistoryNode::new(String::new(), at, None))]),
+            root: 0,
+            current: 0,
+            next: 1,
+        }
+    }
+
+    pub fn get(&self, node: usize) -> Option<&HistoryNode> {
+        self.nodes.get(&node)
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    fn push(&mut self, description: String, at: At) {
+        let node = self.next;
+        self.next += 1;
+
+        self.nodes
+            .insert(node, HistoryNode::new(description, at, Some(self.current)));
+        let current = self.nodes.get_mut(&self.current).unwrap();
+        current.children.push(node);
+        current.active = Some(node);
+        self.current = node;
+    }
+
+    fn undo(&mut self) {
+        if let Some(parent) = self.nodes[&self.current].parent {
+            self.current = parent;
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(active) = self.nodes[&self.current].active {
+            self.current = active;
+        }
+    }
+
+    /// Make `node` the current state, redoing from its ancestors now leads to it
+    fn select(&mut self, node: usize) {
+        let path = self.path(node);
+        for pair in path.windows(2) {
+            self.nodes.get_mut(&pair[0]).unwrap().active = Some(pair[1]);
+        }
+        self.current = node;
+    }
+
+    /// Drop the initial state along with the branches starting from it, once the oldest edit is dropped
+    /// from the history
+    fn drop_oldest(&mut self) {
+        let Some(root) = self.nodes[&self.root].active else {
+            return;
+        };
+
+        let mut stack = vec![self.root];
+        while let Some(node) = stack.pop() {
+            let node = self.nodes.remove(&node).unwrap();
+            stack.extend(node.children.into_iter().filter(|x| *x != root));
+        }
+
+        self.nodes.get_mut(&root).unwrap().parent = None;
+        self.root = root;
+        // every state is one edit closer to the start of the history
+        for node in self.nodes.values_mut() {
+            node.at.index = node.at.index.saturating_sub(1);
+        }
+    }
+
+    /// The states from the initial state to `node`
+    pub fn path(&self, node: usize) -> Vec<usize> {
+        let mut path = vec![node];
+        while let Some(parent) = self.nodes.get(path.last().unwrap()).and_then(|x| x.parent) {
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+
+    /// The state reached by redoing from `node` as much as possible
+    pub fn tip(&self, node: usize) -> usize {
+        *self.branch(node).last().unwrap()
+    }
+
+    /// The states of the current branch, from the initial state to its [tip](Self::tip)
+    pub fn timeline(&self) -> Vec<usize> {
+        let mut timeline = self.path(self.current);
+        timeline.pop();
+        timeline.extend(self.branch(self.current));
+        timeline
+    }
+
+    /// The states from `node` to its [tip](Self::tip)
+    fn branch(&self, node: usize) -> Vec<usize> {
+        let mut branch = vec![node];
+        while let Some(active) = self
+            .nodes
+            .get(branch.last().unwrap())
+            .and_then(|x| x.active)
+        {
+            branch.push(active);
+        }
+        branch
+    }
+
+    /// The tips of all branches, in the order they are created
+    pub fn tips(&self) -> Vec<usize> {
+        self.nodes
+            .iter()
+            .filter(|(_, node)| node.children.is_empty())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+/// A curve note track of the chart, identified by its notes as the chart does not store its [`Id`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct PersistedTrack {
+    id: Id,
+    from: Id,
+    to: Id,
+}
+
+/// The curve note tracks of the world along with the [`Id`]s of their notes
+fn tracks(world: &mut World) -> Vec<(Entity, PersistedTrack)> {
+    let mut query = world.query::<(Entity, &Id, &CurveNoteTrack)>();
+    query
+        .iter(world)
+        .filter_map(|(entity, id, track)| {
+            let (from, to) = track.get_entities()?;
+            let track = PersistedTrack {
+                id: *id,
+                from: *world.get::<Id>(from)?,
+                to: *world.get::<Id>(to)?,
+            };
+            Some((entity, track))
+        })
+        .collect()
+}
+
+/// The history persisted next to the chart, so edits can be undone in later sessions
+///
+/// Only the edits of the current branch are kept, the branches undone from are dropped
+#[derive(Serialize, Deserialize)]
+struct PersistedHistory {
+    /// Curve note tracks of the chart, which are given new IDs every time the chart is loaded
+    tracks: Vec<PersistedTrack>,
+    /// Fingerprint of the chart file the history was saved along, to tell if it changed since
+    fingerprint: u64,
+    /// IDs referenced by the history which are not in the chart, like removed notes
+    detached: Vec<Id>,
+    next_id: u64,
+    /// The edits of the current branch, oldest first
+    edits: serde_json::Value,
+    /// Amount of edits applied to the chart, the ones after them can be redone
+    applied: usize,
+}
+
+/// The file the history is persisted to, under the project directory next to `.autosave`
+pub fn history_path(path: &ProjectPath) -> PathBuf {
+    path.sub_path(".history.json")
+}
+
+/// FNV-1a hash of the chart file, stable across builds unlike [`std::hash::DefaultHasher`]
+fn fingerprint(path: &ProjectPath) -> anyhow::Result<u64> {
+    let chart = std::fs::read(path.chart_path()).context("Failed to read chart")?;
+    Ok(chart.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    }))
+}
+
+/// Persist the history of the world, this should be done right after the chart is saved
+pub fn persist_history(world: &mut World, path: &ProjectPath) -> anyhow::Result<()> {
+    let tracks = tracks(world)
+        .into_iter()
+        .map(|(_, track)| track)
+        .collect::<Vec<_>>();
+    let mut in_chart = world
+        .query_filtered::<&Id, Or<(With<Line>, With<Note>, With<LineEvent>)>>()
+        .iter(world)
+        .copied()
+        .collect::<HashSet<_>>();
+    in_chart.extend(tracks.iter().map(|track| track.id));
+
+    let applied = world.resource::<EditorHistory>().0.head().index;
+    let (edits, mapping) = IdMapping::new(world).scope(|| {
+        let history = &world.resource::<EditorHistory>().0;
+        serde_json::to_value(
+            history
+                .entries()
+                .map(|entry| entry.get())
+                .collect::<Vec<_>>(),
+        )
+    });
+    let edits = edits.context("Failed to serialize history")?;
+    world.resource_mut::<IdGen>().reserve(mapping.next());
+
+    let persisted = PersistedHistory {
+        detached: mapping
+            .written
+            .into_iter()
+            .filter(|id| !in_chart.contains(id))
+            .collect(),
+        tracks,
+        fingerprint: fingerprint(path)?,
+        next_id: world.resource::<IdGen>().peek(),
+        edits,
+        applied,
+    };
+
+    std::fs::write(
+        history_path(path),
+        serde_json::to_string(&persisted).context("Failed to serialize history")?,
+    )
+    .context("Failed to write history")?;
+
+    Ok(())
+}
+
+/// Restore the history persisted along the chart, which must be loaded into the world already
+///
+/// The history keeps at most `limit` edits from now on, like [`EditorHistory::with_limit`]. The
+/// oldest edits are dropped if there are more
+///
+/// Returns [`None`] if there is no persisted history
+pub fn restore_history(
+    world: &mut World,
+    path: &ProjectPath,
+    limit: usize,
+) -> anyhow::Result<Option<(History<EditorCommand>, HistoryTree)>> {
+    let history_path = history_path(path);
+    if !history_path.exists() {
+        return Ok(None);
+    }
+
+    let persisted: PersistedHistory = serde_json::from_str(
+        &std::fs::read_to_string(history_path).context("Failed to read history")?,
+    )
+    .context("Invalid history")?;
+    if persisted.fingerprint != fingerprint(path)? {
+        bail!("The chart has been changed outside of the editor");
+    }
+
+    // make sure the ids of the loaded items are assigned before the ones of the tracks are replaced
+    world.flush();
+    let mut persisted_tracks = HashMap::<_, Vec<_>>::new();
+    for track in persisted.tracks {
+        persisted_tracks
+            .entry((track.from, track.to))
+            .or_default()
+            .push(track.id);
+    }
+    let tracks = tracks(world)
+        .into_iter()
+        .map(|(entity, track)| {
+            let id = persisted_tracks
+                .get_mut(&(track.from, track.to))
+                .and_then(|ids| ids.pop());
+            id.map(|id| (entity, id))
+        })
+        .collect::<Option<Vec<_>>>();
+    let Some(tracks) = tracks.filter(|_| persisted_tracks.values().all(|x| x.is_empty())) else {
+        bail!("The history does not match the chart");
+    };
+
+    for (entity, id) in tracks {
+        world.entity_mut(entity).insert(id);
+    }
+    // removed items are kept as entities holding only their id, like `keep_entity` does
+    for id in persisted.detached {
+        world.spawn(id);
+    }
+    world.resource_mut::<IdGen>().reserve(persisted.next_id);
+
+    let (edits, _) = IdMapping::new(world)
+        .scope(|| serde_json::from_value::<Vec<EditorCommand>>(persisted.edits));
+    let mut edits = edits.context("Invalid history")?;
+    if persisted.applied > edits.len() {
+        bail!("Invalid history");
+    }
+
+    // the oldest edits are dropped to fit the limit, but the state of the chart must stay in the
+    // history, so redoable edits past the limit are dropped instead if needed
+    let limit = limit.max(1);
+    let start = edits.len().saturating_sub(limit).min(persisted.applied);
+    edits.truncate(start + limit);
+    let mut edits = edits.split_off(start);
+    let applied = persisted.applied - start;
+
+    // rewind the chart to before the kept edits, then make them again so the history holds them
+    for edit in edits[..applied].iter_mut().rev() {
+        edit.undo(world);
+    }
+    let redoable = edits.len() - applied;
+    let mut history = EditorHistory::with_limit(limit);
+    world.insert_resource(HistoryTree::new(history.0.head()));
+    for edit in edits {
+        history.edit(world, edit);
+    }
+    for _ in 0..redoable {
+        history.undo(world);
+    }
+    history.0.set_saved();
+
+    let tree = world
+        .remove_resource::<HistoryTree>()
+        .context("Invalid history")?;
+    Ok(Some((history.0, tree)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart_id::ChartIdPlugin;
+    use crate::editing::command::note::{CreateNote, RemoveNote};
+    use phichain_chart::beat::Beat;
+    use phichain_chart::note::NoteKind;
+    use phichain_game::index::ChartIndexPlugin;
+    use std::collections::BTreeSet;
+    use tempfile::TempDir;
+
+    fn edit(world: &mut World, command: EditorCommand) {
+        world.resource_scope(|world, mut history: Mut<EditorHistory>| {
+            history.edit(world, command);
+        });
+    }
+
+    /// A world with the chart as saved by `test_persist_and_restore`, the removed note is not in it
+    fn saved_chart(drag: Note) -> App {
+        let mut app = App::new();
+        app.add_plugins((ChartIdPlugin, ChartIndexPlugin));
+        let world = app.world_mut();
+        let line = world.spawn((Line::default(), Id(0))).id();
+        world.spawn((drag, Id(2), ChildOf(line)));
+        app
+    }
+
+    fn note_ids(world: &mut World) -> BTreeSet<Id> {
+        world
+            .query_filtered::<&Id, With<Note>>()
+            .iter(world)
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn test_history_tree() {
+        let mut tree = HistoryTree::new(At::new(0, 0));
+        tree.push("a".to_owned(), At::new(0, 1));
+        tree.push("b".to_owned(), At::new(0, 2));
+        tree.undo();
+        tree.push("c".to_owned(), At::new(1, 2));
+        assert_eq!(tree.timeline(), vec![0, 1, 3]);
+        assert_eq!(tree.tips(), vec![2, 3]);
+
+        tree.undo();
+        assert_eq!(tree.current(), 1);
+        tree.redo();
+        assert_eq!(tree.current(), 3);
+
+        // switching back to the first branch makes redoing follow it
+        tree.select(2);
+        tree.undo();
+        tree.redo();
+        assert_eq!(tree.current(), 2);
+        assert_eq!(tree.timeline(), vec![0, 1, 2]);
+
+        tree.drop_oldest();
+        assert_eq!(tree.path(2), vec![1, 2]);
+        assert_eq!(tree.get(2).unwrap().at.index, 1);
+        assert!(tree.get(0).is_none());
+    }
+
+    #[test]
+    fn test_persist_and_restore() {
+        let tmp = TempDir::new().unwrap();
+        let path = ProjectPath(tmp.path().to_path_buf());
+        std::fs::write(path.chart_path(), "{}").unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((ChartIdPlugin, ChartIndexPlugin));
+        let world = app.world_mut();
+        world.insert_resource(EditorHistory::with_limit(10));
+        world.init_resource::<HistoryTree>();
+
+        let line = world.spawn(Line::default()).id();
+        world.flush();
+        let tap = Note::new(NoteKind::Tap, true, Beat::ZERO, 0.0, 1.0);
+        let drag = Note::new(NoteKind::Drag, true, Beat::ONE, 0.0, 1.0);
+        edit(world, EditorCommand::CreateNote(CreateNote::new(line, tap)));
+        edit(
+            world,
+            EditorCommand::CreateNote(CreateNote::new(line, drag)),
+        );
+        let removed = world
+            .query::<(Entity, &Note)>()
+            .iter(world)
+            .find(|(_, note)| note.kind == NoteKind::Tap)
+            .map(|(entity, _)| entity)
+            .unwrap();
+        edit(world, EditorCommand::RemoveNote(RemoveNote::new(removed)));
+        world.flush();
+        assert_eq!(note_ids(world), BTreeSet::from([Id(2)]));
+
+        persist_history(world, &path).unwrap();
+
+        let mut app = saved_chart(drag);
+        let world = app.world_mut();
+        let (history, tree) = restore_history(world, &path, 10).unwrap().unwrap();
+        assert_eq!(tree.timeline().len(), 4);
+        world.insert_resource(EditorHistory(history));
+        world.insert_resource(tree);
+
+        // undoing the removal brings the note back as the same item
+        world.resource_scope(|world, mut history: Mut<EditorHistory>| history.undo(world));
+        world.flush();
+        assert_eq!(note_ids(world), BTreeSet::from([Id(1), Id(2)]));
+        world.resource_scope(|world, mut history: Mut<EditorHistory>| history.undo(world));
+        world.flush();
+        assert_eq!(note_ids(world), BTreeSet::from([Id(1)]));
+        assert_eq!(world.resource::<HistoryTree>().current(), 1);
+
+        world.resource_scope(|world, mut history: Mut<EditorHistory>| {
+            history.redo(world);
+            history.redo(world);
+        });
+        world.flush();
+        assert_eq!(note_ids(world), BTreeSet::from([Id(2)]));
+        assert_eq!(world.resource::<HistoryTree>().current(), 3);
+        assert!(world.resource::<EditorHistory>().0.is_saved());
+
+        // the oldest edits are dropped to fit a lower limit
+        let mut app = saved_chart(drag);
+        let world = app.world_mut();
+        let (history, tree) = restore_history(world, &path, 2).unwrap().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(tree.timeline().len(), 3);
+        world.insert_resource(EditorHistory(history));
+        world.insert_resource(tree);
+
+        world.resource_scope(|world, mut history: Mut<EditorHistory>| {
+            history.undo(world);
+            history.undo(world);
+            history.undo(world);
+        });
+        world.flush();
+        assert_eq!(note_ids(world), BTreeSet::from([Id(1)]));
+        assert_eq!(world.resource::<HistoryTree>().current(), 0);
     }
 }
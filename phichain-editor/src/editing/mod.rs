@@ -5,9 +5,11 @@ use crate::editing::create_event::CreateEventPlugin;
 use crate::editing::create_note::CreateNotePlugin;
 use crate::editing::curve_note_track::CurveNoteTrackPlugin;
 use crate::editing::delete_selected::DeleteSelectedPlugin;
-use crate::editing::history::EditorHistory;
+use crate::editing::history::{EditorHistory, HistoryTree};
 use crate::editing::line::LineEditingPlugin;
 use crate::editing::moving::MovingPlugin;
+use crate::editing::quantize::QuantizePlugin;
+use crate::editing::transform::TransformPlugin;
 use crate::hotkey::modifier::Modifier;
 use crate::hotkey::Hotkey;
 use crate::schedule::EditorSet;
@@ -24,6 +26,8 @@ pub mod history;
 mod line;
 mod moving;
 pub mod pending;
+mod quantize;
+pub mod transform;
 
 pub struct EditingPlugin;
 
@@ -31,10 +35,13 @@ impl Plugin for EditingPlugin {
     fn build(&self, app: &mut App) {
         app.add_message::<DoCommand>()
             .init_resource::<EditorHistory>()
+            .init_resource::<HistoryTree>()
             .add_plugins(DeleteSelectedPlugin)
             .add_plugins(CreateNotePlugin)
             .add_plugins(CreateEventPlugin)
             .add_plugins(MovingPlugin)
+            .add_plugins(TransformPlugin)
+            .add_plugins(QuantizePlugin)
             .add_plugins(CurveNoteTrackPlugin)
             .add_plugins(ClipboardPlugin)
             .add_plugins(LineEditingPlugin)
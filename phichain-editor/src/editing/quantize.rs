@@ -0,0 +1,113 @@
+use crate::action::ActionRegistrationExt;
+use crate::editing::command::event::EditEvent;
+use crate::editing::command::note::EditNote;
+use crate::editing::command::{CommandSequence, EditorCommand};
+use crate::editing::pending::Pending;
+use crate::editing::DoCommand;
+use crate::notification::{ToastsExt, ToastsStorage};
+use crate::selection::Selected;
+use crate::timeline::settings::TimelineSettings;
+use bevy::prelude::*;
+use phichain_chart::event::LineEvent;
+use phichain_chart::line::Line;
+use phichain_chart::note::Note;
+use phichain_chart::quantize::Quantize;
+use phichain_game::event::EventOf;
+
+pub struct QuantizePlugin;
+
+impl Plugin for QuantizePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_action("phichain.quantize", quantize_system, None);
+    }
+}
+
+/// Snap the selected notes and events to the timeline grid, or the whole chart if nothing is selected
+fn quantize_system(
+    timeline_settings: Res<TimelineSettings>,
+    note_query: Query<(&Note, Entity, &ChildOf, Has<Selected>), Without<Pending>>,
+    event_query: Query<(&LineEvent, Entity, &EventOf, Has<Selected>), Without<Pending>>,
+    line_query: Query<&Line>,
+    mut toasts: ResMut<ToastsStorage>,
+    mut event_writer: MessageWriter<DoCommand>,
+) -> Result {
+    let quantize = Quantize::new(
+        timeline_settings.density,
+        timeline_settings.quantize_tolerance,
+    );
+
+    let any_selected = note_query.iter().any(|(.., selected)| selected)
+        || event_query.iter().any(|(.., selected)| selected);
+    let line_name = |entity: Entity| {
+        line_query
+            .get(entity)
+            .map(|line| line.name.clone())
+            .unwrap_or_default()
+    };
+
+    let mut commands = vec![];
+    let mut deviations = 0;
+
+    for (note, entity, child_of, selected) in &note_query {
+        if any_selected && !selected {
+            continue;
+        }
+
+        let (quantized, distance) = quantize.note(note);
+        if quantize.exceeds(distance) {
+            deviations += 1;
+            warn!(
+                "Note at {:?} on line {:?} moved {} beats by quantization",
+                note.beat,
+                line_name(child_of.parent()),
+                distance
+            );
+        }
+        if quantized != *note {
+            commands.push(EditorCommand::EditNote(EditNote::new(
+                entity, *note, quantized,
+            )));
+        }
+    }
+
+    for (event, entity, event_of, selected) in &event_query {
+        if any_selected && !selected {
+            continue;
+        }
+
+        let (quantized, distance) = quantize.event(event);
+        if quantize.exceeds(distance) {
+            deviations += 1;
+            warn!(
+                "{:?} event from {:?} to {:?} on line {:?} moved {} beats by quantization",
+                event.kind,
+                event.start_beat,
+                event.end_beat,
+                line_name(event_of.target()),
+                distance
+            );
+        }
+        if quantized != *event {
+            commands.push(EditorCommand::EditEvent(EditEvent::new(
+                entity, *event, quantized,
+            )));
+        }
+    }
+
+    if deviations > 0 {
+        toasts.warning(t!(
+            "quantize.deviations",
+            amount = deviations,
+            tolerance = quantize.tolerance
+        ));
+    }
+
+    if !commands.is_empty() {
+        toasts.success(t!("quantize.quantized", amount = commands.len()));
+        event_writer.write(DoCommand(EditorCommand::CommandSequence(CommandSequence(
+            commands,
+        ))));
+    }
+
+    Ok(())
+}
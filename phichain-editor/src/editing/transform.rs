@@ -0,0 +1,402 @@
+use crate::action::ActionRegistrationExt;
+use crate::editing::command::event::EditEvent;
+use crate::editing::command::note::EditNote;
+use crate::editing::command::{CommandSequence, EditorCommand};
+use crate::editing::DoCommand;
+use crate::selection::Selected;
+use bevy::prelude::*;
+use num::{CheckedAdd, CheckedMul, CheckedSub, Rational32};
+use phichain_chart::beat::Beat;
+use phichain_chart::event::{LineEvent, LineEventValue};
+use phichain_chart::note::Note;
+
+pub struct TransformPlugin;
+
+impl Plugin for TransformPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TransformSettings>()
+            .add_action("phichain.mirror", mirror_system, None)
+            .add_action("phichain.flip_side", flip_side_system, None)
+            .add_action("phichain.scale_x", scale_x_system, None)
+            .add_action("phichain.stretch", stretch_system, None)
+            .add_action("phichain.shift_later", shift_later_system, None)
+            .add_action("phichain.shift_earlier", shift_earlier_system, None);
+    }
+}
+
+/// Parameters of the transforms, edited in the inspector
+#[derive(Resource, Debug, Clone)]
+pub struct TransformSettings {
+    pub scale_factor: f32,
+    pub scale_pivot: f32,
+    pub stretch_numer: i32,
+    pub stretch_denom: i32,
+    /// The beat the time is stretched around, the start of the selection if [`None`]
+    pub stretch_anchor: Option<Beat>,
+    pub shift: Beat,
+}
+
+impl Default for TransformSettings {
+    fn default() -> Self {
+        Self {
+            scale_factor: 1.0,
+            scale_pivot: 0.0,
+            stretch_numer: 2,
+            stretch_denom: 1,
+            stretch_anchor: None,
+            shift: Beat::ONE,
+        }
+    }
+}
+
+/// A transform applied to every selected note and event at once
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    /// Mirror notes and X events horizontally around the center of the screen
+    Mirror,
+    /// Move notes above the line below it and vice versa
+    FlipSide,
+    /// Scale the x of notes and X events around a pivot
+    ScaleX { factor: f32, pivot: f32 },
+    /// Stretch the time of notes and events by a ratio around an anchor beat
+    Stretch { ratio: Rational32, anchor: Beat },
+    /// Move notes and events along the time, backwards if negative
+    Shift(Beat),
+}
+
+impl Transform {
+    /// Transform a note, [`None`] if it would be moved before beat 0 or out of range
+    pub fn note(&self, note: Note) -> Option<Note> {
+        Some(match *self {
+            Transform::Mirror => Note { x: -note.x, ..note },
+            Transform::FlipSide => Note {
+                above: !note.above,
+                ..note
+            },
+            Transform::ScaleX { factor, pivot } => Note {
+                x: pivot + (note.x - pivot) * factor,
+                ..note
+            },
+            Transform::Stretch { .. } | Transform::Shift(_) => {
+                let mut transformed = Note {
+                    beat: self.beat(note.beat)?,
+                    ..note
+                };
+                transformed.set_end_beat(self.beat(note.end_beat())?);
+                transformed
+            }
+        })
+    }
+
+    /// Transform an event, [`None`] if it would be moved before beat 0 or out of range
+    pub fn event(&self, event: LineEvent) -> Option<LineEvent> {
+        Some(match *self {
+            Transform::Mirror if event.kind.is_x() => LineEvent {
+                value: event.value.negated(),
+                ..event
+            },
+            Transform::ScaleX { factor, pivot } if event.kind.is_x() => LineEvent {
+                value: map_value(event.value, |x| pivot + (x - pivot) * factor),
+                ..event
+            },
+            Transform::Stretch { .. } | Transform::Shift(_) => LineEvent {
+                start_beat: self.beat(event.start_beat)?,
+                end_beat: self.beat(event.end_beat)?,
+                ..event
+            },
+            _ => event,
+        })
+    }
+
+    /// The beat a beat is moved to, [`None`] if it is negative or overflows
+    fn beat(&self, beat: Beat) -> Option<Beat> {
+        let beat = match *self {
+            Transform::Stretch { ratio, anchor } => {
+                let anchor = Rational32::from(anchor);
+                let stretched = Rational32::from(beat)
+                    .checked_sub(&anchor)?
+                    .checked_mul(&ratio)?
+                    .checked_add(&anchor)?;
+                Beat::from(stretched).reduced()
+            }
+            Transform::Shift(delta) => beat.checked_add(&delta)?,
+            _ => beat,
+        };
+
+        (beat >= Beat::ZERO).then_some(beat)
+    }
+}
+
+fn map_value(value: LineEventValue, f: impl Fn(f32) -> f32) -> LineEventValue {
+    match value {
+        LineEventValue::Transition { start, end, easing } => {
+            LineEventValue::transition(f(start), f(end), easing)
+        }
+        LineEventValue::Constant { value } => LineEventValue::constant(f(value)),
+    }
+}
+
+type SelectedNotes<'w, 's> = Query<'w, 's, (&'static Note, Entity), With<Selected>>;
+type SelectedEvents<'w, 's> = Query<'w, 's, (&'static LineEvent, Entity), With<Selected>>;
+
+/// Apply a transform to the selection as a single [`CommandSequence`]
+///
+/// Nothing is changed if any item would be moved before beat 0 or out of range
+fn transform_selected(
+    transform: Transform,
+    selected_notes: &SelectedNotes,
+    selected_events: &SelectedEvents,
+    event_writer: &mut MessageWriter<DoCommand>,
+) -> Result {
+    let mut commands = vec![];
+    for (note, entity) in selected_notes {
+        let to = transform
+            .note(*note)
+            .ok_or("Transformed notes must stay within beat 0 and the largest beat")?;
+        if to != *note {
+            commands.push(EditorCommand::EditNote(EditNote::new(entity, *note, to)));
+        }
+    }
+    for (event, entity) in selected_events {
+        let to = transform
+            .event(*event)
+            .ok_or("Transformed events must stay within beat 0 and the largest beat")?;
+        if to != *event {
+            commands.push(EditorCommand::EditEvent(EditEvent::new(entity, *event, to)));
+        }
+    }
+
+    if !commands.is_empty() {
+        event_writer.write(DoCommand(EditorCommand::CommandSequence(CommandSequence(
+            commands,
+        ))));
+    }
+
+    Ok(())
+}
+
+fn mirror_system(
+    selected_notes: SelectedNotes,
+    selected_events: SelectedEvents,
+    mut event_writer: MessageWriter<DoCommand>,
+) -> Result {
+    transform_selected(
+        Transform::Mirror,
+        &selected_notes,
+        &selected_events,
+        &mut event_writer,
+    )
+}
+
+fn flip_side_system(
+    selected_notes: SelectedNotes,
+    selected_events: SelectedEvents,
+    mut event_writer: MessageWriter<DoCommand>,
+) -> Result {
+    transform_selected(
+        Transform::FlipSide,
+        &selected_notes,
+        &selected_events,
+        &mut event_writer,
+    )
+}
+
+fn scale_x_system(
+    settings: Res<TransformSettings>,
+    selected_notes: SelectedNotes,
+    selected_events: SelectedEvents,
+    mut event_writer: MessageWriter<DoCommand>,
+) -> Result {
+    transform_selected(
+        Transform::ScaleX {
+            factor: settings.scale_factor,
+            pivot: settings.scale_pivot,
+        },
+        &selected_notes,
+        &selected_events,
+        &mut event_writer,
+    )
+}
+
+fn stretch_system(
+    settings: Res<TransformSettings>,
+    selected_notes: SelectedNotes,
+    selected_events: SelectedEvents,
+    mut event_writer: MessageWriter<DoCommand>,
+) -> Result {
+    if settings.stretch_numer <= 0 || settings.stretch_denom <= 0 {
+        return Err("Stretch ratio must be positive".into());
+    }
+
+    let selection_start = selected_notes
+        .iter()
+        .map(|(note, _)| note.beat)
+        .chain(selected_events.iter().map(|(event, _)| event.start_beat))
+        .min();
+    let Some(anchor) = settings.stretch_anchor.or(selection_start) else {
+        return Ok(());
+    };
+
+    transform_selected(
+        Transform::Stretch {
+            ratio: Rational32::new(settings.stretch_numer, settings.stretch_denom),
+            anchor,
+        },
+        &selected_notes,
+        &selected_events,
+        &mut event_writer,
+    )
+}
+
+fn shift_later_system(
+    settings: Res<TransformSettings>,
+    selected_notes: SelectedNotes,
+    selected_events: SelectedEvents,
+    mut event_writer: MessageWriter<DoCommand>,
+) -> Result {
+    transform_selected(
+        Transform::Shift(settings.shift),
+        &selected_notes,
+        &selected_events,
+        &mut event_writer,
+    )
+}
+
+fn shift_earlier_system(
+    settings: Res<TransformSettings>,
+    selected_notes: SelectedNotes,
+    selected_events: SelectedEvents,
+    mut event_writer: MessageWriter<DoCommand>,
+) -> Result {
+    transform_selected(
+        Transform::Shift(Beat::ZERO - settings.shift),
+        &selected_notes,
+        &selected_events,
+        &mut event_writer,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::beat;
+    use phichain_chart::event::LineEventKind;
+    use phichain_chart::note::NoteKind;
+
+    fn hold(beat: Beat, hold_beat: Beat, x: f32) -> Note {
+        Note::new(NoteKind::Hold { hold_beat }, true, beat, x, 1.0)
+    }
+
+    fn event(kind: LineEventKind, start: f32, end: f32) -> LineEvent {
+        LineEvent {
+            id: None,
+            kind,
+            start_beat: beat!(1),
+            end_beat: beat!(3),
+            value: LineEventValue::transition(start, end, Default::default()),
+        }
+    }
+
+    #[test]
+    fn test_mirror() {
+        let note = hold(beat!(1), beat!(1), 100.0);
+        assert_eq!(Transform::Mirror.note(note).unwrap().x, -100.0);
+
+        let x = event(LineEventKind::X, 100.0, -50.0);
+        assert_eq!(Transform::Mirror.event(x).unwrap().value, x.value.negated());
+
+        let y = event(LineEventKind::Y, 100.0, -50.0);
+        assert_eq!(Transform::Mirror.event(y), Some(y));
+    }
+
+    #[test]
+    fn test_flip_side() {
+        let note = hold(beat!(1), beat!(1), 100.0);
+        assert!(!Transform::FlipSide.note(note).unwrap().above);
+    }
+
+    #[test]
+    fn test_scale_x() {
+        let transform = Transform::ScaleX {
+            factor: 2.0,
+            pivot: 100.0,
+        };
+
+        assert_eq!(
+            transform.note(hold(beat!(1), beat!(1), 50.0)).unwrap().x,
+            0.0
+        );
+
+        let x = transform
+            .event(event(LineEventKind::X, 100.0, 150.0))
+            .unwrap();
+        assert_eq!(x.value.start(), 100.0);
+        assert_eq!(x.value.end(), 200.0);
+
+        let rotation = event(LineEventKind::Rotation, 100.0, 150.0);
+        assert_eq!(transform.event(rotation), Some(rotation));
+    }
+
+    #[test]
+    fn test_stretch() {
+        let transform = Transform::Stretch {
+            ratio: Rational32::new(3, 2),
+            anchor: beat!(1),
+        };
+
+        let note = transform.note(hold(beat!(2), beat!(1), 0.0)).unwrap();
+        assert_eq!(note.beat, beat!(2, 1, 2));
+        assert_eq!(note.end_beat(), beat!(4));
+
+        let event = transform.event(event(LineEventKind::X, 0.0, 0.0)).unwrap();
+        assert_eq!(event.start_beat, beat!(1));
+        assert_eq!(event.end_beat, beat!(4));
+
+        let transform = Transform::Stretch {
+            ratio: Rational32::new(1, 2),
+            anchor: beat!(4),
+        };
+        assert_eq!(
+            transform.note(hold(beat!(1), beat!(1), 0.0)).unwrap().beat,
+            beat!(2, 1, 2)
+        );
+    }
+
+    #[test]
+    fn test_stretch_out_of_range() {
+        // the note would be stretched to beat -2
+        let transform = Transform::Stretch {
+            ratio: Rational32::new(2, 1),
+            anchor: beat!(4),
+        };
+        assert_eq!(transform.note(hold(beat!(1), beat!(1), 0.0)), None);
+
+        let transform = Transform::Stretch {
+            ratio: Rational32::new(i32::MAX, 1),
+            anchor: Beat::ZERO,
+        };
+        assert_eq!(transform.note(hold(beat!(2), beat!(1), 0.0)), None);
+        assert_eq!(transform.event(event(LineEventKind::X, 0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_shift() {
+        let note = Transform::Shift(beat!(0, 1, 4))
+            .note(hold(beat!(1), beat!(1), 0.0))
+            .unwrap();
+        assert_eq!(note.beat, beat!(1, 1, 4));
+        assert_eq!(note.hold_beat(), Some(&beat!(1)));
+
+        let shifted = Transform::Shift(Beat::ZERO - beat!(1))
+            .event(event(LineEventKind::X, 0.0, 0.0))
+            .unwrap();
+        assert_eq!(shifted.start_beat, Beat::ZERO);
+        assert_eq!(shifted.end_beat, beat!(2));
+
+        // the event would start before beat 0
+        assert_eq!(
+            Transform::Shift(Beat::ZERO - beat!(1, 1, 2)).event(event(LineEventKind::X, 0.0, 0.0)),
+            None
+        );
+    }
+}
@@ -4,9 +4,12 @@ use crate::utils::entity::replace_with_empty;
 use bevy::ecs::system::SystemState;
 use bevy::prelude::*;
 use bon::Builder;
-use phichain_chart::line::Line;
+use phichain_chart::id::Id;
+use phichain_chart::line::{Line, LineTextEvents};
 use phichain_chart::serialization::SerializedLine;
+use phichain_game::curve_note_track::CurveNoteTrack;
 use phichain_game::event::EventOf;
+use std::collections::HashMap;
 
 pub struct LineEventPlugin;
 
@@ -81,6 +84,10 @@ impl EditorEvent for DespawnLineEvent {
     }
 }
 
+/// Spawn a line and its child lines into the world
+///
+/// Lines, notes and events are spawned along with their [`Id`] if they have one, otherwise they are
+/// given a new one
 #[derive(Debug, Clone, Message, Builder)]
 pub struct SpawnLineEvent {
     /// The line data
@@ -95,18 +102,40 @@ impl EditorEvent for SpawnLineEvent {
     type Output = Entity;
 
     // TODO: move part of the logic to phichain-game utils, duplication of phichain_game::loader::load_line()
-    fn run(self, world: &mut World) -> Self::Output {
+    fn run(mut self, world: &mut World) -> Self::Output {
+        let line_id = self.line.line.id.take();
+        let bundle = (self.line.line, LineTextEvents(self.line.text_events));
         let id = match self.target {
-            None => world.spawn(self.line.line).id(),
-            Some(target) => world.entity_mut(target).insert(self.line.line).id(),
+            None => spawn_with_id(world, line_id, bundle),
+            Some(target) => world.entity_mut(target).insert(bundle).id(),
         };
 
-        for event in self.line.notes {
-            world.spawn((event, ChildOf(id)));
+        let mut note_entities = HashMap::new();
+        for mut note in self.line.notes {
+            let note_id = note.id.take();
+            let entity = spawn_with_id(world, note_id, (note, ChildOf(id)));
+            if let Some(note_id) = note_id {
+                note_entities.insert(note_id, entity);
+            }
         }
 
-        for event in self.line.events {
-            world.spawn((event, EventOf(id)));
+        for track in self.line.curve_note_tracks {
+            if let (Some(from), Some(to)) =
+                (note_entities.get(&track.from), note_entities.get(&track.to))
+            {
+                world.spawn((
+                    CurveNoteTrack {
+                        from: Some(*from),
+                        to: Some(*to),
+                        options: track.options,
+                    },
+                    ChildOf(id),
+                ));
+            }
+        }
+
+        for mut event in self.line.events {
+            spawn_with_id(world, event.id.take(), (event, EventOf(id)));
         }
 
         if let Some(parent) = self.parent {
@@ -124,3 +153,11 @@ impl EditorEvent for SpawnLineEvent {
         id
     }
 }
+
+/// Spawn an entity with the given [`Id`] at once, so that no new [`Id`] is assigned to it
+fn spawn_with_id(world: &mut World, id: Option<Id>, bundle: impl Bundle) -> Entity {
+    match id {
+        Some(id) => world.spawn((bundle, id)).id(),
+        None => world.spawn(bundle).id(),
+    }
+}
@@ -10,6 +10,7 @@ use bevy::prelude::*;
 use phichain_chart::serialization::PhichainChart;
 use phichain_format::official::OfficialChart;
 use phichain_format::official::OfficialOutputOptions;
+use phichain_format::pez::export_pez;
 use phichain_format::rpe::RpeChart;
 use phichain_format::ChartFormat;
 use rfd::FileDialog;
@@ -20,6 +21,7 @@ use zip::write::SimpleFileOptions;
 
 struct ExportOfficialPick;
 struct ExportRpePick;
+struct ExportPezPick;
 
 pub struct ExportPlugin;
 
@@ -27,8 +29,10 @@ impl Plugin for ExportPlugin {
     fn build(&self, app: &mut App) {
         app.register_picking_event::<ExportOfficialPick>()
             .register_picking_event::<ExportRpePick>()
+            .register_picking_event::<ExportPezPick>()
             .add_observer(export_official_observer)
             .add_observer(export_rpe_observer)
+            .add_observer(export_pez_observer)
             .add_heavy_action(
                 "phichain.export_as_official",
                 export_as_official_system,
@@ -37,7 +41,8 @@ impl Plugin for ExportPlugin {
                     vec![Modifier::Control, Modifier::Shift],
                 )),
             )
-            .add_heavy_action("phichain.export_as_rpe", export_as_rpe_system, None);
+            .add_heavy_action("phichain.export_as_rpe", export_as_rpe_system, None)
+            .add_heavy_action("phichain.export_as_pez", export_as_pez_system, None);
     }
 }
 
@@ -53,19 +58,25 @@ fn export_as_rpe_system(world: &mut World) -> Result {
     Ok(())
 }
 
-/// Generates the export path under a path, ensuring the path does not already exist
-fn get_export_path(path: &Path, index: usize) -> Option<PathBuf> {
+fn export_as_pez_system(world: &mut World) -> Result {
+    pick_folder::<ExportPezPick>(world, FileDialog::new());
+
+    Ok(())
+}
+
+/// Generates the export path of `{name}.{extension}` under a path, ensuring the path does not already exist
+fn get_export_path(path: &Path, name: &str, extension: &str, index: usize) -> Option<PathBuf> {
     if index >= 10 {
         None
     } else {
         let zip_path = path.join(if index == 0 {
-            "chart.zip".to_string()
+            format!("{name}.{extension}")
         } else {
-            format!("chart({index}).zip")
+            format!("{name}({index}).{extension}")
         });
 
         if zip_path.exists() {
-            get_export_path(path, index + 1)
+            get_export_path(path, name, extension, index + 1)
         } else {
             Some(zip_path)
         }
@@ -73,7 +84,7 @@ fn get_export_path(path: &Path, index: usize) -> Option<PathBuf> {
 }
 
 fn export(path: &Path, project: &Project, chart_string: &str) -> anyhow::Result<PathBuf> {
-    let zip_path = get_export_path(path, 0).context("Failed to get export path")?;
+    let zip_path = get_export_path(path, "chart", "zip", 0).context("Failed to get export path")?;
 
     let file = fs::File::create(&zip_path)?;
 
@@ -189,3 +200,35 @@ fn export_rpe_observer(
         }
     }
 }
+
+fn export_pez_to(path: &Path, project: &Project) -> anyhow::Result<PathBuf> {
+    let name = if project.meta.name.is_empty() {
+        "chart".to_owned()
+    } else {
+        project.meta.name.replace(['/', '\\'], "_")
+    };
+    let pez_path = get_export_path(path, &name, "pez", 0).context("Failed to get export path")?;
+
+    export_pez(project, fs::File::create(&pez_path)?)?;
+
+    Ok(pez_path)
+}
+
+fn export_pez_observer(
+    event: On<PickedFile<ExportPezPick>>,
+    project: Res<Project>,
+    mut toasts: ResMut<ToastsStorage>,
+) {
+    let Some(ref path) = event.event().path else {
+        return;
+    };
+
+    match export_pez_to(path, &project) {
+        Ok(path) => {
+            toasts.success(t!("export.pez.success", path = path.to_string_lossy()));
+        }
+        Err(error) => {
+            toasts.error(t!("export.pez.failed", error = error));
+        }
+    }
+}
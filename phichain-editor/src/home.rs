@@ -1,3 +1,5 @@
+use crate::action::ActionRegistry;
+use crate::import::import_chart_dialog;
 use crate::recent_projects::{PersistentRecentProjectsExt, RecentProjects};
 use crate::settings::EditorSettings;
 use crate::tab::settings::settings_ui;
@@ -16,14 +18,18 @@ use bevy_egui::{EguiContext, EguiPrimaryContextPass};
 use bevy_persistent::Persistent;
 use egui::{Color32, CursorIcon, Id, RichText, ScrollArea, Sense};
 
+use phichain_chart::serialization::PhichainChart;
+use phichain_format::pez::import_pez;
+use phichain_format::rpe::RpeInputOptions;
 use phichain_game::loader::nonblocking::LoadingProject;
 use rfd::FileDialog;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 struct ProjectPick;
 struct IllustrationPick;
 struct MusicPick;
 struct CreateProjectPick;
+struct PezPick;
 
 #[derive(Resource, Debug, Default)]
 pub struct CreateProjectForm {
@@ -50,6 +56,7 @@ impl Plugin for HomePlugin {
             .register_picking_event::<IllustrationPick>()
             .register_picking_event::<MusicPick>()
             .register_picking_event::<CreateProjectPick>()
+            .register_picking_event::<PezPick>()
             .insert_resource(CreateProjectForm::default())
             .add_systems(
                 EguiPrimaryContextPass,
@@ -58,7 +65,8 @@ impl Plugin for HomePlugin {
             .add_observer(load_project_observer)
             .add_observer(handle_select_illustration_observer)
             .add_observer(handle_select_music_observer)
-            .add_observer(handle_create_project_observer);
+            .add_observer(handle_create_project_observer)
+            .add_observer(import_pez_observer);
     }
 }
 
@@ -193,6 +201,8 @@ fn ui_system(world: &mut World) {
             }
         }
 
+        import_chart_dialog(ctx, world);
+
         let languages = world.resource::<Languages>().0.clone();
         let editor_settings = world.resource::<Persistent<EditorSettings>>();
 
@@ -213,6 +223,17 @@ fn ui_system(world: &mut World) {
                     if ui.button(t!("home.create_project.create")).clicked() {
                         world.insert_resource(CreatingProject);
                     }
+                    if ui.button(t!("home.import_chart.import")).clicked() {
+                        world.resource_scope(|world, mut actions: Mut<ActionRegistry>| {
+                            actions.run_action(world, "phichain.import_chart");
+                        });
+                    }
+                    if ui.button(t!("home.import_pez.import")).clicked() {
+                        pick_file::<PezPick>(
+                            world,
+                            FileDialog::new().add_filter("Phira Package", &["pez"]),
+                        );
+                    }
                 });
             },
             |ui| {
@@ -362,6 +383,7 @@ fn handle_create_project_observer(
         music_path.clone(),
         form.illustration.clone(),
         form.meta.clone(),
+        PhichainChart::default(),
     ) {
         Ok(_) => {
             load_project_events.write(LoadProject(root_path.clone()));
@@ -370,3 +392,45 @@ fn handle_create_project_observer(
         Err(error) => toasts.error(format!("{error:?}")),
     }
 }
+
+/// Find a directory next to the package to unpack it into, e.g. `song.pez` is unpacked into `song/`
+fn pez_target_dir(pez_path: &Path) -> Option<PathBuf> {
+    let parent = pez_path.parent()?;
+    let stem = pez_path.file_stem()?.to_string_lossy();
+
+    (0..10)
+        .map(|index| {
+            parent.join(if index == 0 {
+                stem.to_string()
+            } else {
+                format!("{stem}({index})")
+            })
+        })
+        .find(|path| !path.exists())
+}
+
+fn import_pez_observer(
+    event: On<PickedFile<PezPick>>,
+    mut commands: Commands,
+    mut load_project_events: MessageWriter<LoadProject>,
+    mut toasts: ResMut<ToastsStorage>,
+) {
+    let Some(ref pez_path) = event.event().path else {
+        return;
+    };
+
+    let result = pez_target_dir(pez_path)
+        .ok_or_else(|| anyhow::anyhow!("no available directory next to the package"))
+        .and_then(|target| {
+            let file = std::fs::File::open(pez_path)?;
+            Ok(import_pez(file, &target, &RpeInputOptions::default())?)
+        });
+
+    match result {
+        Ok(project_path) => {
+            load_project_events.write(LoadProject(project_path.0));
+            commands.remove_resource::<CreatingProject>();
+        }
+        Err(error) => toasts.error(t!("home.import_pez.failed", error = error)),
+    }
+}
@@ -0,0 +1,414 @@
+use crate::action::ActionRegistrationExt;
+use crate::file::{pick_file, pick_folder, FilePickingAppExt, PickedFile};
+use crate::notification::{ToastsExt, ToastsStorage};
+use crate::project::{create_project, LoadProject, Project, ProjectMeta};
+use crate::ui::widgets::beat_value::BeatExt;
+use bevy::prelude::*;
+use egui::{Context, Id};
+use phichain_chart::project::ProjectPath;
+use phichain_chart::serialization::PhichainChart;
+use phichain_format::detect::{detect, Format};
+use phichain_format::official::{OfficialChart, OfficialInputOptions};
+use phichain_format::rpe::{RpeChart, RpeInputOptions};
+use phichain_format::ChartFormat;
+use rfd::FileDialog;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MUSIC_EXTENSIONS: [&str; 4] = ["wav", "mp3", "ogg", "flac"];
+const ILLUSTRATION_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+struct ImportChartPick;
+struct ImportMusicPick;
+struct ImportIllustrationPick;
+struct ImportTargetPick;
+
+/// A chart picked for importing, controls the visibility of the import dialog on the home screen
+///
+/// This should always be removed after sending [`LoadProject`]
+#[derive(Resource, Debug)]
+pub struct ImportingChart {
+    content: String,
+    format: Format,
+    official_options: OfficialInputOptions,
+    rpe_options: RpeInputOptions,
+    meta: ProjectMeta,
+    music: Option<PathBuf>,
+    illustration: Option<PathBuf>,
+}
+
+impl ImportingChart {
+    fn new(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let format = detect(&content)?;
+
+        let mut meta = ProjectMeta {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            ..default()
+        };
+        let mut song = None;
+        let mut background = None;
+        if format == Format::Rpe {
+            if let Ok(rpe) = serde_json::from_str::<RpeChart>(&content) {
+                meta.name = rpe.meta.name;
+                meta.composer = rpe.meta.composer;
+                meta.charter = rpe.meta.charter;
+                meta.level = rpe.meta.level;
+                song = Some(rpe.meta.song);
+                background = Some(rpe.meta.background);
+            }
+        }
+
+        let dir = path.parent().unwrap_or(Path::new("."));
+
+        Ok(Self {
+            format,
+            official_options: default(),
+            rpe_options: default(),
+            meta,
+            music: locate(dir, song.as_deref(), &MUSIC_EXTENSIONS),
+            illustration: locate(dir, background.as_deref(), &ILLUSTRATION_EXTENSIONS),
+            content,
+        })
+    }
+
+    fn to_phichain(&self) -> anyhow::Result<PhichainChart> {
+        Ok(match self.format {
+            Format::Official => serde_json::from_str::<OfficialChart>(&self.content)?
+                .to_phichain(&self.official_options)?,
+            Format::Phichain => PhichainChart::from_json_str(&self.content)?,
+            Format::Rpe => {
+                serde_json::from_str::<RpeChart>(&self.content)?.to_phichain(&self.rpe_options)?
+            }
+        })
+    }
+}
+
+/// Find the music or the illustration of a chart in `dir`
+///
+/// The file the chart refers to is used if it exists inside `dir`, otherwise the first file with one
+/// of the `extensions`
+fn locate(dir: &Path, referred: Option<&str>, extensions: &[&str]) -> Option<PathBuf> {
+    if let Some(path) =
+        referred.and_then(|referred| ProjectPath(dir.to_path_buf()).resolve(referred))
+    {
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+
+    let mut files = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path.extension().is_some_and(|extension| {
+                    extensions.iter().any(|x| extension.eq_ignore_ascii_case(x))
+                })
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    files.into_iter().next()
+}
+
+/// If `path` is an empty directory
+///
+/// Charts are only imported into one, or the files of the project would overwrite the ones already
+/// there, like the chart being imported and its music
+fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_none())
+}
+
+pub struct ImportPlugin;
+
+impl Plugin for ImportPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_picking_event::<ImportChartPick>()
+            .register_picking_event::<ImportMusicPick>()
+            .register_picking_event::<ImportIllustrationPick>()
+            .register_picking_event::<ImportTargetPick>()
+            .add_observer(import_chart_observer)
+            .add_observer(import_music_observer)
+            .add_observer(import_illustration_observer)
+            .add_observer(import_target_observer)
+            .add_heavy_action("phichain.import_chart", import_chart_system, None);
+    }
+}
+
+fn import_chart_system(world: &mut World) -> Result {
+    if world.contains_resource::<Project>() {
+        world
+            .resource_mut::<ToastsStorage>()
+            .error(t!("home.import_chart.project_loaded"));
+        return Ok(());
+    }
+
+    pick_file::<ImportChartPick>(world, FileDialog::new().add_filter("Chart", &["json"]));
+
+    Ok(())
+}
+
+fn import_chart_observer(
+    event: On<PickedFile<ImportChartPick>>,
+    mut commands: Commands,
+    mut toasts: ResMut<ToastsStorage>,
+) {
+    let Some(ref path) = event.event().path else {
+        return;
+    };
+
+    match ImportingChart::new(path) {
+        Ok(importing) => commands.insert_resource(importing),
+        Err(error) => toasts.error(t!("home.import_chart.failed", error = error)),
+    }
+}
+
+fn import_music_observer(
+    event: On<PickedFile<ImportMusicPick>>,
+    importing: Option<ResMut<ImportingChart>>,
+) {
+    if let (Some(mut importing), Some(path)) = (importing, &event.event().path) {
+        importing.music = Some(path.clone());
+    }
+}
+
+fn import_illustration_observer(
+    event: On<PickedFile<ImportIllustrationPick>>,
+    importing: Option<ResMut<ImportingChart>>,
+) {
+    if let (Some(mut importing), Some(path)) = (importing, &event.event().path) {
+        importing.illustration = Some(path.clone());
+    }
+}
+
+fn import_target_observer(
+    event: On<PickedFile<ImportTargetPick>>,
+    mut commands: Commands,
+    importing: Option<Res<ImportingChart>>,
+    mut load_project_events: MessageWriter<LoadProject>,
+    mut toasts: ResMut<ToastsStorage>,
+) {
+    let (Some(importing), Some(root_path)) = (importing, &event.event().path) else {
+        return;
+    };
+    let Some(ref music_path) = importing.music else {
+        return;
+    };
+    if !is_empty_dir(root_path) {
+        toasts.error(t!("home.import_chart.target_not_empty"));
+        return;
+    }
+
+    let result = importing.to_phichain().and_then(|chart| {
+        create_project(
+            root_path.clone(),
+            music_path.clone(),
+            importing.illustration.clone(),
+            importing.meta.clone(),
+            chart,
+        )
+    });
+
+    match result {
+        Ok(_) => {
+            load_project_events.write(LoadProject(root_path.clone()));
+            commands.remove_resource::<ImportingChart>();
+        }
+        Err(error) => toasts.error(t!("home.import_chart.failed", error = error)),
+    }
+}
+
+/// Show the import dialog if a chart is being imported
+pub fn import_chart_dialog(ctx: &Context, world: &mut World) {
+    if !world.contains_resource::<ImportingChart>() {
+        return;
+    }
+
+    let modal = egui::Modal::new(Id::new("home.import_chart")).show(ctx, |ui| {
+        ui.set_width(400.0);
+        ui.heading(t!("home.import_chart.label"));
+        ui.separator();
+        egui::Grid::new("import_chart_grid")
+            .num_columns(2)
+            .spacing([20.0, 2.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(t!("home.import_chart.format"));
+                ui.label(match world.resource::<ImportingChart>().format {
+                    Format::Official => "Official",
+                    Format::Phichain => "Phichain",
+                    Format::Rpe => "RPE",
+                });
+                ui.end_row();
+
+                if ui.button(t!("home.create_project.select_music")).clicked() {
+                    pick_file::<ImportMusicPick>(
+                        world,
+                        FileDialog::new().add_filter("Music", &MUSIC_EXTENSIONS),
+                    );
+                }
+                let importing = world.resource::<ImportingChart>();
+                let music_path = match &importing.music {
+                    None => t!("home.create_project.unselected").to_string(),
+                    Some(path) => path.display().to_string(),
+                };
+                ui.label(music_path);
+                ui.end_row();
+
+                if ui
+                    .button(t!("home.create_project.select_illustration"))
+                    .clicked()
+                {
+                    pick_file::<ImportIllustrationPick>(
+                        world,
+                        FileDialog::new().add_filter("Illustration", &ILLUSTRATION_EXTENSIONS),
+                    );
+                }
+                let importing = world.resource::<ImportingChart>();
+                let illustration_text = match &importing.illustration {
+                    None => t!("home.create_project.unselected").to_string(),
+                    Some(path) => path.display().to_string(),
+                };
+                ui.label(illustration_text);
+                ui.end_row();
+
+                let mut importing = world.resource_mut::<ImportingChart>();
+                let importing = &mut *importing;
+
+                ui.label(t!("home.create_project.name"));
+                ui.text_edit_singleline(&mut importing.meta.name);
+                ui.end_row();
+
+                ui.label(t!("home.create_project.level"));
+                ui.text_edit_singleline(&mut importing.meta.level);
+                ui.end_row();
+
+                ui.label(t!("home.create_project.composer"));
+                ui.text_edit_singleline(&mut importing.meta.composer);
+                ui.end_row();
+
+                ui.label(t!("home.create_project.charter"));
+                ui.text_edit_singleline(&mut importing.meta.charter);
+                ui.end_row();
+
+                ui.label(t!("home.create_project.illustrator"));
+                ui.text_edit_singleline(&mut importing.meta.illustrator);
+                ui.end_row();
+
+                match importing.format {
+                    Format::Official => {
+                        let options = &mut importing.official_options;
+
+                        ui.label(t!("home.import_chart.official.easing_fitting"));
+                        ui.checkbox(&mut options.easing_fitting, "");
+                        ui.end_row();
+
+                        ui.label(t!("home.import_chart.official.easing_fitting_epsilon"));
+                        ui.add_enabled(
+                            options.easing_fitting,
+                            egui::DragValue::new(&mut options.easing_fitting_epsilon)
+                                .speed(0.01)
+                                .range(0.0..=f32::INFINITY),
+                        );
+                        ui.end_row();
+
+                        ui.label(t!("home.import_chart.official.constant_event_shrink_to"));
+                        ui.beat(&mut options.constant_event_shrink_to);
+                        ui.end_row();
+                    }
+                    Format::Rpe => {
+                        let options = &mut importing.rpe_options;
+
+                        ui.label(t!("home.import_chart.rpe.remove_fake_notes"));
+                        ui.checkbox(&mut options.remove_fake_notes, "");
+                        ui.end_row();
+
+                        ui.label(t!("home.import_chart.rpe.remove_ui_controls"));
+                        ui.checkbox(&mut options.remove_ui_controls, "");
+                        ui.end_row();
+                    }
+                    Format::Phichain => {}
+                }
+            });
+
+        if ui.button(t!("home.import_chart.import")).clicked() {
+            if world.resource::<ImportingChart>().music.is_none() {
+                let mut toasts = world.resource_mut::<ToastsStorage>();
+                toasts.error(t!("home.create_project.music_unselected"));
+                return;
+            }
+
+            pick_folder::<ImportTargetPick>(world, FileDialog::new());
+        }
+    });
+
+    if modal.should_close() {
+        world.remove_resource::<ImportingChart>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_locate() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path();
+        for name in ["chart.json", "b.ogg", "a.MP3", "song.wav", "cover.png"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+
+        assert_eq!(
+            locate(dir, Some("song.wav"), &MUSIC_EXTENSIONS),
+            Some(dir.join("song.wav"))
+        );
+        // falls back to the first file with a matching extension when the referred file does not exist
+        assert_eq!(
+            locate(dir, Some("missing.wav"), &MUSIC_EXTENSIONS),
+            Some(dir.join("a.MP3"))
+        );
+        assert_eq!(
+            locate(dir, None, &ILLUSTRATION_EXTENSIONS),
+            Some(dir.join("cover.png"))
+        );
+        assert_eq!(locate(dir, None, &["flac"]), None);
+
+        // referred files outside the folder are never used
+        let inner = dir.join("inner");
+        fs::create_dir(&inner).unwrap();
+        fs::write(inner.join("cover.jpg"), b"").unwrap();
+        assert_eq!(locate(&inner, Some("../song.wav"), &MUSIC_EXTENSIONS), None);
+        assert_eq!(
+            locate(
+                &inner,
+                Some(dir.join("song.wav").to_str().unwrap()),
+                &MUSIC_EXTENSIONS
+            ),
+            None
+        );
+        assert_eq!(
+            locate(&inner, Some("../cover.png"), &ILLUSTRATION_EXTENSIONS),
+            Some(inner.join("cover.jpg"))
+        );
+    }
+
+    #[test]
+    fn test_is_empty_dir() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path();
+        assert!(is_empty_dir(dir));
+
+        // the folder of the chart being imported
+        fs::write(dir.join("chart.json"), b"").unwrap();
+        fs::write(dir.join("song.wav"), b"").unwrap();
+        assert!(!is_empty_dir(dir));
+
+        assert!(!is_empty_dir(&dir.join("missing")));
+        assert!(!is_empty_dir(&dir.join("chart.json")));
+    }
+}
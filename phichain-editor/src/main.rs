@@ -2,9 +2,11 @@
 extern crate rust_i18n;
 
 mod action;
+mod analysis;
 mod audio;
 mod autosave;
 mod bench;
+mod chart_id;
 mod cli;
 mod constants;
 mod editing;
@@ -18,6 +20,7 @@ mod home;
 mod hotkey;
 mod identifier;
 mod ime;
+mod import;
 mod l10n;
 mod layout;
 mod logging;
@@ -30,21 +33,26 @@ mod recent_projects;
 mod respack;
 mod schedule;
 mod screenshot;
+mod script;
 mod selection;
 mod settings;
 mod spectrogram;
 mod tab;
 mod telemetry;
+mod tempo;
 mod timeline;
 mod timing;
 mod translation;
 mod ui;
 mod utils;
+mod waveform;
 mod zoom;
 
 use crate::action::{ActionPlugin, ActionRegistry};
+use crate::analysis::AnalysisPlugin;
 use crate::audio::AudioPlugin;
 use crate::autosave::AutoSavePlugin;
+use crate::chart_id::ChartIdPlugin;
 use crate::cli::{Args, CliPlugin};
 use crate::editing::history::EditorHistory;
 use crate::editing::EditingPlugin;
@@ -56,6 +64,7 @@ use crate::hit_sound::HitSoundPlugin;
 use crate::home::HomePlugin;
 use crate::hotkey::HotkeyPlugin;
 use crate::ime::ImeCompatPlugin;
+use crate::import::ImportPlugin;
 use crate::layout::ui_state::UiState;
 use crate::layout::{layout_menu, LayoutPlugin};
 use crate::logging::custom_layer;
@@ -69,6 +78,7 @@ use crate::recent_projects::RecentProjectsPlugin;
 use crate::respack::RespackPlugin;
 use crate::schedule::EditorSet;
 use crate::screenshot::ScreenshotPlugin;
+use crate::script::ScriptPlugin;
 use crate::selection::Selected;
 use crate::settings::{EditorSettings, EditorSettingsPlugin};
 use crate::tab::game::GameCamera;
@@ -77,6 +87,7 @@ use crate::tab::quick_action::quick_action;
 use crate::tab::TabPlugin;
 use crate::tab::TabRegistry;
 use crate::telemetry::TelemetryPlugin;
+use crate::tempo::TempoPlugin;
 use crate::timeline::TimelinePlugin;
 use crate::timing::TimingPlugin;
 use crate::translation::TranslationPlugin;
@@ -163,12 +174,17 @@ fn main() {
         .add_plugins(MetronomePlugin)
         .add_plugins(GameTabPlugin)
         .add_plugins(TimelinePlugin)
+        .add_plugins(AnalysisPlugin)
+        .add_plugins(TempoPlugin)
         .add_plugins(EguiPlugin::default())
         .add_plugins(ProjectPlugin)
         .add_plugins(ExportPlugin)
+        .add_plugins(ImportPlugin)
         .add_plugins(selection::SelectionPlugin)
         .add_plugins(TabPlugin)
+        .add_plugins(ChartIdPlugin)
         .add_plugins(EditingPlugin)
+        .add_plugins(ScriptPlugin)
         .add_plugins(FrameTimeDiagnosticsPlugin::default())
         .add_plugins(AssetsPlugin)
         .add_plugins(NotificationPlugin)
@@ -319,6 +335,12 @@ fn ui_system(world: &mut World) {
                     });
                     ui.close();
                 }
+                if ui.button(t!("menu_bar.export.as_pez")).clicked() {
+                    world.resource_scope(|world, mut actions: Mut<ActionRegistry>| {
+                        actions.run_action(world, "phichain.export_as_pez");
+                    });
+                    ui.close();
+                }
             });
 
             layout_menu(ui, world);
@@ -63,4 +63,7 @@ impl WorkingDirectory {
     pub fn respacks(&self) -> io::Result<PathBuf> {
         self.directory("respacks")
     }
+    pub fn scripts(&self) -> io::Result<PathBuf> {
+        self.directory("scripts")
+    }
 }
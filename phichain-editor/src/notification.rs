@@ -17,6 +17,7 @@ impl Plugin for NotificationPlugin {
 
 pub trait ToastsExt {
     fn info(&mut self, message: impl Into<WidgetText>);
+    fn warning(&mut self, message: impl Into<WidgetText>);
     fn error(&mut self, message: impl Into<WidgetText>);
     fn success(&mut self, message: impl Into<WidgetText>);
 }
@@ -37,6 +38,10 @@ impl ToastsExt for Toasts {
         self.add(create_toast(ToastKind::Info, text.into()));
     }
 
+    fn warning(&mut self, text: impl Into<WidgetText>) {
+        self.add(create_toast(ToastKind::Warning, text.into()));
+    }
+
     fn error(&mut self, text: impl Into<WidgetText>) {
         self.add(create_toast(ToastKind::Error, text.into()));
     }
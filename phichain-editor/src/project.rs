@@ -2,13 +2,16 @@ use anyhow::Context;
 use bevy::prelude::*;
 
 use crate::action::ActionRegistrationExt;
-use crate::editing::history::EditorHistory;
+use crate::chart_id::Id;
+use crate::editing::history::{self, EditorHistory, HistoryTree};
 use crate::hotkey::modifier::Modifier;
 use crate::hotkey::Hotkey;
 use crate::notification::{ToastsExt, ToastsStorage};
 use crate::recent_projects::{PersistentRecentProjectsExt, RecentProject, RecentProjects};
+use crate::settings::EditorSettings;
 use crate::spectrogram::{self, PendingSpectrogram};
 use crate::telemetry::PushTelemetry;
+use crate::waveform::{self, PendingWaveform};
 use bevy::ecs::system::SystemState;
 use bevy_kira_audio::{Audio, AudioControl, AudioSource};
 use bevy_persistent::Persistent;
@@ -39,6 +42,7 @@ impl Plugin for ProjectPlugin {
         app.add_message::<LoadProject>()
             .add_systems(Update, load_project_system.run_if(project_not_loaded()))
             .add_systems(Update, spectrogram::poll_spectrogram_system)
+            .add_systems(Update, waveform::poll_waveform_system)
             .add_message::<UnloadProject>()
             .add_systems(PreUpdate, unload_project_system.run_if(project_loaded()))
             .add_observer(project_loading_result_observer)
@@ -69,7 +73,9 @@ impl Plugin for ProjectPlugin {
 }
 
 fn save_project_system(
+    mut commands: Commands,
     project: Res<Project>,
+    settings: Res<Persistent<EditorSettings>>,
     mut toasts: ResMut<ToastsStorage>,
     mut history: ResMut<EditorHistory>,
 
@@ -92,6 +98,18 @@ fn save_project_system(
         Ok(_) => {
             toasts.success(t!("project.save.succeed"));
             history.0.set_saved();
+
+            if settings.autosave.persist_history {
+                let path = project.path.clone();
+                commands.queue(move |world: &mut World| {
+                    if let Err(error) = history::persist_history(world, &path) {
+                        warn!("Failed to persist history: {:?}", error);
+                        world
+                            .resource_mut::<ToastsStorage>()
+                            .warning(t!("project.history.persist_failed", error = error));
+                    }
+                });
+            }
         }
         Err(error) => {
             toasts.error(t!("project.save.failed", error = error));
@@ -172,22 +190,50 @@ fn project_loading_result_observer(
                 data.project.path.0.clone(),
             ));
 
-            commands.queue(|world: &mut World| {
+            let path = data.project.path.clone();
+            commands.queue(move |world: &mut World| {
                 let mut query = world.query_filtered::<Entity, With<Line>>();
                 if let Some(first) = query.iter(world).next() {
                     world.insert_resource(crate::selection::SelectedLine(first));
                 }
 
-                // generate spectrogram in the background
+                // generate spectrogram and waveform in the background
                 let audio_asset_id = world.resource::<phichain_game::audio::AudioAssetId>().0;
                 let audio_assets = world.resource::<Assets<AudioSource>>();
                 let source = audio_assets
                     .get(audio_asset_id)
                     .expect("Expected audio loaded in Assets<AudioSource>");
                 let sound = source.sound.clone();
-                let task = bevy::tasks::AsyncComputeTaskPool::get()
-                    .spawn(async move { spectrogram::make_spectrogram(&sound) });
+                let pool = bevy::tasks::AsyncComputeTaskPool::get();
+                let spectrogram_sound = sound.clone();
+                let task =
+                    pool.spawn(async move { spectrogram::make_spectrogram(&spectrogram_sound) });
                 world.spawn(PendingSpectrogram(task));
+                let task = pool.spawn(async move { waveform::make_waveform(&sound) });
+                world.spawn(PendingWaveform(task));
+
+                // restore the history persisted along the chart
+                let settings = world.resource::<Persistent<EditorSettings>>().autosave;
+                let restored = if settings.persist_history {
+                    history::restore_history(world, &path, settings.history_limit).unwrap_or_else(
+                        |error| {
+                            warn!("Failed to restore history: {:?}", error);
+                            world
+                                .resource_mut::<ToastsStorage>()
+                                .warning(t!("project.history.restore_failed", error = error));
+                            None
+                        },
+                    )
+                } else {
+                    None
+                };
+                let (history, tree) = restored.unwrap_or_else(|| {
+                    let history = EditorHistory::with_limit(settings.history_limit).0;
+                    let tree = HistoryTree::new(history.head());
+                    (history, tree)
+                });
+                world.insert_resource(EditorHistory(history));
+                world.insert_resource(tree);
             });
 
             commands.insert_resource(data.project.clone());
@@ -265,6 +311,15 @@ fn unload_project_system(
             world.entity_mut(entity).despawn();
         }
 
+        // unload waveform resource and cancel any pending generation task
+        use crate::waveform::Waveform;
+        world.remove_resource::<Waveform>();
+        let mut pending_query = world.query_filtered::<Entity, With<PendingWaveform>>();
+        let entities = pending_query.iter(world).collect::<Vec<_>>();
+        for entity in entities {
+            world.entity_mut(entity).despawn();
+        }
+
         // unload illustration
         use phichain_game::illustration::{Illustration, IllustrationAssetId};
         let mut illustration_query = world.query_filtered::<Entity, With<Illustration>>();
@@ -279,6 +334,11 @@ fn unload_project_system(
             images.remove(illustration_asset_id);
         }
 
+        // unload line textures
+        use phichain_game::line::{LineTextureRoot, LineTextures};
+        world.remove_resource::<LineTextureRoot>();
+        world.resource_mut::<LineTextures>().clear();
+
         // unload chart basic components
         use crate::selection::SelectedLine;
         use phichain_chart::{bpm_list::BpmList, offset::Offset};
@@ -309,8 +369,20 @@ fn unload_project_system(
             world.entity_mut(entity).despawn();
         }
 
+        // despawn entities kept for undoing removals, they hold nothing but their id
+        let mut id_query = world.query_filtered::<Entity, With<Id>>();
+        let entities = id_query.iter(world).collect::<Vec<_>>();
+        for entity in entities {
+            world.entity_mut(entity).despawn();
+        }
+
         // clear editor history
-        world.resource_mut::<EditorHistory>().0.clear();
+        let head = {
+            let mut history = world.resource_mut::<EditorHistory>();
+            history.0.clear();
+            history.0.head()
+        };
+        world.insert_resource(HistoryTree::new(head));
 
         // reset editor timing
         use crate::timing::{ChartTime, Timing};
@@ -319,12 +391,13 @@ fn unload_project_system(
     }
 }
 
-/// Create a new empty project
+/// Create a new project holding `chart`, pass [`PhichainChart::default()`] for an empty project
 pub fn create_project(
     root_path: PathBuf,
     music_path: PathBuf,
     illustration_path: Option<PathBuf>,
     project_meta: ProjectMeta,
+    chart: PhichainChart,
 ) -> anyhow::Result<()> {
     let project_path = ProjectPath(root_path);
 
@@ -348,7 +421,7 @@ pub fn create_project(
     let meta_string = serde_json::to_string_pretty(&project_meta)?;
     std::fs::write(project_path.meta_path(), meta_string).context("Failed to write meta")?;
 
-    let chart_string = serde_json::to_string_pretty(&PhichainChart::default())?;
+    let chart_string = serde_json::to_string_pretty(&chart)?;
     std::fs::write(project_path.chart_path(), chart_string).context("Failed to write chart")?;
 
     Ok(())
@@ -411,7 +484,14 @@ mod tests {
         create_dummy_wav(&music_file);
 
         let meta = sample_meta();
-        create_project(project_dir.clone(), music_file, None, meta.clone()).unwrap();
+        create_project(
+            project_dir.clone(),
+            music_file,
+            None,
+            meta.clone(),
+            PhichainChart::default(),
+        )
+        .unwrap();
 
         // Verify music was copied
         assert!(project_dir.join("music.wav").exists());
@@ -450,6 +530,7 @@ mod tests {
             music_file,
             Some(illustration_file),
             sample_meta(),
+            PhichainChart::default(),
         )
         .unwrap();
 
@@ -467,7 +548,14 @@ mod tests {
         create_dummy_wav(&music_file);
 
         let meta = sample_meta();
-        create_project(project_dir.clone(), music_file, None, meta.clone()).unwrap();
+        create_project(
+            project_dir.clone(),
+            music_file,
+            None,
+            meta.clone(),
+            PhichainChart::default(),
+        )
+        .unwrap();
 
         // Open the project we just created
         let project = Project::open(project_dir).unwrap();
@@ -528,7 +616,34 @@ mod tests {
         std::fs::create_dir(&project_dir).unwrap();
 
         let nonexistent_music = tmp.path().join("nonexistent.wav");
-        let result = create_project(project_dir, nonexistent_music, None, sample_meta());
+        let result = create_project(
+            project_dir,
+            nonexistent_music,
+            None,
+            sample_meta(),
+            PhichainChart::default(),
+        );
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_create_project_with_chart() {
+        let tmp = TempDir::new().unwrap();
+        let project_dir = tmp.path().join("my_project");
+        std::fs::create_dir(&project_dir).unwrap();
+
+        let music_file = tmp.path().join("song.wav");
+        create_dummy_wav(&music_file);
+
+        let chart = PhichainChart::new(1.5, Default::default(), vec![]);
+        create_project(project_dir.clone(), music_file, None, sample_meta(), chart).unwrap();
+
+        let project = Project::open(project_dir).unwrap();
+        let saved = PhichainChart::from_json_str(
+            &std::fs::read_to_string(project.path.chart_path()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(saved.offset.0, 1.5);
+        assert!(saved.lines.is_empty());
+    }
 }
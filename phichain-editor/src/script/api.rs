@@ -0,0 +1,743 @@
+//! The chart API exposed to scripts
+//!
+//! Scripts work on a snapshot of the chart and never touch the world directly, every change they
+//! make is recorded as a [`ScriptEdit`] and applied afterward as one undoable command
+
+use phichain_chart::beat;
+use phichain_chart::beat::Beat;
+use phichain_chart::event::{LineEvent, LineEventKind, LineEventValue};
+use phichain_chart::note::{Note, NoteKind};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope, AST};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use thiserror::Error;
+
+/// Beats assigned by scripts are attached to this density, so `1/32` and `1/12` beats are exact
+const BEAT_DENSITY: u32 = 96;
+
+/// Hold length given to notes turned into holds by a script
+const DEFAULT_HOLD_BEAT: Beat = Beat::ONE;
+
+/// Operations a script may run before it is aborted
+///
+/// Scripts run on the main thread, without a limit an endless loop would freeze the editor
+const MAX_OPERATIONS: u64 = 10_000_000;
+/// Nesting depth of expressions at the global level and within functions
+const MAX_EXPR_DEPTHS: (usize, usize) = (64, 32);
+/// Depth of nested function calls, bounding runaway recursion
+const MAX_CALL_LEVELS: usize = 64;
+
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    /// The script ran over [`MAX_OPERATIONS`], most likely stuck in an endless loop
+    #[error("aborted after too many operations")]
+    Aborted,
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// A line of the chart, as seen by scripts
+#[derive(Debug, Clone)]
+pub struct ScriptLine {
+    pub id: u64,
+    pub name: String,
+    pub notes: Vec<ScriptNote>,
+    pub events: Vec<ScriptEvent>,
+}
+
+/// A note seen by scripts, `id` is [`None`] for notes created by the script
+#[derive(Debug, Clone)]
+pub struct ScriptNote {
+    pub id: Option<u64>,
+    pub line: Option<u64>,
+    pub note: Note,
+}
+
+/// An event seen by scripts, `id` is [`None`] for events created by the script
+#[derive(Debug, Clone)]
+pub struct ScriptEvent {
+    pub id: Option<u64>,
+    pub line: Option<u64>,
+    pub event: LineEvent,
+}
+
+/// The state of the editor a script runs against
+#[derive(Debug, Clone, Default)]
+pub struct ScriptChart {
+    pub lines: Vec<ScriptLine>,
+    pub selected_line: Option<u64>,
+    pub selected_notes: Vec<u64>,
+    pub selected_events: Vec<u64>,
+    pub current_beat: f32,
+}
+
+/// A change made by a script, in the order it is made
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptEdit {
+    CreateNote {
+        line: u64,
+        note: Note,
+    },
+    EditNote {
+        id: u64,
+        from: Note,
+        to: Note,
+    },
+    RemoveNote {
+        id: u64,
+    },
+    CreateEvent {
+        line: u64,
+        event: LineEvent,
+    },
+    EditEvent {
+        id: u64,
+        from: LineEvent,
+        to: LineEvent,
+    },
+    RemoveEvent {
+        id: u64,
+    },
+}
+
+/// Changes made so far, along with the latest state of each note and event
+#[derive(Debug, Default)]
+struct Recorder {
+    notes: HashMap<u64, Note>,
+    events: HashMap<u64, LineEvent>,
+    edits: Vec<ScriptEdit>,
+}
+
+type Recording = Rc<RefCell<Recorder>>;
+
+fn error(message: impl Into<String>) -> Box<EvalAltResult> {
+    message.into().into()
+}
+
+/// Accept both integers and floats for numeric properties
+fn number(value: Dynamic) -> Result<f32, Box<EvalAltResult>> {
+    value
+        .as_float()
+        .or_else(|_| value.as_int().map(|x| x as f64))
+        .map(|x| x as f32)
+        .map_err(|kind| error(format!("expected a number, got {kind}")))
+}
+
+/// Keep the original beat if the value did not change, attach it to [`BEAT_DENSITY`] otherwise
+fn to_beat(value: f32, original: Beat) -> Beat {
+    if value == original.value() {
+        original
+    } else {
+        beat::utils::attach(value, BEAT_DENSITY)
+    }
+}
+
+fn note_kind(name: &str, hold_beat: Beat) -> Result<NoteKind, Box<EvalAltResult>> {
+    match name {
+        "tap" => Ok(NoteKind::Tap),
+        "drag" => Ok(NoteKind::Drag),
+        "hold" => Ok(NoteKind::Hold { hold_beat }),
+        "flick" => Ok(NoteKind::Flick),
+        _ => Err(error(format!("unknown note kind `{name}`"))),
+    }
+}
+
+fn event_kind(name: &str) -> Result<LineEventKind, Box<EvalAltResult>> {
+    serde_json::from_value(serde_json::Value::String(name.to_owned()))
+        .map_err(|_| error(format!("unknown event kind `{name}`")))
+}
+
+fn event_kind_name(kind: LineEventKind) -> String {
+    serde_json::to_value(kind)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_owned))
+        .unwrap_or_default()
+}
+
+/// Set both ends of an event value, keeping its easing if it stays a transition
+fn set_value(value: &mut LineEventValue, start: f32, end: f32) {
+    *value = match *value {
+        LineEventValue::Transition { easing, .. } => LineEventValue::transition(start, end, easing),
+        LineEventValue::Constant { .. } if start == end => LineEventValue::constant(start),
+        LineEventValue::Constant { .. } => {
+            LineEventValue::transition(start, end, Default::default())
+        }
+    };
+}
+
+fn register_types(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<ScriptLine>("Line")
+        .register_get("id", |line: &mut ScriptLine| line.id as i64)
+        .register_get("name", |line: &mut ScriptLine| line.name.clone())
+        .register_get("notes", |line: &mut ScriptLine| {
+            line.notes
+                .iter()
+                .cloned()
+                .map(Dynamic::from)
+                .collect::<Array>()
+        })
+        .register_get("events", |line: &mut ScriptLine| {
+            line.events
+                .iter()
+                .cloned()
+                .map(Dynamic::from)
+                .collect::<Array>()
+        });
+
+    engine
+        .register_type_with_name::<ScriptNote>("Note")
+        .register_get("kind", |note: &mut ScriptNote| {
+            note.note.kind.to_string().to_lowercase()
+        })
+        .register_set(
+            "kind",
+            |note: &mut ScriptNote, kind: &str| -> Result<(), Box<EvalAltResult>> {
+                let hold_beat = note.note.hold_beat().copied().unwrap_or(DEFAULT_HOLD_BEAT);
+                note.note.kind = note_kind(kind, hold_beat)?;
+                Ok(())
+            },
+        )
+        .register_get("beat", |note: &mut ScriptNote| {
+            note.note.beat.value() as f64
+        })
+        .register_set(
+            "beat",
+            |note: &mut ScriptNote, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+                note.note.beat = to_beat(number(value)?, note.note.beat);
+                Ok(())
+            },
+        )
+        .register_get("end_beat", |note: &mut ScriptNote| {
+            note.note.end_beat().value() as f64
+        })
+        .register_get("hold_beat", |note: &mut ScriptNote| {
+            note.note
+                .hold_beat()
+                .map_or(0.0, |hold_beat| hold_beat.value() as f64)
+        })
+        .register_set(
+            "hold_beat",
+            |note: &mut ScriptNote, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+                let value = number(value)?;
+                let Some(hold_beat) = note.note.hold_beat_mut() else {
+                    return Err(error("only holds have a hold beat"));
+                };
+                *hold_beat = to_beat(value, *hold_beat);
+                Ok(())
+            },
+        )
+        .register_get("x", |note: &mut ScriptNote| note.note.x as f64)
+        .register_set(
+            "x",
+            |note: &mut ScriptNote, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+                note.note.x = number(value)?;
+                Ok(())
+            },
+        )
+        .register_get("speed", |note: &mut ScriptNote| note.note.speed as f64)
+        .register_set(
+            "speed",
+            |note: &mut ScriptNote, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+                note.note.speed = number(value)?;
+                Ok(())
+            },
+        )
+        .register_get("above", |note: &mut ScriptNote| note.note.above)
+        .register_set("above", |note: &mut ScriptNote, above: bool| {
+            note.note.above = above;
+        })
+        .register_get("fake", |note: &mut ScriptNote| note.note.is_fake)
+        .register_set("fake", |note: &mut ScriptNote, fake: bool| {
+            note.note.is_fake = fake;
+        })
+        .register_get("line", |note: &mut ScriptNote| {
+            note.line
+                .map_or(Dynamic::UNIT, |line| Dynamic::from(line as i64))
+        });
+
+    engine
+        .register_type_with_name::<ScriptEvent>("Event")
+        .register_get("kind", |event: &mut ScriptEvent| {
+            event_kind_name(event.event.kind)
+        })
+        .register_set(
+            "kind",
+            |event: &mut ScriptEvent, kind: &str| -> Result<(), Box<EvalAltResult>> {
+                event.event.kind = event_kind(kind)?;
+                Ok(())
+            },
+        )
+        .register_get("start_beat", |event: &mut ScriptEvent| {
+            event.event.start_beat.value() as f64
+        })
+        .register_set(
+            "start_beat",
+            |event: &mut ScriptEvent, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+                event.event.start_beat = to_beat(number(value)?, event.event.start_beat);
+                Ok(())
+            },
+        )
+        .register_get("end_beat", |event: &mut ScriptEvent| {
+            event.event.end_beat.value() as f64
+        })
+        .register_set(
+            "end_beat",
+            |event: &mut ScriptEvent, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+                event.event.end_beat = to_beat(number(value)?, event.event.end_beat);
+                Ok(())
+            },
+        )
+        .register_get("start", |event: &mut ScriptEvent| {
+            event.event.value.start() as f64
+        })
+        .register_set(
+            "start",
+            |event: &mut ScriptEvent, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+                let end = event.event.value.end();
+                set_value(&mut event.event.value, number(value)?, end);
+                Ok(())
+            },
+        )
+        .register_get("end", |event: &mut ScriptEvent| {
+            event.event.value.end() as f64
+        })
+        .register_set(
+            "end",
+            |event: &mut ScriptEvent, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+                let start = event.event.value.start();
+                set_value(&mut event.event.value, start, number(value)?);
+                Ok(())
+            },
+        )
+        .register_get("line", |event: &mut ScriptEvent| {
+            event
+                .line
+                .map_or(Dynamic::UNIT, |line| Dynamic::from(line as i64))
+        });
+}
+
+fn register_queries(engine: &mut Engine, chart: &Rc<ScriptChart>) {
+    let lines = chart.clone();
+    engine.register_fn("lines", move || {
+        lines
+            .lines
+            .iter()
+            .cloned()
+            .map(Dynamic::from)
+            .collect::<Array>()
+    });
+
+    let lines = chart.clone();
+    engine.register_fn("line", move |id: i64| {
+        lines
+            .lines
+            .iter()
+            .find(|line| line.id == id as u64)
+            .cloned()
+            .map_or(Dynamic::UNIT, Dynamic::from)
+    });
+
+    let selected = chart.clone();
+    engine.register_fn("selected_line", move || {
+        selected
+            .selected_line
+            .and_then(|id| selected.lines.iter().find(|line| line.id == id))
+            .cloned()
+            .map_or(Dynamic::UNIT, Dynamic::from)
+    });
+
+    let selected = chart.clone();
+    engine.register_fn("selected_notes", move || {
+        selected
+            .lines
+            .iter()
+            .flat_map(|line| &line.notes)
+            .filter(|note| {
+                note.id
+                    .is_some_and(|id| selected.selected_notes.contains(&id))
+            })
+            .cloned()
+            .map(Dynamic::from)
+            .collect::<Array>()
+    });
+
+    let selected = chart.clone();
+    engine.register_fn("selected_events", move || {
+        selected
+            .lines
+            .iter()
+            .flat_map(|line| &line.events)
+            .filter(|event| {
+                event
+                    .id
+                    .is_some_and(|id| selected.selected_events.contains(&id))
+            })
+            .cloned()
+            .map(Dynamic::from)
+            .collect::<Array>()
+    });
+
+    let current = chart.clone();
+    engine.register_fn("current_beat", move || current.current_beat as f64);
+
+    engine.register_fn(
+        "new_note",
+        |kind: &str, beat: Dynamic, x: Dynamic| -> Result<ScriptNote, Box<EvalAltResult>> {
+            let note = Note::new(
+                note_kind(kind, DEFAULT_HOLD_BEAT)?,
+                true,
+                beat::utils::attach(number(beat)?, BEAT_DENSITY),
+                number(x)?,
+                1.0,
+            );
+            Ok(ScriptNote {
+                id: None,
+                line: None,
+                note,
+            })
+        },
+    );
+
+    engine.register_fn(
+        "new_event",
+        |kind: &str,
+         start_beat: Dynamic,
+         end_beat: Dynamic,
+         start: Dynamic,
+         end: Dynamic|
+         -> Result<ScriptEvent, Box<EvalAltResult>> {
+            let mut value = LineEventValue::constant(0.0);
+            set_value(&mut value, number(start)?, number(end)?);
+            Ok(ScriptEvent {
+                id: None,
+                line: None,
+                event: LineEvent {
+                    id: None,
+                    kind: event_kind(kind)?,
+                    start_beat: beat::utils::attach(number(start_beat)?, BEAT_DENSITY),
+                    end_beat: beat::utils::attach(number(end_beat)?, BEAT_DENSITY),
+                    value,
+                },
+            })
+        },
+    );
+}
+
+/// Resolve the id of a line passed either as a [`ScriptLine`] or as its id
+fn line_id(chart: &ScriptChart, line: &Dynamic) -> Result<u64, Box<EvalAltResult>> {
+    let id = if let Some(line) = line.clone().try_cast::<ScriptLine>() {
+        line.id
+    } else if let Ok(id) = line.as_int() {
+        id as u64
+    } else {
+        return Err(error("expected a line or a line id"));
+    };
+
+    if chart.lines.iter().any(|line| line.id == id) {
+        Ok(id)
+    } else {
+        Err(error(format!("no line with id {id}")))
+    }
+}
+
+fn register_edits(engine: &mut Engine, chart: &Rc<ScriptChart>, recording: &Recording) {
+    let recorder = recording.clone();
+    engine.register_fn(
+        "edit",
+        move |note: ScriptNote| -> Result<(), Box<EvalAltResult>> {
+            let mut recorder = recorder.borrow_mut();
+            let id = note.id.ok_or_else(|| error("use `add` for new notes"))?;
+            let Some(current) = recorder.notes.get(&id).copied() else {
+                return Err(error("the note has been removed"));
+            };
+            if current != note.note {
+                recorder.notes.insert(id, note.note);
+                recorder.edits.push(ScriptEdit::EditNote {
+                    id,
+                    from: current,
+                    to: note.note,
+                });
+            }
+            Ok(())
+        },
+    );
+
+    let recorder = recording.clone();
+    engine.register_fn(
+        "edit",
+        move |event: ScriptEvent| -> Result<(), Box<EvalAltResult>> {
+            let mut recorder = recorder.borrow_mut();
+            let id = event.id.ok_or_else(|| error("use `add` for new events"))?;
+            let Some(current) = recorder.events.get(&id).copied() else {
+                return Err(error("the event has been removed"));
+            };
+            if current != event.event {
+                recorder.events.insert(id, event.event);
+                recorder.edits.push(ScriptEdit::EditEvent {
+                    id,
+                    from: current,
+                    to: event.event,
+                });
+            }
+            Ok(())
+        },
+    );
+
+    let recorder = recording.clone();
+    engine.register_fn(
+        "remove",
+        move |note: ScriptNote| -> Result<(), Box<EvalAltResult>> {
+            let mut recorder = recorder.borrow_mut();
+            let id = note
+                .id
+                .ok_or_else(|| error("the note is not in the chart"))?;
+            if recorder.notes.remove(&id).is_some() {
+                recorder.edits.push(ScriptEdit::RemoveNote { id });
+            }
+            Ok(())
+        },
+    );
+
+    let recorder = recording.clone();
+    engine.register_fn(
+        "remove",
+        move |event: ScriptEvent| -> Result<(), Box<EvalAltResult>> {
+            let mut recorder = recorder.borrow_mut();
+            let id = event
+                .id
+                .ok_or_else(|| error("the event is not in the chart"))?;
+            if recorder.events.remove(&id).is_some() {
+                recorder.edits.push(ScriptEdit::RemoveEvent { id });
+            }
+            Ok(())
+        },
+    );
+
+    let (recorder, lines) = (recording.clone(), chart.clone());
+    engine.register_fn(
+        "add",
+        move |line: Dynamic, note: ScriptNote| -> Result<(), Box<EvalAltResult>> {
+            let line = line_id(&lines, &line)?;
+            recorder.borrow_mut().edits.push(ScriptEdit::CreateNote {
+                line,
+                note: note.note,
+            });
+            Ok(())
+        },
+    );
+
+    let (recorder, lines) = (recording.clone(), chart.clone());
+    engine.register_fn(
+        "add",
+        move |line: Dynamic, event: ScriptEvent| -> Result<(), Box<EvalAltResult>> {
+            let line = line_id(&lines, &line)?;
+            recorder.borrow_mut().edits.push(ScriptEdit::CreateEvent {
+                line,
+                event: event.event,
+            });
+            Ok(())
+        },
+    );
+}
+
+/// An engine with the limits scripts run under
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_expr_depths(MAX_EXPR_DEPTHS.0, MAX_EXPR_DEPTHS.1)
+        .set_max_call_levels(MAX_CALL_LEVELS);
+    engine
+}
+
+/// Compile a script, reporting syntax errors before it is ever run
+pub fn compile(source: &str) -> Result<AST, ScriptError> {
+    engine()
+        .compile(source)
+        .map_err(|error| ScriptError::Failed(error.to_string()))
+}
+
+/// Run a compiled script against a chart, returning the changes it made
+///
+/// Nothing is returned if the script fails halfway, so a failing script never leaves a partial edit
+pub fn run(ast: &AST, chart: ScriptChart) -> Result<Vec<ScriptEdit>, ScriptError> {
+    let recording = Rc::new(RefCell::new(Recorder {
+        notes: chart
+            .lines
+            .iter()
+            .flat_map(|line| &line.notes)
+            .filter_map(|note| Some((note.id?, note.note)))
+            .collect(),
+        events: chart
+            .lines
+            .iter()
+            .flat_map(|line| &line.events)
+            .filter_map(|event| Some((event.id?, event.event)))
+            .collect(),
+        edits: vec![],
+    }));
+    let chart = Rc::new(chart);
+
+    let mut engine = engine();
+    register_types(&mut engine);
+    register_queries(&mut engine, &chart);
+    register_edits(&mut engine, &chart, &recording);
+
+    engine
+        .run_ast_with_scope(&mut Scope::new(), ast)
+        .map_err(|error| match *error {
+            EvalAltResult::ErrorTooManyOperations(_) => ScriptError::Aborted,
+            error => ScriptError::Failed(error.to_string()),
+        })?;
+
+    // the engine holds the other references to the recording
+    drop(engine);
+    let edits = std::mem::take(&mut recording.borrow_mut().edits);
+    Ok(edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::event::LineEventValue;
+
+    fn chart() -> ScriptChart {
+        let note = |id: u64, beat: i32, x: f32| ScriptNote {
+            id: Some(id),
+            line: Some(1),
+            note: Note::new(NoteKind::Tap, true, beat!(beat), x, 1.0),
+        };
+        ScriptChart {
+            lines: vec![ScriptLine {
+                id: 1,
+                name: "Line".to_owned(),
+                notes: vec![note(10, 0, 100.0), note(11, 1, -200.0), note(12, 2, 0.0)],
+                events: vec![ScriptEvent {
+                    id: Some(20),
+                    line: Some(1),
+                    event: LineEvent {
+                        id: None,
+                        kind: LineEventKind::X,
+                        start_beat: beat!(0),
+                        end_beat: beat!(4),
+                        value: LineEventValue::transition(0.0, 100.0, Default::default()),
+                    },
+                }],
+            }],
+            selected_line: Some(1),
+            selected_notes: vec![10, 11],
+            selected_events: vec![],
+            current_beat: 0.0,
+        }
+    }
+
+    fn run_source(source: &str) -> Result<Vec<ScriptEdit>, String> {
+        compile(source)
+            .and_then(|ast| run(&ast, chart()))
+            .map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn test_mirror_selected_notes() {
+        let edits = run_source(
+            r#"
+            for note in selected_notes() {
+                note.x = -note.x;
+                edit(note);
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(edits.len(), 2);
+        assert!(matches!(
+            edits[0],
+            ScriptEdit::EditNote { id: 10, from, to } if from.x == 100.0 && to.x == -100.0
+        ));
+        assert!(matches!(
+            edits[1],
+            ScriptEdit::EditNote { id: 11, to, .. } if to.x == 200.0
+        ));
+    }
+
+    #[test]
+    fn test_create_and_remove() {
+        let edits = run_source(
+            r#"
+            let line = selected_line();
+            for i in 0..4 {
+                add(line, new_note("drag", 4 + i * 0.25, 0));
+            }
+            add(line.id, new_event("y", 0, 1, 0, 0));
+            for note in line.notes {
+                if note.beat >= 2.0 { remove(note); }
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(edits.len(), 6);
+        assert!(matches!(
+            edits[1],
+            ScriptEdit::CreateNote { line: 1, note } if note.beat == beat!(4, 1, 4) && note.kind == NoteKind::Drag
+        ));
+        assert!(matches!(
+            edits[4],
+            ScriptEdit::CreateEvent { line: 1, event } if event.value == LineEventValue::constant(0.0)
+        ));
+        assert_eq!(edits[5], ScriptEdit::RemoveNote { id: 12 });
+    }
+
+    #[test]
+    fn test_consecutive_edits_chain() {
+        let edits = run_source(
+            r#"
+            let note = line(1).notes[0];
+            note.beat = 0.5;
+            edit(note);
+            note.kind = "hold";
+            edit(note);
+            edit(note);
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(edits.len(), 2);
+        let (ScriptEdit::EditNote { to: first, .. }, ScriptEdit::EditNote { from, to, .. }) =
+            (&edits[0], &edits[1])
+        else {
+            panic!("expected two note edits");
+        };
+        assert_eq!(first, from);
+        assert_eq!(to.hold_beat(), Some(&DEFAULT_HOLD_BEAT));
+    }
+
+    #[test]
+    fn test_error_discards_edits() {
+        let error = run_source(
+            r#"
+            for note in selected_notes() { remove(note); }
+            add(42, new_note("tap", 0, 0));
+            "#,
+        )
+        .unwrap_err();
+        assert!(error.contains("no line with id 42"));
+
+        assert!(run_source(r#"new_note("slide", 0, 0)"#).is_err());
+        assert!(compile("let = ;").is_err());
+    }
+
+    #[test]
+    fn test_limits() {
+        let ast = compile("for note in selected_notes() { remove(note); } loop {}").unwrap();
+        assert!(matches!(run(&ast, chart()), Err(ScriptError::Aborted)));
+
+        let ast = compile("fn f(x) { f(x + 1) } f(0)").unwrap();
+        assert!(matches!(run(&ast, chart()), Err(ScriptError::Failed(_))));
+
+        let nested = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+        assert!(compile(&nested).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+//! User scripts, registered as actions
+//!
+//! Every `*.rhai` file in the `scripts` directory becomes an action named `script.<file name>`.
+//! A script may start with a YAML header in `//!` comments to name the action and give it a
+//! default hotkey:
+//!
+//! ```rhai
+//! //! name: Mirror Selected Notes
+//! //! hotkey:
+//! //!   key: KeyM
+//! //!   modifiers: [control, shift]
+//!
+//! for note in selected_notes() {
+//!     note.x = -note.x;
+//!     edit(note);
+//! }
+//! ```
+//!
+//! Scripts are read again every time they run, so they can be edited without restarting the
+//! editor. All the changes a script makes are undone in one step
+
+mod api;
+
+use crate::action::ActionRegistrationExt;
+use crate::chart_id::Id;
+use crate::editing::command::event::{CreateEvent, EditEvent, RemoveEvent};
+use crate::editing::command::note::{CreateNote, EditNote, RemoveNote};
+use crate::editing::command::{CommandSequence, EditorCommand};
+use crate::editing::pending::Pending;
+use crate::editing::DoCommand;
+use crate::hotkey::Hotkey;
+use crate::misc::WorkingDirectory;
+use crate::notification::{ToastsExt, ToastsStorage};
+use crate::selection::{Selected, SelectedLine};
+use crate::timing::ChartTime;
+use api::{ScriptChart, ScriptEdit, ScriptError, ScriptEvent, ScriptLine, ScriptNote};
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+use phichain_chart::bpm_list::BpmList;
+use phichain_chart::event::LineEvent;
+use phichain_chart::line::Line;
+use phichain_chart::note::Note;
+use phichain_game::curve_note_track::CurveNote;
+use phichain_game::event::EventOf;
+use phichain_game::index::ChartIndex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+pub struct ScriptPlugin;
+
+impl Plugin for ScriptPlugin {
+    fn build(&self, app: &mut App) {
+        // scripts are registered while building the app, so their hotkeys are loaded along with the others
+        let directory = match app.world().resource::<WorkingDirectory>().scripts() {
+            Ok(directory) => directory,
+            Err(error) => {
+                warn!("Failed to open scripts directory: {}", error);
+                return;
+            }
+        };
+
+        for (path, name) in discover_scripts(&directory) {
+            let meta = match std::fs::read_to_string(&path) {
+                Ok(source) => parse_meta(&source).unwrap_or_else(|error| {
+                    warn!("Invalid header of script {}: {}", path.display(), error);
+                    ScriptMeta::default()
+                }),
+                Err(error) => {
+                    warn!("Failed to read script {}: {}", path.display(), error);
+                    continue;
+                }
+            };
+
+            app.add_titled_action(
+                format!("script.{name}").as_str(),
+                meta.name.unwrap_or_else(|| name.clone()),
+                move |world: &mut World| run_script(world, &path),
+                meta.hotkey,
+            );
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ScriptMeta {
+    name: Option<String>,
+    hotkey: Option<Hotkey>,
+}
+
+/// Find the scripts in a directory, sorted by name
+///
+/// Returns the path and the name of each script, dots in the name are replaced since they separate
+/// the segments of an action identifier
+fn discover_scripts(directory: &Path) -> Vec<(PathBuf, String)> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return vec![];
+    };
+
+    let mut scripts = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|x| x == "rhai"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_str()?.replace('.', "_");
+            Some((path, name))
+        })
+        .collect::<Vec<_>>();
+    scripts.sort_by(|a, b| a.1.cmp(&b.1));
+
+    scripts
+}
+
+/// Parse the YAML header made of the leading `//!` comments of a script
+fn parse_meta(source: &str) -> Result<ScriptMeta, serde_yaml::Error> {
+    let header = source
+        .lines()
+        .map_while(|line| line.trim_start().strip_prefix("//!"))
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if header.trim().is_empty() {
+        return Ok(ScriptMeta::default());
+    }
+
+    serde_yaml::from_str(&header)
+}
+
+/// Take a snapshot of the lines, notes and events for a script to work on
+///
+/// Items are identified by their [`Id`], notes generated by curve note tracks are left out
+fn snapshot(world: &mut World) -> ScriptChart {
+    let line_ids = world
+        .query_filtered::<(Entity, &Id), With<Line>>()
+        .iter(world)
+        .map(|(entity, id)| (entity, id.0))
+        .collect::<EntityHashMap<_>>();
+
+    let mut lines = world
+        .query::<(&Id, &Line)>()
+        .iter(world)
+        .map(|(id, line)| ScriptLine {
+            id: id.0,
+            name: line.name.clone(),
+            notes: vec![],
+            events: vec![],
+        })
+        .collect::<Vec<_>>();
+    lines.sort_by_key(|line| line.id);
+
+    let mut line_of = |entity: Entity| {
+        let id = line_ids.get(&entity)?;
+        lines.iter_mut().find(|line| line.id == *id)
+    };
+
+    let mut note_query =
+        world.query_filtered::<(&Id, &Note, &ChildOf), (Without<Pending>, Without<CurveNote>)>();
+    for (id, note, child_of) in note_query.iter(world) {
+        if let Some(line) = line_of(child_of.parent()) {
+            line.notes.push(ScriptNote {
+                id: Some(id.0),
+                line: Some(line.id),
+                note: *note,
+            });
+        }
+    }
+
+    let mut event_query = world.query_filtered::<(&Id, &LineEvent, &EventOf), Without<Pending>>();
+    for (id, event, event_of) in event_query.iter(world) {
+        if let Some(line) = line_of(event_of.target()) {
+            line.events.push(ScriptEvent {
+                id: Some(id.0),
+                line: Some(line.id),
+                event: *event,
+            });
+        }
+    }
+
+    for line in &mut lines {
+        line.notes.sort_by_key(|note| note.note.beat);
+        line.events.sort_by_key(|event| event.event.start_beat);
+    }
+
+    let selected = world
+        .query_filtered::<&Id, With<Selected>>()
+        .iter(world)
+        .map(|id| id.0)
+        .collect::<Vec<_>>();
+    let selected_line = line_ids.get(&world.resource::<SelectedLine>().0).copied();
+
+    let time = world.resource::<ChartTime>().0;
+    let current_beat = world.resource::<BpmList>().beat_at(time).value();
+
+    ScriptChart {
+        selected_notes: selected.clone(),
+        selected_events: selected,
+        selected_line,
+        current_beat,
+        lines,
+    }
+}
+
+/// Turn an edit into a command, [`None`] if the item it refers to no longer exists
+fn into_command(index: &ChartIndex, edit: ScriptEdit) -> Option<EditorCommand> {
+    let entity = |id: u64| index.get(Id(id));
+    Some(match edit {
+        ScriptEdit::CreateNote { line, note } => {
+            EditorCommand::CreateNote(CreateNote::new(entity(line)?, note))
+        }
+        ScriptEdit::EditNote { id, from, to } => {
+            EditorCommand::EditNote(EditNote::new(entity(id)?, from, to))
+        }
+        ScriptEdit::RemoveNote { id } => EditorCommand::RemoveNote(RemoveNote::new(entity(id)?)),
+        ScriptEdit::CreateEvent { line, event } => {
+            EditorCommand::CreateEvent(CreateEvent::new(entity(line)?, event))
+        }
+        ScriptEdit::EditEvent { id, from, to } => {
+            EditorCommand::EditEvent(EditEvent::new(entity(id)?, from, to))
+        }
+        ScriptEdit::RemoveEvent { id } => EditorCommand::RemoveEvent(RemoveEvent::new(entity(id)?)),
+    })
+}
+
+fn run_script(world: &mut World, path: &Path) -> Result {
+    let result = std::fs::read_to_string(path)
+        .map_err(|error| ScriptError::Failed(error.to_string()))
+        .and_then(|source| api::compile(&source))
+        .and_then(|ast| api::run(&ast, snapshot(world)));
+
+    match result {
+        Ok(edits) => {
+            // the ids are taken from the snapshot just made, so they all resolve
+            let index = world.resource::<ChartIndex>();
+            let commands = edits
+                .into_iter()
+                .filter_map(|edit| into_command(index, edit))
+                .collect::<Vec<_>>();
+            if !commands.is_empty() {
+                world.write_message(DoCommand(EditorCommand::CommandSequence(CommandSequence(
+                    commands,
+                ))));
+            }
+            Ok(())
+        }
+        Err(ScriptError::Aborted) => {
+            world
+                .resource_mut::<ToastsStorage>()
+                .error(t!("script.aborted", script = path.display()));
+            Err(ScriptError::Aborted.into())
+        }
+        Err(error) => {
+            world.resource_mut::<ToastsStorage>().error(t!(
+                "script.failed",
+                script = path.display(),
+                error = error
+            ));
+            Err(error.into())
+        }
+    }
+}
@@ -147,6 +147,10 @@ pub struct AutoSaveSettings {
     pub interval_secs: f32,
     pub max_backup_count: usize,
     pub idle_delay_secs: f32,
+    /// Save the undo history along the project, so it can be restored when the project is opened
+    pub persist_history: bool,
+    /// Maximum number of edits kept in the undo history of a newly opened project
+    pub history_limit: usize,
 }
 
 impl Default for AutoSaveSettings {
@@ -156,6 +160,8 @@ impl Default for AutoSaveSettings {
             interval_secs: 120.0,
             max_backup_count: 5,
             idle_delay_secs: 3.0,
+            persist_history: true,
+            history_limit: 500,
         }
     }
 }
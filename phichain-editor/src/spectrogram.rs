@@ -24,7 +24,7 @@ pub fn make_spectrogram(sound: &StaticSoundData) -> SpectrogramU8 {
     make_spectrogram_u8(&mono, 2048, 512, 80.0)
 }
 
-fn load_audio(sound: &StaticSoundData) -> AudioMono {
+pub fn load_audio(sound: &StaticSoundData) -> AudioMono {
     AudioMono {
         sample_rate: sound.sample_rate,
         data: sound
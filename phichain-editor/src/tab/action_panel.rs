@@ -92,7 +92,7 @@ fn action_panel_ui_system(
         entries.push(ActionPanelEntry {
             kind: ActionPanelEntryKind::Action,
             id: id.clone(),
-            title: t!(format!("action.{id}").as_str()).to_string(),
+            title: actions.title(id),
             hotkey: hotkeys.0.get(id).cloned(),
         })
     }
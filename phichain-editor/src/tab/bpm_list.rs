@@ -1,6 +1,8 @@
+use crate::action::RunAction;
 use crate::editing::command::bpm_list::{CreateBpmPoint, EditBpmPoint, RemoveBpmPoint};
 use crate::editing::command::EditorCommand;
 use crate::editing::DoCommand;
+use crate::tempo::{self, TapTempo, TempoDetection};
 use crate::ui::latch;
 use crate::ui::widgets::beat_value::BeatValue;
 use bevy::prelude::*;
@@ -13,6 +15,9 @@ pub fn bpm_list_tab(
     In(mut ui): In<Ui>,
     mut bpm_list: ResMut<BpmList>,
     mut event_writer: MessageWriter<DoCommand>,
+    mut detection: ResMut<TempoDetection>,
+    mut tap: ResMut<TapTempo>,
+    mut commands: Commands,
 ) {
     let mut changes = Vec::new();
     let mut deletes = Vec::new();
@@ -99,6 +104,12 @@ pub fn bpm_list_tab(
                 CreateBpmPoint::new(BpmPoint::new(beat, 120.0)),
             )));
         }
+
+        ui.separator();
+        detection_ui(ui, &mut detection, &mut commands);
+
+        ui.separator();
+        tap_tempo_ui(ui, &mut tap, &mut commands);
     });
 
     // recompute after all changes are applied
@@ -115,3 +126,84 @@ pub fn bpm_list_tab(
         bpm_list.compute();
     }
 }
+
+fn detection_ui(ui: &mut Ui, detection: &mut TempoDetection, commands: &mut Commands) {
+    ui.heading(t!("tab.bpm_list.detect.title"));
+    ui.checkbox(
+        &mut detection.detect_changes,
+        t!("tab.bpm_list.detect.detect_changes"),
+    );
+
+    if detection.is_running() {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(t!("tab.bpm_list.detect.detecting"));
+        });
+        return;
+    }
+
+    if ui.button(t!("tab.bpm_list.detect.detect")).clicked() {
+        commands.trigger(RunAction("phichain.detect_tempo".into()));
+    }
+
+    let Some(Some(estimate)) = &detection.result else {
+        return;
+    };
+
+    egui::Grid::new("bpm_detection_grid")
+        .num_columns(2)
+        .spacing([20.0, 2.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label(t!("tab.bpm_list.detect.offset"));
+            ui.label(format!("{:.0} ms", estimate.offset));
+            ui.end_row();
+
+            for point in &estimate.points {
+                ui.label(format!("{:?}", point.beat));
+                ui.label(format!("{} BPM", point.bpm));
+                ui.end_row();
+            }
+        });
+
+    if ui.button(t!("tab.bpm_list.detect.apply")).clicked() {
+        let estimate = estimate.clone();
+        commands.queue(move |world: &mut World| {
+            tempo::apply(world, &estimate.points, Some(estimate.offset));
+        });
+    }
+}
+
+fn tap_tempo_ui(ui: &mut Ui, tap: &mut TapTempo, commands: &mut Commands) {
+    ui.heading(t!("tab.bpm_list.tap.title"));
+
+    ui.horizontal(|ui| {
+        if ui.button(t!("tab.bpm_list.tap.tap")).clicked() {
+            commands.trigger(RunAction("phichain.tap_tempo".into()));
+        }
+        if ui.button(t!("tab.bpm_list.tap.reset")).clicked() {
+            tap.reset();
+        }
+    });
+
+    let Some(tempo) = tap.tempo() else {
+        ui.label(t!("tab.bpm_list.tap.hint"));
+        return;
+    };
+
+    let bpm = (tempo.bpm * 100.0).round() / 100.0;
+    ui.label(t!("tab.bpm_list.tap.bpm", bpm = bpm, taps = tap.taps()));
+    if tap.aligned() {
+        ui.label(t!(
+            "tab.bpm_list.tap.offset",
+            offset = format!("{:.0}", tempo.phase * 1000.0)
+        ));
+    }
+
+    if ui.button(t!("tab.bpm_list.tap.apply")).clicked() {
+        let offset = tap.aligned().then_some(tempo.phase * 1000.0);
+        commands.queue(move |world: &mut World| {
+            tempo::apply(world, &[BpmPoint::new(Beat::ZERO, bpm)], offset);
+        });
+    }
+}
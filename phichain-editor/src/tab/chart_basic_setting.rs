@@ -1,3 +1,4 @@
+use crate::analysis::Analysis;
 use crate::editing::command::meta::{EditMeta, EditOffset};
 use crate::editing::command::EditorCommand;
 use crate::editing::DoCommand;
@@ -11,6 +12,7 @@ pub fn chart_basic_setting_tab(
     In(mut ui): In<Ui>,
     mut offset: ResMut<Offset>,
     mut project: ResMut<Project>,
+    analysis: Res<Analysis>,
 
     mut event_writer: MessageWriter<DoCommand>,
 ) {
@@ -76,4 +78,61 @@ pub fn chart_basic_setting_tab(
                 }
             }
         });
+
+    ui.separator();
+
+    let analysis = &analysis.0;
+    let level = analysis.estimated_level();
+
+    egui::Grid::new("chart_basic_setting_analysis_grid")
+        .num_columns(2)
+        .spacing([20.0, 2.0])
+        .striped(true)
+        .show(&mut ui, |ui| {
+            ui.label(t!("tab.chart_basic_setting.analysis.estimated_level"));
+            ui.horizontal(|ui| {
+                ui.label(format!("{level:.1}"));
+                if ui
+                    .button(t!("tab.chart_basic_setting.analysis.apply_level"))
+                    .clicked()
+                {
+                    let from = project.meta.clone();
+                    project.meta.level = format!("{}", level.round());
+                    event_writer.write(DoCommand(EditorCommand::EditMeta(EditMeta::new(
+                        from,
+                        project.meta.clone(),
+                    ))));
+                }
+            });
+            ui.end_row();
+
+            ui.label(t!("tab.chart_basic_setting.analysis.average_nps"));
+            ui.label(format!("{:.2}", analysis.average_nps()));
+            ui.end_row();
+
+            for peak in &analysis.peak_nps {
+                ui.label(t!(
+                    "tab.chart_basic_setting.analysis.peak_nps",
+                    window = peak.window
+                ));
+                ui.label(format!("{:.2} @ {:.2}s", peak.nps, peak.start));
+                ui.end_row();
+            }
+
+            ui.label(t!("tab.chart_basic_setting.analysis.note_kinds"));
+            let kinds = analysis.note_kinds;
+            ui.label(format!(
+                "{} / {} / {} / {}",
+                kinds.tap, kinds.drag, kinds.hold, kinds.flick
+            ));
+            ui.end_row();
+
+            ui.label(t!("tab.chart_basic_setting.analysis.hold_coverage"));
+            ui.label(format!("{:.1}%", analysis.hold_coverage * 100.0));
+            ui.end_row();
+
+            ui.label(t!("tab.chart_basic_setting.analysis.movement"));
+            ui.label(format!("{:.2}", analysis.movement_intensity()));
+            ui.end_row();
+        });
 }
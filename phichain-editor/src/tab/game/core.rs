@@ -8,20 +8,19 @@ use bevy_prototype_lyon::prelude::*;
 use phichain_chart::line::Line;
 use phichain_chart::note::Note;
 use phichain_chart::project::Project;
-use phichain_game::core::HoldComponent;
+use phichain_game::core::NoteTint;
 use phichain_game::curve_note_track::CurveNote;
-use phichain_game::GameConfig;
+use phichain_game::{GameConfig, GameSet};
 
 pub struct CoreGamePlugin;
 
 impl Plugin for CoreGamePlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Update, sync_game_config_system.run_if(project_loaded()))
-            .add_systems(Update, update_note_tint_system.run_if(project_loaded()))
             .add_systems(
                 Update,
-                sync_hold_components_tint_system
-                    .after(update_note_tint_system)
+                update_note_tint_system
+                    .before(GameSet)
                     .run_if(project_loaded()),
             )
             .add_systems(
@@ -35,10 +34,11 @@ impl Plugin for CoreGamePlugin {
     }
 }
 
+/// Tint notes by their state in the editor, the game multiplies the alpha of the notes into it
 fn update_note_tint_system(
     mut query: Query<
         (
-            &mut Sprite,
+            &mut NoteTint,
             Option<&CurveNote>,
             Option<&Selected>,
             Option<&Pending>,
@@ -46,7 +46,7 @@ fn update_note_tint_system(
         With<Note>,
     >,
 ) {
-    for (mut sprite, curve_note, selected, pending) in &mut query {
+    for (mut note_tint, curve_note, selected, pending) in &mut query {
         let tint = if selected.is_some() {
             bevy::color::palettes::css::LIMEGREEN
         } else {
@@ -59,18 +59,7 @@ fn update_note_tint_system(
         } else {
             1.0
         };
-        sprite.color = tint.with_alpha(alpha).into();
-    }
-}
-
-fn sync_hold_components_tint_system(
-    mut component_query: Query<(&mut Sprite, &ChildOf), With<HoldComponent>>,
-    parent_query: Query<&Sprite, Without<HoldComponent>>,
-) {
-    for (mut sprite, child_of) in &mut component_query {
-        if let Ok(parent_sprite) = parent_query.get(child_of.parent()) {
-            sprite.color = parent_sprite.color;
-        }
+        note_tint.0 = tint.with_alpha(alpha).into();
     }
 }
 
@@ -1,6 +1,8 @@
 pub mod core;
+pub mod play_test;
 
 use self::core::CoreGamePlugin;
+use self::play_test::{play_test_feedback_ui, PlayTestPlugin};
 use crate::project::project_loaded;
 use crate::settings::{AspectRatio, EditorSettings};
 use crate::utils;
@@ -28,6 +30,8 @@ pub fn game_tab(In(ui): In<Ui>, world: &mut World) {
 
     let mut game_viewport = world.resource_mut::<phichain_game::GameViewport>();
     game_viewport.0 = viewport.into_bevy();
+
+    play_test_feedback_ui(&ui, world, viewport);
 }
 
 pub struct GameTabPlugin;
@@ -39,7 +43,8 @@ impl Plugin for GameTabPlugin {
                 PostUpdate,
                 update_game_camera_viewport_system.run_if(project_loaded()),
             )
-            .add_plugins(CoreGamePlugin);
+            .add_plugins(CoreGamePlugin)
+            .add_plugins(PlayTestPlugin);
     }
 }
 
@@ -0,0 +1,391 @@
+//! Play-test mode of the game tab, where keyboard keys and mouse clicks act as touches
+//!
+//! While enabled, the inputs of each playback are recorded and judged by `phichain-game` through
+//! [`GameInput`]. Once the playback stops, a [`PlayTestReport`] with the accuracy of each segment is shown
+
+use crate::action::ActionRegistrationExt;
+use crate::editing::pending::Pending;
+use crate::hotkey::Hotkey;
+use crate::project::project_loaded;
+use crate::tab::game::GameViewport;
+use crate::timing::{ChartTime, Pause, Paused, Resume};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_egui::{EguiContext, EguiContextSettings, EguiPrimaryContextPass};
+use egui::{Align2, Color32, FontId, Ui};
+use phichain_chart::bpm_list::BpmList;
+use phichain_chart::note::Note;
+use phichain_game::judgement::{InputEvent, InputKind, Judge, JudgeNote, Judgement, ScoreCounter};
+use phichain_game::score::{GameInput, GameScore};
+use phichain_game::GameSet;
+use std::collections::BTreeMap;
+
+/// Keys acting as touches, each key is a touch of its own
+///
+/// Keys are unable to move, so a key press also flicks
+const PLAY_KEYS: [KeyCode; 8] = [
+    KeyCode::KeyA,
+    KeyCode::KeyS,
+    KeyCode::KeyD,
+    KeyCode::KeyF,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::Semicolon,
+];
+
+/// The touch of the left mouse button, keys take the touches after it
+const MOUSE_TOUCH: u32 = 0;
+
+/// Moving the pressed mouse faster than this many game viewport heights per second flicks
+const FLICK_SPEED: f32 = 2.0;
+
+/// Length of a segment of the report in beats
+const SEGMENT_BEATS: i32 = 16;
+
+/// How long a judgement stays on screen in seconds
+const FEEDBACK_DURATION: f32 = 0.5;
+
+#[derive(Resource, Debug, Default)]
+pub struct PlayTest {
+    pub enabled: bool,
+    /// If the inputs of the current playback are being recorded
+    recording: bool,
+}
+
+pub struct PlayTestPlugin;
+
+impl Plugin for PlayTestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayTest>()
+            .add_action(
+                "phichain.toggle_play_test",
+                toggle_play_test_system,
+                Some(Hotkey::new(KeyCode::F5, vec![])),
+            )
+            .add_observer(start_recording_observer)
+            .add_observer(stop_recording_observer)
+            .add_systems(
+                Update,
+                record_input_system.before(GameSet).run_if(project_loaded()),
+            )
+            .add_systems(
+                EguiPrimaryContextPass,
+                report_ui_system.run_if(resource_exists::<PlayTestReport>),
+            );
+    }
+}
+
+fn toggle_play_test_system(
+    mut commands: Commands,
+    mut play_test: ResMut<PlayTest>,
+    mut input: ResMut<GameInput>,
+) -> Result {
+    play_test.enabled = !play_test.enabled;
+
+    if !play_test.enabled {
+        play_test.recording = false;
+        *input = GameInput::Autoplay;
+        commands.remove_resource::<PlayTestReport>();
+    }
+
+    Ok(())
+}
+
+fn start_recording_observer(
+    _: On<Resume>,
+    mut commands: Commands,
+    mut play_test: ResMut<PlayTest>,
+    mut input: ResMut<GameInput>,
+    time: Res<ChartTime>,
+) {
+    if !play_test.enabled {
+        return;
+    }
+
+    play_test.recording = true;
+    *input = GameInput::Recorded {
+        start: time.0,
+        events: vec![],
+    };
+    commands.remove_resource::<PlayTestReport>();
+}
+
+/// Stop recording and report the recorded playback
+///
+/// The recorded inputs are kept, so the game tab keeps showing the result of the playback
+fn stop_recording_observer(
+    _: On<Pause>,
+    mut commands: Commands,
+    mut play_test: ResMut<PlayTest>,
+    input: Res<GameInput>,
+    time: Res<ChartTime>,
+    bpm_list: Res<BpmList>,
+    note_query: Query<&Note, Without<Pending>>,
+) {
+    if !play_test.recording {
+        return;
+    }
+    play_test.recording = false;
+
+    if let GameInput::Recorded { start, events } = input.as_ref() {
+        let notes = note_query.iter().collect::<Vec<_>>();
+        commands.insert_resource(PlayTestReport::new(
+            &notes, &bpm_list, *start, time.0, events,
+        ));
+    }
+}
+
+fn record_input_system(
+    play_test: Res<PlayTest>,
+    paused: Res<Paused>,
+    time: Res<ChartTime>,
+    real_time: Res<Time>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    egui_settings: Query<&EguiContextSettings>,
+    game_viewport: Res<GameViewport>,
+    mut input: ResMut<GameInput>,
+
+    mut mouse_down: Local<bool>,
+    mut last_cursor: Local<Option<Vec2>>,
+) -> Result {
+    if !play_test.recording || paused.0 {
+        return Ok(());
+    }
+
+    let GameInput::Recorded { start, events } = input.as_mut() else {
+        return Ok(());
+    };
+
+    // seeking backwards restarts the recording from there
+    if time.0 < *start || events.last().is_some_and(|event| event.time > time.0) {
+        *start = time.0;
+        events.clear();
+    }
+
+    let mut push = |touch: u32, kind: InputKind| events.push(InputEvent::new(time.0, touch, kind));
+
+    for (touch, key) in (MOUSE_TOUCH + 1..).zip(PLAY_KEYS) {
+        if keyboard.just_pressed(key) {
+            push(touch, InputKind::Press);
+            push(touch, InputKind::Flick);
+        }
+        if keyboard.just_released(key) {
+            push(touch, InputKind::Release);
+        }
+    }
+
+    // the cursor position is in logical window pixels, while the viewport is in egui points
+    let scale_factor = egui_settings.single()?.scale_factor;
+    let cursor = window_query
+        .single()?
+        .cursor_position()
+        .map(|position| position / scale_factor);
+
+    if mouse.just_pressed(MouseButton::Left)
+        && cursor.is_some_and(|cursor| game_viewport.0.contains(cursor))
+    {
+        *mouse_down = true;
+        push(MOUSE_TOUCH, InputKind::Press);
+    } else if *mouse_down && mouse.just_released(MouseButton::Left) {
+        *mouse_down = false;
+        push(MOUSE_TOUCH, InputKind::Release);
+    } else if *mouse_down {
+        if let (Some(cursor), Some(last_cursor)) = (cursor, *last_cursor) {
+            let speed = cursor.distance(last_cursor) / real_time.delta_secs().max(f32::EPSILON);
+            if speed >= FLICK_SPEED * game_viewport.0.height() {
+                push(MOUSE_TOUCH, InputKind::Flick);
+            }
+        }
+    }
+    *last_cursor = cursor;
+
+    Ok(())
+}
+
+/// Judgements of the notes in a range of beats
+#[derive(Debug, Clone, Default)]
+struct SegmentStats {
+    counter: ScoreCounter,
+    /// Offsets in seconds of the hit notes
+    offsets: Vec<f32>,
+}
+
+impl SegmentStats {
+    fn push(&mut self, judgement: Judgement, offset: Option<f32>) {
+        self.counter.push(judgement);
+        self.offsets.extend(offset);
+    }
+
+    /// Mean offset of the hit notes in milliseconds, negative when early
+    fn mean_offset(&self) -> Option<f32> {
+        (!self.offsets.is_empty())
+            .then(|| self.offsets.iter().sum::<f32>() / self.offsets.len() as f32 * 1000.0)
+    }
+}
+
+/// Accuracy statistics of a play-test, for each [`SEGMENT_BEATS`] beats
+#[derive(Resource, Debug, Clone)]
+pub struct PlayTestReport {
+    /// Segments with judged notes, keyed by their index
+    segments: BTreeMap<i32, SegmentStats>,
+    total: SegmentStats,
+}
+
+impl PlayTestReport {
+    /// Judge the notes played between `start` and `end` in seconds against the recorded `events`
+    fn new(
+        notes: &[&Note],
+        bpm_list: &BpmList,
+        start: f32,
+        end: f32,
+        events: &[InputEvent],
+    ) -> Self {
+        let (beats, notes): (Vec<_>, Vec<_>) = notes
+            .iter()
+            .filter(|note| !note.is_fake)
+            .map(|note| (note.beat, JudgeNote::new(note, bpm_list)))
+            .filter(|(_, note)| (start..=end).contains(&note.time))
+            .unzip();
+
+        let mut judge = Judge::new(notes);
+        for event in events {
+            judge.feed(*event);
+        }
+        judge.advance(end);
+
+        let mut segments = BTreeMap::<i32, SegmentStats>::new();
+        let mut total = SegmentStats::default();
+        for record in judge.records() {
+            let segment = beats[record.note].beat().div_euclid(SEGMENT_BEATS);
+            segments
+                .entry(segment)
+                .or_default()
+                .push(record.judgement, record.offset);
+            total.push(record.judgement, record.offset);
+        }
+
+        Self { segments, total }
+    }
+}
+
+fn stats_row(ui: &mut Ui, label: String, stats: &SegmentStats) {
+    let counter = &stats.counter;
+    ui.label(label);
+    ui.label(counter.judged().to_string());
+    ui.label(counter.perfect.to_string());
+    ui.label(counter.good.to_string());
+    ui.label(counter.bad.to_string());
+    ui.label(counter.miss.to_string());
+    ui.label(format!("{:.2}%", counter.accuracy() * 100.0));
+    ui.label(
+        stats
+            .mean_offset()
+            .map(|offset| format!("{offset:+.0} ms"))
+            .unwrap_or_else(|| "-".to_owned()),
+    );
+    ui.end_row();
+}
+
+fn report_ui_system(
+    mut commands: Commands,
+    mut context: Query<&mut EguiContext>,
+    report: Res<PlayTestReport>,
+) {
+    let Ok(mut egui_context) = context.single_mut() else {
+        return;
+    };
+
+    let mut open = true;
+    egui::Window::new(t!("game.play_test.report.title"))
+        .open(&mut open)
+        .collapsible(false)
+        .show(egui_context.get_mut(), |ui| {
+            if report.segments.is_empty() {
+                ui.label(t!("game.play_test.report.empty"));
+                return;
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("play_test_report")
+                    .striped(true)
+                    .num_columns(8)
+                    .show(ui, |ui| {
+                        for header in [
+                            "segment", "notes", "perfect", "good", "bad", "miss", "accuracy",
+                            "offset",
+                        ] {
+                            ui.strong(t!(format!("game.play_test.report.{header}").as_str()));
+                        }
+                        ui.end_row();
+
+                        for (segment, stats) in &report.segments {
+                            let start = segment * SEGMENT_BEATS;
+                            stats_row(ui, format!("{} - {}", start, start + SEGMENT_BEATS), stats);
+                        }
+
+                        stats_row(
+                            ui,
+                            t!("game.play_test.report.total").to_string(),
+                            &report.total,
+                        );
+                    });
+            });
+        });
+
+    if !open {
+        commands.remove_resource::<PlayTestReport>();
+    }
+}
+
+fn judgement_color(judgement: Judgement) -> Color32 {
+    match judgement {
+        Judgement::Perfect => Color32::from_rgb(254, 255, 169),
+        Judgement::Good => Color32::from_rgb(162, 238, 255),
+        Judgement::Bad => Color32::from_rgb(255, 140, 80),
+        Judgement::Miss => Color32::from_rgb(160, 160, 160),
+    }
+}
+
+/// Show the play-test hint and the latest judgement over the game viewport
+pub fn play_test_feedback_ui(ui: &Ui, world: &mut World, viewport: egui::Rect) {
+    if !world.resource::<PlayTest>().enabled {
+        return;
+    }
+
+    let painter = ui.painter_at(viewport);
+    painter.text(
+        viewport.left_top() + egui::vec2(8.0, 8.0),
+        Align2::LEFT_TOP,
+        t!("game.play_test.hint"),
+        FontId::proportional(14.0),
+        Color32::WHITE,
+    );
+
+    let now = world.resource::<ChartTime>().0;
+    let latest = world
+        .resource::<GameScore>()
+        .records()
+        .iter()
+        .filter(|record| (now - FEEDBACK_DURATION..=now).contains(&record.time))
+        .max_by(|a, b| a.time.total_cmp(&b.time));
+
+    if let Some(record) = latest {
+        let label = format!("{:?}", record.judgement).to_uppercase();
+        let text = match record.offset {
+            Some(offset) if record.judgement != Judgement::Perfect => {
+                format!("{label} {:+.0} ms", offset * 1000.0)
+            }
+            _ => label,
+        };
+        painter.text(
+            viewport.center_bottom() - egui::vec2(0.0, viewport.height() * 0.2),
+            Align2::CENTER_CENTER,
+            text,
+            FontId::proportional(viewport.height() * 0.05),
+            judgement_color(record.judgement),
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use crate::editing::history::{EditorHistory, HistoryTree};
+use bevy::prelude::*;
+use egui::{RichText, ScrollArea, Ui};
+
+pub fn history_tab(In(mut ui): In<Ui>, tree: Res<HistoryTree>, mut commands: Commands) {
+    let mut target = None;
+    let current = tree.current();
+
+    let tips = tree.tips();
+    if tips.len() > 1 {
+        ui.label(RichText::new(t!("tab.history.branches")).strong());
+        let current_tip = tree.tip(current);
+        for tip in tips {
+            let Some(node) = tree.get(tip) else {
+                continue;
+            };
+            let label = t!(
+                "tab.history.branch",
+                description = node.description,
+                count = tree.path(tip).len() - 1
+            );
+            if ui.selectable_label(tip == current_tip, label).clicked() && tip != current_tip {
+                target = Some(tip);
+            }
+        }
+        ui.separator();
+    }
+
+    let timeline = tree.timeline();
+    let position = timeline.iter().position(|x| *x == current);
+    ScrollArea::vertical().show(&mut ui, |ui| {
+        for (index, id) in timeline.into_iter().enumerate() {
+            let Some(node) = tree.get(id) else {
+                continue;
+            };
+
+            let mut text = if node.description.is_empty() {
+                RichText::new(t!("tab.history.initial")).italics()
+            } else {
+                RichText::new(&node.description)
+            };
+            // states after the current one are only reachable by redoing
+            if position.is_some_and(|position| index > position) {
+                text = text.weak();
+            }
+
+            let mut response = ui.selectable_label(id == current, text);
+            if node.children.len() > 1 {
+                response =
+                    response.on_hover_text(t!("tab.history.forked", count = node.children.len()));
+            }
+            if response.clicked() && id != current {
+                target = Some(id);
+            }
+        }
+    });
+
+    if let Some(node) = target {
+        commands.queue(move |world: &mut World| {
+            world.resource_scope(|world, mut history: Mut<EditorHistory>| {
+                history.go_to(world, node);
+            });
+        });
+    }
+}
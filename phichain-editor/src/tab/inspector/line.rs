@@ -1,7 +1,7 @@
 use crate::selection::SelectedLine;
 use crate::ui::latch;
 use bevy::prelude::*;
-use egui::Ui;
+use egui::{DragValue, Ui};
 use phichain_chart::line::Line;
 
 pub fn line_inspector(
@@ -27,6 +27,49 @@ pub fn line_inspector(
                 finished |= response.lost_focus();
                 ui.end_row();
 
+                ui.label(t!("tab.inspector.line.texture"));
+                let mut texture = line.texture.clone().unwrap_or_default();
+                let response = ui
+                    .text_edit_singleline(&mut texture)
+                    .on_hover_text(t!("tab.inspector.line.texture_hint"));
+                if response.changed() {
+                    line.texture = (!texture.is_empty()).then_some(texture);
+                }
+                finished |= response.lost_focus();
+                ui.end_row();
+
+                ui.label(t!("tab.inspector.line.anchor"));
+                ui.horizontal(|ui| {
+                    let [mut x, mut y] = line.anchor();
+                    let response_x = ui.add(DragValue::new(&mut x).speed(0.01).range(0.0..=1.0));
+                    let response_y = ui.add(DragValue::new(&mut y).speed(0.01).range(0.0..=1.0));
+                    if response_x.changed() || response_y.changed() {
+                        line.anchor = ([x, y] != [0.5, 0.5]).then_some([x, y]);
+                    }
+                    for response in [response_x, response_y] {
+                        finished |= response.drag_stopped() || response.lost_focus();
+                    }
+                });
+                ui.end_row();
+
+                ui.label(t!("tab.inspector.line.z_order"));
+                let mut z_order = line.z_order();
+                let response = ui.add(DragValue::new(&mut z_order).speed(0.1));
+                if response.changed() {
+                    line.z_order = (z_order != 0).then_some(z_order);
+                }
+                finished |= response.drag_stopped() || response.lost_focus();
+                ui.end_row();
+
+                ui.label(t!("tab.inspector.line.cover"));
+                let mut cover = line.is_cover();
+                let response = ui.checkbox(&mut cover, "");
+                if response.changed() {
+                    line.cover = (!cover).then_some(false);
+                }
+                finished |= response.changed();
+                ui.end_row();
+
                 finished
             });
 
@@ -2,8 +2,10 @@ mod curve_note_track;
 mod line;
 mod multiple_events;
 mod multiple_notes;
+mod note_visual;
 mod single_event;
 mod single_note;
+mod transform;
 
 use crate::selection::Selected;
 use crate::tab::inspector::curve_note_track::curve_note_track_inspector;
@@ -1,15 +1,21 @@
 use crate::editing::command::event::EditEvent;
 use crate::editing::command::{CommandSequence, EditorCommand};
+use crate::editing::transform::TransformSettings;
 use crate::editing::DoCommand;
 use crate::selection::Selected;
+use crate::tab::inspector::transform::transform_ui;
+use crate::timeline::TimelineContext;
 use bevy::prelude::*;
 use egui::{Align, Layout, Ui};
-use phichain_chart::event::{LineEvent, LineEventKind};
+use phichain_chart::event::LineEvent;
 
 pub fn multiple_events_inspector(
     In(mut ui): In<Ui>,
     query: Query<(&LineEvent, Entity), With<Selected>>,
     mut event_writer: MessageWriter<DoCommand>,
+    mut settings: ResMut<TransformSettings>,
+    mut commands: Commands,
+    ctx: TimelineContext,
 ) -> Result {
     ui.label(t!(
         "tab.inspector.multiple_events.title",
@@ -24,7 +30,7 @@ pub fn multiple_events_inspector(
         {
             let commands = query
                 .iter()
-                .filter(|(event, _)| event.kind != LineEventKind::Opacity)
+                .filter(|(event, _)| !event.kind.is_opacity() && !event.kind.is_color())
                 .map(|(event, entity)| {
                     EditorCommand::EditEvent(EditEvent::new(
                         entity,
@@ -43,5 +49,15 @@ pub fn multiple_events_inspector(
         }
     });
 
+    ui.separator();
+
+    transform_ui(
+        &mut ui,
+        &mut settings,
+        &mut commands,
+        ctx.settings.density,
+        false,
+    );
+
     Ok(())
 }
@@ -1,7 +1,12 @@
 use crate::editing::command::note::EditNote;
 use crate::editing::command::{CommandSequence, EditorCommand};
+use crate::editing::transform::TransformSettings;
 use crate::editing::DoCommand;
 use crate::selection::Selected;
+use crate::tab::inspector::note_visual::note_visual_ui;
+use crate::tab::inspector::transform::transform_ui;
+use crate::timeline::TimelineContext;
+use crate::ui::latch;
 use bevy::prelude::*;
 use egui::{Align, Layout, Ui};
 use phichain_chart::beat;
@@ -9,8 +14,11 @@ use phichain_chart::note::{Note, NoteKind};
 
 pub fn multiple_notes_inspector(
     In(mut ui): In<Ui>,
-    query: Query<(&Note, Entity), With<Selected>>,
+    mut query: Query<(&mut Note, Entity), With<Selected>>,
     mut event_writer: MessageWriter<DoCommand>,
+    mut settings: ResMut<TransformSettings>,
+    mut commands: Commands,
+    ctx: TimelineContext,
 ) -> Result {
     ui.label(t!(
         "tab.inspector.multiple_notes.title",
@@ -19,28 +27,6 @@ pub fn multiple_notes_inspector(
     ui.separator();
 
     ui.with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
-        if ui
-            .button(t!("tab.inspector.multiple_notes.flip_by_x"))
-            .clicked()
-        {
-            let commands = query
-                .iter()
-                .map(|(note, entity)| {
-                    EditorCommand::EditNote(EditNote::new(
-                        entity,
-                        *note,
-                        Note {
-                            x: -note.x,
-                            ..*note
-                        },
-                    ))
-                })
-                .collect::<Vec<_>>();
-
-            event_writer.write(DoCommand(EditorCommand::CommandSequence(CommandSequence(
-                commands,
-            ))));
-        }
         if ui
             .button(t!("tab.inspector.multiple_notes.flip_by_selection"))
             .clicked()
@@ -67,29 +53,6 @@ pub fn multiple_notes_inspector(
             ))));
         }
 
-        if ui
-            .button(t!("tab.inspector.multiple_notes.flip_side"))
-            .clicked()
-        {
-            let commands = query
-                .iter()
-                .map(|(note, entity)| {
-                    EditorCommand::EditNote(EditNote::new(
-                        entity,
-                        *note,
-                        Note {
-                            above: !note.above,
-                            ..*note
-                        },
-                    ))
-                })
-                .collect::<Vec<_>>();
-
-            event_writer.write(DoCommand(EditorCommand::CommandSequence(CommandSequence(
-                commands,
-            ))));
-        }
-
         let mut into_kind = |kind: NoteKind| {
             let commands = query
                 .iter()
@@ -129,7 +92,94 @@ pub fn multiple_notes_inspector(
                 hold_beat: beat!(1, 32),
             });
         }
+
+        let mut into_fake = |is_fake: bool| {
+            let commands = query
+                .iter()
+                .map(|(note, entity)| {
+                    EditorCommand::EditNote(EditNote::new(entity, *note, Note { is_fake, ..*note }))
+                })
+                .collect::<Vec<_>>();
+
+            event_writer.write(DoCommand(EditorCommand::CommandSequence(CommandSequence(
+                commands,
+            ))));
+        };
+
+        if ui
+            .button(t!("tab.inspector.multiple_notes.into_fake"))
+            .clicked()
+        {
+            into_fake(true);
+        }
+        if ui
+            .button(t!("tab.inspector.multiple_notes.into_real"))
+            .clicked()
+        {
+            into_fake(false);
+        }
     });
 
+    ui.separator();
+
+    transform_ui(
+        &mut ui,
+        &mut settings,
+        &mut commands,
+        ctx.settings.density,
+        true,
+    );
+
+    ui.separator();
+
+    // the first note acts as a template, attributes edited on it are copied to every selected note
+    let Some(template) = query.iter().map(|(note, _)| *note).next() else {
+        return Ok(());
+    };
+    let snapshot = query
+        .iter()
+        .map(|(note, entity)| (entity, *note))
+        .collect::<Vec<_>>();
+
+    let result = latch::latch(&mut ui, "multiple_notes", snapshot, |ui| {
+        let mut edited = template;
+        let response = note_visual_ui(ui, &mut edited);
+
+        if response.changed {
+            for (mut note, _) in &mut query {
+                if edited.alpha != template.alpha {
+                    note.alpha = edited.alpha;
+                }
+                if edited.size != template.size {
+                    note.size = edited.size;
+                }
+                if edited.y_offset != template.y_offset {
+                    note.y_offset = edited.y_offset;
+                }
+                if edited.visible_time != template.visible_time {
+                    note.visible_time = edited.visible_time;
+                }
+            }
+        }
+
+        response.finished
+    });
+
+    if let Some(from) = result {
+        let commands = from
+            .into_iter()
+            .filter_map(|(entity, from)| {
+                let (to, _) = query.get(entity).ok()?;
+                (from != *to).then(|| EditorCommand::EditNote(EditNote::new(entity, from, *to)))
+            })
+            .collect::<Vec<_>>();
+
+        if !commands.is_empty() {
+            event_writer.write(DoCommand(EditorCommand::CommandSequence(CommandSequence(
+                commands,
+            ))));
+        }
+    }
+
     Ok(())
 }
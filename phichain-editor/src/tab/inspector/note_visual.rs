@@ -0,0 +1,95 @@
+use crate::ui::sides::SidesExt;
+use egui::{DragValue, Ui};
+use phichain_chart::note::Note;
+
+/// The outcome of editing the visual attributes of a [`Note`]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoteVisualResponse {
+    pub changed: bool,
+    pub finished: bool,
+}
+
+/// Edit an optional attribute through its effective value, storing [`None`] back when the value equals `default`
+fn optional_value<T: PartialEq + Copy>(
+    ui: &mut Ui,
+    value: &mut Option<T>,
+    default: T,
+    add: impl FnOnce(&mut Ui, &mut T) -> egui::Response,
+    response: &mut NoteVisualResponse,
+) {
+    let mut bind = value.unwrap_or(default);
+    let widget = add(ui, &mut bind);
+    if widget.changed() {
+        *value = (bind != default).then_some(bind);
+        response.changed = true;
+    }
+    response.finished |= widget.drag_stopped() || widget.lost_focus();
+}
+
+/// Show the visual attributes (alpha, size, y offset and visible time) of a [`Note`] for editing
+pub fn note_visual_ui(ui: &mut Ui, note: &mut Note) -> NoteVisualResponse {
+    let mut response = NoteVisualResponse::default();
+
+    ui.sides(
+        |ui| ui.label(t!("tab.inspector.note_visual.alpha")),
+        |ui| {
+            optional_value(
+                ui,
+                &mut note.alpha,
+                u8::MAX,
+                |ui, value| ui.add(DragValue::new(value).speed(1)),
+                &mut response,
+            );
+        },
+    );
+
+    ui.sides(
+        |ui| ui.label(t!("tab.inspector.note_visual.size")),
+        |ui| {
+            optional_value(
+                ui,
+                &mut note.size,
+                1.0,
+                |ui, value| ui.add(DragValue::new(value).range(0.0..=f32::MAX).speed(0.01)),
+                &mut response,
+            );
+        },
+    );
+
+    ui.sides(
+        |ui| ui.label(t!("tab.inspector.note_visual.y_offset")),
+        |ui| {
+            optional_value(
+                ui,
+                &mut note.y_offset,
+                0.0,
+                |ui, value| ui.add(DragValue::new(value).speed(1)),
+                &mut response,
+            );
+        },
+    );
+
+    ui.sides(
+        |ui| ui.label(t!("tab.inspector.note_visual.visible_time")),
+        |ui| {
+            let mut limited = note.visible_time.is_some();
+            if let Some(visible_time) = &mut note.visible_time {
+                let widget = ui.add(
+                    DragValue::new(visible_time)
+                        .range(0.0..=f32::MAX)
+                        .speed(0.01)
+                        .suffix("s"),
+                );
+                response.changed |= widget.changed();
+                response.finished |= widget.drag_stopped() || widget.lost_focus();
+            }
+            if ui.checkbox(&mut limited, "").changed() {
+                note.visible_time = limited.then_some(1.0);
+                response.changed = true;
+                response.finished = true;
+            }
+        },
+    );
+
+    response
+}
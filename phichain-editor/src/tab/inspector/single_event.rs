@@ -27,6 +27,13 @@ pub fn single_event_inspector(
         LineEventKind::Rotation => t!("game.event.kind.rotation"),
         LineEventKind::Opacity => t!("game.event.kind.opacity"),
         LineEventKind::Speed => t!("game.event.kind.speed"),
+        LineEventKind::ScaleX => t!("game.event.kind.scale_x"),
+        LineEventKind::ScaleY => t!("game.event.kind.scale_y"),
+        LineEventKind::ColorR => t!("game.event.kind.color_r"),
+        LineEventKind::ColorG => t!("game.event.kind.color_g"),
+        LineEventKind::ColorB => t!("game.event.kind.color_b"),
+        LineEventKind::Incline => t!("game.event.kind.incline"),
+        LineEventKind::Paint => t!("game.event.kind.paint"),
     };
 
     ui.label(t!("tab.inspector.single_event.title", kind = kind));
@@ -100,7 +107,10 @@ pub fn single_event_inspector(
                 ref mut easing,
             } => {
                 let range = match event.kind {
-                    LineEventKind::Opacity => 0.0..=255.0,
+                    LineEventKind::Opacity
+                    | LineEventKind::ColorR
+                    | LineEventKind::ColorG
+                    | LineEventKind::ColorB => 0.0..=255.0,
                     _ => f32::MIN..=f32::MAX,
                 };
                 ui.sides(
@@ -134,7 +144,10 @@ pub fn single_event_inspector(
             }
             LineEventValue::Constant { ref mut value } => {
                 let range = match event.kind {
-                    LineEventKind::Opacity => 0.0..=255.0,
+                    LineEventKind::Opacity
+                    | LineEventKind::ColorR
+                    | LineEventKind::ColorG
+                    | LineEventKind::ColorB => 0.0..=255.0,
                     _ => f32::MIN..=f32::MAX,
                 };
                 ui.sides(
@@ -2,6 +2,7 @@ use crate::editing::command::note::EditNote;
 use crate::editing::command::EditorCommand;
 use crate::editing::DoCommand;
 use crate::selection::Selected;
+use crate::tab::inspector::note_visual::note_visual_ui;
 use crate::timeline::TimelineContext;
 use crate::ui::latch;
 use crate::ui::sides::SidesExt;
@@ -78,6 +79,18 @@ pub fn single_note_inspector(
             },
         );
 
+        ui.sides(
+            |ui| ui.label(t!("tab.inspector.single_note.fake")),
+            |ui| {
+                let response = ui.checkbox(&mut note.is_fake, "");
+                finished |= response.changed();
+            },
+        );
+
+        ui.separator();
+
+        finished |= note_visual_ui(ui, &mut note).finished;
+
         finished
     });
 
@@ -0,0 +1,134 @@
+use crate::action::RunAction;
+use crate::editing::transform::TransformSettings;
+use crate::ui::sides::SidesExt;
+use crate::ui::widgets::beat_value::BeatValue;
+use bevy::prelude::*;
+use egui::{Align, DragValue, Layout, Ui};
+use phichain_chart::beat::Beat;
+
+/// Parameters and buttons of the transforms applied to the selection
+///
+/// Every button runs the corresponding action, so a transform can be repeated with its hotkey
+pub fn transform_ui(
+    ui: &mut Ui,
+    settings: &mut TransformSettings,
+    commands: &mut Commands,
+    density: u32,
+    notes: bool,
+) {
+    let mut run = |ui: &mut Ui, label: String, action: &str| {
+        if ui.button(label).clicked() {
+            commands.trigger(RunAction(action.into()));
+        }
+    };
+
+    ui.with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
+        run(
+            ui,
+            t!("tab.inspector.transform.mirror").into(),
+            "phichain.mirror",
+        );
+        if notes {
+            run(
+                ui,
+                t!("tab.inspector.transform.flip_side").into(),
+                "phichain.flip_side",
+            );
+        }
+    });
+
+    ui.separator();
+
+    ui.sides(
+        |ui| ui.label(t!("tab.inspector.transform.scale_factor")),
+        |ui| ui.add(DragValue::new(&mut settings.scale_factor).speed(0.01)),
+    );
+    ui.sides(
+        |ui| ui.label(t!("tab.inspector.transform.scale_pivot")),
+        |ui| ui.add(DragValue::new(&mut settings.scale_pivot).speed(1)),
+    );
+    ui.with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
+        run(
+            ui,
+            t!("tab.inspector.transform.scale_x").into(),
+            "phichain.scale_x",
+        );
+    });
+
+    ui.separator();
+
+    ui.sides(
+        |ui| ui.label(t!("tab.inspector.transform.stretch_ratio")),
+        |ui| {
+            // the right side is laid out right-to-left
+            ui.add(DragValue::new(&mut settings.stretch_denom).range(1..=i32::MAX));
+            ui.label("/");
+            ui.add(DragValue::new(&mut settings.stretch_numer).range(1..=i32::MAX));
+        },
+    );
+    ui.sides(
+        |ui| ui.label(t!("tab.inspector.transform.stretch_anchor")),
+        |ui| {
+            let mut from_selection = settings.stretch_anchor.is_none();
+            if ui
+                .checkbox(
+                    &mut from_selection,
+                    t!("tab.inspector.transform.selection_start"),
+                )
+                .changed()
+            {
+                settings.stretch_anchor = (!from_selection).then_some(Beat::ZERO);
+            }
+        },
+    );
+    if let Some(anchor) = settings.stretch_anchor.as_mut() {
+        ui.sides(
+            |_| {},
+            |ui| {
+                ui.add(
+                    BeatValue::new(anchor)
+                        .range(Beat::ZERO..=Beat::MAX)
+                        .reversed(true)
+                        .density(density),
+                )
+            },
+        );
+    }
+    ui.with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
+        run(
+            ui,
+            t!("tab.inspector.transform.stretch").into(),
+            "phichain.stretch",
+        );
+    });
+
+    ui.separator();
+
+    ui.sides(
+        |ui| ui.label(t!("tab.inspector.transform.shift")),
+        |ui| {
+            ui.add(
+                BeatValue::new(&mut settings.shift)
+                    .range(Beat::ZERO..=Beat::MAX)
+                    .reversed(true)
+                    .density(density),
+            )
+        },
+    );
+    ui.columns(2, |columns| {
+        columns[0].with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
+            run(
+                ui,
+                t!("tab.inspector.transform.shift_earlier").into(),
+                "phichain.shift_earlier",
+            );
+        });
+        columns[1].with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
+            run(
+                ui,
+                t!("tab.inspector.transform.shift_later").into(),
+                "phichain.shift_later",
+            );
+        });
+    });
+}
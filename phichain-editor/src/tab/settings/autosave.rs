@@ -75,6 +75,37 @@ impl SettingCategory for AutoSave {
                 );
             });
 
+            ui.separator();
+
+            finished |= ui.item(
+                t!("tab.settings.category.autosave.persist_history.label"),
+                Some(t!(
+                    "tab.settings.category.autosave.persist_history.description"
+                )),
+                |ui| {
+                    ui.checkbox(&mut settings.autosave.persist_history, "")
+                        .changed()
+                },
+            );
+
+            ui.separator();
+
+            finished |= ui.item(
+                t!("tab.settings.category.autosave.history_limit.label"),
+                Some(t!(
+                    "tab.settings.category.autosave.history_limit.description"
+                )),
+                |ui| {
+                    let response = ui.add(
+                        egui::DragValue::new(&mut settings.autosave.history_limit)
+                            .range(10..=10000)
+                            .speed(10.0)
+                            .suffix(t!("tab.settings.category.autosave.history_limit.suffix")),
+                    );
+                    response.drag_stopped() || response.lost_focus()
+                },
+            );
+
             finished
         })
         .is_some()
@@ -28,13 +28,13 @@ impl SettingCategory for Hotkey {
         let mut spawn = None::<Identifier>;
 
         for (id, default) in ctx.registry.0.clone().iter() {
-            let key = if actions.0.contains_key(id) {
-                format!("action.{id}")
+            let title = if actions.0.contains_key(id) {
+                actions.title(id)
             } else {
-                format!("hotkey.{id}")
+                t!(format!("hotkey.{id}").as_str()).to_string()
             };
 
-            ui.item(t!(key.as_str()), None::<&str>, |ui| {
+            ui.item(title, None::<&str>, |ui| {
                 // the order is revered since we use [`egui::Sides`] for `ui.item`. the right part starts from the end
                 ui.horizontal(|ui| {
                     // Record and reset buttons
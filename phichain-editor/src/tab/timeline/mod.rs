@@ -7,7 +7,7 @@ use crate::timeline::settings::TimelineSettings;
 use crate::timeline::Timeline;
 use crate::timing::{Pause, Paused, Seek};
 use crate::utils::convert::BevyEguiConvert;
-use crate::{spectrogram, timeline};
+use crate::{analysis, spectrogram, timeline, waveform};
 use phichain_chart::note::Note;
 
 pub fn timeline_tab(In(mut ui): In<Ui>, world: &mut World) {
@@ -24,8 +24,10 @@ pub fn timeline_tab(In(mut ui): In<Ui>, world: &mut World) {
         },
     );
 
-    // draw spectrogram background after viewport is updated
+    // draw spectrogram and waveform background after viewport is updated
     spectrogram::draw(ui.painter(), world);
+    waveform::draw(ui.painter(), world);
+    analysis::draw(ui.painter(), world);
 
     let is_hovering = ui.rect_contains_pointer(clip_rect);
     if is_hovering {
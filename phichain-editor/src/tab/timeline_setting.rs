@@ -3,6 +3,7 @@ use crate::timeline::note::NoteTimeline;
 use crate::timeline::settings::TimelineSettings;
 use crate::timeline::Timeline;
 use crate::timeline::TimelineItem;
+use crate::waveform::WaveformChannels;
 use bevy::ecs::system::SystemState;
 use bevy::prelude::*;
 use egui::{RichText, Ui};
@@ -36,6 +37,14 @@ pub fn timeline_setting_tab(In(mut ui): In<Ui>, world: &mut World) {
             );
             ui.end_row();
 
+            ui.label(t!("tab.timeline_setting.quantize_tolerance"));
+            ui.add(
+                egui::DragValue::new(&mut timeline_settings.quantize_tolerance)
+                    .range(0.0..=0.5)
+                    .speed(0.001),
+            );
+            ui.end_row();
+
             ui.label(t!("tab.timeline_setting.lane"));
             ui.add(
                 egui::DragValue::new(&mut timeline_settings.lanes)
@@ -75,6 +84,41 @@ pub fn timeline_setting_tab(In(mut ui): In<Ui>, world: &mut World) {
                     .speed(0.01),
             );
             ui.end_row();
+
+            ui.label(t!("tab.timeline_setting.show_waveform"));
+            ui.checkbox(&mut timeline_settings.show_waveform, "");
+            ui.end_row();
+
+            ui.label(t!("tab.timeline_setting.waveform_channels.title"));
+            ui.horizontal(|ui| {
+                ui.selectable_value(
+                    &mut timeline_settings.waveform_channels,
+                    WaveformChannels::Mixed,
+                    t!("tab.timeline_setting.waveform_channels.mixed"),
+                );
+                ui.selectable_value(
+                    &mut timeline_settings.waveform_channels,
+                    WaveformChannels::Split,
+                    t!("tab.timeline_setting.waveform_channels.split"),
+                );
+            });
+            ui.end_row();
+
+            ui.label(t!("tab.timeline_setting.show_transients"));
+            ui.checkbox(&mut timeline_settings.show_transients, "");
+            ui.end_row();
+
+            ui.label(t!("tab.timeline_setting.waveform_opacity"));
+            ui.add(
+                egui::DragValue::new(&mut timeline_settings.waveform_opacity)
+                    .range(0.0..=1.0)
+                    .speed(0.01),
+            );
+            ui.end_row();
+
+            ui.label(t!("tab.timeline_setting.show_density_graph"));
+            ui.checkbox(&mut timeline_settings.show_density_graph, "");
+            ui.end_row();
         });
 
     {
@@ -0,0 +1,607 @@
+//! Tempo estimation from decoded audio
+//!
+//! The audio is reduced to an onset strength envelope, the spectral flux of its STFT. The tempo is
+//! first guessed from the autocorrelation of the envelope, then refined together with the phase
+//! of the beats by matching a comb of beats against the envelope over the whole song
+
+use crate::spectrogram::AudioMono;
+use phichain_chart::beat::Beat;
+use phichain_chart::bpm_list::BpmPoint;
+use realfft::RealFftPlanner;
+
+const N_FFT: usize = 1024;
+const HOP: usize = 256;
+
+const MIN_BPM: f32 = 60.0;
+const MAX_BPM: f32 = 240.0;
+/// Tempo the autocorrelation is weighted towards, to settle between a tempo and its multiples
+const PRIOR_BPM: f32 = 120.0;
+
+/// Length of the windows the tempo is estimated in when detecting tempo changes, in seconds
+const WINDOW: f32 = 10.0;
+/// Tempos of consecutive windows closer than this ratio belong to the same segment
+const CHANGE_THRESHOLD: f32 = 0.03;
+
+/// Onsets are at least this many seconds apart
+const ONSET_SPACING: f32 = 0.05;
+/// Onsets stand out from the mean strength by this many standard deviations
+const ONSET_THRESHOLD: f32 = 1.5;
+
+/// Onset strength of each frame of the audio
+#[derive(Debug, Clone)]
+pub struct OnsetEnvelope {
+    /// Frames per second
+    pub frame_rate: f32,
+    /// Time of the first frame, in seconds
+    pub start: f32,
+    pub values: Vec<f32>,
+}
+
+impl OnsetEnvelope {
+    pub fn new(audio: &AudioMono) -> Self {
+        let frame_rate = audio.sample_rate as f32 / HOP as f32;
+        // the flux of a frame peaks as an onset passes the steepest rise of the window, three
+        // quarters into it
+        let start = N_FFT as f32 * 0.75 / audio.sample_rate as f32;
+
+        if audio.data.len() < N_FFT {
+            return Self {
+                frame_rate,
+                start,
+                values: vec![],
+            };
+        }
+
+        let window = (0..N_FFT)
+            .map(|n| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * n as f32 / N_FFT as f32).cos())
+            .collect::<Vec<_>>();
+
+        let mut planner = RealFftPlanner::<f32>::new();
+        let fft = planner.plan_fft_forward(N_FFT);
+        let mut input = fft.make_input_vec();
+        let mut output = fft.make_output_vec();
+
+        let n_frames = 1 + (audio.data.len() - N_FFT) / HOP;
+        let mut previous = vec![0.0; output.len()];
+        let mut values = Vec::with_capacity(n_frames);
+
+        for frame in 0..n_frames {
+            let samples = &audio.data[frame * HOP..frame * HOP + N_FFT];
+            for ((input, sample), window) in input.iter_mut().zip(samples).zip(&window) {
+                *input = sample * window;
+            }
+            fft.process(&mut input, &mut output).unwrap();
+
+            let mut flux = 0.0;
+            for (bin, previous) in output.iter().zip(previous.iter_mut()) {
+                // log compression keeps loud bins from drowning the others
+                let magnitude = (1.0 + 100.0 * bin.norm()).ln();
+                flux += (magnitude - *previous).max(0.0);
+                *previous = magnitude;
+            }
+            values.push(flux);
+        }
+        // the first frame has nothing to compare with
+        if let Some(first) = values.first_mut() {
+            *first = 0.0;
+        }
+
+        Self {
+            frame_rate,
+            start,
+            values: normalize(&values, frame_rate),
+        }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.values.len() as f32 / self.frame_rate
+    }
+
+    /// Times of the onsets in seconds, the frames standing out the most within [`ONSET_SPACING`]
+    pub fn onsets(&self) -> Vec<f32> {
+        let count = self.values.len().max(1) as f32;
+        let mean = self.values.iter().sum::<f32>() / count;
+        let deviation = (self
+            .values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f32>()
+            / count)
+            .sqrt();
+        let threshold = mean + ONSET_THRESHOLD * deviation;
+        let radius = ((ONSET_SPACING * self.frame_rate) as usize).max(1);
+
+        self.values
+            .iter()
+            .enumerate()
+            .filter(|(i, value)| {
+                let from = i.saturating_sub(radius);
+                let to = (i + radius + 1).min(self.values.len());
+                // the first of equal frames wins, so a flat peak gives a single onset
+                **value > threshold
+                    && self.values[from..*i].iter().all(|other| other < value)
+                    && self.values[i + 1..to].iter().all(|other| other <= value)
+            })
+            .map(|(i, _)| self.start + i as f32 / self.frame_rate)
+            .collect()
+    }
+
+    /// The envelope between two times, in seconds
+    fn slice(&self, from: f32, to: f32) -> Self {
+        let index = |time: f32| {
+            (((time - self.start) * self.frame_rate).max(0.0) as usize).min(self.values.len())
+        };
+        let (from, to) = (index(from), index(to));
+
+        Self {
+            frame_rate: self.frame_rate,
+            start: self.start + from as f32 / self.frame_rate,
+            values: self.values[from..to].to_vec(),
+        }
+    }
+
+    /// Average strength at the beats a window long from a time, going backwards with a negative
+    /// period. The beat at the time itself is only counted going forwards
+    fn comb_strength(&self, time: f32, period: f32) -> f32 {
+        let beats = (WINDOW / period.abs()) as usize;
+        let skip = usize::from(period < 0.0);
+        (skip..beats + skip)
+            .map(|beat| {
+                let time = time + beat as f32 * period;
+                self.at((time - self.start).max(0.0) * self.frame_rate)
+            })
+            .sum::<f32>()
+            / beats.max(1) as f32
+    }
+
+    /// Linearly interpolated strength at a fractional frame
+    fn at(&self, frame: f32) -> f32 {
+        let index = frame as usize;
+        match (self.values.get(index), self.values.get(index + 1)) {
+            (Some(a), Some(b)) => a + (b - a) * frame.fract(),
+            (Some(a), None) => *a,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Subtract the local average and keep what sticks out, so sustained loud parts don't count as onsets
+fn normalize(values: &[f32], frame_rate: f32) -> Vec<f32> {
+    let radius = (frame_rate * 0.25) as usize;
+
+    let mut prefix = vec![0.0; values.len() + 1];
+    for (i, value) in values.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + value;
+    }
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let from = i.saturating_sub(radius);
+            let to = (i + radius + 1).min(values.len());
+            let mean = (prefix[to] - prefix[from]) / (to - from) as f32;
+            (value - mean).max(0.0)
+        })
+        .collect()
+}
+
+/// A tempo and the time of a beat
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tempo {
+    pub bpm: f32,
+    /// Time of a beat in seconds, the first one after the start of the envelope it was estimated on
+    pub phase: f32,
+}
+
+impl Tempo {
+    pub fn period(&self) -> f32 {
+        60.0 / self.bpm
+    }
+}
+
+/// Guess the tempo from the autocorrelation of the envelope
+///
+/// Only tempos within `range` are considered, weighted towards [`PRIOR_BPM`]
+fn autocorrelation_bpm(envelope: &OnsetEnvelope, range: (f32, f32)) -> Option<f32> {
+    let values = &envelope.values;
+    let min_lag = (envelope.frame_rate * 60.0 / range.1).floor().max(1.0) as usize;
+    let max_lag = (envelope.frame_rate * 60.0 / range.0).ceil() as usize;
+    if values.len() <= max_lag + 1 {
+        return None;
+    }
+
+    let correlation = |lag: usize| {
+        values
+            .iter()
+            .zip(&values[lag..])
+            .map(|(a, b)| a * b)
+            .sum::<f32>()
+            / (values.len() - lag) as f32
+    };
+    let correlations = (min_lag..=max_lag + 1).map(correlation).collect::<Vec<_>>();
+
+    let weight = |lag: usize| {
+        let bpm = envelope.frame_rate * 60.0 / lag as f32;
+        (-0.5 * (bpm / PRIOR_BPM).log2().powi(2)).exp()
+    };
+
+    let (best, _) = (min_lag..=max_lag)
+        .map(|lag| (lag, correlations[lag - min_lag] * weight(lag)))
+        .filter(|(_, score)| *score > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    // parabolic interpolation around the peak for a fractional lag
+    let lag = if best > min_lag {
+        let (a, b, c) = (
+            correlations[best - min_lag - 1],
+            correlations[best - min_lag],
+            correlations[best - min_lag + 1],
+        );
+        let denominator = a - 2.0 * b + c;
+        if denominator.abs() > f32::EPSILON {
+            best as f32 + (0.5 * (a - c) / denominator).clamp(-0.5, 0.5)
+        } else {
+            best as f32
+        }
+    } else {
+        best as f32
+    };
+
+    Some(envelope.frame_rate * 60.0 / lag)
+}
+
+/// Find the phase of a comb of beats matching the envelope the best, returns the phase in frames
+/// and the average strength at the beats
+fn comb(envelope: &OnsetEnvelope, bpm: f32) -> (f32, f32) {
+    let period = envelope.frame_rate * 60.0 / bpm;
+    let beats = (envelope.values.len() as f32 / period) as usize;
+    if beats == 0 {
+        return (0.0, 0.0);
+    }
+
+    let steps = (period * 4.0) as usize;
+    (0..steps)
+        .map(|step| {
+            let phase = step as f32 / 4.0;
+            let score = (0..beats)
+                .map(|beat| envelope.at(phase + beat as f32 * period))
+                .sum::<f32>()
+                / beats as f32;
+            (phase, score)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or_default()
+}
+
+/// Refine a tempo guess and find its phase, searching within 2% of the guess
+fn refine(envelope: &OnsetEnvelope, guess: f32) -> Tempo {
+    let mut best = (guess, 0.0, f32::MIN);
+
+    // coarse to fine, the comb gets more selective the more beats it covers
+    let mut center = guess;
+    for step in [0.1, 0.01] {
+        let span = if step > 0.05 { guess * 0.02 } else { 0.1 };
+        let candidates = (span / step) as i32;
+        for i in -candidates..=candidates {
+            let bpm = center + i as f32 * step;
+            let (phase, score) = comb(envelope, bpm);
+            if score > best.2 {
+                best = (bpm, phase, score);
+            }
+        }
+        center = best.0;
+    }
+
+    Tempo {
+        bpm: best.0,
+        phase: envelope.start + best.1 / envelope.frame_rate,
+    }
+}
+
+/// Estimate a single tempo for the whole envelope
+pub fn estimate_tempo(envelope: &OnsetEnvelope) -> Option<Tempo> {
+    let guess = autocorrelation_bpm(envelope, (MIN_BPM, MAX_BPM))?;
+    Some(refine(envelope, guess))
+}
+
+/// A suggested BPM list and offset
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoEstimate {
+    pub points: Vec<BpmPoint>,
+    /// Offset of the first beat in milliseconds, see [`Offset`](phichain_chart::offset::Offset)
+    pub offset: f32,
+}
+
+/// Estimate the BPM list and the offset of some audio
+///
+/// With `detect_changes`, the tempo is estimated in windows and windows with different tempos
+/// start new BPM points, placed on whole beats
+pub fn estimate(audio: &AudioMono, detect_changes: bool) -> Option<TempoEstimate> {
+    let envelope = OnsetEnvelope::new(audio);
+    let global = estimate_tempo(&envelope)?;
+
+    let segments = if detect_changes {
+        segments(&envelope, global.bpm)
+    } else {
+        vec![]
+    };
+
+    if segments.len() < 2 {
+        return Some(TempoEstimate {
+            points: vec![BpmPoint::new(Beat::ZERO, round(global.bpm))],
+            offset: global.phase * 1000.0,
+        });
+    }
+
+    let mut points: Vec<BpmPoint> = vec![];
+    let mut offset = 0.0;
+    // the tempo of the previous segment, with its phase at its BPM point, and the beat of the point
+    let mut previous: Option<(Tempo, f32)> = None;
+    for (index, segment) in segments.iter().enumerate() {
+        // the tempo is estimated away from the boundaries, where the windows mix both tempos
+        let from = if index > 0 {
+            segment.from + WINDOW / 2.0
+        } else {
+            segment.from
+        };
+        let to = if index + 1 < segments.len() {
+            segment.to - WINDOW / 2.0
+        } else {
+            segment.to
+        };
+        let tempo = refine(&envelope.slice(from, to), segment.bpm);
+
+        match previous {
+            None => {
+                offset = tempo.phase * 1000.0;
+                points.push(BpmPoint::new(Beat::ZERO, round(tempo.bpm)));
+                previous = Some((tempo, 0.0));
+            }
+            Some((last, last_beat)) => {
+                // the change is put on the beat of the previous tempo around the boundary where
+                // the previous tempo before it and the new one after it match the onsets the best
+                let around = ((segment.from - last.phase) / last.period()).round();
+                let span = (WINDOW / last.period()).ceil() as i32;
+                let Some(beats) = (-span..=span)
+                    .map(|i| around + i as f32)
+                    .filter(|beats| *beats >= 1.0)
+                    .max_by(|a, b| {
+                        let score = |beats: f32| {
+                            let time = last.phase + beats * last.period();
+                            envelope.comb_strength(time, -last.period())
+                                + envelope.comb_strength(time, tempo.period())
+                        };
+                        score(*a).total_cmp(&score(*b))
+                    })
+                else {
+                    continue;
+                };
+
+                let beat = last_beat + beats;
+                points.push(BpmPoint::new(Beat::from(beat), round(tempo.bpm)));
+                previous = Some((
+                    Tempo {
+                        bpm: tempo.bpm,
+                        phase: last.phase + beats * last.period(),
+                    },
+                    beat,
+                ));
+            }
+        }
+    }
+
+    Some(TempoEstimate { points, offset })
+}
+
+/// A time range of steady tempo
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Segment {
+    from: f32,
+    to: f32,
+    /// Median tempo of the windows in the segment
+    bpm: f32,
+}
+
+/// Split the envelope into time ranges of steady tempo
+///
+/// Window tempos are searched near the global tempo, so a window doesn't jump to a multiple of it
+fn segments(envelope: &OnsetEnvelope, global: f32) -> Vec<Segment> {
+    let end = envelope.start + envelope.duration();
+
+    let mut windows = vec![];
+    let mut time = envelope.start;
+    while time + WINDOW <= end {
+        if let Some(bpm) = autocorrelation_bpm(
+            &envelope.slice(time, time + WINDOW),
+            (global * 0.7, global * 1.4),
+        ) {
+            windows.push((time, bpm));
+        }
+        time += WINDOW / 2.0;
+    }
+
+    let group = |windows: Vec<(f32, f32)>| {
+        let mut groups: Vec<Vec<(f32, f32)>> = vec![];
+        for window in windows {
+            match groups.last_mut() {
+                Some(group) if (window.1 / median(group) - 1.0).abs() < CHANGE_THRESHOLD => {
+                    group.push(window)
+                }
+                _ => groups.push(vec![window]),
+            }
+        }
+        groups
+    };
+
+    // a change lasting a single window is more likely noise than a tempo change, once it is
+    // dropped its neighbours may join
+    let windows = group(windows)
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .flatten()
+        .collect();
+    let groups = group(windows);
+
+    let mut segments = groups
+        .iter()
+        .map(|group| Segment {
+            from: group[0].0,
+            to: group[group.len() - 1].0 + WINDOW,
+            bpm: median(group),
+        })
+        .collect::<Vec<_>>();
+
+    // consecutive segments overlap, split them in the middle
+    for i in 1..segments.len() {
+        let middle = (segments[i - 1].to + segments[i].from) / 2.0;
+        segments[i - 1].to = middle;
+        segments[i].from = middle;
+    }
+    if let Some(first) = segments.first_mut() {
+        first.from = envelope.start;
+    }
+    if let Some(last) = segments.last_mut() {
+        last.to = end;
+    }
+
+    segments
+}
+
+fn median(windows: &[(f32, f32)]) -> f32 {
+    let mut bpms = windows.iter().map(|(_, bpm)| *bpm).collect::<Vec<_>>();
+    bpms.sort_by(f32::total_cmp);
+    bpms[bpms.len() / 2]
+}
+
+/// Round a detected BPM to two decimal places
+fn round(bpm: f32) -> f32 {
+    (bpm * 100.0).round() / 100.0
+}
+
+/// Fit a tempo to tap times in seconds with a linear regression of the taps against their index
+///
+/// The phase is the time of the first beat at or after zero. Returns [`None`] with less than two taps
+pub fn tap_tempo(taps: &[f32]) -> Option<Tempo> {
+    if taps.len() < 2 {
+        return None;
+    }
+
+    let n = taps.len() as f32;
+    let mean_index = (n - 1.0) / 2.0;
+    let mean_time = taps.iter().sum::<f32>() / n;
+
+    let (covariance, variance) = taps.iter().enumerate().fold((0.0, 0.0), |acc, (i, time)| {
+        let di = i as f32 - mean_index;
+        (acc.0 + di * (time - mean_time), acc.1 + di * di)
+    });
+    let period = covariance / variance;
+    if period <= 0.0 {
+        return None;
+    }
+
+    let intercept = mean_time - period * mean_index;
+    Some(Tempo {
+        bpm: 60.0 / period,
+        phase: intercept.rem_euclid(period),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 22050;
+
+    /// Short bursts of noise at each beat, louder on the first beat of a bar
+    fn clicks(bpm: f32, first: f32, duration: f32) -> AudioMono {
+        let mut data = vec![0.0; (duration * SAMPLE_RATE as f32) as usize];
+        let mut seed = 1u32;
+        let mut beat = 0;
+        loop {
+            let time = first + beat as f32 * 60.0 / bpm;
+            let start = (time * SAMPLE_RATE as f32) as usize;
+            if start >= data.len() {
+                break;
+            }
+            let gain = if beat % 4 == 0 { 1.0 } else { 0.6 };
+            for (i, sample) in data[start..].iter_mut().take(400).enumerate() {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                let noise = (seed >> 16) as f32 / 32768.0 - 1.0;
+                *sample = noise * gain * (1.0 - i as f32 / 400.0);
+            }
+            beat += 1;
+        }
+
+        AudioMono {
+            sample_rate: SAMPLE_RATE,
+            data,
+        }
+    }
+
+    #[test]
+    fn test_estimate() {
+        let estimate = estimate(&clicks(128.0, 0.35, 30.0), false).unwrap();
+
+        assert_eq!(estimate.points.len(), 1);
+        assert!((estimate.points[0].bpm - 128.0).abs() < 0.1);
+        assert!((estimate.offset - 350.0).abs() < 20.0);
+    }
+
+    #[test]
+    fn test_estimate_slow() {
+        let estimate = estimate(&clicks(87.5, 1.2, 30.0), false).unwrap();
+        let period = 60.0 / 87.5 * 1000.0;
+
+        assert!((estimate.points[0].bpm - 87.5).abs() < 0.1);
+        // the phase is the first beat of the envelope, any beat is a valid offset
+        let error = (estimate.offset - 1200.0).rem_euclid(period);
+        assert!(error.min(period - error) < 20.0);
+    }
+
+    #[test]
+    fn test_estimate_changes() {
+        let mut audio = clicks(120.0, 0.5, 35.0);
+        // the 60th beat at 120 BPM is at 30.5s, continue at 150 BPM from there
+        let faster = clicks(150.0, 0.0, 30.0);
+        audio.data.truncate((30.5 * SAMPLE_RATE as f32) as usize);
+        audio.data.extend(faster.data);
+
+        let estimate = estimate(&audio, true).unwrap();
+
+        assert_eq!(estimate.points.len(), 2);
+        assert!((estimate.points[0].bpm - 120.0).abs() < 0.1);
+        assert!((estimate.points[1].bpm - 150.0).abs() < 0.1);
+        assert_eq!(estimate.points[1].beat, Beat::from(60.0));
+    }
+
+    #[test]
+    fn test_onsets() {
+        let onsets = OnsetEnvelope::new(&clicks(150.0, 0.5, 10.0)).onsets();
+
+        assert_eq!(onsets.len(), 24);
+        for (beat, onset) in onsets.iter().enumerate() {
+            let expected = 0.5 + beat as f32 * 0.4;
+            assert!((onset - expected).abs() < 0.015, "{onset} != {expected}");
+        }
+    }
+
+    #[test]
+    fn test_silence() {
+        let silence = AudioMono {
+            sample_rate: SAMPLE_RATE,
+            data: vec![0.0; SAMPLE_RATE as usize * 10],
+        };
+        assert_eq!(estimate(&silence, false), None);
+    }
+
+    #[test]
+    fn test_tap_tempo() {
+        assert_eq!(tap_tempo(&[1.0]), None);
+
+        let taps = [2.26, 2.76, 3.25, 3.76, 4.25];
+        let tempo = tap_tempo(&taps).unwrap();
+        assert!((tempo.bpm - 120.0).abs() < 0.5);
+        assert!((tempo.phase - 0.25).abs() < 0.02);
+    }
+}
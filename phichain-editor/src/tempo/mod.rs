@@ -0,0 +1,198 @@
+//! Suggest the BPM list and the offset of a song, detected from the music or tapped along with it
+
+pub mod detect;
+
+use crate::action::ActionRegistrationExt;
+use crate::editing::command::bpm_list::{CreateBpmPoint, EditBpmPoint, RemoveBpmPoint};
+use crate::editing::command::meta::EditOffset;
+use crate::editing::command::{CommandSequence, EditorCommand};
+use crate::editing::DoCommand;
+use crate::hotkey::Hotkey;
+use crate::notification::{ToastsExt, ToastsStorage};
+use crate::project::project_loaded;
+use crate::spectrogram;
+use crate::timing::{ChartTime, Paused};
+use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, AsyncComputeTaskPool, Task};
+use bevy_kira_audio::AudioSource;
+use detect::{Tempo, TempoEstimate};
+use phichain_chart::beat::Beat;
+use phichain_chart::bpm_list::{BpmList, BpmPoint};
+use phichain_chart::offset::Offset;
+use phichain_game::audio::AudioAssetId;
+use std::time::Instant;
+
+/// Taps further apart than this many seconds start a new tap tempo measure
+const TAP_TIMEOUT: f32 = 2.0;
+/// Only the latest taps are used, so the tempo follows the music as the taps go on
+const MAX_TAPS: usize = 32;
+
+pub struct TempoPlugin;
+
+impl Plugin for TempoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TempoDetection>()
+            .init_resource::<TapTempo>()
+            .add_systems(Update, poll_detection_system.run_if(project_loaded()))
+            .add_action("phichain.detect_tempo", detect_tempo_system, None)
+            .add_action(
+                "phichain.tap_tempo",
+                tap_tempo_system,
+                Some(Hotkey::new(KeyCode::KeyT, vec![])),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct TempoDetection {
+    /// Detect tempo changes as well, instead of a single tempo for the whole song
+    pub detect_changes: bool,
+    task: Option<Task<Option<TempoEstimate>>>,
+    /// The result of the last detection, [`None`] inside if no tempo was found
+    pub result: Option<Option<TempoEstimate>>,
+}
+
+impl TempoDetection {
+    pub fn is_running(&self) -> bool {
+        self.task.is_some()
+    }
+}
+
+/// Detect the tempo of the music in the background
+fn detect_tempo_system(
+    mut detection: ResMut<TempoDetection>,
+    audio_asset_id: Res<AudioAssetId>,
+    audio_assets: Res<Assets<AudioSource>>,
+) -> Result {
+    if detection.is_running() {
+        return Ok(());
+    }
+
+    let source = audio_assets
+        .get(audio_asset_id.0)
+        .ok_or("Audio is not loaded")?;
+    let sound = source.sound.clone();
+    let detect_changes = detection.detect_changes;
+
+    detection.task =
+        Some(AsyncComputeTaskPool::get().spawn(async move {
+            detect::estimate(&spectrogram::load_audio(&sound), detect_changes)
+        }));
+    detection.result = None;
+
+    Ok(())
+}
+
+fn poll_detection_system(mut detection: ResMut<TempoDetection>, mut toasts: ResMut<ToastsStorage>) {
+    let Some(task) = detection.task.as_mut() else {
+        return;
+    };
+
+    if let Some(result) = block_on(future::poll_once(task)) {
+        if result.is_none() {
+            toasts.error(t!("tempo.detect.not_found"));
+        }
+        detection.task = None;
+        detection.result = Some(result);
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct TapTempo {
+    /// Times of the taps in seconds, on the music if they are aligned
+    taps: Vec<f32>,
+    /// Whether every tap was made during playback, so the taps line up with the music
+    aligned: bool,
+    last: Option<Instant>,
+}
+
+impl TapTempo {
+    pub fn taps(&self) -> usize {
+        self.taps.len()
+    }
+
+    /// The tempo of the taps, the phase is only meaningful if they are aligned with the music
+    pub fn tempo(&self) -> Option<Tempo> {
+        detect::tap_tempo(&self.taps)
+    }
+
+    pub fn aligned(&self) -> bool {
+        self.aligned
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+fn tap_tempo_system(
+    mut tap: ResMut<TapTempo>,
+    paused: Res<Paused>,
+    time: Res<ChartTime>,
+    offset: Res<Offset>,
+) -> Result {
+    let now = Instant::now();
+    if tap
+        .last
+        .is_some_and(|last| now.duration_since(last).as_secs_f32() > TAP_TIMEOUT)
+    {
+        tap.reset();
+    }
+
+    let first = tap.taps.is_empty();
+    // during playback the taps are placed on the music, which also follows the playback rate
+    let time = if paused.0 {
+        tap.last
+            .zip(tap.taps.last())
+            .map(|(last, time)| time + now.duration_since(last).as_secs_f32())
+            .unwrap_or_default()
+    } else {
+        time.0 + offset.0 / 1000.0
+    };
+
+    tap.aligned = (first || tap.aligned) && !paused.0;
+    tap.taps.push(time);
+    tap.last = Some(now);
+
+    if tap.taps.len() > MAX_TAPS {
+        tap.taps.remove(0);
+    }
+
+    Ok(())
+}
+
+/// Replace the BPM list, and the offset if provided, in a single undoable step
+pub fn apply(world: &mut World, points: &[BpmPoint], offset: Option<f32>) {
+    let Some(first) = points.first() else {
+        return;
+    };
+
+    let current = world.resource::<BpmList>().0.clone();
+    let mut commands = vec![EditorCommand::EditBpmPoint(EditBpmPoint::new(
+        0,
+        current[0],
+        BpmPoint::new(Beat::ZERO, first.bpm),
+    ))];
+    commands.extend(
+        (1..current.len())
+            .rev()
+            .map(|index| EditorCommand::RemoveBpmPoint(RemoveBpmPoint::new(index))),
+    );
+    commands.extend(
+        points[1..]
+            .iter()
+            .map(|point| EditorCommand::CreateBpmPoint(CreateBpmPoint::new(*point))),
+    );
+    if let Some(offset) = offset {
+        let from = world.resource::<Offset>().0;
+        commands.push(EditorCommand::EditOffset(EditOffset::new(from, offset)));
+    }
+
+    world.write_message(DoCommand(EditorCommand::CommandSequence(CommandSequence(
+        commands,
+    ))));
+    world
+        .resource_mut::<ToastsStorage>()
+        .success(t!("tempo.applied"));
+}
@@ -37,77 +37,66 @@ impl EventTimeline {
     }
 }
 
+/// Number of event tracks, one for each [`LineEventKind`]
+pub const EVENT_TRACK_COUNT: usize = LineEventKind::ALL.len();
+
 #[derive(Debug, Clone)]
-struct EventTrackData<T> {
-    x: T,
-    y: T,
-    rotation: T,
-    opacity: T,
-    speed: T,
-}
+struct EventTrackData<T>([T; EVENT_TRACK_COUNT]);
 
 impl<T> EventTrackData<T> {
+    fn index(kind: LineEventKind) -> usize {
+        u8::from(kind) as usize - 1
+    }
+
     fn get(&self, kind: LineEventKind) -> &T {
-        match kind {
-            LineEventKind::X => &self.x,
-            LineEventKind::Y => &self.y,
-            LineEventKind::Rotation => &self.rotation,
-            LineEventKind::Opacity => &self.opacity,
-            LineEventKind::Speed => &self.speed,
-        }
+        &self.0[Self::index(kind)]
     }
 
     fn get_mut(&mut self, kind: LineEventKind) -> &mut T {
-        match kind {
-            LineEventKind::X => &mut self.x,
-            LineEventKind::Y => &mut self.y,
-            LineEventKind::Rotation => &mut self.rotation,
-            LineEventKind::Opacity => &mut self.opacity,
-            LineEventKind::Speed => &mut self.speed,
-        }
+        &mut self.0[Self::index(kind)]
     }
 
     fn iter(&self) -> impl Iterator<Item = (LineEventKind, &T)> {
-        [
-            (LineEventKind::X, &self.x),
-            (LineEventKind::Y, &self.y),
-            (LineEventKind::Rotation, &self.rotation),
-            (LineEventKind::Opacity, &self.opacity),
-            (LineEventKind::Speed, &self.speed),
-        ]
-        .into_iter()
+        LineEventKind::ALL.into_iter().zip(self.0.iter())
     }
 }
 
 impl<T: Clone> EventTrackData<T> {
     fn splat(value: T) -> Self {
-        Self {
-            x: value.clone(),
-            y: value.clone(),
-            rotation: value.clone(),
-            opacity: value.clone(),
-            speed: value.clone(),
-        }
+        Self(std::array::from_fn(|_| value.clone()))
     }
 }
 
 impl<T: PartialEq> EventTrackData<T> {
     fn contains(&self, value: &T) -> bool {
-        &self.x == value
-            || &self.y == value
-            || &self.rotation == value
-            || &self.opacity == value
-            || &self.speed == value
+        self.0.contains(value)
+    }
+}
+
+/// The indicator shown on top of the track of the given kind
+fn track_indicator(kind: LineEventKind) -> &'static str {
+    match kind {
+        LineEventKind::X => "X",
+        LineEventKind::Y => "Y",
+        LineEventKind::Rotation => egui_phosphor::regular::ARROWS_CLOCKWISE,
+        LineEventKind::Opacity => egui_phosphor::regular::CIRCLE_HALF,
+        LineEventKind::Speed => egui_phosphor::regular::GAUGE,
+        LineEventKind::ScaleX => egui_phosphor::regular::ARROWS_OUT_LINE_HORIZONTAL,
+        LineEventKind::ScaleY => egui_phosphor::regular::ARROWS_OUT_LINE_VERTICAL,
+        LineEventKind::ColorR => "R",
+        LineEventKind::ColorG => "G",
+        LineEventKind::ColorB => "B",
+        LineEventKind::Incline => egui_phosphor::regular::ANGLE,
+        LineEventKind::Paint => egui_phosphor::regular::PAINT_BRUSH,
     }
 }
 
 impl Timeline for EventTimeline {
     fn ui(&self, ui: &mut Ui, world: &mut World, viewport: Rect) {
         // lane
-        // [0.2, 0.4, 0.6, 0.8]
-        let lane_percents = iter::repeat_n(0.0, 5 - 1)
+        let lane_percents = iter::repeat_n(0.0, EVENT_TRACK_COUNT - 1)
             .enumerate()
-            .map(|(i, _)| (i + 1) as f32 * 1.0 / 5.0)
+            .map(|(i, _)| (i + 1) as f32 * 1.0 / EVENT_TRACK_COUNT as f32)
             .collect::<Vec<_>>();
         for percent in lane_percents {
             ui.painter().rect_filled(
@@ -145,7 +134,7 @@ impl Timeline for EventTimeline {
             mut seek_to,
         ) = state.get_mut(world);
 
-        let track_width = viewport.width() / 5.0;
+        let track_width = viewport.width() / EVENT_TRACK_COUNT as f32;
         let event_width = track_width / 2.0;
 
         let compute_x =
@@ -349,22 +338,11 @@ impl Timeline for EventTimeline {
 
         // event track type indicator
         ui.style_mut().interaction.selectable_labels = false;
-        for (i, txt) in [
-            "X",
-            "Y",
-            egui_phosphor::regular::ARROWS_CLOCKWISE,
-            egui_phosphor::regular::CIRCLE_HALF,
-            egui_phosphor::regular::GAUGE,
-        ]
-        .iter()
-        .enumerate()
-        {
+        for (i, txt) in LineEventKind::ALL.map(track_indicator).iter().enumerate() {
             ui.put(
                 Rect::from_center_size(
                     egui::Pos2::new(
-                        viewport.min.x
-                            + viewport.width() / 5.0 * i as f32
-                            + viewport.width() / 10.0,
+                        viewport.min.x + track_width * i as f32 + track_width / 2.0,
                         viewport.max.y * INDICATOR_POSITION + 20.0,
                     ),
                     egui::Vec2::splat(10.0),
@@ -391,7 +369,9 @@ impl Timeline for EventTimeline {
             .filter(|x| {
                 let event = x.0;
                 let track: u8 = event.kind.into();
-                let target_x = (track - 1) as f32 * (1.0 / 5.0) + (1.0 / (5.0 * 2.0));
+                let track_count = EVENT_TRACK_COUNT as f32;
+                let target_x =
+                    (track - 1) as f32 * (1.0 / track_count) + (1.0 / (track_count * 2.0));
                 x_range.contains(target_x * viewport.width())
                     && time_range.contains(bpm_list.time_at(event.start_beat))
             })
@@ -11,7 +11,7 @@ use crate::ui::widgets::easing::EasingGraph;
 use bevy::ecs::system::SystemState;
 use bevy::prelude::*;
 use bevy_egui::EguiUserTextures;
-use egui::{Color32, Pos2, Rect, Sense, Ui};
+use egui::{Color32, Pos2, Rect, Sense, Stroke, StrokeKind, Ui};
 use phichain_chart::bpm_list::BpmList;
 use phichain_chart::constants::CANVAS_WIDTH;
 use phichain_chart::line::Line;
@@ -97,7 +97,7 @@ impl Timeline for NoteTimeline {
         });
 
         macro_rules! render_note {
-            (note: $note:expr, highlighted: $highlighted:expr, fake: $fake:expr, tint: $tint:expr) => {{
+            (note: $note:expr, highlighted: $highlighted:expr, pending: $pending:expr, tint: $tint:expr) => {{
                 let note = $note;
 
                 let x = viewport.min.x + (note.x / CANVAS_WIDTH + 0.5) * viewport.width();
@@ -140,8 +140,10 @@ impl Timeline for NoteTimeline {
 
                 let mut tint = $tint;
 
-                if $fake {
+                if $pending {
                     tint = Color32::from_rgba_unmultiplied(tint.r(), tint.g(), tint.b(), 20);
+                } else if note.is_fake {
+                    tint = Color32::from_rgba_unmultiplied(tint.r(), tint.g(), tint.b(), 100);
                 }
 
                 let rect = Rect::from_center_size(center, size);
@@ -155,6 +157,16 @@ impl Timeline for NoteTimeline {
                         .sense(Sense::click()),
                 );
 
+                // outline fake notes so they stand out from real notes
+                if note.is_fake && !$pending {
+                    ui.painter().rect_stroke(
+                        rect,
+                        0.0,
+                        Stroke::new(1.5, Color32::ORANGE),
+                        StrokeKind::Outside,
+                    );
+                }
+
                 (response, rect)
             }};
         }
@@ -173,7 +185,7 @@ impl Timeline for NoteTimeline {
             let (response, rect) = render_note!(
                 note: &note,
                 highlighted: highlighted.is_some(),
-                fake: pending.is_some(),
+                pending: pending.is_some(),
                 tint: if selected.is_some() {
                     Color32::LIGHT_GREEN
                 } else if curve_note.is_some() {
@@ -1,5 +1,6 @@
 use crate::tab::timeline::NoteSideFilter;
 use crate::timeline::container::TimelineContainer;
+use crate::waveform::WaveformChannels;
 use bevy::prelude::Resource;
 use phichain_chart::beat;
 use phichain_chart::beat::Beat;
@@ -9,6 +10,8 @@ use phichain_chart::constants::CANVAS_WIDTH;
 pub struct TimelineSettings {
     pub zoom: f32,
     pub density: u32,
+    /// Notes and events moved further than this many beats by quantization are reported
+    pub quantize_tolerance: f32,
     pub lanes: u32,
 
     pub container: TimelineContainer,
@@ -17,6 +20,14 @@ pub struct TimelineSettings {
 
     pub show_spectrogram: bool,
     pub spectrogram_opacity: f32,
+
+    pub show_waveform: bool,
+    pub waveform_opacity: f32,
+    pub waveform_channels: WaveformChannels,
+    /// Mark the transients of the audio, where notes are usually placed
+    pub show_transients: bool,
+
+    pub show_density_graph: bool,
 }
 
 impl Default for TimelineSettings {
@@ -24,6 +35,7 @@ impl Default for TimelineSettings {
         Self {
             zoom: 2.0,
             density: 4,
+            quantize_tolerance: 0.01,
             lanes: 11,
 
             container: TimelineContainer::default(),
@@ -32,6 +44,13 @@ impl Default for TimelineSettings {
 
             show_spectrogram: false,
             spectrogram_opacity: 0.5,
+
+            show_waveform: false,
+            waveform_opacity: 0.5,
+            waveform_channels: WaveformChannels::default(),
+            show_transients: false,
+
+            show_density_graph: true,
         }
     }
 }
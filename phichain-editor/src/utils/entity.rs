@@ -1,6 +1,7 @@
+use crate::chart_id::Id;
 use bevy::prelude::{Children, Entity, World};
 
-/// Replace the given entity with an empty one. Removes all its children and components
+/// Replace the given entity with an empty one. Removes all its children and components but its [`Id`]
 pub fn replace_with_empty(world: &mut World, entity: Entity) {
     // despawn all children
     if let Some(children) = world.entity_mut(entity).take::<Children>() {
@@ -9,6 +10,6 @@ pub fn replace_with_empty(world: &mut World, entity: Entity) {
         }
     }
 
-    // remove all components
-    world.entity_mut(entity).retain::<()>();
+    // remove all components, the id is kept so the entity is the same item once restored
+    world.entity_mut(entity).retain::<Id>();
 }
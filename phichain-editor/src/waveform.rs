@@ -0,0 +1,232 @@
+//! Waveform of the audio drawn behind the timeline, with markers on its transients
+//!
+//! The RMS and peak envelopes are computed once per block of samples in the background, and
+//! aggregated over the blocks each row of the timeline covers when drawing
+
+use crate::spectrogram;
+use crate::tempo::detect::OnsetEnvelope;
+use crate::timeline::TimelineContext;
+use crate::utils::convert::BevyEguiConvert;
+use bevy::ecs::system::SystemState;
+use bevy::prelude::{Commands, Component, Entity, Query, Res, Resource, World};
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, Task};
+use bevy_kira_audio::prelude::StaticSoundData;
+use egui::{Color32, Mesh, Painter, Pos2, Rect, Stroke};
+use phichain_chart::offset::Offset;
+
+/// Samples per envelope block
+const BLOCK: usize = 256;
+
+const PEAK_COLOR: Color32 = Color32::from_rgb(70, 130, 180);
+const RMS_COLOR: Color32 = Color32::from_rgb(135, 206, 250);
+const TRANSIENT_COLOR: Color32 = Color32::from_rgb(255, 165, 0);
+
+/// How the channels of the audio are laid out on the timeline
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WaveformChannels {
+    /// The channels mixed down, centered on the timeline
+    #[default]
+    Mixed,
+    /// The left channel on the left half of the timeline and the right channel on the right half
+    Split,
+}
+
+/// RMS and peak amplitude of each block of samples
+#[derive(Debug, Clone, Default)]
+pub struct Envelope {
+    pub rms: Vec<f32>,
+    pub peak: Vec<f32>,
+}
+
+impl Envelope {
+    pub fn new(samples: &[f32]) -> Self {
+        let (rms, peak) = samples
+            .chunks(BLOCK)
+            .map(|block| {
+                let power = block.iter().map(|x| x * x).sum::<f32>() / block.len() as f32;
+                let peak = block.iter().fold(0.0f32, |peak, x| peak.max(x.abs()));
+                (power.sqrt(), peak)
+            })
+            .unzip();
+
+        Self { rms, peak }
+    }
+
+    /// RMS and peak amplitude over the blocks in `from..to`, at least one block is always covered
+    fn range(&self, from: usize, to: usize) -> Option<(f32, f32)> {
+        if from >= self.rms.len() {
+            return None;
+        }
+        let to = to.clamp(from + 1, self.rms.len());
+
+        let power = self.rms[from..to].iter().map(|x| x * x).sum::<f32>() / (to - from) as f32;
+        let peak = self.peak[from..to]
+            .iter()
+            .fold(0.0f32, |peak, x| peak.max(*x));
+
+        Some((power.sqrt(), peak))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WaveformData {
+    pub sample_rate: u32,
+    pub left: Envelope,
+    pub right: Envelope,
+    pub mixed: Envelope,
+    /// Times of the transients in seconds, on the audio
+    pub transients: Vec<f32>,
+}
+
+pub fn make_waveform(sound: &StaticSoundData) -> WaveformData {
+    let (left, right): (Vec<_>, Vec<_>) = sound
+        .frames
+        .iter()
+        .map(|frame| (frame.left, frame.right))
+        .unzip();
+    let mono = spectrogram::load_audio(sound);
+
+    WaveformData {
+        sample_rate: sound.sample_rate,
+        left: Envelope::new(&left),
+        right: Envelope::new(&right),
+        mixed: Envelope::new(&mono.data),
+        transients: OnsetEnvelope::new(&mono).onsets(),
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct Waveform(pub WaveformData);
+
+/// A background task generating the [`Waveform`] for the loaded audio
+#[derive(Component)]
+pub struct PendingWaveform(pub Task<WaveformData>);
+
+pub fn poll_waveform_system(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut PendingWaveform)>,
+) {
+    for (entity, mut task) in &mut tasks {
+        if let Some(waveform) = block_on(future::poll_once(&mut task.0)) {
+            commands.entity(entity).despawn();
+            commands.insert_resource(Waveform(waveform));
+        }
+    }
+}
+
+pub fn draw(painter: &Painter, world: &mut World) {
+    let mut state =
+        SystemState::<(TimelineContext, Option<Res<Waveform>>, Res<Offset>)>::new(world);
+    let (ctx, waveform, offset) = state.get_mut(world);
+
+    let opacity = ctx.settings.waveform_opacity.clamp(0.0, 1.0);
+    if !(ctx.settings.show_waveform || ctx.settings.show_transients) || opacity <= 0.01 {
+        return;
+    }
+
+    // the waveform is generated in the background and may not be ready yet
+    let Some(waveform) = waveform else {
+        return;
+    };
+
+    let waveform = &waveform.0;
+    let rect = ctx.viewport.0.into_egui();
+    let offset = offset.0 / 1000.0;
+
+    if ctx.settings.show_waveform {
+        let y_to_block = |y: f32| {
+            let time = ctx.y_to_time(y) + offset;
+            time * waveform.sample_rate as f32 / BLOCK as f32
+        };
+
+        match ctx.settings.waveform_channels {
+            WaveformChannels::Mixed => {
+                render_envelope(painter, rect, &waveform.mixed, &y_to_block, opacity);
+            }
+            WaveformChannels::Split => {
+                let (left, right) = rect.split_left_right_at_fraction(0.5);
+                render_envelope(painter, left, &waveform.left, &y_to_block, opacity);
+                render_envelope(painter, right, &waveform.right, &y_to_block, opacity);
+            }
+        }
+    }
+
+    if ctx.settings.show_transients {
+        let stroke = Stroke::new(1.0, TRANSIENT_COLOR.gamma_multiply(opacity));
+        let (from, to) = (ctx.y_to_time(rect.bottom()), ctx.y_to_time(rect.top()));
+        for transient in &waveform.transients {
+            let time = transient - offset;
+            if (from..=to).contains(&time) {
+                painter.hline(rect.x_range(), ctx.time_to_y(time), stroke);
+            }
+        }
+    }
+}
+
+/// Draw an envelope centered in `rect`, one row of the peak and the RMS every few pixels
+fn render_envelope(
+    painter: &Painter,
+    rect: Rect,
+    envelope: &Envelope,
+    y_to_block: &dyn Fn(f32) -> f32,
+    opacity: f32,
+) {
+    let rows = (rect.height() / 2.0).clamp(1.0, 1024.0) as usize;
+    let dy = rect.height() / rows as f32;
+    let center = rect.center().x;
+    let half_width = rect.width() / 2.0;
+
+    let peak_color = PEAK_COLOR.gamma_multiply(opacity);
+    let rms_color = RMS_COLOR.gamma_multiply(opacity);
+
+    let mut mesh = Mesh::default();
+    for row in 0..rows {
+        let y0 = rect.top() + row as f32 * dy;
+        let y1 = y0 + dy;
+
+        // time goes up the timeline, so the bottom of the row is its earliest block
+        let from = y_to_block(y1).floor();
+        let to = y_to_block(y0).ceil();
+        if to <= 0.0 {
+            continue;
+        }
+        let Some((rms, peak)) = envelope.range(from.max(0.0) as usize, to as usize) else {
+            continue;
+        };
+
+        let bar = |amplitude: f32| {
+            let width = amplitude.min(1.0) * half_width;
+            Rect::from_min_max(Pos2::new(center - width, y0), Pos2::new(center + width, y1))
+        };
+        mesh.add_colored_rect(bar(peak), peak_color);
+        mesh.add_colored_rect(bar(rms), rms_color);
+    }
+
+    painter.add(mesh);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope() {
+        let mut samples = vec![0.5; BLOCK];
+        samples.extend(std::iter::repeat_n(0.0, BLOCK));
+        samples[BLOCK + 10] = -1.0;
+
+        let envelope = Envelope::new(&samples);
+        assert_eq!(envelope.rms.len(), 2);
+        assert_eq!(envelope.rms[0], 0.5);
+        assert_eq!(envelope.peak[1], 1.0);
+
+        let (rms, peak) = envelope.range(0, 2).unwrap();
+        assert_eq!(peak, 1.0);
+        assert!((rms - (0.125f32 + 1.0 / (2.0 * BLOCK as f32)).sqrt()).abs() < 1e-6);
+
+        // an empty range still covers a block
+        assert_eq!(envelope.range(1, 1).unwrap().1, 1.0);
+        assert_eq!(envelope.range(2, 3), None);
+    }
+}
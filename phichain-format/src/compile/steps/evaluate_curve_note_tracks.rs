@@ -3,10 +3,9 @@ use phichain_chart::serialization::{PhichainChart, SerializedLine};
 
 fn evaluate_line(line: &mut SerializedLine) {
     let original_notes = line.notes.clone();
+    let note = |id| original_notes.iter().find(|note| note.id == Some(id));
     for track in &line.curve_note_tracks {
-        if let (Some(from), Some(to)) =
-            (original_notes.get(track.from), original_notes.get(track.to))
-        {
+        if let (Some(from), Some(to)) = (note(track.from), note(track.to)) {
             line.notes
                 .extend(generate_notes(*from, *to, &track.options));
         }
@@ -39,35 +38,42 @@ mod tests {
     use super::*;
     use phichain_chart::beat;
     use phichain_chart::curve_note_track::{CurveNoteTrack, CurveNoteTrackOptions};
+    use phichain_chart::id::Id;
     use phichain_chart::line::Line;
     use phichain_chart::note::{Note, NoteKind};
     use phichain_chart::serialization::SerializedLine;
 
-    /// Curve note tracks use indices into the original `notes` array.
+    /// Curve note tracks refer to notes of the original `notes` array by id.
     /// When track A generates new notes and appends them, the array grows.
-    /// Track B's indices (from=2, to=50) are out of bounds in the original
-    /// 2-element array and should be skipped. Without snapshotting the
-    /// original notes, track B would accidentally reference notes generated
-    /// by track A, producing unwanted extra notes.
+    /// Track B's ids (from=2, to=50) match none of the 2 original notes
+    /// and it should be skipped. Without snapshotting the original notes,
+    /// track B could reference notes generated by track A, producing
+    /// unwanted extra notes.
     #[test]
     fn later_track_should_not_reference_notes_generated_by_earlier_track() {
-        // 2 original notes at indices 0 and 1
+        // 2 original notes with ids 0 and 1
         let notes = vec![
-            Note::new(NoteKind::Tap, true, beat!(0), 0.0, 1.0),
-            Note::new(NoteKind::Tap, true, beat!(4), 100.0, 1.0),
+            Note {
+                id: Some(Id(0)),
+                ..Note::new(NoteKind::Tap, true, beat!(0), 0.0, 1.0)
+            },
+            Note {
+                id: Some(Id(1)),
+                ..Note::new(NoteKind::Tap, true, beat!(4), 100.0, 1.0)
+            },
         ];
 
         // Track A: from=0, to=1, valid, will generate notes and append them
-        // Track B: from=2, to=3, out of bounds in original notes, should be skipped
+        // Track B: from=2, to=50, missing in original notes, should be skipped
         let tracks = vec![
             CurveNoteTrack {
-                from: 0,
-                to: 1,
+                from: Id(0),
+                to: Id(1),
                 options: CurveNoteTrackOptions::default(),
             },
             CurveNoteTrack {
-                from: 2,
-                to: 50,
+                from: Id(2),
+                to: Id(50),
                 options: CurveNoteTrackOptions::default(),
             },
         ];
@@ -76,11 +82,13 @@ mod tests {
         chart.lines = vec![SerializedLine {
             line: Line {
                 name: "test".to_string(),
+                ..Default::default()
             },
             notes,
             events: vec![],
             children: vec![],
             curve_note_tracks: tracks,
+            text_events: vec![],
         }];
 
         // Count notes generated by track A alone
@@ -96,8 +104,8 @@ mod tests {
 
         assert_eq!(
             actual_count, expected_count,
-            "track B (from=2, to=50) should be skipped because those indices are out of bounds \
-             in the original notes, but got {} notes instead of {}",
+            "track B (from=2, to=50) should be skipped because no original note has those ids, \
+             but got {} notes instead of {}",
             actual_count, expected_count,
         );
     }
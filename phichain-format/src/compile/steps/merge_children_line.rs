@@ -45,6 +45,12 @@ fn merge(parent: SerializedLine) -> Vec<SerializedLine> {
 
             let minimum = beat!(1, 32);
 
+            // filter the events once rather than for every step
+            let sequences =
+                |line: &SerializedLine| (line.events.x(), line.events.y(), line.events.rotation());
+            let parent_sequences = sequences(&parent);
+            let child_sequences = sequences(&child);
+
             if let (Some(first), Some(last)) = (splits.first().copied(), splits.last().copied()) {
                 let mut current = first;
                 while current < last {
@@ -52,19 +58,19 @@ fn merge(parent: SerializedLine) -> Vec<SerializedLine> {
                     let end_beat = current + minimum;
 
                     macro_rules! evaluate {
-                        ($target:ident, $filter:ident) => {
+                        ($events:expr) => {
                             (
-                                $target.events.$filter().evaluate_inclusive(start_beat),
-                                $target.events.$filter().evaluate_inclusive(end_beat),
+                                $events.evaluate_inclusive(start_beat),
+                                $events.evaluate_inclusive(end_beat),
                             )
                         };
                     }
 
                     macro_rules! evaluate_line {
-                        ($target:ident) => {{
-                            let (start_x, end_x) = evaluate!($target, x);
-                            let (start_y, end_y) = evaluate!($target, y);
-                            let (start_rotation, end_rotation) = evaluate!($target, rotation);
+                        ($sequences:ident) => {{
+                            let (start_x, end_x) = evaluate!($sequences.0);
+                            let (start_y, end_y) = evaluate!($sequences.1);
+                            let (start_rotation, end_rotation) = evaluate!($sequences.2);
 
                             let start = Isometry2::new(
                                 Vector2::new(start_x, start_y),
@@ -79,13 +85,14 @@ fn merge(parent: SerializedLine) -> Vec<SerializedLine> {
                         }};
                     }
 
-                    let (parent_start, parent_end) = evaluate_line!(parent);
-                    let (child_start, child_end) = evaluate_line!(child);
+                    let (parent_start, parent_end) = evaluate_line!(parent_sequences);
+                    let (child_start, child_end) = evaluate_line!(child_sequences);
 
                     let start = parent_start * child_start;
                     let end = parent_end * child_end;
 
                     merged_move_events.push(LineEvent {
+                        id: None,
                         kind: LineEventKind::X,
                         start_beat,
                         end_beat,
@@ -96,6 +103,7 @@ fn merge(parent: SerializedLine) -> Vec<SerializedLine> {
                         ),
                     });
                     merged_move_events.push(LineEvent {
+                        id: None,
                         kind: LineEventKind::Y,
                         start_beat,
                         end_beat,
@@ -106,6 +114,7 @@ fn merge(parent: SerializedLine) -> Vec<SerializedLine> {
                         ),
                     });
                     merged_rotate_events.push(LineEvent {
+                        id: None,
                         kind: LineEventKind::Rotation,
                         start_beat,
                         end_beat,
@@ -136,7 +145,10 @@ fn merge(parent: SerializedLine) -> Vec<SerializedLine> {
             merged_children.push(merged);
         }
 
-        merged_children.push(parent);
+        merged_children.push(SerializedLine {
+            children: vec![],
+            ..parent
+        });
 
         merged_children
     }
@@ -152,3 +164,32 @@ pub fn merge_children_line(chart: PhichainChart) -> PhichainChart {
 
     PhichainChart { lines, ..chart }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::line::Line;
+
+    #[test]
+    fn test_merged_parent_has_no_children() {
+        let line = |name: &str, children| {
+            let line = Line {
+                name: name.to_owned(),
+                ..Default::default()
+            };
+            SerializedLine::new(line, vec![], vec![], children, vec![])
+        };
+        let mut chart = PhichainChart::empty();
+        chart.lines = vec![line("parent", vec![line("child", vec![])])];
+
+        let chart = merge_children_line(chart);
+        let names = chart
+            .lines
+            .iter()
+            .map(|line| line.line.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["child", "parent"]);
+        // the children are flattened into the root level, keeping them would export them twice
+        assert!(chart.lines.iter().all(|line| line.children.is_empty()));
+    }
+}
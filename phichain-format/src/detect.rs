@@ -0,0 +1,178 @@
+//! Detect the format of a chart from its content
+//!
+//! Every format [sniffs](ChartFormat::sniff) the chart, the chart is detected as the format scoring
+//! the highest as long as it has every key the format expects
+
+use crate::official::OfficialChart;
+use crate::rpe::RpeChart;
+use crate::ChartFormat;
+use phichain_chart::serialization::PhichainChart;
+use serde_json::Value;
+use std::fmt;
+use thiserror::Error;
+
+/// A chart format which can be detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Official,
+    Phichain,
+    Rpe,
+}
+
+impl Format {
+    /// All formats, the ones checked first come first when scoring the same
+    pub const ALL: [Format; 3] = [Format::Rpe, Format::Official, Format::Phichain];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Official => "official",
+            Format::Phichain => "phichain",
+            Format::Rpe => "rpe",
+        }
+    }
+
+    /// [Sniff](ChartFormat::sniff) a chart as this format
+    pub fn sniff(&self, value: &Value) -> Sniff {
+        match self {
+            Format::Official => OfficialChart::sniff(value),
+            Format::Phichain => PhichainChart::sniff(value),
+            Format::Rpe => RpeChart::sniff(value),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// How much a chart looks like a format
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sniff {
+    /// From `0.0` to `1.0`, `1.0` if the chart has every key the format expects
+    pub score: f32,
+    /// Keys the format expects which the chart is missing
+    pub missing: Vec<&'static str>,
+}
+
+impl Sniff {
+    /// Score a chart by the share of the top-level `keys` it has
+    pub fn keys(value: &Value, keys: &[&'static str]) -> Self {
+        let missing = keys
+            .iter()
+            .copied()
+            .filter(|key| value.get(key).is_none())
+            .collect::<Vec<_>>();
+
+        Self {
+            score: if keys.is_empty() {
+                1.0
+            } else {
+                (keys.len() - missing.len()) as f32 / keys.len() as f32
+            },
+            missing,
+        }
+    }
+
+    /// If the chart has every key the format expects
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// A format a chart is sniffed as
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub format: Format,
+    pub sniff: Sniff,
+}
+
+#[derive(Debug, Error)]
+pub enum DetectError {
+    #[error("invalid chart: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("unable to detect the format of the chart: {}", describe(.0))]
+    Unrecognized(Vec<Candidate>),
+}
+
+fn describe(candidates: &[Candidate]) -> String {
+    candidates
+        .iter()
+        .map(|candidate| {
+            let missing = candidate
+                .sniff
+                .missing
+                .iter()
+                .map(|key| format!("`{key}`"))
+                .collect::<Vec<_>>();
+            format!("{} is missing {}", candidate.format, missing.join(", "))
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Sniff a chart as every format, the most likely first
+pub fn sniff(value: &Value) -> Vec<Candidate> {
+    let mut candidates = Format::ALL
+        .into_iter()
+        .map(|format| Candidate {
+            format,
+            sniff: format.sniff(value),
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| b.sniff.score.total_cmp(&a.sniff.score));
+    candidates
+}
+
+/// Detect the format of a parsed chart
+///
+/// Fails with every format sniffed if the chart is missing keys of all of them
+pub fn detect_value(value: &Value) -> Result<Format, DetectError> {
+    let candidates = sniff(value);
+    match candidates.first() {
+        Some(candidate) if candidate.sniff.is_complete() => Ok(candidate.format),
+        _ => Err(DetectError::Unrecognized(candidates)),
+    }
+}
+
+/// Detect the format of the content of a chart file
+pub fn detect(content: &str) -> Result<Format, DetectError> {
+    detect_value(&serde_json::from_str(content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detect() {
+        let rpe = json!({ "BPMList": [], "META": {}, "judgeLineList": [] });
+        assert_eq!(detect_value(&rpe).unwrap(), Format::Rpe);
+
+        let official = json!({ "formatVersion": 3, "offset": 0.0, "judgeLineList": [] });
+        assert_eq!(detect_value(&official).unwrap(), Format::Official);
+
+        let phichain = serde_json::to_string(&PhichainChart::default()).unwrap();
+        assert_eq!(detect(&phichain).unwrap(), Format::Phichain);
+    }
+
+    #[test]
+    fn test_detect_failure() {
+        let value = json!({ "META": {}, "judgeLineList": [] });
+        let Err(DetectError::Unrecognized(candidates)) = detect_value(&value) else {
+            panic!("expected the format to be unrecognized");
+        };
+
+        assert_eq!(candidates[0].format, Format::Rpe);
+        assert_eq!(candidates[0].sniff.score, 0.5);
+        assert_eq!(candidates[0].sniff.missing, vec!["BPMList"]);
+        assert_eq!(candidates[1].format, Format::Official);
+        assert_eq!(candidates[1].sniff.missing, vec!["formatVersion"]);
+        assert_eq!(candidates[2].sniff.score, 0.0);
+
+        assert!(matches!(detect("[]"), Err(DetectError::Unrecognized(_))));
+        assert!(matches!(detect("{"), Err(DetectError::Json(_))));
+    }
+}
@@ -1,11 +1,17 @@
 mod compile;
+pub mod detect;
 pub mod official;
+#[cfg(feature = "pez")]
+pub mod pez;
+#[cfg(test)]
+mod round_trip;
 pub mod rpe;
 
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::convert::Infallible;
 
+use crate::detect::Sniff;
 use phichain_chart::serialization::{PhichainChart, SerializedLine};
 
 #[derive(Debug, Clone)]
@@ -35,6 +41,9 @@ pub trait ChartFormat: Serialize + DeserializeOwned {
 
     /// Apply common output options (like rounding) to the chart
     fn apply_common_output_options(self, common_options: &CommonOutputOptions) -> Self;
+
+    /// Score how much a parsed chart looks like this format, see [`detect`]
+    fn sniff(value: &serde_json::Value) -> Sniff;
 }
 
 impl ChartFormat for PhichainChart {
@@ -54,6 +63,10 @@ impl ChartFormat for PhichainChart {
         Ok(phichain)
     }
 
+    fn sniff(value: &serde_json::Value) -> Sniff {
+        Sniff::keys(value, &["format", "bpm_list", "lines"])
+    }
+
     fn apply_common_output_options(mut self, common_options: &CommonOutputOptions) -> Self {
         use phichain_chart::event::LineEventValue;
 
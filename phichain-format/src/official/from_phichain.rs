@@ -6,13 +6,78 @@ use crate::official::schema::{
 use crate::official::{OfficialOutputError, OfficialOutputOptions};
 use phichain_chart::beat;
 use phichain_chart::beat::Beat;
+use phichain_chart::bpm_list::BpmList;
 use phichain_chart::constants::{CANVAS_HEIGHT, CANVAS_WIDTH};
 use phichain_chart::event::{LineEvent, LineEventKind};
 use phichain_chart::note::NoteKind;
 use phichain_chart::serialization::{PhichainChart, SerializedLine};
-use phichain_compiler::helpers::{cut, cut_with_options, fill_gap, CutOptions};
+use phichain_compiler::helpers::{cut_with_options, fill_gap, CutOptions};
 use phichain_compiler::sequence::EventSequence;
+use tracing::warn;
+
+/// Event kinds exported as a single gapless sequence, which must not overlap
+const SEQUENCE_EVENT_KINDS: [LineEventKind; 3] = [
+    LineEventKind::Rotation,
+    LineEventKind::Opacity,
+    LineEventKind::Speed,
+];
+
+/// Fill the gaps between the events of a kind on a line, see [`fill_gap`]
+fn fill_event_gap(
+    line: &SerializedLine,
+    kind: LineEventKind,
+) -> Result<Vec<LineEvent>, OfficialOutputError> {
+    let events = line
+        .events
+        .iter()
+        .filter(|e| e.kind == kind)
+        .copied()
+        .collect::<Vec<_>>();
+
+    fill_gap(&events, 0.0).map_err(|source| OfficialOutputError::EventSequenceError {
+        line_name: line.line.name.clone(),
+        event_kind: kind,
+        source,
+    })
+}
+
+/// Whether a BPM change happens strictly within the event
+///
+/// Such an event is not linear in time even with a linear easing, while official events always are
+fn crosses_bpm_change(event: &LineEvent, bpm_list: &BpmList) -> bool {
+    bpm_list
+        .0
+        .iter()
+        .any(|point| point.beat > event.start_beat && point.beat < event.end_beat)
+}
+
+/// Check whether a Phichain chart can be converted into the official format
+///
+/// Unlike [`phichain_to_official`], which stops at the first error, every error is collected
+pub fn check_phichain_to_official(phichain: PhichainChart) -> Vec<OfficialOutputError> {
+    let mut errors = vec![];
+
+    if phichain.bpm_list.0.is_empty() {
+        errors.push(OfficialOutputError::EmptyBpmList);
+    }
+
+    let phichain = merge_children_line(phichain);
+    for line in &phichain.lines {
+        for kind in SEQUENCE_EVENT_KINDS {
+            if let Err(error) = fill_event_gap(line, kind) {
+                errors.push(error);
+            }
+        }
+    }
+
+    errors
+}
 
+/// Convert a Phichain chart into the official format
+///
+/// The official format has no notion of fake notes, so fake notes are dropped with a warning
+/// rather than being exported as real, judged notes. Likewise, extended events (scale, color,
+/// incline, paint) and text events are dropped with a warning
 pub fn phichain_to_official(
     phichain: PhichainChart,
     options: &OfficialOutputOptions,
@@ -28,7 +93,11 @@ pub fn phichain_to_official(
         .bpm;
     let offset = phichain.offset.0 / 1000.0;
 
-    let time = |beat: Beat| phichain.bpm_list.normalize_beat(bpm, beat).value() * 60.0 / 1.875;
+    // official times are in 1/32 beats of the first BPM. They are computed in seconds rather than
+    // through `BpmList::normalize_beat`, which overflows `Beat` for far beats (e.g. the end of the
+    // last move event) when a later BPM is slower than the first one. Times are capped at 1e9,
+    // which official charts use as the end of the chart
+    let time = |beat: Beat| (phichain.bpm_list.time_at(beat) * bpm / 1.875).min(1e9);
 
     let mut chart = OfficialChart {
         format_version: 3,
@@ -36,7 +105,7 @@ pub fn phichain_to_official(
         lines: vec![],
     };
 
-    for line in phichain.lines {
+    for (index, line) in phichain.lines.into_iter().enumerate() {
         let mut official_line = OfficialLine {
             bpm,
             move_events: vec![],
@@ -53,6 +122,7 @@ pub fn phichain_to_official(
             line: &SerializedLine,
             kind: LineEventKind,
             minimum_beat: Beat,
+            bpm_list: &BpmList,
             mut transform: F,
             target: &mut Vec<T>,
         ) -> Result<(), OfficialOutputError>
@@ -60,27 +130,12 @@ pub fn phichain_to_official(
             F: FnMut(&LineEvent) -> T,
             T: std::fmt::Debug,
         {
-            let events = line
-                .events
-                .iter()
-                .filter(|e| e.kind == kind)
-                .copied()
-                .collect::<Vec<_>>();
-
-            let filled_gap = fill_gap(&events, 0.0).map_err(|source| {
-                OfficialOutputError::EventSequenceError {
-                    line_name: line.line.name.clone(),
-                    event_kind: kind,
-                    source,
-                }
-            })?;
-
-            for event in filled_gap {
+            for event in fill_event_gap(line, kind)? {
                 let event_segments = cut_with_options(
                     event,
                     minimum_beat,
                     CutOptions {
-                        force_linear: kind.is_speed(),
+                        force_linear: kind.is_speed() || crosses_bpm_change(&event, bpm_list),
                     },
                 );
                 let mut transformed_events = event_segments
@@ -97,6 +152,7 @@ pub fn phichain_to_official(
             &line,
             LineEventKind::Rotation,
             options.minimum_beat,
+            &phichain.bpm_list,
             |e| OfficialNumericLineEvent {
                 start_time: time(e.start_beat),
                 end_time: time(e.end_beat),
@@ -110,6 +166,7 @@ pub fn phichain_to_official(
             &line,
             LineEventKind::Opacity,
             options.minimum_beat,
+            &phichain.bpm_list,
             |e| OfficialNumericLineEvent {
                 start_time: time(e.start_beat),
                 end_time: time(e.end_beat),
@@ -123,6 +180,7 @@ pub fn phichain_to_official(
             &line,
             LineEventKind::Speed,
             options.minimum_beat,
+            &phichain.bpm_list,
             |e| OfficialSpeedEvent {
                 start_time: time(e.start_beat),
                 end_time: time(e.end_beat),
@@ -134,17 +192,27 @@ pub fn phichain_to_official(
 
         // -------- Move events --------
 
+        let cut_move_event = |event: LineEvent| {
+            cut_with_options(
+                event,
+                options.minimum_beat,
+                CutOptions {
+                    force_linear: crosses_bpm_change(&event, &phichain.bpm_list),
+                },
+            )
+        };
+
         let mut x_events = vec![];
         let mut y_events = vec![];
 
         for event in &line.events {
             match event.kind {
                 LineEventKind::X => {
-                    let mut events = cut(*event, options.minimum_beat);
+                    let mut events = cut_move_event(*event);
                     x_events.append(&mut events);
                 }
                 LineEventKind::Y => {
-                    let mut events = cut(*event, options.minimum_beat);
+                    let mut events = cut_move_event(*event);
                     y_events.append(&mut events);
                 }
                 _ => {}
@@ -198,7 +266,39 @@ pub fn phichain_to_official(
             .collect::<Vec<_>>();
         speed_events.sort_by_key(|e| e.start_beat);
 
-        let mut notes = line.notes.clone();
+        let fake_count = line.notes.iter().filter(|note| note.is_fake).count();
+        if fake_count > 0 {
+            warn!(
+                "Line {} has {} fake note(s) that will be removed, the official format does not support fake notes",
+                index, fake_count
+            );
+        }
+
+        let extended_count = line
+            .events
+            .iter()
+            .filter(|event| event.kind.is_extended())
+            .count();
+        if extended_count > 0 {
+            warn!(
+                "Line {} has {} extended event(s) that will be removed, the official format does not support them",
+                index, extended_count
+            );
+        }
+        if !line.text_events.is_empty() {
+            warn!(
+                "Line {} has {} text event(s) that will be removed, the official format does not support text events",
+                index,
+                line.text_events.len()
+            );
+        }
+
+        let mut notes = line
+            .notes
+            .iter()
+            .filter(|note| !note.is_fake)
+            .cloned()
+            .collect::<Vec<_>>();
         notes.sort_by_key(|n| n.beat);
 
         for note in notes {
@@ -303,3 +403,162 @@ pub fn phichain_to_official(
 
     Ok(chart)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::bpm_list::BpmPoint;
+    use phichain_chart::easing::Easing;
+    use phichain_chart::event::LineEventValue;
+    use phichain_chart::line::Line;
+
+    #[test]
+    fn test_check_phichain_to_official() {
+        let overlapping = |kind| {
+            vec![
+                LineEvent {
+                    id: None,
+                    kind,
+                    start_beat: beat!(0),
+                    end_beat: beat!(2),
+                    value: LineEventValue::constant(0.0),
+                },
+                LineEvent {
+                    id: None,
+                    kind,
+                    start_beat: beat!(1),
+                    end_beat: beat!(3),
+                    value: LineEventValue::constant(0.0),
+                },
+            ]
+        };
+
+        let line = |name: &str, events| {
+            let line = Line {
+                name: name.to_owned(),
+                ..Default::default()
+            };
+            SerializedLine::new(line, vec![], events, vec![], vec![])
+        };
+
+        let chart = || PhichainChart {
+            lines: vec![
+                line("a", overlapping(LineEventKind::Rotation)),
+                line(
+                    "b",
+                    [
+                        overlapping(LineEventKind::Opacity),
+                        overlapping(LineEventKind::Speed),
+                        // move events are sampled instead of being exported as a sequence
+                        overlapping(LineEventKind::X),
+                    ]
+                    .concat(),
+                ),
+            ],
+            ..Default::default()
+        };
+
+        assert!(phichain_to_official(chart(), &Default::default()).is_err());
+
+        let errors = check_phichain_to_official(chart())
+            .into_iter()
+            .map(|error| match error {
+                OfficialOutputError::EventSequenceError {
+                    line_name,
+                    event_kind,
+                    ..
+                } => (line_name, event_kind),
+                error => panic!("unexpected error: {error}"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                ("a".to_owned(), LineEventKind::Rotation),
+                ("b".to_owned(), LineEventKind::Opacity),
+                ("b".to_owned(), LineEventKind::Speed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_far_times() {
+        let events = vec![
+            LineEvent {
+                id: None,
+                kind: LineEventKind::Rotation,
+                start_beat: beat!(0),
+                end_beat: beat!(3),
+                value: LineEventValue::constant(0.0),
+            },
+            LineEvent {
+                id: None,
+                kind: LineEventKind::X,
+                start_beat: beat!(0),
+                end_beat: beat!(2_000_000_000),
+                value: LineEventValue::constant(0.0),
+            },
+        ];
+        let chart = PhichainChart {
+            // the later BPM is slower than the first one
+            bpm_list: BpmList::new(vec![
+                BpmPoint::new(beat!(0), 120.0),
+                BpmPoint::new(beat!(2), 60.0),
+            ]),
+            lines: vec![SerializedLine::new(
+                Line::default(),
+                vec![],
+                events,
+                vec![],
+                vec![],
+            )],
+            ..Default::default()
+        };
+
+        let official = phichain_to_official(chart, &Default::default()).unwrap();
+        let line = &official.lines[0];
+        // 2 beats at 120 BPM and 1 beat at 60 BPM are 2 seconds, or 128 1/32 beats at 120 BPM
+        assert_eq!(line.rotate_events[0].end_time, 128.0);
+        // times past the end of the chart are capped
+        assert_eq!(line.move_events.last().unwrap().end_time, 1e9);
+    }
+
+    #[test]
+    fn test_linear_events_crossing_bpm_changes() {
+        let events = vec![LineEvent {
+            id: None,
+            kind: LineEventKind::X,
+            start_beat: beat!(0),
+            end_beat: beat!(4),
+            value: LineEventValue::transition(
+                -CANVAS_WIDTH / 2.0,
+                CANVAS_WIDTH / 2.0,
+                Easing::Linear,
+            ),
+        }];
+        let chart = PhichainChart {
+            bpm_list: BpmList::new(vec![
+                BpmPoint::new(beat!(0), 120.0),
+                BpmPoint::new(beat!(2), 60.0),
+            ]),
+            lines: vec![SerializedLine::new(
+                Line::default(),
+                vec![],
+                events,
+                vec![],
+                vec![],
+            )],
+            ..Default::default()
+        };
+
+        let official = phichain_to_official(chart, &Default::default()).unwrap();
+        // the event is halfway at beat 2, which is 1 second in while the whole event takes 3
+        let halfway = official.lines[0]
+            .move_events
+            .iter()
+            .find(|event| event.start_time == 64.0)
+            .unwrap();
+        assert_eq!(halfway.start_x, 0.5);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::official::schema::{OfficialChart, OfficialNote, OfficialNoteKind};
 use crate::official::{merge_constant_events, OfficialInputError, OfficialInputOptions};
+use phichain_chart::beat;
 use phichain_chart::beat::Beat;
 use phichain_chart::bpm_list::BpmList;
 use phichain_chart::constants::{CANVAS_HEIGHT, CANVAS_WIDTH};
@@ -12,6 +13,9 @@ use phichain_compiler::fitting::fit_events;
 use phichain_compiler::helpers::{map_if, remove_if};
 use phichain_compiler::sequence::EventSequence;
 
+/// Density of the beat grid imported times are snapped to, a multiple of the 1/32 beat official time unit
+const TIME_DENSITY: u32 = 960;
+
 pub fn official_to_phichain(
     official: OfficialChart,
     options: &OfficialInputOptions,
@@ -33,7 +37,9 @@ pub fn official_to_phichain(
     };
 
     for line in official.lines {
-        let t: fn(f32) -> Beat = |x| Beat::from(x * 1.875 / 60.0);
+        // snap to a fine grid, exact float conversions of fractional times carry denominators
+        // large enough for beat arithmetic to overflow
+        let t: fn(f32) -> Beat = |x| beat::utils::attach(x * 1.875 / 60.0, TIME_DENSITY);
         let x: fn(f32) -> f32 = |x| (x - 0.5) * CANVAS_WIDTH;
         let y: fn(f32) -> f32 = |x| (x - 0.5) * CANVAS_HEIGHT;
 
@@ -223,3 +229,53 @@ pub fn official_to_phichain(
 
     Ok(phichain)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chart(note_time: f32, event_times: &[f32]) -> OfficialChart {
+        let events = event_times
+            .windows(2)
+            .map(|x| json!({ "startTime": x[0], "endTime": x[1], "start": 0.0, "end": 1.0 }))
+            .collect::<Vec<_>>();
+        serde_json::from_value(json!({
+            "formatVersion": 3,
+            "offset": 0.0,
+            "judgeLineList": [{
+                "bpm": 120.0,
+                "judgeLineMoveEvents": events,
+                "judgeLineRotateEvents": events,
+                "judgeLineDisappearEvents": events,
+                "speedEvents": [],
+                "notesAbove": [{
+                    "type": 1,
+                    "time": note_time,
+                    "holdTime": 0.0,
+                    "positionX": 0.0,
+                    "speed": 1.0,
+                    "floorPosition": 0.0
+                }],
+                "notesBelow": []
+            }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fractional_times() {
+        // whole times are 1/32 beats, which are on the grid already
+        let phichain = official_to_phichain(chart(48.0, &[0.0, 1e9]), &Default::default()).unwrap();
+        assert_eq!(phichain.lines[0].notes[0].beat, beat!(1, 1, 2));
+
+        // the exact values of fractional times carry denominators large enough for the duration of
+        // the events to overflow
+        let phichain = official_to_phichain(
+            chart(100.3, &[0.0, 24.850838, 397.28192, 1e9]),
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(phichain.lines[0].notes[0].beat, beat!(3, 129, 960));
+    }
+}
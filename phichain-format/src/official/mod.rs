@@ -1,3 +1,4 @@
+use crate::detect::Sniff;
 use crate::official::from_phichain::phichain_to_official;
 use crate::official::into_phichain::official_to_phichain;
 use crate::{ChartFormat, CommonOutputOptions};
@@ -12,6 +13,7 @@ mod options;
 mod schema;
 
 pub use errors::{OfficialInputError, OfficialOutputError};
+pub use from_phichain::check_phichain_to_official;
 pub use options::{OfficialInputOptions, OfficialOutputOptions};
 pub use schema::OfficialChart;
 
@@ -52,6 +54,10 @@ impl ChartFormat for OfficialChart {
         phichain_to_official(phichain, opts)
     }
 
+    fn sniff(value: &serde_json::Value) -> Sniff {
+        Sniff::keys(value, &["formatVersion", "judgeLineList"])
+    }
+
     fn apply_common_output_options(mut self, common_options: &CommonOutputOptions) -> Self {
         let round = |value: f32| -> f32 {
             let multiplier = 10_f32.powi(common_options.round as i32);
@@ -0,0 +1,32 @@
+use crate::detect::DetectError;
+use crate::official::OfficialInputError;
+use crate::rpe::RpeInputError;
+use phichain_chart::serialization::ParseChartError;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PezError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid package: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("invalid chart: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("missing {0} in package")]
+    MissingEntry(String),
+    #[error("info.txt does not specify the chart or the music")]
+    InvalidInfo,
+    #[error("{0}")]
+    Detect(#[from] DetectError),
+    #[error("unsupported chart format, expected RPE or official")]
+    UnsupportedChart,
+    #[error("{0}")]
+    RpeInput(#[from] RpeInputError),
+    #[error("{0}")]
+    OfficialInput(#[from] OfficialInputError),
+    #[error("{0}")]
+    ParseChart(#[from] ParseChartError),
+    #[error("target directory {0} is not empty")]
+    TargetNotEmpty(PathBuf),
+}
@@ -0,0 +1,138 @@
+use phichain_chart::project::ProjectMeta;
+
+/// Metadata of a Phira package, stored as `info.txt` in the archive
+///
+/// Each line of `info.txt` is a `Key: Value` pair, a leading `#` line is used as a header
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PezInfo {
+    pub name: String,
+    pub level: String,
+    pub composer: String,
+    pub charter: String,
+    pub illustrator: String,
+    /// File name of the chart inside the archive
+    pub chart: String,
+    /// File name of the music inside the archive
+    pub song: String,
+    /// File name of the illustration inside the archive
+    pub picture: Option<String>,
+}
+
+impl PezInfo {
+    /// Parse the content of an `info.txt`, unknown keys are ignored
+    ///
+    /// Returns [`None`] if the chart or the music is not specified
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut info = Self::default();
+
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim().to_owned();
+
+            match key.trim() {
+                "Name" => info.name = value,
+                "Level" => info.level = value,
+                "Composer" => info.composer = value,
+                "Charter" => info.charter = value,
+                "Illustrator" => info.illustrator = value,
+                "Chart" => info.chart = value,
+                "Song" => info.song = value,
+                "Picture" => info.picture = (!value.is_empty()).then_some(value),
+                _ => {}
+            }
+        }
+
+        (!info.chart.is_empty() && !info.song.is_empty()).then_some(info)
+    }
+
+    /// Serialize into the content of an `info.txt`
+    pub fn to_info_txt(&self) -> String {
+        let mut content = format!(
+            "#
+Name: {}
+Song: {}
+Chart: {}
+Level: {}
+Composer: {}
+Charter: {}
+Illustrator: {}
+",
+            self.name,
+            self.song,
+            self.chart,
+            self.level,
+            self.composer,
+            self.charter,
+            self.illustrator
+        );
+
+        if let Some(picture) = &self.picture {
+            content.push_str(format!("Picture: {}\n", picture).as_str());
+        }
+
+        content
+    }
+
+    /// Build the [`ProjectMeta`] of a project imported from this package
+    pub fn project_meta(&self) -> ProjectMeta {
+        ProjectMeta {
+            composer: self.composer.clone(),
+            charter: self.charter.clone(),
+            illustrator: self.illustrator.clone(),
+            name: self.name.clone(),
+            level: self.level.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let info = PezInfo::parse(
+            "#
+Name: Test Song
+Path: 114514
+Song: 114514.ogg
+Picture: 114514.png
+Chart: 114514.json
+Level: IN Lv.15
+Composer: Composer
+Charter: Charter
+",
+        )
+        .unwrap();
+
+        assert_eq!(info.name, "Test Song");
+        assert_eq!(info.song, "114514.ogg");
+        assert_eq!(info.picture.as_deref(), Some("114514.png"));
+        assert_eq!(info.chart, "114514.json");
+        assert_eq!(info.level, "IN Lv.15");
+        assert_eq!(info.illustrator, "");
+
+        assert_eq!(
+            PezInfo::parse("#\nName: Missing Chart\nSong: a.ogg\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let info = PezInfo {
+            name: "Name: With Colon".to_owned(),
+            level: "SP Lv.?".to_owned(),
+            composer: "Composer".to_owned(),
+            charter: "Charter".to_owned(),
+            illustrator: "Illustrator".to_owned(),
+            chart: "chart.json".to_owned(),
+            song: "music.ogg".to_owned(),
+            picture: None,
+        };
+
+        assert_eq!(PezInfo::parse(&info.to_info_txt()), Some(info));
+    }
+}
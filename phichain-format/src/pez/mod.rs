@@ -0,0 +1,303 @@
+//! Reading and writing Phira packages (`.pez`)
+//!
+//! A package is a zip archive holding an `info.txt`, the chart, the music, the illustration
+//! and the textures of image lines. Charts are stored in the RPE format
+
+mod errors;
+mod info;
+
+use crate::detect::{detect_value, Format};
+use crate::official::OfficialChart;
+use crate::rpe::{RpeChart, RpeInputOptions};
+use crate::ChartFormat;
+use phichain_chart::project::{Project, ProjectPath};
+use phichain_chart::serialization::{PhichainChart, SerializedLine};
+use std::fs;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use tracing::warn;
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+pub use errors::PezError;
+pub use info::PezInfo;
+
+const INFO_FILE: &str = "info.txt";
+const CHART_FILE: &str = "chart.json";
+
+fn read_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Vec<u8>, PezError> {
+    let mut file = archive.by_name(name).map_err(|error| match error {
+        ZipError::FileNotFound => PezError::MissingEntry(name.to_owned()),
+        error => PezError::Zip(error),
+    })?;
+
+    let mut content = Vec::new();
+    file.read_to_end(&mut content)?;
+
+    Ok(content)
+}
+
+fn read_info<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<PezInfo, PezError> {
+    let content = read_entry(archive, INFO_FILE)?;
+    PezInfo::parse(&String::from_utf8_lossy(&content)).ok_or(PezError::InvalidInfo)
+}
+
+/// Convert the chart of a package into a [`PhichainChart`], detecting whether it is an RPE or an official chart
+fn chart_to_phichain(content: &[u8], options: &RpeInputOptions) -> Result<PhichainChart, PezError> {
+    let value: serde_json::Value = serde_json::from_slice(content)?;
+
+    match detect_value(&value)? {
+        Format::Rpe => {
+            let rpe: RpeChart = serde_json::from_value(value)?;
+            Ok(rpe.to_phichain(options)?)
+        }
+        Format::Official => {
+            let official: OfficialChart = serde_json::from_value(value)?;
+            Ok(official.to_phichain(&Default::default())?)
+        }
+        Format::Phichain => Err(PezError::UnsupportedChart),
+    }
+}
+
+/// Collect the textures of all lines in a chart, including child lines
+fn collect_textures(lines: &[SerializedLine], textures: &mut Vec<String>) {
+    for line in lines {
+        if let Some(texture) = &line.line.texture {
+            if !textures.contains(texture) {
+                textures.push(texture.clone());
+            }
+        }
+        collect_textures(&line.children, textures);
+    }
+}
+
+/// Name a file after `stem`, keeping the extension of `original`
+fn rename_keeping_extension(original: &str, stem: &str) -> String {
+    match Path::new(original).extension() {
+        Some(extension) => format!("{}.{}", stem, extension.to_string_lossy()),
+        None => stem.to_owned(),
+    }
+}
+
+/// Read the raw chart of a package without converting it
+pub fn read_chart<R: Read + Seek>(reader: R) -> Result<Vec<u8>, PezError> {
+    let mut archive = ZipArchive::new(reader)?;
+    let info = read_info(&mut archive)?;
+    read_entry(&mut archive, &info.chart)
+}
+
+/// Unpack a package into a new project at `target`
+///
+/// `target` is created if it does not exist, an existing `target` must be empty.
+/// Line textures missing from the package are skipped with a warning
+pub fn import_pez<R: Read + Seek>(
+    reader: R,
+    target: &Path,
+    options: &RpeInputOptions,
+) -> Result<ProjectPath, PezError> {
+    if target.exists() && fs::read_dir(target)?.next().is_some() {
+        return Err(PezError::TargetNotEmpty(target.to_path_buf()));
+    }
+
+    let mut archive = ZipArchive::new(reader)?;
+    let info = read_info(&mut archive)?;
+
+    let chart = chart_to_phichain(&read_entry(&mut archive, &info.chart)?, options)?;
+    let music = read_entry(&mut archive, &info.song)?;
+    let illustration = match &info.picture {
+        Some(picture) => Some((picture, read_entry(&mut archive, picture)?)),
+        None => None,
+    };
+
+    fs::create_dir_all(target)?;
+    let project_path = ProjectPath(target.to_path_buf());
+
+    let mut textures = vec![];
+    collect_textures(&chart.lines, &mut textures);
+    for texture in textures {
+        let Some(path) = project_path.resolve(&texture) else {
+            warn!("Line texture {} is outside the package, skipping", texture);
+            continue;
+        };
+        match read_entry(&mut archive, &texture) {
+            Ok(content) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(path, content)?;
+            }
+            Err(PezError::MissingEntry(_)) => {
+                warn!(
+                    "Line texture {} is missing in the package, skipping",
+                    texture
+                );
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    fs::write(project_path.chart_path(), serde_json::to_string(&chart)?)?;
+    fs::write(
+        project_path.sub_path(rename_keeping_extension(&info.song, "music")),
+        music,
+    )?;
+    if let Some((picture, illustration)) = illustration {
+        fs::write(
+            project_path.sub_path(rename_keeping_extension(picture, "illustration")),
+            illustration,
+        )?;
+    }
+    fs::write(
+        project_path.meta_path(),
+        serde_json::to_string_pretty(&info.project_meta())?,
+    )?;
+
+    Ok(project_path)
+}
+
+/// Build a package from a project, converting its chart into the RPE format
+pub fn export_pez<W: Write + Seek>(project: &Project, writer: W) -> Result<(), PezError> {
+    let chart = PhichainChart::from_json_str(&fs::read_to_string(project.path.chart_path())?)?;
+
+    let mut textures = vec![];
+    collect_textures(&chart.lines, &mut textures);
+
+    let mut rpe = match RpeChart::from_phichain(chart, &()) {
+        Ok(rpe) => rpe,
+        Err(infallible) => match infallible {},
+    };
+
+    let music_path = project
+        .path
+        .music_path()
+        .ok_or_else(|| PezError::MissingEntry("music".to_owned()))?;
+    let music = rename_keeping_extension(&music_path.to_string_lossy(), "music");
+    let illustration = project.path.illustration_path().map(|path| {
+        (
+            rename_keeping_extension(&path.to_string_lossy(), "illustration"),
+            path,
+        )
+    });
+
+    let info = PezInfo {
+        name: project.meta.name.clone(),
+        level: project.meta.level.clone(),
+        composer: project.meta.composer.clone(),
+        charter: project.meta.charter.clone(),
+        illustrator: project.meta.illustrator.clone(),
+        chart: CHART_FILE.to_owned(),
+        song: music.clone(),
+        picture: illustration.as_ref().map(|(name, _)| name.clone()),
+    };
+
+    rpe.meta.name = info.name.clone();
+    rpe.meta.level = info.level.clone();
+    rpe.meta.composer = info.composer.clone();
+    rpe.meta.charter = info.charter.clone();
+    rpe.meta.song = info.song.clone();
+    rpe.meta.background = info.picture.clone().unwrap_or_default();
+
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file(INFO_FILE, options)?;
+    zip.write_all(info.to_info_txt().as_bytes())?;
+
+    zip.start_file(CHART_FILE, options)?;
+    zip.write_all(serde_json::to_string(&rpe)?.as_bytes())?;
+
+    zip.start_file(music.as_str(), options)?;
+    zip.write_all(&fs::read(music_path)?)?;
+
+    if let Some((name, path)) = illustration {
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(&fs::read(path)?)?;
+    }
+
+    for texture in textures {
+        match project.path.resolve(&texture).filter(|path| path.is_file()) {
+            Some(path) => {
+                zip.start_file(texture.as_str(), options)?;
+                zip.write_all(&fs::read(path)?)?;
+            }
+            None => warn!(
+                "Line texture {} does not exist in the project, skipping",
+                texture
+            ),
+        }
+    }
+
+    zip.finish()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::project::ProjectMeta;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_round_trip() {
+        let root = std::env::temp_dir().join(format!("phichain-pez-{}", std::process::id()));
+        let project_dir = root.join("project");
+        fs::create_dir_all(&project_dir).unwrap();
+
+        let mut chart = PhichainChart::default();
+        chart.lines[0].line.texture = Some("textures/cat.png".to_owned());
+        fs::write(
+            project_dir.join("chart.json"),
+            serde_json::to_string(&chart).unwrap(),
+        )
+        .unwrap();
+        fs::write(project_dir.join("music.ogg"), b"music").unwrap();
+        fs::create_dir_all(project_dir.join("textures")).unwrap();
+        fs::write(project_dir.join("textures/cat.png"), b"cat").unwrap();
+        let meta = ProjectMeta {
+            name: "Song".to_owned(),
+            level: "IN Lv.12".to_owned(),
+            ..Default::default()
+        };
+        fs::write(
+            project_dir.join("meta.json"),
+            serde_json::to_string(&meta).unwrap(),
+        )
+        .unwrap();
+
+        let project = Project::open(project_dir).unwrap();
+        let mut package = Cursor::new(Vec::new());
+        export_pez(&project, &mut package).unwrap();
+
+        let imported = import_pez(
+            Cursor::new(package.into_inner()),
+            &root.join("imported"),
+            &RpeInputOptions::default(),
+        )
+        .unwrap();
+        let project = imported.into_project().unwrap();
+        assert_eq!(project.meta, meta);
+        assert_eq!(
+            fs::read(project.path.sub_path("music.ogg")).unwrap(),
+            b"music"
+        );
+        assert_eq!(
+            fs::read(project.path.sub_path("textures/cat.png")).unwrap(),
+            b"cat"
+        );
+
+        let chart =
+            PhichainChart::from_json_str(&fs::read_to_string(project.path.chart_path()).unwrap())
+                .unwrap();
+        assert_eq!(
+            chart.lines[0].line.texture.as_deref(),
+            Some("textures/cat.png")
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
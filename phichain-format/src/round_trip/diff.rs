@@ -0,0 +1,363 @@
+use phichain_chart::bpm_list::BpmList;
+use phichain_chart::event::{LineEvent, LineEventKind};
+use phichain_chart::line::Line;
+use phichain_chart::note::{Note, NoteKind};
+use phichain_chart::serialization::{PhichainChart, SerializedLine};
+use phichain_compiler::sequence::EventSequence;
+use std::fmt;
+
+/// How strictly two charts are compared
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Maximum drift of note and event timings, in seconds
+    pub time: f32,
+    /// Maximum drift of values, relative to the magnitude of the value, or absolute below `1.0`
+    pub value: f32,
+    /// Compare the BPM lists point by point. Timings are always compared in seconds regardless
+    pub bpm_list: bool,
+}
+
+/// A semantic difference between an expected and an actual chart
+#[derive(Debug)]
+pub enum Difference {
+    Offset {
+        expected: f32,
+        actual: f32,
+    },
+    BpmList {
+        expected: BpmList,
+        actual: BpmList,
+    },
+    Children {
+        line: String,
+        expected: usize,
+        actual: usize,
+    },
+    LineAttributes {
+        line: String,
+        expected: Box<Line>,
+        actual: Box<Line>,
+    },
+    NoteCount {
+        line: String,
+        expected: usize,
+        actual: usize,
+    },
+    Note {
+        line: String,
+        index: usize,
+        time: f32,
+        expected: Box<Note>,
+        actual: Box<Note>,
+    },
+    Event {
+        line: String,
+        kind: LineEventKind,
+        time: f32,
+        expected: f32,
+        actual: f32,
+    },
+    TextEvents {
+        line: String,
+    },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difference::Offset { expected, actual } => {
+                write!(f, "offset: {expected} -> {actual}")
+            }
+            Difference::BpmList { expected, actual } => {
+                write!(f, "bpm list: {:?} -> {:?}", expected.0, actual.0)
+            }
+            Difference::Children {
+                line,
+                expected,
+                actual,
+            } => write!(f, "line {line}: {expected} children -> {actual} children"),
+            Difference::LineAttributes {
+                line,
+                expected,
+                actual,
+            } => write!(f, "line {line}: {expected:?} -> {actual:?}"),
+            Difference::NoteCount {
+                line,
+                expected,
+                actual,
+            } => write!(f, "line {line}: {expected} notes -> {actual} notes"),
+            Difference::Note {
+                line,
+                index,
+                time,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "line {line}: note #{index} at {time:.3}s: {expected:?} -> {actual:?}"
+            ),
+            Difference::Event {
+                line,
+                kind,
+                time,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "line {line}: {kind:?} events at {time:.3}s: {expected} -> {actual}"
+            ),
+            Difference::TextEvents { line } => write!(f, "line {line}: text events differ"),
+        }
+    }
+}
+
+struct Differ<'a> {
+    options: &'a DiffOptions,
+    expected_bpm_list: &'a BpmList,
+    actual_bpm_list: &'a BpmList,
+    differences: Vec<Difference>,
+}
+
+impl Differ<'_> {
+    fn close(&self, expected: f32, actual: f32) -> bool {
+        (expected - actual).abs() <= self.options.value * expected.abs().max(1.0)
+    }
+
+    fn close_time(&self, expected: f32, actual: f32) -> bool {
+        (expected - actual).abs() <= self.options.time
+    }
+
+    fn close_option(&self, expected: Option<f32>, actual: Option<f32>) -> bool {
+        match (expected, actual) {
+            (Some(expected), Some(actual)) => self.close(expected, actual),
+            (expected, actual) => expected == actual,
+        }
+    }
+
+    fn same_note(&self, expected: &Note, actual: &Note) -> bool {
+        let same_kind = match (expected.kind, actual.kind) {
+            (NoteKind::Hold { .. }, NoteKind::Hold { .. }) => self.close_time(
+                self.expected_bpm_list.time_at(expected.end_beat()),
+                self.actual_bpm_list.time_at(actual.end_beat()),
+            ),
+            (expected, actual) => {
+                std::mem::discriminant(&expected) == std::mem::discriminant(&actual)
+            }
+        };
+
+        same_kind
+            && expected.above == actual.above
+            && expected.is_fake == actual.is_fake
+            && expected.alpha == actual.alpha
+            && self.close_time(
+                self.expected_bpm_list.time_at(expected.beat),
+                self.actual_bpm_list.time_at(actual.beat),
+            )
+            && self.close(expected.x, actual.x)
+            && self.close(expected.speed, actual.speed)
+            && self.close_option(expected.size, actual.size)
+            && self.close_option(expected.y_offset, actual.y_offset)
+            && self.close_option(expected.visible_time, actual.visible_time)
+    }
+
+    fn diff_notes(&mut self, path: &str, expected: &[Note], actual: &[Note]) {
+        if expected.len() != actual.len() {
+            self.differences.push(Difference::NoteCount {
+                line: path.to_owned(),
+                expected: expected.len(),
+                actual: actual.len(),
+            });
+        }
+
+        let mut expected = expected.to_vec();
+        let mut actual = actual.to_vec();
+        expected.sort_by_key(|note| note.beat);
+        actual.sort_by_key(|note| note.beat);
+
+        for (index, (expected, actual)) in expected.iter().zip(&actual).enumerate() {
+            if !self.same_note(expected, actual) {
+                self.differences.push(Difference::Note {
+                    line: path.to_owned(),
+                    index,
+                    time: self.expected_bpm_list.time_at(expected.beat),
+                    expected: Box::new(*expected),
+                    actual: Box::new(*actual),
+                });
+            }
+        }
+    }
+
+    /// Compare the events of a kind by sampling both sequences between their boundaries
+    ///
+    /// Samples are taken away from the boundaries and compared against the actual values around them,
+    /// so a curve drifting within the time tolerance does not count
+    fn diff_events(
+        &mut self,
+        path: &str,
+        kind: LineEventKind,
+        expected: &[LineEvent],
+        actual: &[LineEvent],
+    ) {
+        let expected = expected
+            .iter()
+            .filter(|event| event.kind == kind)
+            .copied()
+            .collect::<Vec<_>>()
+            .sorted();
+        let actual = actual
+            .iter()
+            .filter(|event| event.kind == kind)
+            .copied()
+            .collect::<Vec<_>>()
+            .sorted();
+
+        let boundaries = |events: &[LineEvent], bpm_list: &BpmList| {
+            events
+                .iter()
+                .flat_map(|event| [event.start_beat, event.end_beat])
+                .map(|beat| bpm_list.time_at(beat))
+                .collect::<Vec<_>>()
+        };
+        let expected_boundaries = boundaries(&expected, self.expected_bpm_list);
+        let actual_boundaries = boundaries(&actual, self.actual_bpm_list);
+
+        // some formats extend the last event far beyond the chart, which is not worth sampling
+        let Some(limit) = expected_boundaries
+            .iter()
+            .chain(if expected.is_empty() {
+                actual_boundaries.as_slice()
+            } else {
+                &[]
+            })
+            .copied()
+            .reduce(f32::max)
+        else {
+            return;
+        };
+        let limit = limit + 1.0;
+
+        let mut times = expected_boundaries
+            .into_iter()
+            .chain(actual_boundaries)
+            .filter(|time| *time < limit)
+            .chain([limit])
+            .collect::<Vec<_>>();
+        times.sort_by(f32::total_cmp);
+
+        for (start, end) in times.iter().zip(times.iter().skip(1)) {
+            if end - start < 0.01 {
+                continue;
+            }
+
+            for fraction in [0.1, 0.3, 0.5, 0.7, 0.9] {
+                let time = start + (end - start) * fraction;
+                let expected_value =
+                    expected.evaluate_inclusive(self.expected_bpm_list.beat_at(time));
+                let actual_at =
+                    |time: f32| actual.evaluate_inclusive(self.actual_bpm_list.beat_at(time));
+                let actual_value = actual_at(time);
+
+                // on steep curves, a timing drift within the tolerance shows up as a large value drift
+                let window = [time - self.options.time, time + self.options.time].map(actual_at);
+                let low = window.into_iter().fold(actual_value, f32::min);
+                let high = window.into_iter().fold(actual_value, f32::max);
+                let within = (low..=high).contains(&expected_value);
+
+                if !within && !self.close(expected_value, actual_value) {
+                    self.differences.push(Difference::Event {
+                        line: path.to_owned(),
+                        kind,
+                        time,
+                        expected: expected_value,
+                        actual: actual_value,
+                    });
+                    // the first drift of a sequence is enough to locate it
+                    return;
+                }
+            }
+        }
+    }
+
+    fn diff_line(&mut self, path: String, expected: &SerializedLine, actual: &SerializedLine) {
+        // names are not compared, formats such as RPE number the lines when importing
+        let same_attributes = expected.line.texture == actual.line.texture
+            && expected.line.z_order() == actual.line.z_order()
+            && expected.line.is_cover() == actual.line.is_cover()
+            && expected
+                .line
+                .anchor()
+                .iter()
+                .zip(actual.line.anchor())
+                .all(|(expected, actual)| self.close(*expected, actual));
+        if !same_attributes {
+            self.differences.push(Difference::LineAttributes {
+                line: path.clone(),
+                expected: Box::new(expected.line.clone()),
+                actual: Box::new(actual.line.clone()),
+            });
+        }
+
+        self.diff_notes(&path, &expected.notes, &actual.notes);
+
+        for kind in LineEventKind::ALL {
+            self.diff_events(&path, kind, &expected.events, &actual.events);
+        }
+
+        if expected.text_events != actual.text_events {
+            self.differences
+                .push(Difference::TextEvents { line: path.clone() });
+        }
+
+        self.diff_lines(&path, &expected.children, &actual.children);
+    }
+
+    fn diff_lines(&mut self, path: &str, expected: &[SerializedLine], actual: &[SerializedLine]) {
+        if expected.len() != actual.len() {
+            self.differences.push(Difference::Children {
+                line: path.to_owned(),
+                expected: expected.len(),
+                actual: actual.len(),
+            });
+        }
+
+        for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+            self.diff_line(format!("{path}/{index}"), expected, actual);
+        }
+    }
+}
+
+/// Compare two charts semantically, lines are matched by their position in the line tree
+///
+/// Notes and events are compared in seconds, so charts with different BPM lists can be compared.
+/// Events are sampled rather than compared one by one, as formats may split, merge or refit them
+pub fn diff(
+    expected: &PhichainChart,
+    actual: &PhichainChart,
+    options: &DiffOptions,
+) -> Vec<Difference> {
+    let mut differ = Differ {
+        options,
+        expected_bpm_list: &expected.bpm_list,
+        actual_bpm_list: &actual.bpm_list,
+        differences: vec![],
+    };
+
+    // offsets are in milliseconds
+    if !differ.close_time(expected.offset.0 / 1000.0, actual.offset.0 / 1000.0) {
+        differ.differences.push(Difference::Offset {
+            expected: expected.offset.0,
+            actual: actual.offset.0,
+        });
+    }
+
+    if options.bpm_list && expected.bpm_list.0 != actual.bpm_list.0 {
+        differ.differences.push(Difference::BpmList {
+            expected: expected.bpm_list.clone(),
+            actual: actual.bpm_list.clone(),
+        });
+    }
+
+    differ.diff_lines("", &expected.lines, &actual.lines);
+
+    differ.differences
+}
@@ -0,0 +1,303 @@
+use crate::rpe::schema::RPE_EASING;
+use phichain_chart::beat;
+use phichain_chart::beat::Beat;
+use phichain_chart::bpm_list::{BpmList, BpmPoint};
+use phichain_chart::easing::Easing;
+use phichain_chart::event::{LineEvent, LineEventKind, LineEventValue, TextEvent};
+use phichain_chart::line::Line;
+use phichain_chart::note::{Note, NoteKind};
+use phichain_chart::serialization::{PhichainChart, SerializedLine};
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+
+/// Features of the generated charts, so a format is only fed what it is able to represent
+#[derive(Debug, Clone)]
+pub struct GenerateOptions {
+    /// Generate child lines
+    pub children: bool,
+    /// Generate fake notes
+    pub fake_notes: bool,
+    /// Generate note alpha, size, y offset and visible time
+    pub note_attributes: bool,
+    /// Generate line textures, anchors, z-orders and cover flags
+    pub line_attributes: bool,
+    /// Generate scale, color, incline, paint and text events
+    pub extended_events: bool,
+    /// Generate speed events with linear transitions, otherwise speed events are constant
+    pub speed_transitions: bool,
+}
+
+const BASIC_KINDS: [LineEventKind; 5] = [
+    LineEventKind::X,
+    LineEventKind::Y,
+    LineEventKind::Rotation,
+    LineEventKind::Opacity,
+    LineEventKind::Speed,
+];
+
+const EXTENDED_KINDS: [LineEventKind; 4] = [
+    LineEventKind::ScaleX,
+    LineEventKind::ScaleY,
+    LineEventKind::Incline,
+    LineEventKind::Paint,
+];
+
+/// Generates random [`PhichainChart`]s from a seed
+pub struct ChartGenerator {
+    rng: StdRng,
+    options: GenerateOptions,
+}
+
+impl ChartGenerator {
+    pub fn new(seed: u64, options: GenerateOptions) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            options,
+        }
+    }
+
+    /// A beat on a grid of 1/1, 1/2, 1/3, 1/4 or 1/8, between `0` and `max` whole beats
+    fn beat(&mut self, max: i32) -> Beat {
+        let denom = *[1, 2, 3, 4, 8].choose(&mut self.rng).unwrap();
+        beat!(
+            self.rng.random_range(0..max),
+            self.rng.random_range(0..denom),
+            denom
+        )
+    }
+
+    /// A positive duration on the same grid as [`Self::beat`], at most `max` whole beats
+    fn duration(&mut self, max: i32) -> Beat {
+        let denom = *[1, 2, 3, 4].choose(&mut self.rng).unwrap();
+        beat!(self.rng.random_range(1..=max * denom), denom)
+    }
+
+    fn easing(&mut self) -> Easing {
+        if self.rng.random_bool(0.1) {
+            Easing::Custom {
+                x1: self.rng.random_range(0.0..1.0),
+                y1: self.rng.random_range(0.0..1.0),
+                x2: self.rng.random_range(0.0..1.0),
+                y2: self.rng.random_range(0.0..1.0),
+            }
+        } else {
+            *RPE_EASING.choose(&mut self.rng).unwrap()
+        }
+    }
+
+    /// A random value for an event of the kind, integral for kinds stored as integers by some formats
+    fn value(&mut self, kind: LineEventKind) -> f32 {
+        match kind {
+            LineEventKind::X => self.rng.random_range(-600.0..600.0),
+            LineEventKind::Y => self.rng.random_range(-400.0..400.0),
+            LineEventKind::Rotation => self.rng.random_range(-360.0..360.0),
+            LineEventKind::Opacity
+            | LineEventKind::ColorR
+            | LineEventKind::ColorG
+            | LineEventKind::ColorB => self.rng.random_range(0..=255) as f32,
+            LineEventKind::Speed => self.rng.random_range(1.0..15.0),
+            LineEventKind::ScaleX | LineEventKind::ScaleY => self.rng.random_range(0.5..2.0),
+            LineEventKind::Incline => self.rng.random_range(-1.0..1.0),
+            LineEventKind::Paint => self.rng.random_range(-100..=100) as f32,
+        }
+    }
+
+    /// Start and end beats of a sequence of events, which never overlap and sometimes leave gaps
+    fn ranges(&mut self) -> Vec<(Beat, Beat)> {
+        let mut start = self.beat(2);
+        (0..self.rng.random_range(1..8))
+            .map(|_| {
+                if self.rng.random_bool(0.3) {
+                    start += self.duration(2);
+                }
+                let end = start + self.duration(4);
+                let range = (start, end);
+                start = end;
+                range
+            })
+            .collect()
+    }
+
+    fn events(&mut self, kind: LineEventKind) -> Vec<LineEvent> {
+        self.ranges()
+            .into_iter()
+            .map(|(start_beat, end_beat)| {
+                let constant = if kind.is_speed() {
+                    !self.options.speed_transitions || self.rng.random_bool(0.5)
+                } else {
+                    self.rng.random_bool(0.3)
+                };
+
+                let value = if constant {
+                    LineEventValue::constant(self.value(kind))
+                } else {
+                    let easing = if kind.is_speed() {
+                        // RPE speed events are always linear
+                        Easing::Linear
+                    } else {
+                        self.easing()
+                    };
+                    LineEventValue::transition(self.value(kind), self.value(kind), easing)
+                };
+
+                LineEvent {
+                    id: None,
+                    kind,
+                    start_beat,
+                    end_beat,
+                    value,
+                }
+            })
+            .collect()
+    }
+
+    /// Color events sharing the same timing and easing across the channels
+    fn color_events(&mut self) -> Vec<LineEvent> {
+        let mut events = vec![];
+        for (start_beat, end_beat) in self.ranges() {
+            let easing = self.easing();
+            for kind in [
+                LineEventKind::ColorR,
+                LineEventKind::ColorG,
+                LineEventKind::ColorB,
+            ] {
+                events.push(LineEvent {
+                    id: None,
+                    kind,
+                    start_beat,
+                    end_beat,
+                    value: LineEventValue::transition(self.value(kind), self.value(kind), easing),
+                });
+            }
+        }
+
+        events
+    }
+
+    fn text_events(&mut self) -> Vec<TextEvent> {
+        self.ranges()
+            .into_iter()
+            .map(|(start_beat, end_beat)| TextEvent {
+                start_beat,
+                end_beat,
+                start: "phi".to_owned(),
+                end: "phichain".to_owned(),
+                easing: self.easing(),
+            })
+            .collect()
+    }
+
+    fn note(&mut self, beat: Beat) -> Note {
+        let kind = match self.rng.random_range(0..4) {
+            0 => NoteKind::Tap,
+            1 => NoteKind::Drag,
+            2 => NoteKind::Hold {
+                hold_beat: self.duration(4),
+            },
+            _ => NoteKind::Flick,
+        };
+
+        let mut note = Note::new(
+            kind,
+            self.rng.random_bool(0.8),
+            beat,
+            self.rng.random_range(-600.0..600.0),
+            self.rng.random_range(0.5..3.0),
+        );
+
+        if self.options.fake_notes {
+            note.is_fake = self.rng.random_bool(0.1);
+        }
+
+        if self.options.note_attributes && self.rng.random_bool(0.3) {
+            note.alpha = Some(self.rng.random_range(0..255));
+            note.size = Some(self.rng.random_range(0.5..2.0));
+            note.y_offset = Some(self.rng.random_range(-100.0..100.0));
+            note.visible_time = Some(self.rng.random_range(0.5..5.0));
+        }
+
+        note
+    }
+
+    fn line(&mut self, name: String, depth: usize) -> SerializedLine {
+        let mut line = Line {
+            name,
+            ..Default::default()
+        };
+        if self.options.line_attributes && self.rng.random_bool(0.5) {
+            line.texture = Some("textures/line.png".to_owned());
+            line.anchor = Some([
+                self.rng.random_range(0.0..1.0),
+                self.rng.random_range(0.0..1.0),
+            ]);
+            line.z_order = Some(self.rng.random_range(-5..5));
+            line.cover = Some(self.rng.random_bool(0.5));
+        }
+
+        // notes on a line never share a beat, so they can be matched by order
+        let mut beats = (0..self.rng.random_range(0..24))
+            .map(|_| self.beat(32))
+            .collect::<Vec<_>>();
+        beats.sort();
+        beats.dedup();
+        let notes = beats.into_iter().map(|beat| self.note(beat)).collect();
+
+        let mut events = vec![];
+        for kind in BASIC_KINDS {
+            events.append(&mut self.events(kind));
+        }
+        let mut text_events = vec![];
+        if self.options.extended_events {
+            for kind in EXTENDED_KINDS {
+                if self.rng.random_bool(0.5) {
+                    events.append(&mut self.events(kind));
+                }
+            }
+            if self.rng.random_bool(0.5) {
+                events.append(&mut self.color_events());
+            }
+            if self.rng.random_bool(0.3) {
+                text_events = self.text_events();
+            }
+        }
+
+        let children = if self.options.children && depth < 2 {
+            (0..self.rng.random_range(0..3))
+                .map(|index| self.line(format!("{}.{}", line.name, index), depth + 1))
+                .collect()
+        } else {
+            vec![]
+        };
+
+        SerializedLine {
+            text_events,
+            ..SerializedLine::new(line, notes, events, children, vec![])
+        }
+    }
+
+    pub fn chart(&mut self) -> PhichainChart {
+        let mut bpm_points = vec![BpmPoint::new(
+            beat!(0),
+            self.rng.random_range(60..240) as f32,
+        )];
+        for _ in 0..self.rng.random_range(0..3) {
+            let beat = self.beat(32);
+            if bpm_points.iter().all(|point| point.beat != beat) {
+                bpm_points.push(BpmPoint::new(beat, self.rng.random_range(60..240) as f32));
+            }
+        }
+        bpm_points.sort_by_key(|point| point.beat);
+
+        let lines = (0..self.rng.random_range(1..5))
+            .map(|index| self.line(index.to_string(), 0))
+            .collect();
+
+        PhichainChart::new(
+            // RPE stores the offset in whole milliseconds
+            self.rng.random_range(-500..500) as f32,
+            BpmList::new(bpm_points),
+            lines,
+        )
+    }
+}
@@ -0,0 +1,139 @@
+//! Round-trip tests pushing randomly generated charts through each format and back
+//!
+//! Each case is generated from a seed, a failing case reports its seed along with the semantic
+//! differences between the original chart and the converted one
+
+mod diff;
+mod generate;
+
+use crate::compile::steps::merge_children_line;
+use crate::official::{OfficialChart, OfficialInputOptions, OfficialOutputOptions};
+use crate::rpe::RpeChart;
+use crate::ChartFormat;
+use diff::{diff, DiffOptions};
+use generate::{ChartGenerator, GenerateOptions};
+use phichain_chart::note::NoteKind;
+use phichain_chart::serialization::PhichainChart;
+use phichain_compiler::helpers::cut;
+use phichain_compiler::sequence::EventSequence;
+
+/// Convert `cases` charts generated from consecutive seeds, asserting `convert` does not drift from `expected`
+fn check(
+    cases: u64,
+    options: &GenerateOptions,
+    diff_options: &DiffOptions,
+    expected: impl Fn(PhichainChart) -> PhichainChart,
+    convert: impl Fn(PhichainChart) -> PhichainChart,
+) {
+    let mut failures = vec![];
+
+    for seed in 0..cases {
+        let generate = || ChartGenerator::new(seed, options.clone()).chart();
+        let differences = diff(&expected(generate()), &convert(generate()), diff_options);
+
+        if !differences.is_empty() {
+            let report = differences
+                .iter()
+                .map(|difference| format!("  {difference}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            failures.push(format!("seed {seed}:\n{report}"));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} of {} charts drifted after the round trip\n{}",
+        failures.len(),
+        cases,
+        failures.join("\n")
+    );
+}
+
+/// What the official format is able to keep of a chart
+fn official_expected(chart: PhichainChart) -> PhichainChart {
+    // the official format has no child lines
+    let mut chart = merge_children_line(chart);
+
+    for line in &mut chart.lines {
+        // eased events are exported as linear segments
+        line.events = line
+            .events
+            .iter()
+            .flat_map(|event| cut(*event, OfficialOutputOptions::default().minimum_beat))
+            .collect();
+
+        // hold speeds are stored multiplied by the line speed, which is lost on a stopped line
+        let speed_events = line.events.speed().sorted();
+        for note in &mut line.notes {
+            if matches!(note.kind, NoteKind::Hold { .. })
+                && speed_events.evaluate_inclusive(note.beat) == 0.0
+            {
+                note.speed = 0.0;
+            }
+        }
+    }
+
+    chart
+}
+
+#[test]
+fn test_rpe_round_trip() {
+    check(
+        64,
+        &GenerateOptions {
+            children: true,
+            fake_notes: true,
+            note_attributes: true,
+            line_attributes: true,
+            extended_events: true,
+            speed_transitions: true,
+        },
+        &DiffOptions {
+            time: 1e-3,
+            value: 1e-4,
+            bpm_list: true,
+        },
+        |chart| chart,
+        |chart| {
+            let rpe = RpeChart::from_phichain(chart, &()).unwrap();
+            let rpe: RpeChart =
+                serde_json::from_str(&serde_json::to_string(&rpe).unwrap()).unwrap();
+            rpe.to_phichain(&Default::default()).unwrap()
+        },
+    );
+}
+
+#[test]
+fn test_official_round_trip() {
+    // fewer cases, merging child lines and cutting eased events into segments is slow
+    check(
+        8,
+        &GenerateOptions {
+            children: true,
+            fake_notes: false,
+            note_attributes: false,
+            line_attributes: false,
+            extended_events: false,
+            speed_transitions: false,
+        },
+        &DiffOptions {
+            time: 1e-3,
+            value: 1e-2,
+            bpm_list: false,
+        },
+        official_expected,
+        |chart| {
+            let official = OfficialChart::from_phichain(chart, &Default::default()).unwrap();
+            let official: OfficialChart =
+                serde_json::from_str(&serde_json::to_string(&official).unwrap()).unwrap();
+            // easing fitting is lossy by design, the imported curves are compared segment by segment
+            official
+                .to_phichain(&OfficialInputOptions {
+                    easing_fitting: false,
+                    ..Default::default()
+                })
+                .unwrap()
+        },
+    );
+}
@@ -1,12 +1,16 @@
 use crate::compile::steps::evaluate_curve_note_tracks;
 use crate::rpe::schema::{
-    RpeBpmPoint, RpeChart, RpeCommonEvent, RpeEventLayer, RpeJudgeLine, RpeMeta, RpeNote,
-    RpeNoteKind, RpeSpeedEvent, RPE_EASING,
+    RpeBpmPoint, RpeChart, RpeColor, RpeCommonEvent, RpeEventLayer, RpeExtendedEvents,
+    RpeJudgeLine, RpeMeta, RpeNote, RpeNoteKind, RpeSpeedEvent, RPE_ALWAYS_VISIBLE,
+    RPE_DEFAULT_TEXTURE, RPE_EASING,
 };
+use itertools::Itertools;
+use phichain_chart::beat::Beat;
 use phichain_chart::easing::Easing;
-use phichain_chart::event::{LineEvent, LineEventKind};
+use phichain_chart::event::{Boundary, LineEvent, LineEventKind, TextEvent};
 use phichain_chart::note::{Note, NoteKind};
 use phichain_chart::serialization::{PhichainChart, SerializedLine};
+use phichain_compiler::sequence::EventSequence;
 use tracing::warn;
 
 struct RpeEasingInfo {
@@ -64,6 +68,11 @@ fn note(note: &Note) -> RpeNote {
         },
         position_x: note.x,
         speed: note.speed,
+        alpha: note.alpha() as i32,
+        size: note.size(),
+        y_offset: note.y_offset(),
+        visible_time: note.visible_time.unwrap_or(RPE_ALWAYS_VISIBLE),
+        is_fake: if note.is_fake { 1 } else { 0 },
         // TODO: impl Into<RpeNoteKind> for NoteKind
         kind: match note.kind {
             NoteKind::Tap => RpeNoteKind::Tap,
@@ -71,8 +80,6 @@ fn note(note: &Note) -> RpeNote {
             NoteKind::Hold { .. } => RpeNoteKind::Hold,
             NoteKind::Flick => RpeNoteKind::Flick,
         },
-
-        ..Default::default()
     }
 }
 
@@ -119,7 +126,8 @@ fn event_layer_from_line(line: &SerializedLine) -> RpeEventLayer {
                             end_time: event.end_beat.into(),
                         });
                     }
-                    _ => unreachable!(),
+                    // extended events are exported by `extended_events_from_line`
+                    _ => {}
                 }
             }
         }
@@ -128,14 +136,157 @@ fn event_layer_from_line(line: &SerializedLine) -> RpeEventLayer {
     event_layer
 }
 
+fn text_event(event: &TextEvent) -> RpeCommonEvent<String> {
+    let easing_info = easing(event.easing);
+
+    RpeCommonEvent {
+        bezier: easing_info.bezier,
+        bezier_points: easing_info.bezier_points,
+        easing_type: easing_info.easing_type,
+        start: event.start.clone(),
+        start_time: event.start_beat.into(),
+        end: event.end.clone(),
+        end_time: event.end_beat.into(),
+    }
+}
+
+fn color_channel(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+/// Merge the color channels of a line into RPE color events
+///
+/// Channels sharing the same timing and easing are merged event by event.
+/// Otherwise, the channels are sampled at every event boundary and joined with linear transitions
+fn color_events_from_line(line: &SerializedLine) -> Vec<RpeCommonEvent<RpeColor>> {
+    let channels = [
+        LineEventKind::ColorR,
+        LineEventKind::ColorG,
+        LineEventKind::ColorB,
+    ]
+    .map(|kind| {
+        line.events
+            .iter()
+            .filter(|event| event.kind == kind)
+            .copied()
+            .collect::<Vec<_>>()
+            .sorted()
+    });
+
+    if channels.iter().all(Vec::is_empty) {
+        return vec![];
+    }
+
+    let [r, g, b] = &channels;
+    let aligned = r.len() == g.len()
+        && r.len() == b.len()
+        && r.iter().zip(g).zip(b).all(|((r, g), b)| {
+            [g, b].iter().all(|other| {
+                other.start_beat == r.start_beat
+                    && other.end_beat == r.end_beat
+                    && other.value.easing() == r.value.easing()
+            })
+        });
+
+    if aligned {
+        return r
+            .iter()
+            .zip(g)
+            .zip(b)
+            .map(|((r, g), b)| {
+                let easing_info = easing(r.value.easing());
+                RpeCommonEvent {
+                    bezier: easing_info.bezier,
+                    bezier_points: easing_info.bezier_points,
+                    easing_type: easing_info.easing_type,
+                    start: [r, g, b].map(|event| color_channel(event.value.start())),
+                    start_time: r.start_beat.into(),
+                    end: [r, g, b].map(|event| color_channel(event.value.end())),
+                    end_time: r.end_beat.into(),
+                }
+            })
+            .collect();
+    }
+
+    warn!(
+        "Color events of line {} are not aligned across channels, they will be sampled into linear color events",
+        line.line.name
+    );
+
+    let sample = |beat: Beat, boundary: Boundary| {
+        channels.each_ref().map(|channel| {
+            let before_first = channel.first().is_none_or(|first| match boundary {
+                Boundary::Inclusive => beat < first.start_beat,
+                Boundary::Exclusive => beat <= first.start_beat,
+            });
+            color_channel(if before_first {
+                LineEventKind::ColorR.default_value()
+            } else {
+                channel.evaluate(beat, boundary)
+            })
+        })
+    };
+
+    channels
+        .iter()
+        .flatten()
+        .flat_map(|event| [event.start_beat, event.end_beat])
+        .sorted()
+        .dedup()
+        .tuple_windows()
+        .map(|(start_beat, end_beat)| RpeCommonEvent {
+            bezier: 0,
+            bezier_points: [0.0; 4],
+            easing_type: 1,
+            start: sample(start_beat, Boundary::Inclusive),
+            start_time: start_beat.into(),
+            end: sample(end_beat, Boundary::Exclusive),
+            end_time: end_beat.into(),
+        })
+        .collect()
+}
+
+/// Collect the extended events of a line, [`None`] if the line does not have any
+fn extended_events_from_line(line: &SerializedLine) -> Option<RpeExtendedEvents> {
+    let mut extended = RpeExtendedEvents::default();
+
+    for event in &line.events {
+        let target = match event.kind {
+            LineEventKind::ScaleX => &mut extended.scale_x_events,
+            LineEventKind::ScaleY => &mut extended.scale_y_events,
+            LineEventKind::Incline => &mut extended.incline_events,
+            LineEventKind::Paint => &mut extended.paint_events,
+            _ => continue,
+        };
+        target.push(common_event_from_line_event(event));
+    }
+
+    extended.color_events = color_events_from_line(line);
+    extended.text_events = line.text_events.iter().map(text_event).collect();
+
+    (extended != RpeExtendedEvents::default()).then_some(extended)
+}
+
 fn push_line(line: &SerializedLine, parent_index: Option<usize>, target: &mut Vec<RpeJudgeLine>) {
     let event_layer = event_layer_from_line(line);
     let current_index = target.len();
     target.push(RpeJudgeLine {
         name: line.line.name.clone(),
+        texture: line
+            .line
+            .texture
+            .clone()
+            .unwrap_or_else(|| RPE_DEFAULT_TEXTURE.to_owned()),
+        anchor: {
+            let [x, y] = line.line.anchor();
+            (x, y)
+        },
+        z_order: line.line.z_order(),
+        is_cover: if line.line.is_cover() { 1 } else { 0 },
         father: parent_index.map(|i| i as i32).unwrap_or(-1),
         rotate_with_father: true,
         event_layers: vec![event_layer],
+        extended: extended_events_from_line(line),
         notes: line.notes.iter().map(note).collect(),
         // does not include holds, ref: https://teamflos.github.io/phira-docs/chart-standard/chart-format/rpe/judgeLine.html
         num_of_notes: line
@@ -178,3 +329,126 @@ pub fn phichain_to_rpe(phichain: PhichainChart) -> RpeChart {
 
     rpe
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpe::into_phichain::rpe_to_phichain;
+    use crate::rpe::RpeInputOptions;
+    use phichain_chart::beat;
+    use phichain_chart::event;
+
+    #[test]
+    fn test_aligned_color_channels_are_merged() {
+        let line = SerializedLine {
+            events: vec![
+                event!(LineEventKind::ColorR, beat!(0) => beat!(1), 255.0 => 0.0),
+                event!(LineEventKind::ColorG, beat!(0) => beat!(1), 0.0 => 255.0),
+                event!(LineEventKind::ColorB, beat!(0) => beat!(1), 0.0),
+            ],
+            ..Default::default()
+        };
+
+        let events = color_events_from_line(&line);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].start, [255, 0, 0]);
+        assert_eq!(events[0].end, [0, 255, 0]);
+    }
+
+    #[test]
+    fn test_unaligned_color_channels_are_sampled() {
+        let line = SerializedLine {
+            events: vec![
+                event!(LineEventKind::ColorR, beat!(0) => beat!(2), 0.0 => 200.0),
+                event!(LineEventKind::ColorG, beat!(1) => beat!(2), 100.0),
+            ],
+            ..Default::default()
+        };
+
+        let events = color_events_from_line(&line);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].start, [0, 255, 255]);
+        assert_eq!(events[0].end, [100, 255, 255]);
+        assert_eq!(events[1].start, [100, 100, 255]);
+        assert_eq!(events[1].end, [200, 100, 255]);
+    }
+
+    #[test]
+    fn test_note_visual_attributes_round_trip() {
+        let mut line = SerializedLine::default();
+        line.notes.push(Note {
+            alpha: Some(100),
+            size: Some(1.5),
+            y_offset: Some(-20.0),
+            visible_time: Some(0.5),
+            ..Note::new(NoteKind::Tap, true, beat!(1), 0.0, 1.0)
+        });
+        line.notes.push(Note {
+            is_fake: true,
+            ..Note::new(NoteKind::Flick, true, beat!(3, 1, 2), -50.0, 1.0)
+        });
+        line.notes
+            .push(Note::new(NoteKind::Drag, false, beat!(2), 10.0, 1.0));
+
+        let chart = PhichainChart::new(0.0, Default::default(), vec![line.clone()]);
+        let rpe = phichain_to_rpe(chart);
+        let converted = rpe_to_phichain(rpe, &RpeInputOptions::default()).unwrap();
+
+        assert_eq!(converted.lines[0].notes, line.notes);
+    }
+
+    #[test]
+    fn test_line_attributes_round_trip() {
+        let mut line = SerializedLine::default();
+        line.line.texture = Some("cat.png".to_owned());
+        line.line.anchor = Some([0.0, 1.0]);
+        line.line.z_order = Some(3);
+        line.line.cover = Some(false);
+
+        let chart = PhichainChart::new(0.0, Default::default(), vec![line.clone()]);
+        let rpe = phichain_to_rpe(chart);
+        assert_eq!(rpe.judge_line_list[0].texture, "cat.png");
+        assert_eq!(rpe.judge_line_list[0].is_cover, 0);
+
+        let converted = rpe_to_phichain(rpe, &RpeInputOptions::default()).unwrap();
+        let converted = &converted.lines[0].line;
+        assert_eq!(converted.texture, line.line.texture);
+        assert_eq!(converted.anchor, line.line.anchor);
+        assert_eq!(converted.z_order, line.line.z_order);
+        assert_eq!(converted.cover, line.line.cover);
+    }
+
+    #[test]
+    fn test_extended_events_round_trip() {
+        let mut line = SerializedLine::default();
+        line.events.extend([
+            event!(LineEventKind::ScaleX, beat!(0) => beat!(1), 1.0 => 2.0),
+            event!(LineEventKind::Incline, beat!(0) => beat!(1), 30.0),
+            event!(LineEventKind::Paint, beat!(0) => beat!(1), 1.0),
+            event!(LineEventKind::ColorR, beat!(0) => beat!(1), 10.0),
+            event!(LineEventKind::ColorG, beat!(0) => beat!(1), 20.0),
+            event!(LineEventKind::ColorB, beat!(0) => beat!(1), 30.0),
+        ]);
+        line.text_events.push(TextEvent {
+            start_beat: beat!(0),
+            end_beat: beat!(1),
+            start: "".to_owned(),
+            end: "phichain".to_owned(),
+            easing: Easing::EaseInSine,
+        });
+
+        let chart = PhichainChart::new(0.0, Default::default(), vec![line.clone()]);
+        let rpe = phichain_to_rpe(chart);
+        let converted = rpe_to_phichain(rpe, &RpeInputOptions::default()).unwrap();
+
+        let converted_line = &converted.lines[0];
+        for event in line.events.iter().filter(|event| event.kind.is_extended()) {
+            assert!(
+                converted_line.events.contains(event),
+                "missing event {:?}",
+                event
+            );
+        }
+        assert_eq!(converted_line.text_events, line.text_events);
+    }
+}
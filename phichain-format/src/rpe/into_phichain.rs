@@ -2,14 +2,15 @@ use std::collections::HashMap;
 
 use crate::rpe::errors::RpeInputError;
 use crate::rpe::schema::{
-    RpeChart, RpeCommonEvent, RpeEventLayer, RpeNote, RpeNoteKind, RPE_EASING,
+    RpeChart, RpeCommonEvent, RpeEventLayer, RpeExtendedEvents, RpeJudgeLine, RpeNote, RpeNoteKind,
+    RPE_ALWAYS_VISIBLE, RPE_DEFAULT_TEXTURE, RPE_EASING,
 };
 use crate::rpe::RpeInputOptions;
 use num::{Num, ToPrimitive};
 use phichain_chart::beat::Beat;
 use phichain_chart::bpm_list::{BpmList, BpmPoint};
 use phichain_chart::easing::Easing;
-use phichain_chart::event::{LineEvent, LineEventKind, LineEventValue};
+use phichain_chart::event::{LineEvent, LineEventKind, LineEventValue, TextEvent};
 use phichain_chart::line::Line;
 use phichain_chart::note::{Note, NoteKind};
 use phichain_chart::offset::Offset;
@@ -39,13 +40,20 @@ fn convert_rpe_notes(rpe_notes: &[RpeNote]) -> Result<Vec<Note>, RpeInputError>
                 RpeNoteKind::Flick => NoteKind::Flick,
             };
 
-            Ok(Note::new(
-                kind,
-                note.above == 1,
-                start_beat,
-                note.position_x,
-                note.speed,
-            ))
+            Ok(Note {
+                alpha: (note.alpha != 255).then(|| note.alpha.clamp(0, 255) as u8),
+                size: (note.size != 1.0).then_some(note.size),
+                y_offset: (note.y_offset != 0.0).then_some(note.y_offset),
+                visible_time: (note.visible_time < RPE_ALWAYS_VISIBLE).then_some(note.visible_time),
+                is_fake: note.is_fake == 1,
+                ..Note::new(
+                    kind,
+                    note.above == 1,
+                    start_beat,
+                    note.position_x,
+                    note.speed,
+                )
+            })
         })
         .collect()
 }
@@ -58,12 +66,9 @@ fn rpe_easing(id: i32) -> Easing {
     })
 }
 
-/// Convert a single [RpeCommonEvent] to phichain's [LineEvent]
-fn convert_event<T: Num + ToPrimitive>(
-    kind: LineEventKind,
-    event: RpeCommonEvent<T>,
-) -> Result<LineEvent, RpeInputError> {
-    let easing = if event.bezier == 1 {
+/// Resolve the easing of a [RpeCommonEvent], taking custom bezier curves into account
+fn convert_easing<T>(event: &RpeCommonEvent<T>) -> Easing {
+    if event.bezier == 1 {
         let [a, b, c, d] = event.bezier_points;
         Easing::Custom {
             x1: a,
@@ -73,9 +78,18 @@ fn convert_event<T: Num + ToPrimitive>(
         }
     } else {
         rpe_easing(event.easing_type)
-    };
+    }
+}
+
+/// Convert a single [RpeCommonEvent] to phichain's [LineEvent]
+fn convert_event<T: Num + ToPrimitive>(
+    kind: LineEventKind,
+    event: RpeCommonEvent<T>,
+) -> Result<LineEvent, RpeInputError> {
+    let easing = convert_easing(&event);
 
     Ok(LineEvent {
+        id: None,
         kind,
         start_beat: event.start_time.try_into()?,
         end_beat: event.end_time.try_into()?,
@@ -122,6 +136,7 @@ fn convert_event_layer(layer: &RpeEventLayer) -> Result<Vec<LineEvent>, RpeInput
     // Convert speed events
     for event in &layer.speed_events {
         events.push(LineEvent {
+            id: None,
             kind: LineEventKind::Speed,
             start_beat: event.start_time.clone().try_into()?,
             end_beat: event.end_time.clone().try_into()?,
@@ -136,6 +151,72 @@ fn convert_event_layer(layer: &RpeEventLayer) -> Result<Vec<LineEvent>, RpeInput
     Ok(events)
 }
 
+/// Convert [RpeExtendedEvents] to [LineEvent]s and [TextEvent]s
+///
+/// Each color event is split into three events, one for each channel
+fn convert_extended_events(
+    extended: &RpeExtendedEvents,
+) -> Result<(Vec<LineEvent>, Vec<TextEvent>), RpeInputError> {
+    let mut events = Vec::new();
+
+    for (kind, rpe_events) in [
+        (LineEventKind::ScaleX, &extended.scale_x_events),
+        (LineEventKind::ScaleY, &extended.scale_y_events),
+        (LineEventKind::Incline, &extended.incline_events),
+        (LineEventKind::Paint, &extended.paint_events),
+    ] {
+        for event in rpe_events {
+            events.push(convert_event(kind, event.clone())?);
+        }
+    }
+
+    for event in &extended.color_events {
+        for (channel, kind) in [
+            LineEventKind::ColorR,
+            LineEventKind::ColorG,
+            LineEventKind::ColorB,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            events.push(convert_event(
+                kind,
+                event.clone().map(|color| color[channel] as i32),
+            )?);
+        }
+    }
+
+    let text_events = extended
+        .text_events
+        .iter()
+        .map(|event| -> Result<TextEvent, RpeInputError> {
+            Ok(TextEvent {
+                start_beat: event.start_time.clone().try_into()?,
+                end_beat: event.end_time.clone().try_into()?,
+                start: event.start.clone(),
+                end: event.end.clone(),
+                easing: convert_easing(event),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((events, text_events))
+}
+
+/// Convert the texture, anchor, z-order and cover flag of an RPE line, leaving the name as default
+fn convert_line_attributes(rpe_line: &RpeJudgeLine) -> Line {
+    let (anchor_x, anchor_y) = rpe_line.anchor;
+
+    Line {
+        texture: (!rpe_line.texture.is_empty() && rpe_line.texture != RPE_DEFAULT_TEXTURE)
+            .then(|| rpe_line.texture.clone()),
+        anchor: (rpe_line.anchor != (0.5, 0.5)).then_some([anchor_x, anchor_y]),
+        z_order: (rpe_line.z_order != 0).then_some(rpe_line.z_order),
+        cover: (rpe_line.is_cover != 1).then_some(false),
+        ..Default::default()
+    }
+}
+
 /// Extract the first event layer, ignoring all other layers
 ///
 /// In RPE, event layers are additive - the final value is the sum of all layers.
@@ -153,7 +234,9 @@ fn extract_first_layer(event_layers: Vec<RpeEventLayer>) -> RpeEventLayer {
 fn build_flattened_line(
     line_index: usize,
     line_name: &str,
+    attributes: Line,
     event_layers: Vec<RpeEventLayer>,
+    extended: Option<RpeExtendedEvents>,
     notes: Vec<Note>,
 ) -> Result<SerializedLine, RpeInputError> {
     // Format line name
@@ -177,14 +260,25 @@ fn build_flattened_line(
     let first_layer = extract_first_layer(event_layers);
 
     // Convert the first layer to events
-    let events = convert_event_layer(&first_layer)?;
+    let mut events = convert_event_layer(&first_layer)?;
+
+    // Extended events are not layered, convert them as a whole
+    let text_events = match extended {
+        Some(extended) => {
+            let (mut extended_events, text_events) = convert_extended_events(&extended)?;
+            events.append(&mut extended_events);
+            text_events
+        }
+        None => vec![],
+    };
 
     Ok(SerializedLine {
-        line: Line { name },
+        line: Line { name, ..attributes },
         notes,
         events,
         children: vec![],
         curve_note_tracks: vec![],
+        text_events,
     })
 }
 
@@ -222,6 +316,7 @@ pub fn rpe_to_phichain(
                     rpe_line.num_of_notes = 0;
                     rpe_line.notes.clear();
                     rpe_line.event_layers.clear();
+                    rpe_line.extended = None;
                 } else {
                     warn!(
                         "Line {} has attachUI = {:?}, but Phichain doesn't support UI control lines. \
@@ -246,7 +341,15 @@ pub fn rpe_to_phichain(
             };
 
             let notes = convert_rpe_notes(&filtered_notes)?;
-            let line = build_flattened_line(index, &rpe_line.name, rpe_line.event_layers, notes)?;
+            let attributes = convert_line_attributes(&rpe_line);
+            let line = build_flattened_line(
+                index,
+                &rpe_line.name,
+                attributes,
+                rpe_line.event_layers,
+                rpe_line.extended,
+                notes,
+            )?;
             Ok(LineWithParent {
                 line,
                 father: rpe_line.father,
@@ -278,7 +381,11 @@ pub fn rpe_to_phichain(
 }
 
 fn is_empty_placeholder_line(line: &SerializedLine) -> bool {
-    line.notes.is_empty() && line.curve_note_tracks.is_empty() && line.events.is_empty()
+    line.line.texture.is_none()
+        && line.notes.is_empty()
+        && line.curve_note_tracks.is_empty()
+        && line.events.is_empty()
+        && line.text_events.is_empty()
 }
 
 fn remove_empty_placeholder_lines(lines: Vec<SerializedLine>) -> Vec<SerializedLine> {
@@ -414,11 +521,13 @@ mod tests {
             line: SerializedLine {
                 line: Line {
                     name: name.to_string(),
+                    ..Default::default()
                 },
                 notes: vec![],
                 events: vec![],
                 children: vec![],
                 curve_note_tracks: vec![],
+                text_events: vec![],
             },
             father,
             rotate_with_father: true,
@@ -525,4 +634,43 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn convert_extended_events_splits_color_channels() {
+        let extended = RpeExtendedEvents {
+            color_events: vec![RpeCommonEvent {
+                bezier: 0,
+                bezier_points: [0.0; 4],
+                easing_type: 1,
+                start: [255, 128, 0],
+                start_time: RpeBeat(0, 0, 1),
+                end: [255, 0, 0],
+                end_time: RpeBeat(2, 0, 1),
+            }],
+            text_events: vec![RpeCommonEvent {
+                bezier: 0,
+                bezier_points: [0.0; 4],
+                easing_type: 1,
+                start: "".to_owned(),
+                start_time: RpeBeat(0, 0, 1),
+                end: "phichain".to_owned(),
+                end_time: RpeBeat(1, 0, 1),
+            }],
+            ..Default::default()
+        };
+
+        let (events, text_events) = convert_extended_events(&extended).unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].kind, LineEventKind::ColorR);
+        assert_eq!(events[0].value, LineEventValue::constant(255.0));
+        assert_eq!(events[1].kind, LineEventKind::ColorG);
+        assert_eq!(
+            events[1].value,
+            LineEventValue::transition(128.0, 0.0, Easing::Linear)
+        );
+        assert_eq!(events[2].kind, LineEventKind::ColorB);
+
+        assert_eq!(text_events.len(), 1);
+        assert_eq!(text_events[0].end, "phichain");
+    }
 }
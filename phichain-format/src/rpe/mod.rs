@@ -1,3 +1,4 @@
+use crate::detect::Sniff;
 use crate::rpe::from_phichain::phichain_to_rpe;
 use crate::rpe::into_phichain::rpe_to_phichain;
 use crate::{ChartFormat, CommonOutputOptions};
@@ -8,7 +9,7 @@ pub mod errors;
 mod from_phichain;
 mod into_phichain;
 mod options;
-mod schema;
+pub(crate) mod schema;
 
 pub use errors::RpeInputError;
 pub use options::RpeInputOptions;
@@ -31,6 +32,10 @@ impl ChartFormat for RpeChart {
         Ok(phichain_to_rpe(phichain))
     }
 
+    fn sniff(value: &serde_json::Value) -> Sniff {
+        Sniff::keys(value, &["BPMList", "META"])
+    }
+
     fn apply_common_output_options(mut self, common_options: &CommonOutputOptions) -> Self {
         let round = |value: f32| -> f32 {
             let multiplier = 10_f32.powi(common_options.round as i32);
@@ -62,6 +67,19 @@ impl ChartFormat for RpeChart {
                 }
             }
 
+            if let Some(extended) = &mut line.extended {
+                for event in extended
+                    .scale_x_events
+                    .iter_mut()
+                    .chain(&mut extended.scale_y_events)
+                    .chain(&mut extended.incline_events)
+                    .chain(&mut extended.paint_events)
+                {
+                    event.start = round(event.start);
+                    event.end = round(event.end);
+                }
+            }
+
             for note in &mut line.notes {
                 note.position_x = round(note.position_x);
             }
@@ -3,7 +3,7 @@
 //! Credit: https://teamflos.github.io/phira-docs/chart-standard/chart-format/rpe
 
 use crate::rpe::errors::RpeInputError;
-use num::{Num, Rational32};
+use num::Rational32;
 use phichain_chart::beat::Beat;
 use phichain_chart::easing::Easing;
 use serde::{Deserialize, Serialize};
@@ -94,9 +94,10 @@ pub struct RpeJudgeLine {
     #[serde(rename = "Name")]
     pub name: String,
 
+    /// Path of the line texture relative to the chart, [`RPE_DEFAULT_TEXTURE`] means the default line
     #[serde(rename = "Texture")]
-    pub texture: String, // ignored
-    pub anchor: (f32, f32), // ignored
+    pub texture: String,
+    pub anchor: (f32, f32),
 
     /// Before a certain version, this field may be `null` when layers are empty.
     /// In newer versions, the field may be missing when layers are empty.
@@ -128,14 +129,21 @@ pub struct RpeJudgeLine {
     /// Does the child line inherit the parent line's rotation angle
     #[serde(rename = "rotateWithFather")]
     pub rotate_with_father: bool,
+
+    /// Extended event layer, holding events that are not additive across layers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extended: Option<RpeExtendedEvents>,
 }
 
+/// The texture of lines without a custom texture
+pub const RPE_DEFAULT_TEXTURE: &str = "line.png";
+
 impl Default for RpeJudgeLine {
     fn default() -> Self {
         Self {
             group: 0,
             name: "Untitled".to_string(),
-            texture: "line.png".to_string(),
+            texture: RPE_DEFAULT_TEXTURE.to_string(),
             anchor: (0.5, 0.5),
             event_layers: Default::default(),
             father: -1,
@@ -146,6 +154,7 @@ impl Default for RpeJudgeLine {
             attach_ui: None,
             is_gif: false,
             rotate_with_father: true,
+            extended: None,
         }
     }
 }
@@ -165,9 +174,25 @@ pub struct RpeEventLayer {
     pub speed_events: Vec<RpeSpeedEvent>,
 }
 
+/// An RGB color, each channel ranging from 0 to 255
+pub type RpeColor = [u8; 3];
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RpeExtendedEvents {
+    #[serde(rename = "scaleXEvents")]
+    pub scale_x_events: Vec<RpeCommonEvent<f32>>,
+    #[serde(rename = "scaleYEvents")]
+    pub scale_y_events: Vec<RpeCommonEvent<f32>>,
+    pub color_events: Vec<RpeCommonEvent<RpeColor>>,
+    pub text_events: Vec<RpeCommonEvent<String>>,
+    pub paint_events: Vec<RpeCommonEvent<f32>>,
+    pub incline_events: Vec<RpeCommonEvent<f32>>,
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct RpeCommonEvent<T: Num> {
+pub struct RpeCommonEvent<T> {
     pub bezier: i32,
     #[serde(rename = "bezierPoints")]
     pub bezier_points: [f32; 4],
@@ -178,6 +203,21 @@ pub struct RpeCommonEvent<T: Num> {
     pub start_time: RpeBeat,
 }
 
+impl<T> RpeCommonEvent<T> {
+    /// Map the start and end value of this event, keeping its timing and easing
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> RpeCommonEvent<U> {
+        RpeCommonEvent {
+            bezier: self.bezier,
+            bezier_points: self.bezier_points,
+            easing_type: self.easing_type,
+            end: f(self.end),
+            end_time: self.end_time,
+            start: f(self.start),
+            start_time: self.start_time,
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpeSpeedEvent {
@@ -187,44 +227,60 @@ pub struct RpeSpeedEvent {
     pub start_time: RpeBeat,
 }
 
+/// Notes with a `visibleTime` at or above this value are always visible
+pub const RPE_ALWAYS_VISIBLE: f32 = 999999.0;
+
+fn default_note_alpha() -> i32 {
+    255
+}
+
+fn default_note_size() -> f32 {
+    1.0
+}
+
+fn default_note_visible_time() -> f32 {
+    RPE_ALWAYS_VISIBLE
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RpeNote {
     /// 1 => above, other values => below
     #[serde(default)]
     pub above: i32,
-    #[serde(default)]
-    pub alpha: i32, // ignored, default 255
+    #[serde(default = "default_note_alpha")]
+    pub alpha: i32,
     pub end_time: RpeBeat,
     pub start_time: RpeBeat,
     /// 1 => fake note, other values => real note
     #[serde(default, rename = "isFake")]
     pub is_fake: i32,
     pub position_x: f32,
-    #[serde(default)]
-    pub size: f32, // ignored, default 1.0
+    #[serde(default = "default_note_size")]
+    pub size: f32,
     pub speed: f32,
     #[serde(rename = "type")]
     pub kind: RpeNoteKind,
+    /// Seconds before being hit at which the note shows up
+    #[serde(default = "default_note_visible_time")]
+    pub visible_time: f32,
     #[serde(default)]
-    pub visible_time: f32, // ignored, default 999999.0000
-    #[serde(default)]
-    pub y_offset: f32, // ignored, default 0.0
+    pub y_offset: f32,
 }
 
 impl Default for RpeNote {
     fn default() -> Self {
         Self {
             above: 1, // default above
-            alpha: 255,
+            alpha: default_note_alpha(),
             end_time: Default::default(),
             start_time: Default::default(),
             is_fake: 0, // default real
             position_x: 0.0,
-            size: 1.0,
+            size: default_note_size(),
             speed: 1.0,
             kind: Default::default(),
-            visible_time: 999999.0,
+            visible_time: default_note_visible_time(),
             y_offset: 0.0,
         }
     }
@@ -298,4 +354,17 @@ mod tests {
         assert!(layer.alpha_events.is_empty());
         assert!(layer.speed_events.is_empty());
     }
+
+    #[test]
+    fn deserialize_judge_line_extended_events() {
+        let line: RpeJudgeLine = serde_json::from_str(
+            r#"{"extended":{"colorEvents":[{"bezier":0,"bezierPoints":[0.0,0.0,0.0,0.0],"easingType":1,"start":[255,0,0],"end":[0,0,255],"startTime":[0,0,1],"endTime":[1,0,1]}],"scaleXEvents":[]}}"#,
+        )
+        .expect("failed to deserialize RpeJudgeLine with extended events");
+
+        let extended = line.extended.expect("extended events should be present");
+        assert_eq!(extended.color_events.len(), 1);
+        assert_eq!(extended.color_events[0].start, [255, 0, 0]);
+        assert!(extended.text_events.is_empty());
+    }
 }
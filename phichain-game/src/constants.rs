@@ -4,5 +4,9 @@ use bevy::prelude::Color;
 // #feffa9
 pub const PERFECT_COLOR: Color = Color::srgb(254.0 / 255.0, 1.0, 169.0 / 255.0);
 
+// the color for lines when every note judged so far is hit, but not all perfectly
+// #a2eeff
+pub const FULL_COMBO_COLOR: Color = Color::srgb(162.0 / 255.0, 238.0 / 255.0, 1.0);
+
 pub const ILLUSTRATION_BLUR: f32 = 160.0;
 pub const ILLUSTRATION_ALPHA: f32 = 0.2;
@@ -4,13 +4,18 @@ use phichain_assets::{HoldParts, ImageAssets, RespackDimensions};
 use phichain_chart::bpm_list::BpmList;
 use phichain_chart::constants::{CANVAS_HEIGHT, CANVAS_WIDTH};
 use phichain_chart::event::{EventEvaluationResult, LineEvent, LineEventKind};
-use phichain_chart::line::{Line, LineOpacity, LinePosition, LineRotation};
+use phichain_chart::line::{
+    Line, LineColor, LineIncline, LineOpacity, LinePosition, LineRotation, LineScale, LineText,
+    LineTextEvents,
+};
 
-use crate::constants::PERFECT_COLOR;
+use crate::constants::{FULL_COMBO_COLOR, PERFECT_COLOR};
 use crate::event::Events;
 use crate::highlight::Highlighted;
-use crate::layer::{HOLD_LAYER, NOTE_LAYER};
+use crate::layer::{line_z_order_offset, HOLD_LAYER, LINE_LAYER, NOTE_LAYER};
+use crate::line::{LineTextureRoot, LineTextures};
 use crate::scale;
+use crate::score::GameScore;
 use crate::{ChartTime, GameConfig, GameSet, GameViewport};
 use phichain_chart::line::LineSpeed;
 use phichain_chart::note::{Note, NoteKind};
@@ -19,6 +24,8 @@ pub struct CoreGamePlugin;
 
 impl Plugin for CoreGamePlugin {
     fn build(&self, app: &mut App) {
+        app.register_required_components::<Note, NoteTint>();
+
         app.add_systems(
             // note placement runs on Update, we need to edit them after they are being spawned into the world
             Update,
@@ -33,7 +40,11 @@ impl Plugin for CoreGamePlugin {
         )
         .add_systems(
             Update,
-            (compute_line_system, update_line_system)
+            (
+                compute_line_system,
+                update_line_system,
+                update_line_text_system,
+            )
                 .chain()
                 .in_set(GameSet),
         )
@@ -58,8 +69,26 @@ impl Plugin for CoreGamePlugin {
     }
 }
 
+/// The color a note is tinted with, e.g. by the editor for selected notes
+///
+/// The alpha of the note itself is multiplied into it when drawing, see [`note_color`]
+#[derive(Debug, Clone, Copy, Component)]
+pub struct NoteTint(pub Color);
+
+impl Default for NoteTint {
+    fn default() -> Self {
+        Self(Color::WHITE)
+    }
+}
+
+/// The color a note and its hold components are drawn with
+pub fn note_color(note: &Note, tint: &NoteTint) -> Color {
+    tint.0
+        .with_alpha(tint.0.alpha() * note.alpha() as f32 / 255.0)
+}
+
 pub fn update_note_scale_system(
-    mut query: Query<&mut Transform, With<Note>>,
+    mut query: Query<(&mut Transform, &Note)>,
     game_viewport: Res<GameViewport>,
     config: Res<GameConfig>,
     dimensions: Res<RespackDimensions>,
@@ -69,19 +98,25 @@ pub fn update_note_scale_system(
         dimensions.note_width,
         config.note_scale,
     );
-    for mut transform in &mut query {
-        transform.scale = Vec3::splat(scale);
+    for (mut transform, note) in &mut query {
+        transform.scale = Vec3::new(scale * note.size(), scale, scale);
     }
 }
 
 pub fn update_note_system(
-    mut query: Query<(&mut Transform, &mut Visibility, &Note)>,
+    mut query: Query<(
+        &mut Transform,
+        &mut Visibility,
+        &mut Sprite,
+        &Note,
+        &NoteTint,
+    )>,
     game_viewport: Res<GameViewport>,
     time: Res<ChartTime>,
     bpm_list: Res<BpmList>,
 ) {
     let beat = bpm_list.beat_at(time.0);
-    for (mut transform, mut visibility, note) in &mut query {
+    for (mut transform, mut visibility, mut sprite, note, tint) in &mut query {
         transform.translation.x = (note.x / CANVAS_WIDTH) * game_viewport.0.width()
             / scale::line_world_scale(game_viewport.0.width());
 
@@ -94,11 +129,15 @@ pub fn update_note_system(
             NoteKind::Hold { hold_beat } => hold_beat.value(),
             _ => 0.0,
         };
-        *visibility = if note.beat.value() + hold_beat < beat.into() {
-            Visibility::Hidden
-        } else {
-            Visibility::Visible
-        };
+        let time_until_hit = bpm_list.time_at(note.beat) - time.0;
+        *visibility =
+            if note.beat.value() + hold_beat < beat.into() || !note.is_visible_at(time_until_hit) {
+                Visibility::Hidden
+            } else {
+                Visibility::Visible
+            };
+
+        sprite.color = note_color(note, tint);
     }
 }
 
@@ -111,6 +150,13 @@ pub fn compute_line_system(
             &mut LineRotation,
             &mut LineOpacity,
             &mut LineSpeed,
+            (
+                &mut LineScale,
+                &mut LineColor,
+                &mut LineIncline,
+                &mut LineText,
+                &LineTextEvents,
+            ),
             &Events,
         ),
         With<Line>,
@@ -120,12 +166,25 @@ pub fn compute_line_system(
 ) {
     let beat: f32 = bpm_list.beat_at(time.0).into();
     line_query.par_iter_mut().for_each(
-        |(mut position, mut rotation, mut opacity, mut speed, events)| {
+        |(
+            mut position,
+            mut rotation,
+            mut opacity,
+            mut speed,
+            (mut scale, mut color, mut incline, mut text, text_events),
+            events,
+        )| {
             let mut x_value = EventEvaluationResult::Unaffected;
             let mut y_value = EventEvaluationResult::Unaffected;
             let mut rotation_value = EventEvaluationResult::Unaffected;
             let mut opacity_value = EventEvaluationResult::Unaffected;
             let mut speed_value = EventEvaluationResult::Unaffected;
+            let mut scale_x_value = EventEvaluationResult::Unaffected;
+            let mut scale_y_value = EventEvaluationResult::Unaffected;
+            let mut color_r_value = EventEvaluationResult::Unaffected;
+            let mut color_g_value = EventEvaluationResult::Unaffected;
+            let mut color_b_value = EventEvaluationResult::Unaffected;
+            let mut incline_value = EventEvaluationResult::Unaffected;
 
             for event in events.iter().filter_map(|x| event_query.get(x).ok()) {
                 let value = event.evaluate_inclusive(beat);
@@ -135,6 +194,14 @@ pub fn compute_line_system(
                     LineEventKind::Rotation => rotation_value = rotation_value.max(value),
                     LineEventKind::Opacity => opacity_value = opacity_value.max(value),
                     LineEventKind::Speed => speed_value = speed_value.max(value),
+                    LineEventKind::ScaleX => scale_x_value = scale_x_value.max(value),
+                    LineEventKind::ScaleY => scale_y_value = scale_y_value.max(value),
+                    LineEventKind::ColorR => color_r_value = color_r_value.max(value),
+                    LineEventKind::ColorG => color_g_value = color_g_value.max(value),
+                    LineEventKind::ColorB => color_b_value = color_b_value.max(value),
+                    LineEventKind::Incline => incline_value = incline_value.max(value),
+                    // paint events are kept for round-tripping only, they have no visual effect
+                    LineEventKind::Paint => {}
                 }
             }
 
@@ -155,27 +222,67 @@ pub fn compute_line_system(
             if let Some(speed_value) = speed_value.value() {
                 speed.0 = speed_value;
             }
+
+            scale.0 = Vec2::new(
+                scale_x_value
+                    .value()
+                    .unwrap_or(LineEventKind::ScaleX.default_value()),
+                scale_y_value
+                    .value()
+                    .unwrap_or(LineEventKind::ScaleY.default_value()),
+            );
+
+            let channels = [
+                (color_r_value, LineEventKind::ColorR),
+                (color_g_value, LineEventKind::ColorG),
+                (color_b_value, LineEventKind::ColorB),
+            ];
+            color.0 = channels
+                .iter()
+                .any(|(value, _)| value.value().is_some())
+                .then(|| {
+                    let [r, g, b] =
+                        channels.map(|(value, kind)| value.value().unwrap_or(kind.default_value()));
+                    Vec3::new(r, g, b)
+                });
+
+            incline.0 = incline_value.value().unwrap_or_default().to_radians();
+
+            // later text events take over earlier ones
+            text.0 = text_events
+                .0
+                .iter()
+                .filter_map(|event| event.evaluate(beat).map(|text| (event.start_beat, text)))
+                .max_by_key(|(start_beat, _)| *start_beat)
+                .map(|(_, text)| text);
         },
     );
 }
 
 pub fn update_line_system(
-    mut line_query: Query<
-        (
-            &LinePosition,
-            &LineRotation,
-            &LineOpacity,
-            &mut Transform,
-            &mut Sprite,
-            Option<&ChildOf>,
-        ),
-        With<Line>,
-    >,
+    mut line_query: Query<(
+        &LinePosition,
+        &LineRotation,
+        &LineOpacity,
+        (&LineScale, &LineColor, &LineIncline, &LineText),
+        (&Line, &mut Transform, &mut Sprite, &mut Anchor),
+        Option<&ChildOf>,
+    )>,
     game_viewport: Res<GameViewport>,
+    images: Res<Assets<Image>>,
 
     config: Res<GameConfig>,
+    score: Res<GameScore>,
 ) {
-    for (position, rotation, opacity, mut transform, mut sprite, parent) in &mut line_query {
+    for (
+        position,
+        rotation,
+        opacity,
+        (line_scale, color, incline, text),
+        (line, mut transform, mut sprite, mut anchor),
+        parent,
+    ) in &mut line_query
+    {
         let scale = scale::line_world_scale(game_viewport.0.width());
         transform.scale = Vec3::splat(if parent.is_some() { 1.0 } else { scale });
         transform.translation.x = position.0.x / CANVAS_WIDTH * game_viewport.0.width()
@@ -183,18 +290,119 @@ pub fn update_line_system(
         transform.translation.y = position.0.y / CANVAS_HEIGHT * game_viewport.0.height()
             / if parent.is_some() { scale } else { 1.0 };
         transform.rotation = Quat::from_rotation_z(rotation.0);
+        // child lines are ordered relative to their parent
+        transform.translation.z =
+            line_z_order_offset(line.z_order()) + if parent.is_some() { -0.5 } else { LINE_LAYER };
 
-        sprite.color = if config.fc_ap_indicator {
-            PERFECT_COLOR
+        // inclining a line tilts its plane away from the viewer, which flattens everything on it along the y axis
+        transform.scale.y *= incline.0.cos();
+
+        // custom textures are sized in canvas units rather than in texture pixels
+        let texture_scale = if line.texture.is_some() {
+            game_viewport.0.width() / CANVAS_WIDTH / scale
         } else {
-            Color::WHITE
-        }
-        .with_alpha(opacity.0);
+            1.0
+        };
+
+        // line scale only stretches the line texture, notes and child lines are not affected
+        sprite.custom_size = if line_scale.0 == Vec2::ONE && line.texture.is_none() {
+            None
+        } else {
+            images
+                .get(&sprite.image)
+                .map(|image| image.size_f32() * line_scale.0 * texture_scale)
+        };
+
+        *anchor = Anchor(Vec2::from(line.anchor()) - 0.5);
+
+        sprite.color = if text.0.is_some() {
+            // the line is displayed as text, see `update_line_text_system`
+            Color::NONE
+        } else {
+            line_color(color, &config, &score).with_alpha(opacity.0)
+        };
+    }
+}
+
+fn line_color(color: &LineColor, config: &GameConfig, score: &GameScore) -> Color {
+    match color.0 {
+        Some(color) => Color::srgb_u8(
+            color.x.clamp(0.0, 255.0) as u8,
+            color.y.clamp(0.0, 255.0) as u8,
+            color.z.clamp(0.0, 255.0) as u8,
+        ),
+        None if config.fc_ap_indicator && score.counter().is_all_perfect() => PERFECT_COLOR,
+        None if config.fc_ap_indicator && score.counter().is_full_combo() => FULL_COMBO_COLOR,
+        None => Color::WHITE,
+    }
+}
+
+/// Marker component for the text displayed in place of a line, spawned as a child of the line
+#[derive(Debug, Component, Default, Clone)]
+#[require(Text2d)]
+pub struct LineTextLabel;
+
+pub fn update_line_text_system(
+    mut commands: Commands,
+    line_query: Query<
+        (
+            Entity,
+            &LineText,
+            &LineColor,
+            &LineOpacity,
+            &LineScale,
+            Option<&Children>,
+        ),
+        With<Line>,
+    >,
+    mut label_query: Query<
+        (
+            &mut Text2d,
+            &mut TextColor,
+            &mut TextFont,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        With<LineTextLabel>,
+    >,
+    game_viewport: Res<GameViewport>,
+    config: Res<GameConfig>,
+    score: Res<GameScore>,
+) {
+    let font_size = game_viewport.0.height() * (60.0 / 900.0)
+        / scale::line_world_scale(game_viewport.0.width());
+
+    for (entity, text, color, opacity, line_scale, children) in &line_query {
+        let label = children
+            .into_iter()
+            .flatten()
+            .find(|child| label_query.contains(**child));
+
+        let Some(label) = label else {
+            if text.0.is_some() {
+                commands.entity(entity).with_child(LineTextLabel);
+            }
+            continue;
+        };
+
+        let (mut label_text, mut text_color, mut font, mut transform, mut visibility) =
+            label_query.get_mut(*label).expect("label should exist");
+
+        let Some(text) = &text.0 else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        *visibility = Visibility::Inherited;
+        label_text.0.clone_from(text);
+        text_color.0 = line_color(color, &config, &score).with_alpha(opacity.0);
+        font.font_size = font_size;
+        transform.scale = line_scale.0.extend(1.0);
     }
 }
 
 pub fn update_note_y_system(
-    query: Query<(&Children, Option<&Events>), With<Line>>,
+    query: Query<(&Line, &Children, Option<&Events>)>,
     game_viewport: Res<GameViewport>,
     line_event_query: Query<&LineEvent>,
     mut note_query: Query<(
@@ -208,7 +416,7 @@ pub fn update_note_y_system(
     bpm_list: Res<BpmList>,
     dimensions: Res<RespackDimensions>,
 ) {
-    for (children, events) in &query {
+    for (line, children, events) in &query {
         let mut speed_events: Vec<SpeedSegment> = Vec::new();
         if let Some(events) = events {
             for event_entity in events.iter() {
@@ -258,7 +466,7 @@ pub fn update_note_y_system(
                         transform.scale.y = height / body_height;
 
                         // hide notes behind line (cover)
-                        if height < 0.0 {
+                        if line.is_cover() && height < 0.0 {
                             *visibility = Visibility::Hidden;
                         }
                     }
@@ -267,13 +475,17 @@ pub fn update_note_y_system(
                         transform.rotation = Quat::from_rotation_z(0.0_f32.to_radians());
 
                         // hide notes behind line (cover)
-                        if y < 0.0 {
+                        if line.is_cover() && y < 0.0 {
                             *visibility = Visibility::Hidden;
                         }
                     }
                 }
 
-                transform.translation.y = y * if note.above { 1.0 } else { -1.0 };
+                // the offset only moves the note, it does not affect whether the note is covered by the line
+                let y_offset = note.y_offset() / CANVAS_HEIGHT * game_viewport.0.height()
+                    / scale::line_world_scale(game_viewport.0.width());
+
+                transform.translation.y = (y + y_offset) * if note.above { 1.0 } else { -1.0 };
             }
         }
     }
@@ -345,12 +557,18 @@ pub fn update_hold_components_scale_system(
     mut head_query: Query<&mut Transform, (With<HoldHead>, Without<HoldTail>)>,
     mut tail_query: Query<&mut Transform, (With<HoldTail>, Without<HoldHead>)>,
     parent_query: Query<
-        (&Transform, &Children, Option<&Highlighted>),
+        (&Transform, &Children, &Note, Option<&Highlighted>),
         (Without<HoldHead>, Without<HoldTail>),
     >,
     dimensions: Res<RespackDimensions>,
 ) {
-    for (transform, children, highlighted) in &parent_query {
+    for (transform, children, note, highlighted) in &parent_query {
+        // heads and tails are only stretched horizontally by the note size, undo it on the y axis
+        let base_scale = if note.size() == 0.0 {
+            0.0
+        } else {
+            transform.scale.x / note.size()
+        };
         let body_height = if highlighted.is_some() {
             dimensions.hold_highlight_body_height
         } else {
@@ -358,10 +576,10 @@ pub fn update_hold_components_scale_system(
         };
         for child in children {
             if let Ok(mut head) = head_query.get_mut(*child) {
-                head.scale.y = 1.0 / transform.scale.y * transform.scale.x;
+                head.scale.y = 1.0 / transform.scale.y * base_scale;
             }
             if let Ok(mut tail) = tail_query.get_mut(*child) {
-                tail.scale.y = 1.0 / transform.scale.y * transform.scale.x;
+                tail.scale.y = 1.0 / transform.scale.y * base_scale;
                 tail.translation.y = body_height;
             }
         }
@@ -371,25 +589,27 @@ pub fn update_hold_components_scale_system(
 pub fn update_hold_component_texture_system(
     mut head_query: Query<(&mut Sprite, &ChildOf), (With<HoldHead>, Without<HoldTail>)>,
     mut tail_query: Query<(&mut Sprite, &ChildOf), (With<HoldTail>, Without<HoldHead>)>,
-    parent_query: Query<Option<&Highlighted>>,
+    parent_query: Query<(Option<&Highlighted>, &Note, &NoteTint)>,
     hold_parts: Res<HoldParts>,
 ) {
     for (mut sprite, child_of) in &mut head_query {
-        if let Ok(highlight) = parent_query.get(child_of.parent()).map(|x| x.is_some()) {
-            sprite.image = if highlight {
+        if let Ok((highlighted, note, tint)) = parent_query.get(child_of.parent()) {
+            sprite.image = if highlighted.is_some() {
                 hold_parts.head_highlight.clone()
             } else {
                 hold_parts.head.clone()
             };
+            sprite.color = note_color(note, tint);
         }
     }
     for (mut sprite, child_of) in &mut tail_query {
-        if let Ok(highlight) = parent_query.get(child_of.parent()).map(|x| x.is_some()) {
-            sprite.image = if highlight {
+        if let Ok((highlighted, note, tint)) = parent_query.get(child_of.parent()) {
+            sprite.image = if highlighted.is_some() {
                 hold_parts.tail_highlight.clone()
             } else {
                 hold_parts.tail.clone()
             };
+            sprite.color = note_color(note, tint);
         }
     }
 }
@@ -435,11 +655,18 @@ pub fn despawn_hold_component_system(
 }
 
 pub fn update_line_texture_system(
-    mut query: Query<&mut Sprite, With<Line>>,
+    mut query: Query<(&mut Sprite, &Line)>,
     assets: Res<ImageAssets>,
+    mut textures: ResMut<LineTextures>,
+    root: Option<Res<LineTextureRoot>>,
+    mut images: ResMut<Assets<Image>>,
 ) {
-    for mut sprite in &mut query {
-        sprite.image = assets.line.clone();
+    for (mut sprite, line) in &mut query {
+        let texture = line
+            .texture
+            .as_ref()
+            .and_then(|texture| textures.get_or_load(texture, root.as_deref(), &mut images));
+        sprite.image = texture.unwrap_or_else(|| assets.line.clone());
     }
 }
 
@@ -215,6 +215,10 @@ fn spawn_hit_effect_system(
     }
 
     for (note, child_of, entity, played) in &query {
+        if note.is_fake {
+            continue;
+        }
+
         let events = match line_query.get(child_of.parent()).ok().flatten() {
             Some(events) => events,
             None => continue,
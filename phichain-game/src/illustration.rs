@@ -5,6 +5,7 @@ use crate::{
 use bevy::asset::RenderAssetUsages;
 use bevy::prelude::*;
 use image::{DynamicImage, ImageResult};
+use phichain_chart::line::Line;
 use std::path::PathBuf;
 
 use super::GameViewport;
@@ -93,7 +94,8 @@ fn resize_illustration_system(
 }
 
 fn place_everything_above_illustration_system(
-    mut query: Query<&mut Transform, Without<Illustration>>,
+    // lines are placed above the illustration according to their z-order in `update_line_system`
+    mut query: Query<&mut Transform, (Without<Illustration>, Without<Line>)>,
 ) {
     for mut transform in &mut query {
         transform.translation.z = 1.0;
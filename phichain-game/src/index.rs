@@ -0,0 +1,72 @@
+//! Look up the entities of chart items by their [`Id`]
+
+use bevy::prelude::*;
+use phichain_chart::id::Id;
+use std::collections::HashMap;
+
+pub struct ChartIndexPlugin;
+
+impl Plugin for ChartIndexPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChartIndex>()
+            .add_observer(index_observer)
+            .add_observer(unindex_observer);
+    }
+}
+
+/// The entity holding each [`Id`], kept up to date as [`Id`]s are inserted, replaced and removed
+#[derive(Resource, Debug, Default)]
+pub struct ChartIndex(HashMap<Id, Entity>);
+
+impl ChartIndex {
+    pub fn get(&self, id: Id) -> Option<Entity> {
+        self.0.get(&id).copied()
+    }
+
+    pub fn contains(&self, id: Id) -> bool {
+        self.0.contains_key(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Id, Entity)> + '_ {
+        self.0.iter().map(|(id, entity)| (*id, *entity))
+    }
+}
+
+fn index_observer(event: On<Insert, Id>, query: Query<&Id>, mut index: ResMut<ChartIndex>) {
+    if let Ok(id) = query.get(event.entity) {
+        index.0.insert(*id, event.entity);
+    }
+}
+
+fn unindex_observer(event: On<Replace, Id>, query: Query<&Id>, mut index: ResMut<ChartIndex>) {
+    if let Ok(id) = query.get(event.entity) {
+        // the id may have been given to another entity since
+        if index.0.get(id) == Some(&event.entity) {
+            index.0.remove(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chart_index() {
+        let mut app = App::new();
+        app.add_plugins(ChartIndexPlugin);
+
+        let world = app.world_mut();
+        let a = world.spawn(Id(0)).id();
+        let b = world.spawn(Id(1)).id();
+        assert_eq!(world.resource::<ChartIndex>().get(Id(0)), Some(a));
+        assert_eq!(world.resource::<ChartIndex>().get(Id(1)), Some(b));
+
+        world.entity_mut(b).insert(Id(2));
+        assert!(!world.resource::<ChartIndex>().contains(Id(1)));
+        assert_eq!(world.resource::<ChartIndex>().get(Id(2)), Some(b));
+
+        world.despawn(a);
+        assert_eq!(world.resource::<ChartIndex>().get(Id(0)), None);
+    }
+}
@@ -0,0 +1,652 @@
+//! Judgement of notes against a stream of timed inputs, following the rules of Phigros
+//!
+//! - Taps are judged by the offset of the press hitting them, within [`PERFECT_WINDOW`], [`GOOD_WINDOW`] and [`BAD_WINDOW`]
+//! - Holds are judged by the offset of the press hitting their head, which can not be bad. The judgement
+//!   is given once the hold ends, and turns into a miss if the touch is released before
+//! - Drags are hit by any touch down within [`DRAG_WINDOW`], flicks by a flick within the same window.
+//!   Both are always perfect
+//! - Notes not hit when their window passes are missed
+//!
+//! Inputs are matched with notes by time only, the position of touches is not simulated. A press hits
+//! the earliest tap or hold within its window
+
+use phichain_chart::bpm_list::BpmList;
+use phichain_chart::note::{Note, NoteKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Maximum offset in seconds of a perfect tap or hold
+pub const PERFECT_WINDOW: f32 = 0.08;
+/// Maximum offset in seconds of a good tap or hold
+pub const GOOD_WINDOW: f32 = 0.16;
+/// Maximum offset in seconds of a bad tap
+pub const BAD_WINDOW: f32 = 0.18;
+/// Maximum offset in seconds of a touch hitting a drag or a flick
+pub const DRAG_WINDOW: f32 = 0.18;
+/// A hold released at most this many seconds before its end is still hit
+pub const HOLD_TAIL_TOLERANCE: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Judgement {
+    Perfect,
+    Good,
+    Bad,
+    Miss,
+}
+
+impl Judgement {
+    /// If the combo continues after this judgement
+    pub fn keeps_combo(&self) -> bool {
+        matches!(self, Judgement::Perfect | Judgement::Good)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputKind {
+    Press,
+    /// A quick move of a touch which is down
+    Flick,
+    Release,
+}
+
+/// A timed input of a touch, identified by `touch`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct InputEvent {
+    /// Time in seconds
+    pub time: f32,
+    pub touch: u32,
+    pub kind: InputKind,
+}
+
+impl InputEvent {
+    pub fn new(time: f32, touch: u32, kind: InputKind) -> Self {
+        Self { time, touch, kind }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JudgeNoteKind {
+    Tap,
+    Drag,
+    /// A hold ending at `end_time` in seconds
+    Hold {
+        end_time: f32,
+    },
+    Flick,
+}
+
+/// A note to be judged, timed in seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JudgeNote {
+    pub kind: JudgeNoteKind,
+    pub time: f32,
+}
+
+impl JudgeNote {
+    pub fn new(note: &Note, bpm_list: &BpmList) -> Self {
+        let kind = match note.kind {
+            NoteKind::Tap => JudgeNoteKind::Tap,
+            NoteKind::Drag => JudgeNoteKind::Drag,
+            NoteKind::Hold { .. } => JudgeNoteKind::Hold {
+                end_time: bpm_list.time_at(note.end_beat()),
+            },
+            NoteKind::Flick => JudgeNoteKind::Flick,
+        };
+
+        Self {
+            kind,
+            time: bpm_list.time_at(note.beat),
+        }
+    }
+
+    /// The last time the note is able to be hit
+    fn deadline(&self) -> f32 {
+        self.time
+            + match self.kind {
+                JudgeNoteKind::Tap => BAD_WINDOW,
+                JudgeNoteKind::Hold { .. } => GOOD_WINDOW,
+                JudgeNoteKind::Drag | JudgeNoteKind::Flick => DRAG_WINDOW,
+            }
+    }
+}
+
+/// The judgement of a note
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JudgeRecord {
+    /// Index of the note in the notes given to [`Judge::new`]
+    pub note: usize,
+    pub judgement: Judgement,
+    /// Time in seconds the judgement is given at
+    pub time: f32,
+    /// Signed offset in seconds of the input hitting the note, negative for early hits, `None` for misses
+    pub offset: Option<f32>,
+}
+
+/// Counts of judgements and the score computed from them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScoreCounter {
+    pub perfect: u32,
+    pub good: u32,
+    pub bad: u32,
+    pub miss: u32,
+    pub combo: u32,
+    pub max_combo: u32,
+    /// Amount of notes in the chart, including those not judged yet
+    pub note_amount: u32,
+}
+
+impl ScoreCounter {
+    pub fn new(note_amount: u32) -> Self {
+        Self {
+            note_amount,
+            ..Default::default()
+        }
+    }
+
+    pub fn push(&mut self, judgement: Judgement) {
+        match judgement {
+            Judgement::Perfect => self.perfect += 1,
+            Judgement::Good => self.good += 1,
+            Judgement::Bad => self.bad += 1,
+            Judgement::Miss => self.miss += 1,
+        }
+
+        if judgement.keeps_combo() {
+            self.combo += 1;
+            self.max_combo = self.max_combo.max(self.combo);
+        } else {
+            self.combo = 0;
+        }
+    }
+
+    pub fn judged(&self) -> u32 {
+        self.perfect + self.good + self.bad + self.miss
+    }
+
+    /// The Phigros score: 900000 for the judgements, where a good is worth 65% of a perfect, plus 100000 for the max combo
+    pub fn score(&self) -> f32 {
+        match self.note_amount {
+            0 => 0.0,
+            amount => {
+                let judgement = (self.perfect as f32 + self.good as f32 * 0.65) / amount as f32;
+                let combo = self.max_combo as f32 / amount as f32;
+                (900000.0 * judgement + 100000.0 * combo).round()
+            }
+        }
+    }
+
+    /// Accuracy of the notes judged so far, from `0.0` to `1.0`
+    pub fn accuracy(&self) -> f32 {
+        match self.judged() {
+            0 => 1.0,
+            judged => (self.perfect as f32 + self.good as f32 * 0.65) / judged as f32,
+        }
+    }
+
+    /// If no note judged so far broke the combo
+    pub fn is_full_combo(&self) -> bool {
+        self.bad == 0 && self.miss == 0
+    }
+
+    /// If every note judged so far is perfect
+    pub fn is_all_perfect(&self) -> bool {
+        self.is_full_combo() && self.good == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NoteState {
+    Pending,
+    /// A hold whose head has been hit, held until it ends
+    Holding {
+        head: Judgement,
+        offset: f32,
+    },
+    Judged,
+}
+
+/// Judges notes against inputs fed in time order
+#[derive(Debug, Clone)]
+pub struct Judge {
+    notes: Vec<JudgeNote>,
+    /// Indices of the notes sorted by time
+    order: Vec<usize>,
+    states: Vec<NoteState>,
+    /// Position in `order` before which every note is judged
+    cursor: usize,
+    /// Touches which are down, with the hold they are holding
+    touches: HashMap<u32, Option<usize>>,
+    time: f32,
+    records: Vec<JudgeRecord>,
+    counter: ScoreCounter,
+}
+
+impl Judge {
+    pub fn new(notes: Vec<JudgeNote>) -> Self {
+        Self {
+            order: sorted_order(&notes),
+            states: vec![NoteState::Pending; notes.len()],
+            cursor: 0,
+            touches: HashMap::new(),
+            time: f32::NEG_INFINITY,
+            records: vec![],
+            counter: ScoreCounter::new(notes.len() as u32),
+            notes,
+        }
+    }
+
+    pub fn notes(&self) -> &[JudgeNote] {
+        &self.notes
+    }
+
+    /// Judgements given so far, in the order they are given
+    pub fn records(&self) -> &[JudgeRecord] {
+        &self.records
+    }
+
+    pub fn counter(&self) -> &ScoreCounter {
+        &self.counter
+    }
+
+    /// The time in seconds the judge has advanced to
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Feed an input, inputs must be fed in time order
+    pub fn feed(&mut self, event: InputEvent) {
+        self.advance(event.time);
+        let time = self.time;
+
+        match event.kind {
+            InputKind::Press => {
+                self.touches.insert(event.touch, None);
+                if let Some(index) = self.find_pressable(time) {
+                    let note = self.notes[index];
+                    let offset = time - note.time;
+                    match note.kind {
+                        JudgeNoteKind::Hold { .. } => {
+                            let head = if offset.abs() <= PERFECT_WINDOW {
+                                Judgement::Perfect
+                            } else {
+                                Judgement::Good
+                            };
+                            self.states[index] = NoteState::Holding { head, offset };
+                            self.touches.insert(event.touch, Some(index));
+                        }
+                        _ => {
+                            let judgement = if offset.abs() <= PERFECT_WINDOW {
+                                Judgement::Perfect
+                            } else if offset.abs() <= GOOD_WINDOW {
+                                Judgement::Good
+                            } else {
+                                Judgement::Bad
+                            };
+                            self.judge(index, judgement, time, Some(offset));
+                        }
+                    }
+                }
+                self.hit_drags(time, time, true);
+            }
+            InputKind::Flick => {
+                if !self.touches.contains_key(&event.touch) {
+                    return;
+                }
+                for i in self.cursor..self.order.len() {
+                    let index = self.order[i];
+                    let note = self.notes[index];
+                    if note.time - DRAG_WINDOW > time {
+                        break;
+                    }
+                    if note.kind == JudgeNoteKind::Flick
+                        && self.states[index] == NoteState::Pending
+                        && (note.time - time).abs() <= DRAG_WINDOW
+                    {
+                        self.judge(index, Judgement::Perfect, time, Some(time - note.time));
+                    }
+                }
+            }
+            InputKind::Release => {
+                if let Some(Some(index)) = self.touches.remove(&event.touch) {
+                    if let (NoteState::Holding { .. }, JudgeNoteKind::Hold { end_time }) =
+                        (self.states[index], self.notes[index].kind)
+                    {
+                        if time < end_time - HOLD_TAIL_TOLERANCE {
+                            self.judge(index, Judgement::Miss, time, None);
+                        }
+                    }
+                }
+                if self.touches.is_empty() {
+                    // drags ahead within the window are hit by the last touch before it leaves
+                    self.hit_drags(time, time, true);
+                }
+            }
+        }
+
+        self.update_cursor();
+    }
+
+    /// Advance the time to `time` in seconds, judging holds which ended and notes which were missed
+    pub fn advance(&mut self, time: f32) {
+        if time <= self.time {
+            return;
+        }
+
+        // touches have been down during the whole time passed
+        if !self.touches.is_empty() {
+            self.hit_drags(self.time, time, false);
+        }
+
+        for i in self.cursor..self.order.len() {
+            let index = self.order[i];
+            let note = self.notes[index];
+            if note.time - BAD_WINDOW.max(DRAG_WINDOW) > time {
+                break;
+            }
+
+            match (self.states[index], note.kind) {
+                (NoteState::Pending, _) if note.deadline() < time => {
+                    self.judge(index, Judgement::Miss, note.deadline(), None);
+                }
+                (NoteState::Holding { head, offset }, JudgeNoteKind::Hold { end_time })
+                    if end_time <= time =>
+                {
+                    self.judge(index, head, end_time, Some(offset));
+                }
+                _ => {}
+            }
+        }
+
+        self.time = time;
+        self.update_cursor();
+    }
+
+    /// The earliest pending tap or hold a press at `time` is able to hit
+    fn find_pressable(&self, time: f32) -> Option<usize> {
+        self.order[self.cursor..]
+            .iter()
+            .copied()
+            .take_while(|&index| self.notes[index].time - BAD_WINDOW <= time)
+            .find(|&index| {
+                let note = self.notes[index];
+                self.states[index] == NoteState::Pending
+                    && matches!(note.kind, JudgeNoteKind::Tap | JudgeNoteKind::Hold { .. })
+                    && (note.time - time).abs() <= note.deadline() - note.time
+            })
+    }
+
+    /// Hit pending drags whose window overlaps `from..=to`
+    ///
+    /// Drags after `to` are only hit if `early`, otherwise they are left to be hit on time
+    fn hit_drags(&mut self, from: f32, to: f32, early: bool) {
+        let ahead = if early { DRAG_WINDOW } else { 0.0 };
+        for i in self.cursor..self.order.len() {
+            let index = self.order[i];
+            let note = self.notes[index];
+            if note.time - ahead > to {
+                break;
+            }
+            if note.kind == JudgeNoteKind::Drag
+                && self.states[index] == NoteState::Pending
+                && note.time + DRAG_WINDOW >= from
+            {
+                let time = note.time.clamp(from, to);
+                self.judge(index, Judgement::Perfect, time, Some(time - note.time));
+            }
+        }
+    }
+
+    fn judge(&mut self, note: usize, judgement: Judgement, time: f32, offset: Option<f32>) {
+        self.states[note] = NoteState::Judged;
+        self.counter.push(judgement);
+        self.records.push(JudgeRecord {
+            note,
+            judgement,
+            time,
+            offset,
+        });
+    }
+
+    fn update_cursor(&mut self) {
+        while self
+            .order
+            .get(self.cursor)
+            .is_some_and(|&index| self.states[index] == NoteState::Judged)
+        {
+            self.cursor += 1;
+        }
+    }
+}
+
+fn sorted_order(notes: &[JudgeNote]) -> Vec<usize> {
+    let mut order = (0..notes.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| notes[*a].time.total_cmp(&notes[*b].time));
+    order
+}
+
+/// Inputs hitting every note perfectly
+///
+/// Each tap and hold is pressed by its own touch, while one touch stays down through the whole chart
+/// to hit drags and flicks, so it never takes the press of another note
+pub fn autoplay(notes: &[JudgeNote]) -> Vec<InputEvent> {
+    let Some(first) = notes.iter().map(|note| note.time).reduce(f32::min) else {
+        return vec![];
+    };
+
+    const SWEEPER: u32 = 0;
+
+    let mut events = vec![InputEvent::new(
+        first - BAD_WINDOW.max(DRAG_WINDOW) - 1.0,
+        SWEEPER,
+        InputKind::Press,
+    )];
+    let mut last = first;
+
+    for (touch, index) in (1..).zip(sorted_order(notes)) {
+        let note = notes[index];
+        match note.kind {
+            JudgeNoteKind::Tap => {
+                events.push(InputEvent::new(note.time, touch, InputKind::Press));
+                events.push(InputEvent::new(note.time, touch, InputKind::Release));
+            }
+            JudgeNoteKind::Hold { end_time } => {
+                events.push(InputEvent::new(note.time, touch, InputKind::Press));
+                events.push(InputEvent::new(end_time, touch, InputKind::Release));
+                last = last.max(end_time);
+            }
+            JudgeNoteKind::Flick => {
+                events.push(InputEvent::new(note.time, SWEEPER, InputKind::Flick));
+            }
+            JudgeNoteKind::Drag => {}
+        }
+        last = last.max(note.time);
+    }
+
+    events.push(InputEvent::new(
+        last + DRAG_WINDOW,
+        SWEEPER,
+        InputKind::Release,
+    ));
+
+    // a stable sort keeps presses at the same time in the order of the notes they are meant for
+    events.sort_by(|a, b| {
+        let rank = |kind| match kind {
+            InputKind::Press => 0,
+            InputKind::Flick => 1,
+            InputKind::Release => 2,
+        };
+        a.time
+            .total_cmp(&b.time)
+            .then(rank(a.kind).cmp(&rank(b.kind)))
+    });
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(kind: JudgeNoteKind, time: f32) -> JudgeNote {
+        JudgeNote { kind, time }
+    }
+
+    fn run(notes: Vec<JudgeNote>, events: &[InputEvent], until: f32) -> Judge {
+        let mut judge = Judge::new(notes);
+        for event in events {
+            judge.feed(*event);
+        }
+        judge.advance(until);
+        judge
+    }
+
+    fn judgements(judge: &Judge) -> Vec<(usize, Judgement)> {
+        let mut judgements = judge
+            .records()
+            .iter()
+            .map(|record| (record.note, record.judgement))
+            .collect::<Vec<_>>();
+        judgements.sort_by_key(|(note, _)| *note);
+        judgements
+    }
+
+    #[test]
+    fn test_autoplay() {
+        let notes = vec![
+            note(JudgeNoteKind::Tap, 1.0),
+            note(JudgeNoteKind::Hold { end_time: 3.0 }, 1.0),
+            note(JudgeNoteKind::Drag, 1.0),
+            note(JudgeNoteKind::Flick, 1.0),
+            note(JudgeNoteKind::Tap, 1.05),
+            note(JudgeNoteKind::Hold { end_time: 1.2 }, 1.1),
+            note(JudgeNoteKind::Drag, 2.0),
+            note(JudgeNoteKind::Flick, 2.01),
+        ];
+        let judge = run(notes.clone(), &autoplay(&notes), 10.0);
+
+        let counter = judge.counter();
+        assert_eq!(counter.perfect, 8);
+        assert_eq!(counter.max_combo, 8);
+        assert!(counter.is_all_perfect());
+        assert_eq!(counter.score(), 1000000.0);
+    }
+
+    #[test]
+    fn test_held_drag_on_time() {
+        let notes = vec![note(JudgeNoteKind::Drag, 1.0)];
+        let events = [InputEvent::new(0.0, 0, InputKind::Press)];
+
+        let judge = run(notes.clone(), &events, 0.9);
+        assert!(judge.records().is_empty());
+
+        let judge = run(notes, &events, 1.1);
+        assert_eq!(judge.records()[0].judgement, Judgement::Perfect);
+        assert_eq!(judge.records()[0].offset, Some(0.0));
+    }
+
+    #[test]
+    fn test_tap_windows() {
+        let notes = (0..5)
+            .map(|i| note(JudgeNoteKind::Tap, i as f32))
+            .collect::<Vec<_>>();
+        let events = [0.0, 1.05, 2.0 - 0.12, 3.17]
+            .iter()
+            .enumerate()
+            .map(|(touch, time)| InputEvent::new(*time, touch as u32, InputKind::Press))
+            .collect::<Vec<_>>();
+        let judge = run(notes, &events, 10.0);
+
+        assert_eq!(
+            judgements(&judge),
+            vec![
+                (0, Judgement::Perfect),
+                (1, Judgement::Perfect),
+                (2, Judgement::Good),
+                (3, Judgement::Bad),
+                (4, Judgement::Miss),
+            ]
+        );
+        let counter = judge.counter();
+        assert_eq!(counter.combo, 0);
+        assert_eq!(counter.max_combo, 3);
+        // (2 + 0.65) / 5 * 900000 + 3 / 5 * 100000
+        assert_eq!(counter.score(), 537000.0);
+    }
+
+    #[test]
+    fn test_hold() {
+        let notes = vec![
+            note(JudgeNoteKind::Hold { end_time: 2.0 }, 1.0),
+            note(JudgeNoteKind::Hold { end_time: 4.0 }, 3.0),
+            note(JudgeNoteKind::Hold { end_time: 6.0 }, 5.0),
+        ];
+        let events = [
+            InputEvent::new(1.1, 0, InputKind::Press),
+            InputEvent::new(1.95, 0, InputKind::Release),
+            InputEvent::new(3.0, 0, InputKind::Press),
+            InputEvent::new(3.5, 0, InputKind::Release),
+        ];
+
+        let judge = run(notes.clone(), &events, 3.9);
+        // the second hold is missed as soon as it is released
+        assert_eq!(
+            judgements(&judge),
+            vec![(0, Judgement::Good), (1, Judgement::Miss)]
+        );
+        assert_eq!(judge.records()[1].time, 3.5);
+
+        let judge = run(notes, &events, 10.0);
+        assert_eq!(judgements(&judge)[2], (2, Judgement::Miss));
+    }
+
+    #[test]
+    fn test_drag_and_flick() {
+        let notes = vec![
+            note(JudgeNoteKind::Drag, 1.0),
+            note(JudgeNoteKind::Drag, 2.0),
+            note(JudgeNoteKind::Flick, 3.0),
+            note(JudgeNoteKind::Flick, 4.0),
+            note(JudgeNoteKind::Drag, 5.0),
+        ];
+        let events = [
+            // held through the first drag
+            InputEvent::new(0.5, 0, InputKind::Press),
+            InputEvent::new(1.5, 0, InputKind::Release),
+            // pressed within the window of the second drag
+            InputEvent::new(2.1, 0, InputKind::Press),
+            InputEvent::new(2.1, 0, InputKind::Release),
+            // flicked
+            InputEvent::new(2.9, 0, InputKind::Press),
+            InputEvent::new(2.95, 0, InputKind::Flick),
+            // down, but never flicked
+            InputEvent::new(3.5, 0, InputKind::Release),
+            InputEvent::new(3.9, 0, InputKind::Press),
+            InputEvent::new(4.5, 0, InputKind::Release),
+        ];
+        let judge = run(notes, &events, 10.0);
+
+        assert_eq!(
+            judgements(&judge),
+            vec![
+                (0, Judgement::Perfect),
+                (1, Judgement::Perfect),
+                (2, Judgement::Perfect),
+                (3, Judgement::Miss),
+                (4, Judgement::Miss),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_press_hits_earliest_note() {
+        let notes = vec![note(JudgeNoteKind::Tap, 1.1), note(JudgeNoteKind::Tap, 1.0)];
+        let judge = run(notes, &[InputEvent::new(1.05, 0, InputKind::Press)], 10.0);
+
+        assert_eq!(
+            judgements(&judge),
+            vec![(0, Judgement::Miss), (1, Judgement::Perfect)]
+        );
+    }
+}
@@ -1,3 +1,13 @@
 pub const HOLD_LAYER: f32 = 10.0;
 pub const NOTE_LAYER: f32 = 20.0;
 pub const HIT_EFFECT_LAYER: f32 = 30.0;
+
+/// Lines are placed within `[LINE_LAYER, LINE_LAYER + 1.0)`, below every note
+pub const LINE_LAYER: f32 = 1.0;
+
+/// Map the z-order of a line into `[0.0, 1.0)` while preserving the order
+///
+/// Root lines are placed at [`LINE_LAYER`] plus this value, child lines are placed relative to their parent
+pub fn line_z_order_offset(z_order: i32) -> f32 {
+    0.5 + (z_order as f32).atan() / std::f32::consts::PI
+}
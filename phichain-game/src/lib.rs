@@ -6,11 +6,13 @@ pub mod event;
 pub mod highlight;
 mod hit_effect;
 pub mod illustration;
+pub mod index;
+pub mod judgement;
 mod layer;
 pub mod line;
 pub mod loader;
 pub mod scale;
-mod score;
+pub mod score;
 pub mod serialization;
 mod ui;
 pub mod utils;
@@ -20,6 +22,7 @@ use crate::curve_note_track::CurveNoteTrackPlugin;
 use crate::highlight::HighlightPlugin;
 use crate::hit_effect::HitEffectPlugin;
 use crate::illustration::IllustrationPlugin;
+use crate::index::ChartIndexPlugin;
 use crate::line::LinePlugin;
 use crate::loader::nonblocking::NonblockingLoaderPlugin;
 use crate::score::ScorePlugin;
@@ -97,12 +100,17 @@ pub struct GameSet;
 /// - If [`GameConfig::multi_highlight`] is true, attach [`Highlighted`] for all notes with multi highlight
 /// - Hit effects (including animations and particles)
 /// - Generating and managing [`CurveNote`]s based on [`CurveNoteTrack`]s
+/// - Judging notes against the [`GameInput`] to compute the score and combo
+/// - Indexing the entities of chart items by their [`Id`] in the [`ChartIndex`]
 ///
 /// [`Line`]: phichain_chart::line::Line
 /// [`Note`]: phichain_chart::note::Note
 /// [`Highlighted`]: highlight::Highlighted
 /// [`CurveNote`]: curve_note_track::CurveNote
 /// [`CurveNoteTrack`]: curve_note_track::CurveNoteTrack
+/// [`GameInput`]: score::GameInput
+/// [`Id`]: phichain_chart::id::Id
+/// [`ChartIndex`]: index::ChartIndex
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
@@ -114,6 +122,7 @@ impl Plugin for GamePlugin {
             .add_message::<SeekRequest>()
             .add_message::<PauseToggleRequest>()
             .add_plugins(NonblockingLoaderPlugin)
+            .add_plugins(ChartIndexPlugin)
             .add_plugins(LinePlugin)
             .add_plugins(HighlightPlugin)
             .add_plugins(CoreGamePlugin)
@@ -1,8 +1,12 @@
 use bevy::app::App;
+use bevy::asset::RenderAssetUsages;
 use bevy::ecs::lifecycle::HookContext;
 use bevy::ecs::world::DeferredWorld;
-use bevy::prelude::{Component, Plugin, Resource};
+use bevy::log::warn;
+use bevy::prelude::{Assets, Component, Handle, Image, Plugin, Resource};
 use phichain_chart::line::Line;
+use phichain_chart::project::ProjectPath;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 
 #[derive(Resource, Default)]
@@ -28,11 +32,65 @@ impl LineOrder {
     }
 }
 
+/// The directory line textures are resolved against, inserted when a project is loaded
+#[derive(Resource, Debug, Clone)]
+pub struct LineTextureRoot(pub ProjectPath);
+
+/// Line textures loaded so far, keyed by their path relative to the project directory
+///
+/// Textures that fail to load are cached as [`None`] so they are not retried every frame
+#[derive(Resource, Debug, Default)]
+pub struct LineTextures(HashMap<String, Option<Handle<Image>>>);
+
+impl LineTextures {
+    /// Get the handle of a line texture, loading it from the project directory on first use
+    pub fn get_or_load(
+        &mut self,
+        texture: &str,
+        root: Option<&LineTextureRoot>,
+        images: &mut Assets<Image>,
+    ) -> Option<Handle<Image>> {
+        if let Some(handle) = self.0.get(texture) {
+            return handle.clone();
+        }
+
+        // the root is missing while no project is loaded, try again later
+        let root = root?;
+
+        let handle = match root.0.resolve(texture) {
+            Some(path) => match image::open(&path) {
+                Ok(image) => Some(images.add(Image::from_dynamic(
+                    image,
+                    true,
+                    RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+                ))),
+                Err(error) => {
+                    warn!("Failed to load line texture {}: {}", path.display(), error);
+                    None
+                }
+            },
+            None => {
+                warn!("Line texture {} is outside the project directory", texture);
+                None
+            }
+        };
+
+        self.0.insert(texture.to_owned(), handle.clone());
+        handle
+    }
+
+    /// Drop every loaded texture, e.g. when the project is unloaded
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
 pub struct LinePlugin;
 
 impl Plugin for LinePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<OrderGen>()
+            .init_resource::<LineTextures>()
             .register_required_components::<Line, LineOrder>();
     }
 }
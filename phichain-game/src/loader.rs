@@ -3,10 +3,14 @@ pub mod nonblocking;
 use crate::curve_note_track::CurveNoteTrack;
 use crate::event::EventOf;
 use crate::illustration::{load_illustration, open_illustration};
+use crate::line::{LineTextureRoot, LineTextures};
 use anyhow::Context;
 use bevy::prelude::*;
+use phichain_chart::id::Id;
+use phichain_chart::line::LineTextEvents;
 use phichain_chart::project::Project;
 use phichain_chart::serialization::{PhichainChart, SerializedLine};
+use std::collections::HashMap;
 
 /// Load a project to the world using a [`Commands`]
 ///
@@ -18,11 +22,15 @@ use phichain_chart::serialization::{PhichainChart, SerializedLine};
 ///
 /// - [phichain_chart::offset::Offset] will be inserted into the world
 /// - [phichain_chart::bpm_list::BpmList] will be inserted into the world
+/// - [LineTextureRoot] will be inserted into the world and [LineTextures] will be reset
 /// - Entities with components [`Line`] and [`Note`] will be spawned into the world, with parent-child relationship
+/// - Lines, notes and events are spawned along with their [`Id`]. Curve note tracks are not identified in the
+///   chart, they are given [`Id`]s following the ones of the chart
 pub fn load_project(project: &Project, commands: &mut Commands) -> anyhow::Result<()> {
     let json = std::fs::read_to_string(project.path.chart_path())?;
     let chart = PhichainChart::from_json_str(&json).context("Failed to parse chart")?;
     load(chart, commands);
+    load_line_textures(project, commands);
 
     if let Some(illustration_path) = project.path.illustration_path() {
         // TODO: handle error
@@ -34,27 +42,55 @@ pub fn load_project(project: &Project, commands: &mut Commands) -> anyhow::Resul
     Ok(())
 }
 
-fn load_line(line: SerializedLine, commands: &mut Commands, parent: Option<Entity>) -> Entity {
+/// Resolve line textures against the directory of the project, dropping textures of previous projects
+fn load_line_textures(project: &Project, commands: &mut Commands) {
+    commands.insert_resource(LineTextureRoot(project.path.clone()));
+    commands.insert_resource(LineTextures::default());
+}
+
+/// Take the [`Id`] of an item, or give it a new one if it has none
+fn take_id(id: &mut Option<Id>, next_id: &mut u64) -> Id {
+    id.take().unwrap_or_else(|| {
+        *next_id += 1;
+        Id(*next_id - 1)
+    })
+}
+
+fn load_line(
+    mut line: SerializedLine,
+    commands: &mut Commands,
+    parent: Option<Entity>,
+    next_id: &mut u64,
+) -> Entity {
     let id = commands
-        .spawn(line.line)
+        .spawn((
+            take_id(&mut line.line.id, next_id),
+            line.line,
+            LineTextEvents(line.text_events),
+        ))
         .with_children(|parent| {
-            let mut note_entity_order = vec![];
+            let mut note_entities = HashMap::new();
 
-            for note in line.notes {
-                let id = parent.spawn(note).id();
-                note_entity_order.push(id);
+            for mut note in line.notes {
+                let id = note.id;
+                let entity = parent.spawn((take_id(&mut note.id, next_id), note)).id();
+                if let Some(id) = id {
+                    note_entities.insert(id, entity);
+                }
             }
 
             for track in line.curve_note_tracks {
-                if let (Some(from), Some(to)) = (
-                    note_entity_order.get(track.from),
-                    note_entity_order.get(track.to),
-                ) {
-                    parent.spawn(CurveNoteTrack {
-                        from: Some(*from),
-                        to: Some(*to),
-                        options: track.options,
-                    });
+                if let (Some(from), Some(to)) =
+                    (note_entities.get(&track.from), note_entities.get(&track.to))
+                {
+                    parent.spawn((
+                        CurveNoteTrack {
+                            from: Some(*from),
+                            to: Some(*to),
+                            options: track.options,
+                        },
+                        take_id(&mut None, next_id),
+                    ));
                 } else {
                     warn!("invalid curve note track detected: {:?}", track);
                 }
@@ -62,8 +98,8 @@ fn load_line(line: SerializedLine, commands: &mut Commands, parent: Option<Entit
         })
         .id();
 
-    for event in line.events {
-        commands.spawn((event, EventOf(id)));
+    for mut event in line.events {
+        commands.spawn((take_id(&mut event.id, next_id), event, EventOf(id)));
     }
 
     if let Some(parent) = parent {
@@ -71,7 +107,7 @@ fn load_line(line: SerializedLine, commands: &mut Commands, parent: Option<Entit
     }
 
     for child in line.children {
-        load_line(child, commands, Some(id));
+        load_line(child, commands, Some(id), next_id);
     }
 
     id
@@ -82,7 +118,8 @@ fn load(chart: PhichainChart, commands: &mut Commands) {
     commands.insert_resource(chart.offset);
     commands.insert_resource(chart.bpm_list);
 
+    let mut next_id = chart.next_id();
     for line in chart.lines {
-        load_line(line, commands, None);
+        load_line(line, commands, None, &mut next_id);
     }
 }
@@ -1,6 +1,6 @@
 use crate::audio::{load_audio, open_and_decode_audio, LoadAudioError};
 use crate::illustration::{load_illustration, open_illustration};
-use crate::loader::load_line;
+use crate::loader::{load, load_line_textures};
 use bevy::app::App;
 use bevy::prelude::{Commands, Component, Entity, Event, Plugin, Query, Update};
 use bevy::tasks::futures_lite::future;
@@ -132,19 +132,8 @@ pub fn handle_tasks_system(
                         load_illustration(illustration, &mut commands);
                     }
 
-                    let PhichainChart {
-                        offset,
-                        bpm_list,
-                        lines,
-                        ..
-                    } = chart;
-
-                    commands.insert_resource(offset);
-                    commands.insert_resource(bpm_list);
-
-                    for line in lines {
-                        load_line(line, &mut commands, None);
-                    }
+                    load(chart, &mut commands);
+                    load_line_textures(&project, &mut commands);
 
                     commands.trigger(ProjectLoadingResult(Ok(LoadedProject {
                         duration,
@@ -1,3 +1,4 @@
+use crate::judgement::{autoplay, InputEvent, Judge, JudgeNote, JudgeRecord, ScoreCounter};
 use crate::{ChartTime, GameSet};
 use bevy::prelude::*;
 use phichain_chart::bpm_list::BpmList;
@@ -8,26 +9,44 @@ pub struct ScorePlugin;
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(GameScore::default())
+            .insert_resource(GameInput::default())
+            .insert_resource(ScoreJudge::default())
             .add_systems(Update, update_score_system.in_set(GameSet));
     }
 }
 
+/// Inputs the notes are judged against
+#[derive(Resource, Debug, Clone, Default)]
+pub enum GameInput {
+    /// Every note is hit perfectly
+    #[default]
+    Autoplay,
+    /// Recorded inputs sorted by time, notes before `start` in seconds are not judged
+    Recorded { start: f32, events: Vec<InputEvent> },
+}
+
 #[derive(Resource, Debug, Default)]
 pub struct GameScore {
-    combo: u32,
-    note_amount: u32,
+    counter: ScoreCounter,
+    records: Vec<JudgeRecord>,
 }
 
 impl GameScore {
+    /// Judgements given so far, in the order they are given
+    pub fn records(&self) -> &[JudgeRecord] {
+        &self.records
+    }
+
+    pub fn counter(&self) -> &ScoreCounter {
+        &self.counter
+    }
+
     pub fn combo(&self) -> u32 {
-        self.combo
+        self.counter.combo
     }
 
     pub fn score(&self) -> f32 {
-        match self.note_amount {
-            0 => 0.0,
-            _ => (100_0000.0 * self.combo as f32 / self.note_amount as f32).round(),
-        }
+        self.counter.score()
     }
 
     pub fn score_text(&self) -> String {
@@ -35,18 +54,123 @@ impl GameScore {
     }
 }
 
+/// The judge the score is computed with, advanced incrementally while the time moves forward
+#[derive(Resource, Debug, Default)]
+struct ScoreJudge {
+    judge: Option<Judge>,
+    /// Inputs generated for the notes when judging with [`GameInput::Autoplay`]
+    autoplay: Vec<InputEvent>,
+    /// Amount of inputs fed to the judge
+    fed: usize,
+    /// Start of the recorded inputs the judge was built for, `None` for autoplay
+    start: Option<f32>,
+}
+
 fn update_score_system(
     mut score: ResMut<GameScore>,
+    mut state: ResMut<ScoreJudge>,
     note_query: Query<&Note>,
+    changed_note_query: Query<(), Changed<Note>>,
+    mut removed_notes: RemovedComponents<Note>,
     time: Res<ChartTime>,
     bpm_list: Res<BpmList>,
+    input: Res<GameInput>,
 ) {
-    let notes: Vec<_> = note_query.iter().collect();
-    score.combo = notes
+    let ScoreJudge {
+        judge,
+        autoplay: autoplay_input,
+        fed,
+        start,
+    } = state.as_mut();
+
+    let input_start = match input.as_ref() {
+        GameInput::Autoplay => None,
+        GameInput::Recorded { start, .. } => Some(*start),
+    };
+    // recording pushes inputs to the same recording, which only needs the new inputs to be fed
+    let input_replaced = input.is_changed()
+        && (input_start != *start
+            || matches!(input.as_ref(), GameInput::Recorded { events, .. } if events.len() < *fed));
+    let notes_changed = !changed_note_query.is_empty() || removed_notes.read().count() > 0;
+    let seeked_backwards = judge.as_ref().is_some_and(|judge| time.0 < judge.time());
+
+    let judge = match judge {
+        Some(judge)
+            if !input_replaced && !notes_changed && !seeked_backwards && !bpm_list.is_changed() =>
+        {
+            judge
+        }
+        _ => {
+            // fake notes are never judged, they contribute to neither combo nor score
+            let notes = note_query
+                .iter()
+                .filter(|note| !note.is_fake)
+                .map(|note| JudgeNote::new(note, &bpm_list))
+                .filter(|note| note.time >= input_start.unwrap_or(f32::NEG_INFINITY))
+                .collect::<Vec<_>>();
+
+            *autoplay_input = match input.as_ref() {
+                GameInput::Autoplay => autoplay(&notes),
+                GameInput::Recorded { .. } => vec![],
+            };
+            *fed = 0;
+            *start = input_start;
+            score.records.clear();
+            judge.insert(Judge::new(notes))
+        }
+    };
+
+    let events = match input.as_ref() {
+        GameInput::Autoplay => autoplay_input,
+        GameInput::Recorded { events, .. } => events,
+    };
+    for event in events[*fed..]
         .iter()
-        .filter(|note| bpm_list.time_at(note.end_beat()) <= time.0)
-        .collect::<Vec<_>>()
-        .len() as u32;
+        .take_while(|event| event.time <= time.0)
+    {
+        judge.feed(*event);
+        *fed += 1;
+    }
+    judge.advance(time.0);
 
-    score.note_amount = notes.len() as u32;
+    score.counter = judge.counter().clone();
+    let judged = score.records.len();
+    score.records.extend_from_slice(&judge.records()[judged..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::beat;
+    use phichain_chart::note::NoteKind;
+
+    fn update(app: &mut App, time: f32) -> (u32, usize) {
+        app.insert_resource(ChartTime(time));
+        app.update();
+        let score = app.world().resource::<GameScore>();
+        (score.combo(), score.records().len())
+    }
+
+    #[test]
+    fn test_score_follows_time() {
+        let mut app = App::new();
+        app.add_plugins(ScorePlugin)
+            .insert_resource(BpmList::single(120.0));
+        for beat in [beat!(1), beat!(2), beat!(3)] {
+            app.world_mut()
+                .spawn(Note::new(NoteKind::Tap, true, beat, 0.0, 1.0));
+        }
+
+        assert_eq!(update(&mut app, 1.2), (2, 2));
+        assert_eq!(update(&mut app, 2.0), (3, 3));
+
+        // seeking backwards judges from the start again
+        assert_eq!(update(&mut app, 0.7), (1, 1));
+
+        // a new note before the current time is judged as well
+        app.world_mut()
+            .spawn(Note::new(NoteKind::Tap, true, beat!(1, 1, 2), 0.0, 1.0));
+        assert_eq!(update(&mut app, 0.8), (2, 2));
+        assert_eq!(update(&mut app, 2.0), (4, 4));
+    }
 }
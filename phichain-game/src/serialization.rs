@@ -5,7 +5,8 @@ use bevy::ecs::system::SystemParam;
 use bevy::prelude::{ChildOf, Children, Entity, Query, Res, With, Without};
 use phichain_chart::bpm_list::BpmList;
 use phichain_chart::event::LineEvent;
-use phichain_chart::line::Line;
+use phichain_chart::id::Id;
+use phichain_chart::line::{Line, LineTextEvents};
 use phichain_chart::note::Note;
 use phichain_chart::offset::Offset;
 use phichain_chart::serialization::{PhichainChart, SerializedLine};
@@ -27,12 +28,15 @@ impl SerializeLine for SerializedLine {
         let mut line_events: Vec<LineEvent> = vec![];
         let mut cnts = vec![];
 
-        let mut note_entity_order = vec![];
+        let id = |entity: Entity| params.id.get(entity).ok().copied();
 
         if let Ok(events) = events {
-            for event in events.iter() {
-                if let Ok(event) = params.line_event.get(*event) {
-                    line_events.push(*event);
+            for entity in events.iter() {
+                if let Ok(event) = params.line_event.get(*entity) {
+                    line_events.push(LineEvent {
+                        id: id(*entity),
+                        ..*event
+                    });
                 }
             }
         }
@@ -40,17 +44,22 @@ impl SerializeLine for SerializedLine {
         if let Ok(children) = children {
             for child in children.iter() {
                 if let Ok(note) = params.note.get(*child) {
-                    note_entity_order.push(child);
-                    notes.push(*note);
+                    notes.push(Note {
+                        id: id(*child),
+                        ..*note
+                    });
                 }
             }
+            // tracks can only refer to notes of the same line
+            let is_note =
+                |entity: Entity| children.contains(&entity) && params.note.contains(entity);
             for child in children.iter() {
                 if let Ok(track) = params.curve_note_track.get(*child) {
-                    if let Some((from, to)) = track.get_entities() {
-                        if let (Some(from), Some(to)) = (
-                            note_entity_order.iter().position(|x| **x == from),
-                            note_entity_order.iter().position(|x| **x == to),
-                        ) {
+                    if let Some((from, to)) = track
+                        .get_entities()
+                        .filter(|(from, to)| is_note(*from) && is_note(*to))
+                    {
+                        if let (Some(from), Some(to)) = (id(from), id(to)) {
                             cnts.push(phichain_chart::curve_note_track::CurveNoteTrack {
                                 from,
                                 to,
@@ -72,7 +81,23 @@ impl SerializeLine for SerializedLine {
             }
         }
 
-        SerializedLine::new(line.clone(), notes, line_events, child_lines, cnts)
+        SerializedLine {
+            text_events: params
+                .text_events
+                .get(entity)
+                .map(|x| x.0.clone())
+                .unwrap_or_default(),
+            ..SerializedLine::new(
+                Line {
+                    id: id(entity),
+                    ..line.clone()
+                },
+                notes,
+                line_events,
+                child_lines,
+                cnts,
+            )
+        }
     }
 }
 
@@ -81,6 +106,8 @@ pub struct SerializeLineParam<'w, 's> {
     children: Query<'w, 's, &'static Children>,
     events: Query<'w, 's, &'static Events>,
     line: Query<'w, 's, &'static Line>,
+    id: Query<'w, 's, &'static Id>,
+    text_events: Query<'w, 's, &'static LineTextEvents>,
 
     line_event: Query<'w, 's, &'static LineEvent>,
     note: Query<'w, 's, &'static Note, Without<CurveNote>>,
@@ -0,0 +1,34 @@
+use phichain_chart::serialization::ParseChartError;
+use rust_i18n::t;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum LoadError {
+    NoSuchFile(PathBuf),
+    Io(std::io::Error),
+    Parse(ParseChartError),
+}
+
+impl From<std::io::Error> for LoadError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<ParseChartError> for LoadError {
+    fn from(value: ParseChartError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::NoSuchFile(path) => {
+                write!(f, "{}", t!("cli.error.no_such_file", path = path.display()))
+            }
+            LoadError::Io(e) => write!(f, "{e}"),
+            LoadError::Parse(e) => write!(f, "{}", t!("cli.error.invalid_chart", error = e)),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Headless linter for phichain charts
+//!
+//! Walks every line of a [`PhichainChart`] (including nested child lines) and reports
+//! [`Diagnostic`]s for common authoring mistakes. See [`Rule`] for the available checks.
+
+pub mod rules;
+
+rust_i18n::i18n!("locales", fallback = "en-US");
+
+pub use rules::Rule;
+
+use phichain_chart::beat::Beat;
+use phichain_chart::serialization::{PhichainChart, SerializedLine};
+use serde::Serialize;
+use strum::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// The location of a line in the line tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LineLocation {
+    /// Indices from the root line list down to the line, e.g. `[0, 2]` is the third child of the first root line
+    pub path: Vec<usize>,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub line: LineLocation,
+    /// The beat the diagnostic refers to, if any
+    pub beat: Option<Beat>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct LintSummary {
+    pub error: usize,
+    pub warning: usize,
+    pub info: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LintReport {
+    pub summary: LintSummary,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LintReport {
+    fn new(diagnostics: Vec<Diagnostic>) -> Self {
+        let mut summary = LintSummary::default();
+        for diagnostic in &diagnostics {
+            match diagnostic.severity {
+                Severity::Error => summary.error += 1,
+                Severity::Warning => summary.warning += 1,
+                Severity::Info => summary.info += 1,
+            }
+        }
+
+        Self {
+            summary,
+            diagnostics,
+        }
+    }
+
+    /// The highest severity among all diagnostics, [`None`] if the report is empty
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.diagnostics.iter().map(|x| x.severity).max()
+    }
+
+    /// Drop all diagnostics produced by the given rules
+    pub fn without(self, rules: &[Rule]) -> Self {
+        Self::new(
+            self.diagnostics
+                .into_iter()
+                .filter(|x| !rules.contains(&x.rule))
+                .collect(),
+        )
+    }
+}
+
+/// Run every [`Rule`] against a chart
+pub fn lint(chart: &PhichainChart) -> LintReport {
+    fn visit(lines: &[SerializedLine], parent: &[usize], diagnostics: &mut Vec<Diagnostic>) {
+        for (index, line) in lines.iter().enumerate() {
+            let mut path = parent.to_vec();
+            path.push(index);
+
+            let location = LineLocation {
+                path,
+                name: line.line.name.clone(),
+            };
+            rules::check_line(line, &location, diagnostics);
+
+            visit(&line.children, &location.path, diagnostics);
+        }
+    }
+
+    let mut diagnostics = vec![];
+    visit(&chart.lines, &[], &mut diagnostics);
+
+    LintReport::new(diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::beat;
+    use phichain_chart::line::Line;
+    use phichain_chart::note::{Note, NoteKind};
+
+    #[test]
+    fn test_default_chart_is_clean() {
+        let report = lint(&PhichainChart::default());
+        assert!(report.diagnostics.is_empty());
+        assert_eq!(report.max_severity(), None);
+    }
+
+    #[test]
+    fn test_nested_line_path() {
+        let mut child = SerializedLine {
+            line: Line {
+                name: "child".to_owned(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        child.notes = vec![Note::new(
+            NoteKind::Hold {
+                hold_beat: beat!(0),
+            },
+            true,
+            beat!(1),
+            0.0,
+            1.0,
+        )];
+
+        let mut chart = PhichainChart::default();
+        chart.lines.push(SerializedLine {
+            children: vec![SerializedLine::default(), child],
+            ..Default::default()
+        });
+
+        let report = lint(&chart).without(&[Rule::InvisibleLineWithNotes]);
+        assert_eq!(report.diagnostics.len(), 1);
+        assert_eq!(report.summary.error, 1);
+
+        let diagnostic = &report.diagnostics[0];
+        assert_eq!(diagnostic.rule, Rule::NonPositiveHold);
+        assert_eq!(diagnostic.line.path, vec![1, 1]);
+        assert_eq!(diagnostic.line.name, "child");
+        assert_eq!(diagnostic.beat, Some(beat!(1)));
+    }
+}
@@ -0,0 +1,158 @@
+mod error;
+
+use crate::error::LoadError;
+use clap::{Parser, ValueEnum};
+use owo_colors::OwoColorize;
+use phichain_chart::project::ProjectPath;
+use phichain_chart::serialization::PhichainChart;
+use phichain_i18n::{i18n_str, locale};
+use phichain_lint::{lint, LintReport, Rule, Severity};
+use rust_i18n::t;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+rust_i18n::i18n!("locales", fallback = "en-US");
+
+/// Exit code when the chart has diagnostics at or above `--fail-on`
+const EXIT_LINT_FAILED: i32 = 1;
+/// Exit code when the chart cannot be loaded at all
+const EXIT_LOAD_FAILED: i32 = 2;
+
+#[derive(ValueEnum, Debug, Copy, Clone)]
+#[clap(rename_all = "kebab_case")]
+enum OutputFormat {
+    Human,
+    Json,
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone)]
+#[clap(rename_all = "kebab_case")]
+enum FailOn {
+    Error,
+    Warning,
+    Info,
+    Never,
+}
+
+impl FailOn {
+    fn threshold(&self) -> Option<Severity> {
+        match self {
+            FailOn::Error => Some(Severity::Error),
+            FailOn::Warning => Some(Severity::Warning),
+            FailOn::Info => Some(Severity::Info),
+            FailOn::Never => None,
+        }
+    }
+}
+
+#[derive(Parser, Debug, Clone)]
+#[command(name = "phichain-lint")]
+#[command(about = i18n_str!("cli.about"))]
+#[command(after_help = i18n_str!("cli.examples"))]
+struct Args {
+    #[arg(required = true, help = t!("cli.input").to_string())]
+    input: PathBuf,
+
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human, help = t!("cli.format").to_string())]
+    format: OutputFormat,
+
+    #[arg(long, value_enum, default_value_t = FailOn::Error, help = t!("cli.fail_on").to_string())]
+    fail_on: FailOn,
+
+    #[arg(long, help = t!("cli.allow").to_string())]
+    allow: Vec<Rule>,
+}
+
+fn load(path: &Path) -> Result<PhichainChart, LoadError> {
+    let content = if path.as_os_str() == "-" {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        input
+    } else {
+        let path = if path.is_dir() {
+            ProjectPath(path.to_path_buf()).chart_path()
+        } else {
+            path.to_path_buf()
+        };
+
+        if !path.is_file() {
+            return Err(LoadError::NoSuchFile(path));
+        }
+
+        std::fs::read_to_string(path)?
+    };
+
+    Ok(PhichainChart::from_json_str(&content)?)
+}
+
+fn print_human(report: &LintReport) {
+    for diagnostic in &report.diagnostics {
+        let severity = match diagnostic.severity {
+            Severity::Error => diagnostic.severity.to_string().red().to_string(),
+            Severity::Warning => diagnostic.severity.to_string().yellow().to_string(),
+            Severity::Info => diagnostic.severity.to_string().blue().to_string(),
+        };
+        let path = diagnostic
+            .line
+            .path
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        let beat = diagnostic
+            .beat
+            .map(|beat| format!(" @ {beat:?}"))
+            .unwrap_or_default();
+
+        println!(
+            "{severity}[{}] {} ({path}){beat}: {}",
+            diagnostic.rule,
+            diagnostic.line.name.cyan(),
+            diagnostic.message
+        );
+    }
+
+    eprintln!(
+        "{}",
+        t!(
+            "cli.status.summary",
+            error = report.summary.error,
+            warning = report.summary.warning,
+            info = report.summary.info
+        )
+    );
+}
+
+fn main() {
+    rust_i18n::set_locale(&locale());
+
+    let args = Args::parse();
+
+    let chart = match load(&args.input) {
+        Ok(chart) => chart,
+        Err(err) => {
+            eprintln!("{}", err.red());
+            std::process::exit(EXIT_LOAD_FAILED);
+        }
+    };
+
+    let report = lint(&chart).without(&args.allow);
+
+    match args.format {
+        OutputFormat::Human => print_human(&report),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("failed to serialize lint report")
+        ),
+    }
+
+    let failed = args
+        .fail_on
+        .threshold()
+        .zip(report.max_severity())
+        .is_some_and(|(threshold, max)| max >= threshold);
+
+    if failed {
+        std::process::exit(EXIT_LINT_FAILED);
+    }
+}
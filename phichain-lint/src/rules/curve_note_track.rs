@@ -0,0 +1,79 @@
+use crate::rules::Rule;
+use crate::{Diagnostic, LineLocation};
+use phichain_chart::id::Id;
+use phichain_chart::serialization::SerializedLine;
+use rust_i18n::t;
+
+pub fn check(line: &SerializedLine, location: &LineLocation, diagnostics: &mut Vec<Diagnostic>) {
+    let note = |id: Id| line.notes.iter().find(|note| note.id == Some(id));
+
+    for (index, track) in line.curve_note_tracks.iter().enumerate() {
+        let dangling = [("from", track.from), ("to", track.to)]
+            .into_iter()
+            .filter(|(_, id)| note(*id).is_none())
+            .map(|(field, id)| format!("`{field}` = {}", id.0))
+            .collect::<Vec<_>>();
+
+        if !dangling.is_empty() {
+            // point at the end that still exists, if any
+            let beat = note(track.from)
+                .or_else(|| note(track.to))
+                .map(|note| note.beat);
+
+            diagnostics.push(
+                Rule::DanglingCurveNoteTrack.diagnostic(
+                    location,
+                    beat,
+                    t!(
+                        "lint.dangling_curve_note_track",
+                        index = index,
+                        notes = dangling.join(", ")
+                    )
+                    .into(),
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::beat;
+    use phichain_chart::curve_note_track::{CurveNoteTrack, CurveNoteTrackOptions};
+    use phichain_chart::note::{Note, NoteKind};
+
+    #[test]
+    fn test_dangling_curve_note_track() {
+        let track = |from, to| CurveNoteTrack {
+            from: Id(from),
+            to: Id(to),
+            options: CurveNoteTrackOptions::default(),
+        };
+        let line = SerializedLine {
+            notes: vec![
+                Note {
+                    id: Some(Id(0)),
+                    ..Note::new(NoteKind::Tap, true, beat!(0), 0.0, 1.0)
+                },
+                Note {
+                    id: Some(Id(1)),
+                    ..Note::new(NoteKind::Tap, true, beat!(1), 100.0, 1.0)
+                },
+            ],
+            curve_note_tracks: vec![track(0, 1), track(1, 2), track(5, 6)],
+            ..Default::default()
+        };
+        let location = LineLocation {
+            path: vec![0],
+            name: line.line.name.clone(),
+        };
+
+        let mut diagnostics = vec![];
+        check(&line, &location, &mut diagnostics);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].beat, Some(beat!(1)));
+        assert_eq!(diagnostics[1].beat, None);
+    }
+}
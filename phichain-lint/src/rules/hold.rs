@@ -0,0 +1,58 @@
+use crate::rules::Rule;
+use crate::{Diagnostic, LineLocation};
+use phichain_chart::beat::Beat;
+use phichain_chart::serialization::SerializedLine;
+use rust_i18n::t;
+
+pub fn check(line: &SerializedLine, location: &LineLocation, diagnostics: &mut Vec<Diagnostic>) {
+    for note in &line.notes {
+        if let Some(hold_beat) = note.hold_beat() {
+            if *hold_beat <= Beat::ZERO {
+                diagnostics.push(
+                    Rule::NonPositiveHold.diagnostic(
+                        location,
+                        Some(note.beat),
+                        t!(
+                            "lint.non_positive_hold",
+                            beat = format!("{:?}", note.beat),
+                            hold_beat = format!("{:?}", hold_beat)
+                        )
+                        .into(),
+                    ),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::beat;
+    use phichain_chart::note::{Note, NoteKind};
+
+    #[test]
+    fn test_non_positive_hold() {
+        let hold = |hold_beat| NoteKind::Hold { hold_beat };
+        let line = SerializedLine {
+            notes: vec![
+                Note::new(hold(beat!(1)), true, beat!(0), 0.0, 1.0),
+                Note::new(hold(beat!(0)), true, beat!(1), 0.0, 1.0),
+                Note::new(hold(beat!(0) - beat!(1, 2)), true, beat!(2), 0.0, 1.0),
+                Note::new(NoteKind::Tap, true, beat!(3), 0.0, 1.0),
+            ],
+            ..Default::default()
+        };
+        let location = LineLocation {
+            path: vec![0],
+            name: line.line.name.clone(),
+        };
+
+        let mut diagnostics = vec![];
+        check(&line, &location, &mut diagnostics);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].beat, Some(beat!(1)));
+        assert_eq!(diagnostics[1].beat, Some(beat!(2)));
+    }
+}
@@ -0,0 +1,88 @@
+use crate::rules::Rule;
+use crate::{Diagnostic, LineLocation};
+use phichain_chart::serialization::SerializedLine;
+use rust_i18n::t;
+
+pub fn check(line: &SerializedLine, location: &LineLocation, diagnostics: &mut Vec<Diagnostic>) {
+    if line.notes.is_empty() {
+        return;
+    }
+
+    // a line without any opacity event stays at the default opacity of 0
+    let invisible = line
+        .events
+        .iter()
+        .filter(|event| event.kind.is_opacity())
+        .all(|event| event.value.start() <= 0.0 && event.value.end() <= 0.0);
+
+    if invisible {
+        diagnostics.push(Rule::InvisibleLineWithNotes.diagnostic(
+            location,
+            line.notes.iter().map(|note| note.beat).min(),
+            t!("lint.invisible_line_with_notes", count = line.notes.len()).into(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::beat;
+    use phichain_chart::event;
+    use phichain_chart::event::LineEventKind;
+    use phichain_chart::note::{Note, NoteKind};
+
+    fn run(line: &SerializedLine) -> Vec<Diagnostic> {
+        let location = LineLocation {
+            path: vec![0],
+            name: line.line.name.clone(),
+        };
+        let mut diagnostics = vec![];
+        check(line, &location, &mut diagnostics);
+        diagnostics
+    }
+
+    #[test]
+    fn test_invisible_line_with_notes() {
+        let notes = vec![
+            Note::new(NoteKind::Tap, true, beat!(2), 0.0, 1.0),
+            Note::new(NoteKind::Tap, true, beat!(1), 0.0, 1.0),
+        ];
+
+        let invisible = SerializedLine {
+            notes: notes.clone(),
+            events: vec![event!(LineEventKind::Opacity, beat!(0) => beat!(1), 0.0)],
+            ..Default::default()
+        };
+        let diagnostics = run(&invisible);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].beat, Some(beat!(1)));
+
+        let fading_in = SerializedLine {
+            notes,
+            events: vec![event!(LineEventKind::Opacity, beat!(0) => beat!(1), 0.0 => 255.0)],
+            ..Default::default()
+        };
+        assert!(run(&fading_in).is_empty());
+
+        let empty = SerializedLine::default();
+        assert!(run(&empty).is_empty());
+    }
+
+    #[test]
+    fn test_line_without_opacity_events() {
+        let line = SerializedLine {
+            notes: vec![Note::new(NoteKind::Tap, true, beat!(3), 0.0, 1.0)],
+            // events of other kinds do not make the line visible
+            events: vec![event!(LineEventKind::X, beat!(0) => beat!(1), 0.0 => 100.0)],
+            ..Default::default()
+        };
+        let diagnostics = run(&line);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].beat, Some(beat!(3)));
+        assert_eq!(
+            diagnostics[0].message,
+            "Line has an opacity of 0 throughout the chart but carries 1 note(s)"
+        );
+    }
+}
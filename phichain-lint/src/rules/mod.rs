@@ -0,0 +1,67 @@
+mod curve_note_track;
+mod hold;
+mod invisible_line;
+mod overlapping_events;
+mod stacked_notes;
+
+use crate::{Diagnostic, LineLocation, Severity};
+use phichain_chart::beat::Beat;
+use phichain_chart::serialization::SerializedLine;
+use serde::Serialize;
+use strum::{Display, EnumString};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Rule {
+    /// Two events of the same kind on a line overlap in time
+    OverlappingEvents,
+    /// Two notes on the same side of a line share the same beat and x
+    StackedNotes,
+    /// A hold note with a zero or negative `hold_beat`
+    NonPositiveHold,
+    /// A curve note track whose `from` or `to` refers to no note of its line
+    DanglingCurveNoteTrack,
+    /// A line that never becomes visible but still carries notes
+    InvisibleLineWithNotes,
+}
+
+impl Rule {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Rule::OverlappingEvents => Severity::Error,
+            Rule::StackedNotes => Severity::Warning,
+            Rule::NonPositiveHold => Severity::Error,
+            Rule::DanglingCurveNoteTrack => Severity::Error,
+            Rule::InvisibleLineWithNotes => Severity::Info,
+        }
+    }
+
+    fn diagnostic(
+        self,
+        location: &LineLocation,
+        beat: Option<Beat>,
+        message: String,
+    ) -> Diagnostic {
+        Diagnostic {
+            rule: self,
+            severity: self.severity(),
+            line: location.clone(),
+            beat,
+            message,
+        }
+    }
+}
+
+/// Run every rule against a single line, without descending into its children
+pub(crate) fn check_line(
+    line: &SerializedLine,
+    location: &LineLocation,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    overlapping_events::check(line, location, diagnostics);
+    stacked_notes::check(line, location, diagnostics);
+    hold::check(line, location, diagnostics);
+    curve_note_track::check(line, location, diagnostics);
+    invisible_line::check(line, location, diagnostics);
+}
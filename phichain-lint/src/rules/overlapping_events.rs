@@ -0,0 +1,60 @@
+use crate::rules::Rule;
+use crate::{Diagnostic, LineLocation};
+use phichain_chart::serialization::SerializedLine;
+use phichain_compiler::helpers::{check_overlap, EventSequenceError};
+use phichain_compiler::sequence::EventSequence;
+use rust_i18n::t;
+
+pub fn check(line: &SerializedLine, location: &LineLocation, diagnostics: &mut Vec<Diagnostic>) {
+    let mut groups = line.events.group_by_kind().into_iter().collect::<Vec<_>>();
+    // `group_by_kind` is backed by a HashMap, keep the output stable
+    groups.sort_by_key(|(kind, _)| u8::from(*kind));
+
+    for (kind, events) in groups {
+        if let Err(EventSequenceError::Overlap(beat)) = check_overlap(&events) {
+            diagnostics.push(
+                Rule::OverlappingEvents.diagnostic(
+                    location,
+                    Some(beat),
+                    t!(
+                        "lint.overlapping_events",
+                        kind = format!("{kind:?}"),
+                        beat = format!("{beat:?}")
+                    )
+                    .into(),
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::beat;
+    use phichain_chart::event;
+    use phichain_chart::event::LineEventKind;
+
+    #[test]
+    fn test_overlapping_events() {
+        let line = SerializedLine {
+            events: vec![
+                event!(LineEventKind::X, beat!(0) => beat!(2), 0.0),
+                event!(LineEventKind::X, beat!(1) => beat!(3), 0.0),
+                // different kinds never overlap each other
+                event!(LineEventKind::Y, beat!(0) => beat!(3), 0.0),
+            ],
+            ..Default::default()
+        };
+        let location = LineLocation {
+            path: vec![0],
+            name: line.line.name.clone(),
+        };
+
+        let mut diagnostics = vec![];
+        check(&line, &location, &mut diagnostics);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].beat, Some(beat!(1)));
+    }
+}
@@ -0,0 +1,68 @@
+use crate::rules::Rule;
+use crate::{Diagnostic, LineLocation};
+use phichain_chart::serialization::SerializedLine;
+use rust_i18n::t;
+
+/// Notes closer than this on the x axis are considered to be at the same position
+const X_EPSILON: f32 = 1e-3;
+
+pub fn check(line: &SerializedLine, location: &LineLocation, diagnostics: &mut Vec<Diagnostic>) {
+    let mut notes = line.notes.iter().collect::<Vec<_>>();
+    notes.sort_by_key(|note| note.beat);
+
+    for (i, note) in notes.iter().enumerate() {
+        let stacked = notes[..i]
+            .iter()
+            .rev()
+            .take_while(|other| other.beat == note.beat)
+            .any(|other| other.above == note.above && (other.x - note.x).abs() < X_EPSILON);
+
+        if stacked {
+            diagnostics.push(
+                Rule::StackedNotes.diagnostic(
+                    location,
+                    Some(note.beat),
+                    t!(
+                        "lint.stacked_notes",
+                        kind = note.kind,
+                        beat = format!("{:?}", note.beat),
+                        x = note.x
+                    )
+                    .into(),
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use phichain_chart::beat;
+    use phichain_chart::note::{Note, NoteKind};
+
+    #[test]
+    fn test_stacked_notes() {
+        let line = SerializedLine {
+            notes: vec![
+                Note::new(NoteKind::Tap, true, beat!(1), 100.0, 1.0),
+                Note::new(NoteKind::Drag, true, beat!(1), 100.0, 1.0),
+                // same position but on the other side of the line
+                Note::new(NoteKind::Tap, false, beat!(1), 100.0, 1.0),
+                Note::new(NoteKind::Tap, true, beat!(2), 100.0, 1.0),
+                Note::new(NoteKind::Tap, true, beat!(1), -100.0, 1.0),
+            ],
+            ..Default::default()
+        };
+        let location = LineLocation {
+            path: vec![0],
+            name: line.line.name.clone(),
+        };
+
+        let mut diagnostics = vec![];
+        check(&line, &location, &mut diagnostics);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].beat, Some(beat!(1)));
+    }
+}
@@ -0,0 +1,88 @@
+use clap::Parser;
+use owo_colors::OwoColorize;
+use phichain_chart::merge::merge;
+use phichain_chart::serialization::PhichainChart;
+use phichain_i18n::{i18n_str, locale};
+use rust_i18n::t;
+use std::path::{Path, PathBuf};
+
+rust_i18n::i18n!("locales", fallback = "en-US");
+
+/// Exit code when the merge has conflicts, git then marks the chart as conflicted
+const EXIT_CONFLICTED: i32 = 1;
+/// Exit code when any of the charts cannot be loaded or the result cannot be written
+const EXIT_FAILED: i32 = 2;
+
+#[derive(Parser, Debug, Clone)]
+#[command(name = "phichain-merge")]
+#[command(about = i18n_str!("cli.about"))]
+#[command(after_help = i18n_str!("cli.examples"))]
+struct Args {
+    #[arg(required = true, help = t!("cli.base").to_string())]
+    base: PathBuf,
+
+    #[arg(required = true, help = t!("cli.ours").to_string())]
+    ours: PathBuf,
+
+    #[arg(required = true, help = t!("cli.theirs").to_string())]
+    theirs: PathBuf,
+
+    #[arg(help = t!("cli.path").to_string())]
+    path: Option<String>,
+}
+
+fn load(path: &Path) -> Result<PhichainChart, String> {
+    let load = || -> Result<PhichainChart, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        PhichainChart::from_json_str(&content).map_err(|e| e.to_string())
+    };
+
+    load().map_err(|error| t!("cli.error.load", path = path.display(), error = error).to_string())
+}
+
+fn fail(message: String) -> ! {
+    eprintln!("{}", message.red());
+    std::process::exit(EXIT_FAILED);
+}
+
+fn main() {
+    rust_i18n::set_locale(&locale());
+
+    let args = Args::parse();
+    let path = args
+        .path
+        .clone()
+        .unwrap_or_else(|| args.ours.display().to_string());
+
+    let base = load(&args.base).unwrap_or_else(|e| fail(e));
+    let ours = load(&args.ours).unwrap_or_else(|e| fail(e));
+    let theirs = load(&args.theirs).unwrap_or_else(|e| fail(e));
+
+    let outcome = merge(&base, &ours, &theirs);
+
+    // charts are saved compactly, the same way the editor does
+    let json = serde_json::to_string(&outcome.chart).expect("failed to serialize merged chart");
+    if let Err(error) = std::fs::write(&args.ours, json) {
+        fail(t!("cli.error.write", path = args.ours.display(), error = error).to_string());
+    }
+
+    if outcome.is_clean() {
+        eprintln!("{}", t!("cli.status.merged", path = path).green());
+        return;
+    }
+
+    eprintln!(
+        "{}",
+        t!(
+            "cli.status.conflicts",
+            path = path,
+            count = outcome.conflicts.len()
+        )
+        .yellow()
+    );
+    for conflict in &outcome.conflicts {
+        eprintln!("  {conflict}");
+    }
+
+    std::process::exit(EXIT_CONFLICTED);
+}